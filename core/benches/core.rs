@@ -0,0 +1,15 @@
+use criterion::{criterion_group, criterion_main};
+use ruffle_core::benchmarks::*;
+
+criterion_group!(
+    benches,
+    bitmap_upload,
+    bitmap_plot_pixels,
+    bitmap_draw_and_get_pixel,
+    bitmap_fill_rect,
+    bitmap_threshold,
+    bitmap_compare,
+    domain_memory_fill,
+    domain_global_lookup
+);
+criterion_main!(benches);
@@ -12,6 +12,7 @@ mod callable_value;
 mod clamp;
 mod debug;
 mod error;
+mod filters;
 mod fscommand;
 pub(crate) mod globals;
 mod object;
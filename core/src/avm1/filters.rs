@@ -0,0 +1,545 @@
+//! Conversion between AVM1 filter objects and `ruffle_render` filters.
+
+use crate::avm1::object::NativeObject;
+use crate::avm1::{Activation, ArrayObject, Error, Object, TObject, Value};
+use crate::avm1_stub;
+use ruffle_render::filters::Filter;
+use swf::{
+    BevelFilter, BevelFilterFlags, BlurFilter, BlurFilterFlags, Color, ColorMatrixFilter,
+    ConvolutionFilter, ConvolutionFilterFlags, DropShadowFilter, DropShadowFilterFlags, Fixed16,
+    Fixed8, GlowFilter, GlowFilterFlags, GradientFilter, GradientFilterFlags, GradientRecord,
+};
+
+pub trait FilterAvm1Ext {
+    /// Reads the filter described by an AVM1 `BitmapFilter` object.
+    ///
+    /// Returns `None` if `object` isn't a filter, or is one that can't be rendered yet.
+    fn from_avm1_object<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<Option<Filter>, Error<'gc>>;
+
+    /// Creates a new AVM1 `BitmapFilter` object describing this filter.
+    ///
+    /// Returns `None` for filters that have no AVM1 counterpart.
+    fn as_avm1_object<'gc>(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Option<Object<'gc>>, Error<'gc>>;
+}
+
+impl FilterAvm1Ext for Filter {
+    fn from_avm1_object<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<Option<Filter>, Error<'gc>> {
+        let filter = match object.native() {
+            NativeObject::BevelFilter(_) => avm1_to_bevel_filter(activation, object)?,
+            NativeObject::BlurFilter(_) => avm1_to_blur_filter(activation, object)?,
+            _ if object.as_glow_filter_object().is_some() => {
+                avm1_to_glow_filter(activation, object)?
+            }
+            _ if object.as_drop_shadow_filter_object().is_some() => {
+                avm1_to_drop_shadow_filter(activation, object)?
+            }
+            _ if object.as_color_matrix_filter_object().is_some() => {
+                avm1_to_color_matrix_filter(activation, object)?
+            }
+            _ if object.as_convolution_filter_object().is_some() => {
+                avm1_to_convolution_filter(activation, object)?
+            }
+            _ if object.as_gradient_bevel_filter_object().is_some() => {
+                Filter::GradientBevelFilter(avm1_to_gradient_filter(activation, object)?)
+            }
+            _ if object.as_gradient_glow_filter_object().is_some() => {
+                Filter::GradientGlowFilter(avm1_to_gradient_filter(activation, object)?)
+            }
+            _ if object.as_displacement_map_filter_object().is_some() => {
+                avm1_stub!(
+                    activation,
+                    "MovieClip",
+                    "filters",
+                    "with DisplacementMapFilter"
+                );
+                return Ok(None);
+            }
+            _ => return Ok(None),
+        };
+        Ok(Some(filter))
+    }
+
+    fn as_avm1_object<'gc>(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Option<Object<'gc>>, Error<'gc>> {
+        let object = match self {
+            Filter::BevelFilter(filter) => bevel_filter_to_avm1(activation, filter)?,
+            Filter::BlurFilter(filter) => blur_filter_to_avm1(activation, filter)?,
+            Filter::ColorMatrixFilter(filter) => color_matrix_filter_to_avm1(activation, filter)?,
+            Filter::ConvolutionFilter(filter) => convolution_filter_to_avm1(activation, filter)?,
+            Filter::DropShadowFilter(filter) => drop_shadow_filter_to_avm1(activation, filter)?,
+            Filter::GlowFilter(filter) => glow_filter_to_avm1(activation, filter)?,
+            Filter::GradientBevelFilter(filter) => {
+                let constructor = activation
+                    .context
+                    .avm1
+                    .prototypes()
+                    .gradient_bevel_filter_constructor;
+                gradient_filter_to_avm1(activation, filter, constructor)?
+            }
+            Filter::GradientGlowFilter(filter) => {
+                let constructor = activation
+                    .context
+                    .avm1
+                    .prototypes()
+                    .gradient_glow_filter_constructor;
+                gradient_filter_to_avm1(activation, filter, constructor)?
+            }
+            // This can't be created by `from_avm1_object`.
+            Filter::DisplacementMapFilter(_) => return Ok(None),
+            // `ShaderFilter` only exists in AVM2.
+            Filter::ShaderFilter(_) => return Ok(None),
+        };
+        match object {
+            Value::Object(object) => Ok(Some(object)),
+            _ => Ok(None),
+        }
+    }
+}
+
+fn get_f64<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+    name: &'static str,
+) -> Result<f64, Error<'gc>> {
+    object.get(name, activation)?.coerce_to_f64(activation)
+}
+
+fn get_u32<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+    name: &'static str,
+) -> Result<u32, Error<'gc>> {
+    object.get(name, activation)?.coerce_to_u32(activation)
+}
+
+fn get_bool<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+    name: &'static str,
+) -> Result<bool, Error<'gc>> {
+    Ok(object
+        .get(name, activation)?
+        .as_bool(activation.swf_version()))
+}
+
+/// Reads every element of the array stored in `object[name]`, or nothing if it isn't an object.
+fn get_array<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+    name: &'static str,
+) -> Result<Vec<Value<'gc>>, Error<'gc>> {
+    let Value::Object(array) = object.get(name, activation)? else {
+        return Ok(vec![]);
+    };
+    let length = array.length(activation)?;
+    Ok((0..length)
+        .map(|i| array.get_element(activation, i))
+        .collect())
+}
+
+fn new_array<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    elements: impl IntoIterator<Item = Value<'gc>>,
+) -> Value<'gc> {
+    ArrayObject::new(
+        activation.context.gc_context,
+        activation.context.avm1.prototypes().array,
+        elements,
+    )
+    .into()
+}
+
+fn avm1_to_bevel_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Filter, Error<'gc>> {
+    let angle = get_f64(activation, object, "angle")?;
+    let blur_x = get_f64(activation, object, "blurX")?;
+    let blur_y = get_f64(activation, object, "blurY")?;
+    let distance = get_f64(activation, object, "distance")?;
+    let highlight_alpha = get_f64(activation, object, "highlightAlpha")?;
+    let highlight_color = get_u32(activation, object, "highlightColor")?;
+    let knockout = get_bool(activation, object, "knockout")?;
+    let quality = get_u32(activation, object, "quality")?;
+    let shadow_alpha = get_f64(activation, object, "shadowAlpha")?;
+    let shadow_color = get_u32(activation, object, "shadowColor")?;
+    let strength = get_f64(activation, object, "strength")?;
+    let bevel_type = object
+        .get("type", activation)?
+        .coerce_to_string(activation)?;
+    let mut flags = BevelFilterFlags::COMPOSITE_SOURCE;
+    if &bevel_type == b"inner" {
+        flags |= BevelFilterFlags::INNER_SHADOW;
+    } else if &bevel_type != b"outer" {
+        flags |= BevelFilterFlags::ON_TOP;
+    }
+    flags.set(BevelFilterFlags::KNOCKOUT, knockout);
+    flags |= BevelFilterFlags::from_passes(quality.clamp(1, 15) as u8);
+    Ok(Filter::BevelFilter(BevelFilter {
+        shadow_color: Color::from_rgb(shadow_color, (shadow_alpha * 255.0) as u8),
+        highlight_color: Color::from_rgb(highlight_color, (highlight_alpha * 255.0) as u8),
+        blur_x: Fixed16::from_f64(blur_x.max(0.0)),
+        blur_y: Fixed16::from_f64(blur_y.max(0.0)),
+        angle: Fixed16::from_f64(angle.to_radians()),
+        distance: Fixed16::from_f64(distance),
+        strength: Fixed8::from_f64(strength.clamp(0.0, 255.0)),
+        flags,
+    }))
+}
+
+fn bevel_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &BevelFilter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let constructor = activation
+        .context
+        .avm1
+        .prototypes()
+        .bevel_filter_constructor;
+    constructor.construct(
+        activation,
+        &[
+            filter.distance.to_f64().into(),
+            filter.angle.to_f64().to_degrees().into(),
+            filter.highlight_color.to_rgb().into(),
+            (f64::from(filter.highlight_color.a) / 255.0).into(),
+            filter.shadow_color.to_rgb().into(),
+            (f64::from(filter.shadow_color.a) / 255.0).into(),
+            filter.blur_x.to_f64().into(),
+            filter.blur_y.to_f64().into(),
+            filter.strength.to_f64().into(),
+            filter.num_passes().into(),
+            if filter.is_on_top() {
+                "full"
+            } else if filter.is_inner() {
+                "inner"
+            } else {
+                "outer"
+            }
+            .into(),
+            filter.is_knockout().into(),
+        ],
+    )
+}
+
+fn avm1_to_blur_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Filter, Error<'gc>> {
+    let blur_x = get_f64(activation, object, "blurX")?;
+    let blur_y = get_f64(activation, object, "blurY")?;
+    let quality = get_u32(activation, object, "quality")?;
+    Ok(Filter::BlurFilter(BlurFilter {
+        blur_x: Fixed16::from_f64(blur_x.max(0.0)),
+        blur_y: Fixed16::from_f64(blur_y.max(0.0)),
+        flags: BlurFilterFlags::from_passes(quality.clamp(1, 15) as u8),
+    }))
+}
+
+fn blur_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &BlurFilter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let constructor = activation.context.avm1.prototypes().blur_filter_constructor;
+    constructor.construct(
+        activation,
+        &[
+            filter.blur_x.to_f64().into(),
+            filter.blur_y.to_f64().into(),
+            filter.num_passes().into(),
+        ],
+    )
+}
+
+fn avm1_to_color_matrix_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Filter, Error<'gc>> {
+    let mut matrix = [0.0; 20];
+    for (value, element) in matrix
+        .iter_mut()
+        .zip(get_array(activation, object, "matrix")?)
+    {
+        *value = element.coerce_to_f64(activation)? as f32;
+    }
+    Ok(Filter::ColorMatrixFilter(ColorMatrixFilter { matrix }))
+}
+
+fn color_matrix_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &ColorMatrixFilter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let matrix = new_array(activation, filter.matrix.iter().map(|v| (*v).into()));
+    let constructor = activation
+        .context
+        .avm1
+        .prototypes()
+        .color_matrix_filter_constructor;
+    constructor.construct(activation, &[matrix])
+}
+
+fn avm1_to_convolution_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Filter, Error<'gc>> {
+    let mut matrix = vec![];
+    for value in get_array(activation, object, "matrix")? {
+        matrix.push(Fixed16::from_f64(value.coerce_to_f64(activation)?));
+    }
+    let alpha = get_f64(activation, object, "alpha")?;
+    let bias = get_f64(activation, object, "bias")?;
+    let clamp = get_bool(activation, object, "clamp")?;
+    let color = get_u32(activation, object, "color")?;
+    let divisor = get_f64(activation, object, "divisor")?;
+    let matrix_x = get_u32(activation, object, "matrixX")?.min(255);
+    let matrix_y = get_u32(activation, object, "matrixY")?.min(255);
+    let preserve_alpha = get_bool(activation, object, "preserveAlpha")?;
+    let mut flags = ConvolutionFilterFlags::empty();
+    flags.set(ConvolutionFilterFlags::CLAMP, clamp);
+    flags.set(ConvolutionFilterFlags::PRESERVE_ALPHA, preserve_alpha);
+    matrix.resize((matrix_x * matrix_y) as usize, Fixed16::ZERO);
+    Ok(Filter::ConvolutionFilter(ConvolutionFilter {
+        bias: Fixed16::from_f64(bias),
+        default_color: Color::from_rgb(color, (alpha * 255.0) as u8),
+        divisor: Fixed16::from_f64(divisor),
+        matrix,
+        num_matrix_cols: matrix_x as u8,
+        num_matrix_rows: matrix_y as u8,
+        flags,
+    }))
+}
+
+fn convolution_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &ConvolutionFilter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let matrix = new_array(activation, filter.matrix.iter().map(|v| v.to_f64().into()));
+    let constructor = activation
+        .context
+        .avm1
+        .prototypes()
+        .convolution_filter_constructor;
+    constructor.construct(
+        activation,
+        &[
+            filter.num_matrix_cols.into(),
+            filter.num_matrix_rows.into(),
+            matrix,
+            filter.divisor.to_f64().into(),
+            filter.bias.to_f64().into(),
+            filter.is_preserve_alpha().into(),
+            filter.is_clamped().into(),
+            filter.default_color.to_rgb().into(),
+            (f64::from(filter.default_color.a) / 255.0).into(),
+        ],
+    )
+}
+
+fn avm1_to_drop_shadow_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Filter, Error<'gc>> {
+    let alpha = get_f64(activation, object, "alpha")?;
+    let angle = get_f64(activation, object, "angle")?;
+    let blur_x = get_f64(activation, object, "blurX")?;
+    let blur_y = get_f64(activation, object, "blurY")?;
+    let color = get_u32(activation, object, "color")?;
+    let distance = get_f64(activation, object, "distance")?;
+    let hide_object = get_bool(activation, object, "hideObject")?;
+    let inner = get_bool(activation, object, "inner")?;
+    let knockout = get_bool(activation, object, "knockout")?;
+    let quality = get_u32(activation, object, "quality")?;
+    let strength = get_f64(activation, object, "strength")?;
+    let mut flags = DropShadowFilterFlags::empty();
+    flags.set(DropShadowFilterFlags::COMPOSITE_SOURCE, !hide_object);
+    flags.set(DropShadowFilterFlags::INNER_SHADOW, inner);
+    flags.set(DropShadowFilterFlags::KNOCKOUT, knockout);
+    flags |= DropShadowFilterFlags::from_passes(quality.clamp(1, 15) as u8);
+    Ok(Filter::DropShadowFilter(DropShadowFilter {
+        color: Color::from_rgb(color, (alpha * 255.0) as u8),
+        angle: Fixed16::from_f64(angle.to_radians()),
+        blur_x: Fixed16::from_f64(blur_x.max(0.0)),
+        blur_y: Fixed16::from_f64(blur_y.max(0.0)),
+        distance: Fixed16::from_f64(distance),
+        strength: Fixed8::from_f64(strength.clamp(0.0, 255.0)),
+        flags,
+    }))
+}
+
+fn drop_shadow_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &DropShadowFilter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let constructor = activation
+        .context
+        .avm1
+        .prototypes()
+        .drop_shadow_filter_constructor;
+    constructor.construct(
+        activation,
+        &[
+            filter.distance.to_f64().into(),
+            filter.angle.to_f64().to_degrees().into(),
+            filter.color.to_rgb().into(),
+            (f64::from(filter.color.a) / 255.0).into(),
+            filter.blur_x.to_f64().into(),
+            filter.blur_y.to_f64().into(),
+            filter.strength.to_f64().into(),
+            filter.num_passes().into(),
+            filter.is_inner().into(),
+            filter.is_knockout().into(),
+            filter.hide_object().into(),
+        ],
+    )
+}
+
+fn avm1_to_glow_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Filter, Error<'gc>> {
+    let alpha = get_f64(activation, object, "alpha")?;
+    let blur_x = get_f64(activation, object, "blurX")?;
+    let blur_y = get_f64(activation, object, "blurY")?;
+    let color = get_u32(activation, object, "color")?;
+    let inner = get_bool(activation, object, "inner")?;
+    let knockout = get_bool(activation, object, "knockout")?;
+    let quality = get_u32(activation, object, "quality")?;
+    let strength = get_f64(activation, object, "strength")?;
+    let mut flags = GlowFilterFlags::COMPOSITE_SOURCE;
+    flags.set(GlowFilterFlags::INNER_GLOW, inner);
+    flags.set(GlowFilterFlags::KNOCKOUT, knockout);
+    flags |= GlowFilterFlags::from_passes(quality.clamp(1, 15) as u8);
+    Ok(Filter::GlowFilter(GlowFilter {
+        color: Color::from_rgb(color, (alpha * 255.0) as u8),
+        blur_x: Fixed16::from_f64(blur_x.max(0.0)),
+        blur_y: Fixed16::from_f64(blur_y.max(0.0)),
+        strength: Fixed8::from_f64(strength.clamp(0.0, 255.0)),
+        flags,
+    }))
+}
+
+fn glow_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &GlowFilter,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let constructor = activation.context.avm1.prototypes().glow_filter_constructor;
+    let object = constructor.construct(
+        activation,
+        &[
+            filter.color.to_rgb().into(),
+            (f64::from(filter.color.a) / 255.0).into(),
+            filter.blur_x.to_f64().into(),
+            filter.blur_y.to_f64().into(),
+            filter.strength.to_f64().into(),
+            filter.num_passes().into(),
+        ],
+    )?;
+    // The AVM1 `GlowFilter` constructor ignores these arguments, so set them separately.
+    if let Value::Object(object) = object {
+        object.set("inner", filter.is_inner().into(), activation)?;
+        object.set("knockout", filter.is_knockout().into(), activation)?;
+    }
+    Ok(object)
+}
+
+fn avm1_to_gradient_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<GradientFilter, Error<'gc>> {
+    let angle = get_f64(activation, object, "angle")?;
+    let blur_x = get_f64(activation, object, "blurX")?;
+    let blur_y = get_f64(activation, object, "blurY")?;
+    let distance = get_f64(activation, object, "distance")?;
+    let knockout = get_bool(activation, object, "knockout")?;
+    let quality = get_u32(activation, object, "quality")?;
+    let strength = get_f64(activation, object, "strength")?;
+    let bevel_type = object
+        .get("type", activation)?
+        .coerce_to_string(activation)?;
+
+    // Like Flash, only keep as many entries as the shortest of the three arrays has.
+    let colors = get_array(activation, object, "colors")?;
+    let alphas = get_array(activation, object, "alphas")?;
+    let ratios = get_array(activation, object, "ratios")?;
+    let mut records = vec![];
+    for ((color, alpha), ratio) in colors.into_iter().zip(alphas).zip(ratios) {
+        let color = color.coerce_to_u32(activation)?;
+        let alpha = alpha.coerce_to_f64(activation)?;
+        let ratio = ratio.coerce_to_u32(activation)?;
+        records.push(GradientRecord {
+            ratio: ratio.min(255) as u8,
+            color: Color::from_rgb(color, (alpha * 255.0) as u8),
+        });
+    }
+
+    let mut flags = GradientFilterFlags::COMPOSITE_SOURCE;
+    flags.set(GradientFilterFlags::KNOCKOUT, knockout);
+    if &bevel_type == b"inner" {
+        flags |= GradientFilterFlags::INNER_SHADOW;
+    } else if &bevel_type != b"outer" {
+        flags |= GradientFilterFlags::ON_TOP;
+    }
+    flags |= GradientFilterFlags::from_passes(quality.clamp(1, 15) as u8);
+    Ok(GradientFilter {
+        colors: records,
+        blur_x: Fixed16::from_f64(blur_x.max(0.0)),
+        blur_y: Fixed16::from_f64(blur_y.max(0.0)),
+        angle: Fixed16::from_f64(angle.to_radians()),
+        distance: Fixed16::from_f64(distance),
+        strength: Fixed8::from_f64(strength.clamp(0.0, 255.0)),
+        flags,
+    })
+}
+
+fn gradient_filter_to_avm1<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &GradientFilter,
+    constructor: Object<'gc>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    let colors = new_array(
+        activation,
+        filter.colors.iter().map(|v| v.color.to_rgb().into()),
+    );
+    let alphas = new_array(
+        activation,
+        filter
+            .colors
+            .iter()
+            .map(|v| (f64::from(v.color.a) / 255.0).into()),
+    );
+    let ratios = new_array(activation, filter.colors.iter().map(|v| v.ratio.into()));
+    constructor.construct(
+        activation,
+        &[
+            filter.distance.to_f64().into(),
+            filter.angle.to_f64().to_degrees().into(),
+            colors,
+            alphas,
+            ratios,
+            filter.blur_x.to_f64().into(),
+            filter.blur_y.to_f64().into(),
+            filter.strength.to_f64().into(),
+            filter.num_passes().into(),
+            if filter.is_on_top() {
+                "full"
+            } else if filter.is_inner() {
+                "inner"
+            } else {
+                "outer"
+            }
+            .into(),
+            filter.is_knockout().into(),
+        ],
+    )
+}
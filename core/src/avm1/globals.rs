@@ -1,14 +1,17 @@
 use crate::avm1::activation::Activation;
 use crate::avm1::error::Error;
+use crate::avm1::filters::FilterAvm1Ext;
 use crate::avm1::function::{Executable, FunctionObject};
 use crate::avm1::property::Attribute;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
-use crate::avm1::{Object, ScriptObject, TObject, Value};
+use crate::avm1::{ArrayObject, Object, ScriptObject, TObject, Value};
 use crate::display_object::{DisplayObject, TDisplayObject, TDisplayObjectContainer};
 use crate::string::{AvmString, WStr, WString};
 use gc_arena::Collect;
 use gc_arena::MutationContext;
+use ruffle_render::filters::Filter;
 use std::str;
+use swf::BlendMode;
 
 mod accessibility;
 mod array;
@@ -499,6 +502,8 @@ pub struct SystemPrototypes<'gc> {
     pub context_menu_item_constructor: Object<'gc>,
     pub bitmap_filter: Object<'gc>,
     pub bitmap_filter_constructor: Object<'gc>,
+    pub blur_filter_constructor: Object<'gc>,
+    pub bevel_filter_constructor: Object<'gc>,
     pub glow_filter: Object<'gc>,
     pub glow_filter_constructor: Object<'gc>,
     pub drop_shadow_filter: Object<'gc>,
@@ -1148,6 +1153,8 @@ pub fn create_globals<'gc>(
             context_menu_item_constructor: context_menu_item,
             bitmap_filter: bitmap_filter_proto,
             bitmap_filter_constructor: bitmap_filter,
+            blur_filter_constructor: blur_filter,
+            bevel_filter_constructor: bevel_filter,
             glow_filter: glow_filter_proto,
             glow_filter_constructor: glow_filter,
             drop_shadow_filter: drop_shadow_filter_proto,
@@ -1187,6 +1194,91 @@ const AVM_MAX_DEPTH: i32 = 2_130_706_428;
 /// What is the derivation of this number...?
 const AVM_MAX_REMOVE_DEPTH: i32 = 2_130_706_416;
 
+fn get_filters<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        // Every read hands out fresh copies, so changing them doesn't affect the object.
+        let mut filters = vec![];
+        for filter in display_object.filters() {
+            if let Some(filter) = filter.as_avm1_object(activation)? {
+                filters.push(filter.into());
+            }
+        }
+        return Ok(ArrayObject::new(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().array,
+            filters,
+        )
+        .into());
+    }
+    Ok(Value::Undefined)
+}
+
+fn set_filters<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        let mut filters = vec![];
+        if let Some(Value::Object(array)) = args.get(0) {
+            for i in 0..array.length(activation)? {
+                if let Value::Object(filter) = array.get_element(activation, i) {
+                    filters.extend(Filter::from_avm1_object(activation, filter)?);
+                }
+            }
+        }
+        display_object.set_filters(activation.context.gc_context, filters);
+    }
+    Ok(Value::Undefined)
+}
+
+fn get_cache_as_bitmap<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        // Objects with filters are always cached, whatever was set here.
+        return Ok(display_object.is_bitmap_cached().into());
+    }
+    Ok(Value::Undefined)
+}
+
+fn set_cache_as_bitmap<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(display_object) = this.as_display_object() {
+        let cache = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .as_bool(activation.swf_version());
+        display_object.set_is_bitmap_cached(activation.context.gc_context, cache);
+    }
+    Ok(Value::Undefined)
+}
+
+/// Reads a blend mode from a value, as the `blendMode` properties and `BitmapData.draw` accept it.
+///
+/// Names are matched ignoring case, and numbers (or strings of them) are taken as SWF blend
+/// mode values. `None` is returned for anything else, so that the caller can keep its current
+/// blend mode.
+pub fn blend_mode_from_value<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    value: Value<'gc>,
+) -> Result<Option<BlendMode>, Error<'gc>> {
+    Ok(match value {
+        Value::Undefined | Value::Null => None,
+        Value::Number(_) => BlendMode::from_u8(value.coerce_to_u8(activation)?),
+        _ => BlendMode::from_name_or_number(&value.coerce_to_string(activation)?.to_string()),
+    })
+}
+
 fn get_depth<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
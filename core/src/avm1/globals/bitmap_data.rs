@@ -2,20 +2,20 @@
 
 use super::matrix::object_to_matrix;
 use crate::avm1::function::{Executable, FunctionObject};
+use crate::avm1::globals;
 use crate::avm1::globals::color_transform::ColorTransformObject;
 use crate::avm1::object::bitmap_data::BitmapDataObject;
 use crate::avm1::property_decl::{define_properties_on, Declaration};
 use crate::avm1::{Activation, Error, Object, TObject, Value};
 use crate::bitmap::bitmap_data::{BitmapDataDrawError, IBitmapDrawable};
 use crate::bitmap::bitmap_data::{ChannelOptions, ThresholdOperation};
-use crate::bitmap::{is_size_valid, operations};
+use crate::bitmap::{is_size_valid, operations, BitmapAllocation, BitmapMemoryBudget};
 use crate::character::Character;
 use crate::display_object::TDisplayObject;
 use crate::swf::BlendMode;
 use crate::{avm1_stub, avm_error};
 use gc_arena::MutationContext;
 use ruffle_render::transform::Transform;
-use std::str::FromStr;
 
 const PROTO_DECLS: &[Declaration] = declare_properties! {
     "height" => property(height);
@@ -57,6 +57,13 @@ pub fn constructor<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    // Legacy content may pass an existing `BitmapData` in place of the width to copy it.
+    if let Some(Value::Object(source)) = args.get(0) {
+        if let Some(source) = source.as_bitmap_data_object() {
+            return copy_constructor(activation, this, source);
+        }
+    }
+
     let width = args.get(0).unwrap_or(&0.into()).coerce_to_i32(activation)? as u32;
 
     let height = args.get(1).unwrap_or(&0.into()).coerce_to_i32(activation)? as u32;
@@ -66,26 +73,130 @@ pub fn constructor<'gc>(
         .unwrap_or(&true.into())
         .as_bool(activation.swf_version());
 
-    let fill_color = args
-        .get(3)
-        .unwrap_or(&(-1).into())
-        .coerce_to_i32(activation)?;
+    let fill_color = match args.get(3) {
+        Some(fill_color) => fill_color.coerce_to_i32(activation)?,
+        None => activation.context.default_bitmap_fill_color as i32,
+    };
 
     if !is_size_valid(activation.swf_version(), width, height) {
         tracing::warn!("Invalid BitmapData size: {}x{}", width, height);
         return Ok(Value::Undefined);
     }
 
+    let Some(memory) = allocate_memory(activation, width, height) else {
+        return Ok(Value::Undefined);
+    };
+
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
-        bitmap_data
+        let mut write = bitmap_data
             .bitmap_data()
-            .write(activation.context.gc_context)
-            .init_pixels(width, height, transparency, fill_color);
+            .write(activation.context.gc_context);
+        write.init_pixels(width, height, transparency, fill_color);
+        write.set_memory(memory);
+    }
+
+    Ok(this.into())
+}
+
+/// Initializes `this` as a copy of `source`, sharing its pixels until either is modified.
+fn copy_constructor<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Object<'gc>,
+    source: BitmapDataObject<'gc>,
+) -> Result<Value<'gc>, Error<'gc>> {
+    if source.disposed() {
+        return Ok(Value::Undefined);
+    }
+
+    let (width, height, transparency, pixels) = {
+        // Bring any pending GPU changes back to the CPU before sharing the pixels.
+        let source = source.bitmap_data_wrapper().sync();
+        let read = source.read();
+        (
+            read.width(),
+            read.height(),
+            read.transparency(),
+            read.shared_pixels(),
+        )
+    };
+
+    let Some(memory) = allocate_memory(activation, width, height) else {
+        return Ok(Value::Undefined);
+    };
+
+    if let Some(bitmap_data) = this.as_bitmap_data_object() {
+        let mut write = bitmap_data
+            .bitmap_data()
+            .write(activation.context.gc_context);
+        write.set_pixels(width, height, transparency, pixels);
+        write.set_memory(memory);
     }
 
     Ok(this.into())
 }
 
+fn allocate_memory(
+    activation: &mut Activation<'_, '_>,
+    width: u32,
+    height: u32,
+) -> Option<BitmapAllocation> {
+    let bytes = BitmapMemoryBudget::bytes_for(width, height);
+    let memory = activation.context.bitmap_memory.try_allocate(bytes);
+    if memory.is_none() {
+        tracing::warn!(
+            "BitmapData memory limit exceeded by {}x{} bitmap",
+            width,
+            height
+        );
+    }
+    memory
+}
+
+/// Reads a field of a `Point` or `Rectangle`, or any object standing in for
+/// one. Missing fields (and `NaN`s) count as 0.
+fn coordinate<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+    name: &'static str,
+) -> Result<f64, Error<'gc>> {
+    let value = object.get(name, activation)?.coerce_to_f64(activation)?;
+    Ok(if value.is_nan() { 0.0 } else { value })
+}
+
+/// Reads the coordinates of a destination point, which Flash rounds down
+/// (rather than towards zero), so that e.g. `-0.5` places pixels at `-1`.
+fn point_to_i32<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    point: Object<'gc>,
+) -> Result<(i32, i32), Error<'gc>> {
+    let x = coordinate(activation, point, "x")?;
+    let y = coordinate(activation, point, "y")?;
+    Ok((x.floor() as i32, y.floor() as i32))
+}
+
+/// Reads the pixels covered by a rectangle, as `(x, y, width, height)`.
+///
+/// Like points, every edge is rounded down, with the right and bottom edges
+/// worked out before rounding. So `{x: 0.6, width: 9.5}` covers pixels 0 to 9.
+fn rectangle_to_i32<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    rectangle: Object<'gc>,
+) -> Result<(i32, i32, i32, i32), Error<'gc>> {
+    let x = coordinate(activation, rectangle, "x")?;
+    let y = coordinate(activation, rectangle, "y")?;
+    let width = coordinate(activation, rectangle, "width")?;
+    let height = coordinate(activation, rectangle, "height")?;
+    let (x_min, y_min) = (x.floor() as i32, y.floor() as i32);
+    let x_max = (x + width).floor() as i32;
+    let y_max = (y + height).floor() as i32;
+    Ok((
+        x_min,
+        y_min,
+        x_max.saturating_sub(x_min),
+        y_max.saturating_sub(y_min),
+    ))
+}
+
 pub fn height<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
@@ -93,7 +204,7 @@ pub fn height<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
-            return Ok(bitmap_data.bitmap_data().read().height().into());
+            return Ok(bitmap_data.bitmap_data_wrapper().height().into());
         }
     }
 
@@ -107,7 +218,7 @@ pub fn width<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
-            return Ok(bitmap_data.bitmap_data().read().width().into());
+            return Ok(bitmap_data.bitmap_data_wrapper().width().into());
         }
     }
 
@@ -284,33 +395,34 @@ pub fn copy_channel<'gc>(
         if !bitmap_data.disposed() {
             if let Some(source_bitmap) = source_bitmap.as_bitmap_data_object() {
                 //TODO: what if source is disposed
-                let min_x = dest_point
-                    .get("x", activation)?
-                    .coerce_to_u32(activation)?
-                    .min(bitmap_data.bitmap_data().read().width());
-                let min_y = dest_point
-                    .get("y", activation)?
-                    .coerce_to_u32(activation)?
-                    .min(bitmap_data.bitmap_data().read().height());
-
-                let src_min_x = source_rect
-                    .get("x", activation)?
-                    .coerce_to_u32(activation)?;
-                let src_min_y = source_rect
-                    .get("y", activation)?
-                    .coerce_to_u32(activation)?;
-                let src_width = source_rect
-                    .get("width", activation)?
-                    .coerce_to_u32(activation)?;
-                let src_height = source_rect
-                    .get("height", activation)?
-                    .coerce_to_u32(activation)?;
+                let (mut dest_x, mut dest_y) = point_to_i32(activation, dest_point)?;
+                let (mut src_min_x, mut src_min_y, mut src_width, mut src_height) =
+                    rectangle_to_i32(activation, source_rect)?;
+
+                // `copy_channel` works in unsigned coordinates, so first clip away
+                // anything above or left of the origin of either bitmap.
+                let clip_x = src_min_x.min(dest_x).min(0);
+                let clip_y = src_min_y.min(dest_y).min(0);
+                src_min_x -= clip_x;
+                dest_x -= clip_x;
+                src_width += clip_x;
+                src_min_y -= clip_y;
+                dest_y -= clip_y;
+                src_height += clip_y;
+
+                let min_x = (dest_x as u32).min(bitmap_data.bitmap_data().read().width());
+                let min_y = (dest_y as u32).min(bitmap_data.bitmap_data().read().height());
 
                 operations::copy_channel(
                     &mut activation.context,
                     bitmap_data.bitmap_data_wrapper(),
                     (min_x, min_y),
-                    (src_min_x, src_min_y, src_width, src_height),
+                    (
+                        src_min_x as u32,
+                        src_min_y as u32,
+                        src_width.max(0) as u32,
+                        src_height.max(0) as u32,
+                    ),
                     source_bitmap.bitmap_data_wrapper(),
                     source_channel,
                     dest_channel,
@@ -339,14 +451,7 @@ pub fn fill_rect<'gc>(
             if let Some(color_val) = args.get(1) {
                 let color = color_val.coerce_to_i32(activation)?;
 
-                let x = rectangle.get("x", activation)?.coerce_to_i32(activation)?;
-                let y = rectangle.get("y", activation)?.coerce_to_i32(activation)?;
-                let width = rectangle
-                    .get("width", activation)?
-                    .coerce_to_i32(activation)?;
-                let height = rectangle
-                    .get("height", activation)?
-                    .coerce_to_i32(activation)?;
+                let (x, y, width, height) = rectangle_to_i32(activation, rectangle)?;
 
                 operations::fill_rect(
                     &mut activation.context,
@@ -372,7 +477,12 @@ pub fn clone<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
-            let new_bitmap_data = operations::clone(bitmap_data.bitmap_data_wrapper());
+            let (width, height) = (bitmap_data.width(), bitmap_data.height());
+            let Some(memory) = allocate_memory(activation, width, height) else {
+                return Ok(Value::Undefined);
+            };
+            let mut new_bitmap_data = operations::clone(bitmap_data.bitmap_data_wrapper());
+            new_bitmap_data.set_memory(memory);
             let new_bitmap_data = BitmapDataObject::with_bitmap_data(
                 activation.context.gc_context,
                 activation.context.avm1.prototypes().bitmap_data,
@@ -415,12 +525,14 @@ pub fn flood_fill<'gc>(
                 let y = y_val.coerce_to_u32(activation)?;
                 let color = color_val.coerce_to_i32(activation)?;
 
+                let limit = activation.context.flood_fill_limit;
                 operations::flood_fill(
                     &mut activation.context,
                     bitmap_data.bitmap_data_wrapper(),
                     x,
                     y,
                     color,
+                    limit,
                 );
             }
             return Ok(Value::Undefined);
@@ -488,23 +600,19 @@ pub fn draw<'gc>(
                 .and_then(|o| object_to_matrix(o, activation).ok())
                 .unwrap_or_default();
 
-            let color_transform = args
-                .get(2)
-                .and_then(|v| ColorTransformObject::cast(*v))
-                .map(|color_transform| color_transform.read().clone().into())
-                .unwrap_or_default();
+            let color_transform = match args.get(2) {
+                Some(value) => ColorTransformObject::from_value(activation, *value)?
+                    .map(Into::into)
+                    .unwrap_or_default(),
+                None => Default::default(),
+            };
 
             let mut blend_mode = BlendMode::Normal;
-            if let Some(mode) = args.get(3) {
-                if let Ok(mode) =
-                    BlendMode::from_str(&mode.coerce_to_string(activation)?.to_string())
-                {
-                    blend_mode = mode;
-                } else if let Ok(Some(mode)) = mode.coerce_to_u8(activation).map(BlendMode::from_u8)
-                {
-                    blend_mode = mode;
-                } else {
-                    tracing::error!("Unknown blend mode {:?}", mode);
+            if let Some(&mode) = args.get(3) {
+                match globals::blend_mode_from_value(activation, mode)? {
+                    Some(mode) => blend_mode = mode,
+                    None if matches!(mode, Value::Undefined | Value::Null) => {}
+                    None => tracing::error!("Unknown blend mode {:?}", mode),
                 }
             }
 
@@ -549,6 +657,7 @@ pub fn draw<'gc>(
                 blend_mode,
                 None,
                 quality,
+                false,
             ) {
                 Ok(()) => {}
                 Err(BitmapDataDrawError::Unimplemented) => {
@@ -597,26 +706,19 @@ pub fn color_transform<'gc>(
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
             if let [rectangle, color_transform, ..] = args {
-                // TODO: Re-use `object_to_rectangle` in `movie_clip.rs`.
                 let rectangle = rectangle.coerce_to_object(activation);
-                let x = rectangle.get("x", activation)?.coerce_to_f64(activation)? as i32;
-                let y = rectangle.get("y", activation)?.coerce_to_f64(activation)? as i32;
-                let width = rectangle
-                    .get("width", activation)?
-                    .coerce_to_f64(activation)? as i32;
-                let height = rectangle
-                    .get("height", activation)?
-                    .coerce_to_f64(activation)? as i32;
+                let (x, y, width, height) = rectangle_to_i32(activation, rectangle)?;
 
                 let x_min = x.max(0) as u32;
-                let x_max = (x + width) as u32;
+                let x_max = x.saturating_add(width).max(0) as u32;
                 let y_min = y.max(0) as u32;
-                let y_max = (y + height) as u32;
+                let y_max = y.saturating_add(height).max(0) as u32;
 
-                let color_transform = match ColorTransformObject::cast(*color_transform) {
-                    Some(color_transform) => color_transform.read().clone(),
-                    None => return Ok((-3).into()),
-                };
+                let color_transform =
+                    match ColorTransformObject::from_value(activation, *color_transform)? {
+                        Some(color_transform) => color_transform,
+                        None => return Ok((-3).into()),
+                    };
 
                 operations::color_transform(
                     &mut activation.context,
@@ -879,26 +981,15 @@ pub fn copy_pixels<'gc>(
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let src_min_x = source_rect
-                .get("x", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_min_y = source_rect
-                .get("y", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_width = source_rect
-                .get("width", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_height = source_rect
-                .get("height", activation)?
-                .coerce_to_f64(activation)? as i32;
+            let (src_min_x, src_min_y, src_width, src_height) =
+                rectangle_to_i32(activation, source_rect)?;
 
             let dest_point = args
                 .get(2)
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let dest_x = dest_point.get("x", activation)?.coerce_to_f64(activation)? as i32;
-            let dest_y = dest_point.get("y", activation)?.coerce_to_f64(activation)? as i32;
+            let (dest_x, dest_y) = point_to_i32(activation, dest_point)?;
 
             if let Some(src_bitmap) = source_bitmap.as_bitmap_data_object() {
                 if !src_bitmap.disposed() {
@@ -924,15 +1015,7 @@ pub fn copy_pixels<'gc>(
                                 .unwrap_or(&Value::Undefined)
                                 .coerce_to_object(activation);
 
-                            let alpha_x = alpha_point
-                                .get("x", activation)?
-                                .coerce_to_f64(activation)?
-                                as i32;
-
-                            let alpha_y = alpha_point
-                                .get("y", activation)?
-                                .coerce_to_f64(activation)?
-                                as i32;
+                            let (alpha_x, alpha_y) = point_to_i32(activation, alpha_point)?;
 
                             operations::copy_pixels_with_alpha_source(
                                 &mut activation.context,
@@ -972,6 +1055,9 @@ pub fn merge<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
+    const NOT_BITMAP: i32 = -1;
+    const BITMAP_DISPOSED: i32 = -2;
+
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
             let source_bitmap = args
@@ -984,26 +1070,15 @@ pub fn merge<'gc>(
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let src_min_x = source_rect
-                .get("x", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_min_y = source_rect
-                .get("y", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_width = source_rect
-                .get("width", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_height = source_rect
-                .get("height", activation)?
-                .coerce_to_f64(activation)? as i32;
+            let (src_min_x, src_min_y, src_width, src_height) =
+                rectangle_to_i32(activation, source_rect)?;
 
             let dest_point = args
                 .get(2)
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let dest_x = dest_point.get("x", activation)?.coerce_to_f64(activation)? as i32;
-            let dest_y = dest_point.get("y", activation)?.coerce_to_f64(activation)? as i32;
+            let (dest_x, dest_y) = point_to_i32(activation, dest_point)?;
 
             let red_mult = args
                 .get(3)
@@ -1025,24 +1100,30 @@ pub fn merge<'gc>(
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_i32(activation)?;
 
-            if let Some(src_bitmap) = source_bitmap.as_bitmap_data_object() {
-                if !src_bitmap.disposed() {
-                    operations::merge(
-                        &mut activation.context,
-                        bitmap_data.bitmap_data_wrapper(),
-                        src_bitmap.bitmap_data_wrapper(),
-                        (src_min_x, src_min_y, src_width, src_height),
-                        (dest_x, dest_y),
-                        (red_mult, green_mult, blue_mult, alpha_mult),
-                    );
-                }
+            let src_bitmap = if let Some(src_bitmap) = source_bitmap.as_bitmap_data_object() {
+                src_bitmap
+            } else {
+                return Ok(NOT_BITMAP.into());
+            };
+
+            if src_bitmap.disposed() {
+                return Ok(BITMAP_DISPOSED.into());
             }
 
+            operations::merge(
+                &mut activation.context,
+                bitmap_data.bitmap_data_wrapper(),
+                src_bitmap.bitmap_data_wrapper(),
+                (src_min_x, src_min_y, src_width, src_height),
+                (dest_x, dest_y),
+                (red_mult, green_mult, blue_mult, alpha_mult),
+            );
+
             return Ok(Value::Undefined);
         }
     }
 
-    Ok((-1).into())
+    Ok(NOT_BITMAP.into())
 }
 
 pub fn palette_map<'gc>(
@@ -1062,26 +1143,15 @@ pub fn palette_map<'gc>(
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let src_min_x = source_rect
-                .get("x", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_min_y = source_rect
-                .get("y", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_width = source_rect
-                .get("width", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_height = source_rect
-                .get("height", activation)?
-                .coerce_to_f64(activation)? as i32;
+            let (src_min_x, src_min_y, src_width, src_height) =
+                rectangle_to_i32(activation, source_rect)?;
 
             let dest_point = args
                 .get(2)
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let dest_x = dest_point.get("x", activation)?.coerce_to_f64(activation)? as i32;
-            let dest_y = dest_point.get("y", activation)?.coerce_to_f64(activation)? as i32;
+            let (dest_x, dest_y) = point_to_i32(activation, dest_point)?;
 
             let mut get_channel = |index: usize, shift: usize| -> Result<[u32; 256], Error<'gc>> {
                 let arg = args.get(index).unwrap_or(&Value::Null);
@@ -1127,11 +1197,53 @@ pub fn palette_map<'gc>(
 pub fn pixel_dissolve<'gc>(
     activation: &mut Activation<'_, 'gc>,
     this: Object<'gc>,
-    _args: &[Value<'gc>],
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.as_bitmap_data_object() {
         if !bitmap_data.disposed() {
-            avm1_stub!(activation, "BitmapData", "pixelDissolve");
+            let source_bitmap = args
+                .get(0)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+
+            let source_rect = args
+                .get(1)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+
+            let (src_min_x, src_min_y, src_width, src_height) =
+                rectangle_to_i32(activation, source_rect)?;
+
+            let dest_point = args
+                .get(2)
+                .unwrap_or(&Value::Undefined)
+                .coerce_to_object(activation);
+
+            let (dest_x, dest_y) = point_to_i32(activation, dest_point)?;
+
+            let random_seed = args.get(3).unwrap_or(&0.into()).coerce_to_i32(activation)?;
+
+            let num_pixels = args.get(4).unwrap_or(&0.into()).coerce_to_i32(activation)?;
+
+            let fill_color = args.get(5).unwrap_or(&0.into()).coerce_to_u32(activation)?;
+
+            if let Some(src_bitmap) = source_bitmap.as_bitmap_data_object() {
+                if !src_bitmap.disposed() {
+                    let next_seed = operations::pixel_dissolve(
+                        &mut activation.context,
+                        bitmap_data.bitmap_data_wrapper(),
+                        Some(src_bitmap.bitmap_data_wrapper()),
+                        (src_min_x, src_min_y, src_width, src_height),
+                        (dest_x, dest_y),
+                        random_seed,
+                        num_pixels,
+                        fill_color,
+                    );
+
+                    return Ok(next_seed.into());
+                }
+            }
+
             return Ok(Value::Undefined);
         }
     }
@@ -1186,26 +1298,15 @@ pub fn threshold<'gc>(
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let src_min_x = source_rect
-                .get("x", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_min_y = source_rect
-                .get("y", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_width = source_rect
-                .get("width", activation)?
-                .coerce_to_f64(activation)? as i32;
-            let src_height = source_rect
-                .get("height", activation)?
-                .coerce_to_f64(activation)? as i32;
+            let (src_min_x, src_min_y, src_width, src_height) =
+                rectangle_to_i32(activation, source_rect)?;
 
             let dest_point = args
                 .get(2)
                 .unwrap_or(&Value::Undefined)
                 .coerce_to_object(activation);
 
-            let dest_x = dest_point.get("x", activation)?.coerce_to_f64(activation)? as i32;
-            let dest_y = dest_point.get("y", activation)?.coerce_to_f64(activation)? as i32;
+            let (dest_x, dest_y) = point_to_i32(activation, dest_point)?;
 
             let operation = args.get(3);
             let operation = match ThresholdOperation::from_wstr(
@@ -1309,13 +1410,20 @@ pub fn compare<'gc>(
         return Ok(DIFFERENT_HEIGHTS.into());
     }
 
+    let (width, height) = (this_bitmap_data.width(), this_bitmap_data.height());
     match operations::compare(this_bitmap_data, other_bitmap_data) {
-        Some(bitmap_data) => Ok(BitmapDataObject::with_bitmap_data(
-            activation.context.gc_context,
-            activation.context.avm1.prototypes().bitmap_data,
-            bitmap_data,
-        )
-        .into()),
+        Some(mut bitmap_data) => {
+            let Some(memory) = allocate_memory(activation, width, height) else {
+                return Ok(Value::Undefined);
+            };
+            bitmap_data.set_memory(memory);
+            Ok(BitmapDataObject::with_bitmap_data(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().bitmap_data,
+                bitmap_data,
+            )
+            .into())
+        }
         None => Ok(EQUIVALENT.into()),
     }
 }
@@ -1344,28 +1452,35 @@ pub fn load_bitmap<'gc>(
     let library = &*activation.context.library;
 
     let movie = activation.target_clip_or_root().movie();
+    let case_sensitive = activation.is_case_sensitive();
 
     let character = library
         .library_for_movie(movie)
-        .and_then(|l| l.character_by_export_name(name));
+        .and_then(|l| l.character_by_export_name(name, case_sensitive))
+        .cloned();
 
     if let Some(Character::Bitmap(bitmap)) = character {
+        let width = bitmap.width() as u32;
+        let height = bitmap.height() as u32;
+        let Some(memory) = allocate_memory(activation, width, height) else {
+            return Ok(Value::Undefined);
+        };
+
         let new_bitmap_data = BitmapDataObject::empty_object(
             activation.context.gc_context,
             activation.context.avm1.prototypes().bitmap_data,
         );
 
-        let width = bitmap.width() as u32;
-        let height = bitmap.height() as u32;
-
-        let pixels: Vec<_> = bitmap.bitmap_data().read().pixels().to_vec();
-
-        new_bitmap_data
-            .as_bitmap_data_object()
-            .unwrap()
+        // Keep the embedded bitmap's own transparency, so that an opaque JPEG stays opaque
+        // and the (premultiplied) alpha of a transparent PNG comes across unchanged.
+        let source = bitmap.bitmap_data();
+        let source = source.read();
+        let new_bitmap_data_object = new_bitmap_data.as_bitmap_data_object().unwrap();
+        let mut write = new_bitmap_data_object
             .bitmap_data()
-            .write(activation.context.gc_context)
-            .set_pixels(width, height, true, pixels);
+            .write(activation.context.gc_context);
+        write.set_pixels(width, height, source.transparency(), source.shared_pixels());
+        write.set_memory(memory);
 
         return Ok(new_bitmap_data.into());
     }
@@ -1389,3 +1504,732 @@ pub fn create_bitmap_data_object<'gc>(
     define_properties_on(OBJECT_DECLS, gc_context, object, fn_proto);
     bitmap_data
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::avm1::ScriptObject;
+    use crate::bitmap::bitmap_data::{BitmapData, Color};
+    use crate::string::AvmString;
+    use crate::test_utils::RecordingSyncHandle;
+    use ruffle_render::bitmap::PixelRegion;
+    use ruffle_render::matrix::Matrix;
+
+    fn new_bitmap_data<'gc>(activation: &mut Activation<'_, 'gc>) -> BitmapDataObject<'gc> {
+        BitmapDataObject::with_bitmap_data(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().bitmap_data,
+            BitmapData::new_with_pixels(2, 2, true, vec![Color::from(0); 4]),
+        )
+    }
+
+    fn new_object<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        properties: &[(&'static str, f64)],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let object = ScriptObject::new(
+            activation.context.gc_context,
+            Some(activation.context.avm1.prototypes().object),
+        );
+        for (name, value) in properties {
+            object.set(*name, (*value).into(), activation)?;
+        }
+        Ok(object.into())
+    }
+
+    fn merge_from<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        source: Value<'gc>,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let target = new_bitmap_data(activation);
+        let rect = new_object(
+            activation,
+            &[("x", 0.0), ("y", 0.0), ("width", 2.0), ("height", 2.0)],
+        )?;
+        let point = new_object(activation, &[("x", 0.0), ("y", 0.0)])?;
+        merge(
+            activation,
+            target.into(),
+            &[
+                source,
+                rect,
+                point,
+                128.into(),
+                128.into(),
+                128.into(),
+                128.into(),
+            ],
+        )
+    }
+
+    #[test]
+    fn construct_from_bitmap_data() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let source = new_bitmap_data(activation);
+            source
+                .bitmap_data()
+                .write(activation.context.gc_context)
+                .set_pixel32_raw(1, 0, Color::from(0x7F102030));
+
+            let copy = BitmapDataObject::empty_object(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().bitmap_data,
+            );
+            constructor(activation, copy.into(), &[source.into()])?;
+
+            assert_eq!((copy.width(), copy.height()), (2, 2));
+            assert_eq!(copy.transparency(), source.transparency());
+            assert_eq!(
+                copy.bitmap_data().read().pixels(),
+                source.bitmap_data().read().pixels()
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn construct_from_untouched_bitmap_data() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            // The source's pixels haven't been allocated yet, only its fill color.
+            let source = BitmapDataObject::empty_object(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().bitmap_data,
+            );
+            let fill = 0xFF102030u32 as i32;
+            constructor(
+                activation,
+                source.into(),
+                &[2.into(), 2.into(), false.into(), fill.into()],
+            )?;
+
+            let copy = BitmapDataObject::empty_object(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().bitmap_data,
+            );
+            constructor(activation, copy.into(), &[source.into()])?;
+
+            assert_eq!((copy.width(), copy.height()), (2, 2));
+            assert_eq!(
+                copy.bitmap_data().read().get_pixel32_raw(1, 1),
+                Color::from(fill)
+            );
+            assert_eq!(
+                operations::get_pixel32(copy.bitmap_data_wrapper(), 1, 1),
+                fill
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn construct_from_gpu_modified_bitmap_data() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let source = BitmapDataObject::empty_object(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().bitmap_data,
+            );
+            constructor(
+                activation,
+                source.into(),
+                &[2.into(), 2.into(), true.into(), (-1).into()],
+            )?;
+            // Pretend a GPU operation cleared the bitmap, without reading it back yet.
+            source
+                .bitmap_data()
+                .write(activation.context.gc_context)
+                .set_gpu_dirty(
+                    Box::new(RecordingSyncHandle {
+                        width: 2,
+                        height: 2,
+                    }),
+                    PixelRegion::for_whole_size(2, 2),
+                );
+
+            let copy = BitmapDataObject::empty_object(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().bitmap_data,
+            );
+            constructor(activation, copy.into(), &[source.into()])?;
+            assert_eq!(operations::get_pixel32(copy.bitmap_data_wrapper(), 1, 1), 0);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn copy_pixels_floors_dest_point() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let source = new_bitmap_data(activation);
+            source
+                .bitmap_data()
+                .write(activation.context.gc_context)
+                .set_pixel32_raw(1, 1, Color::from(-1));
+
+            let target = new_bitmap_data(activation);
+            let rect = new_object(
+                activation,
+                &[("x", 0.0), ("y", 0.0), ("width", 2.0), ("height", 2.0)],
+            )?;
+            let point = new_object(activation, &[("x", -0.5), ("y", -0.5)])?;
+            copy_pixels(activation, target.into(), &[source.into(), rect, point])?;
+
+            // Truncating would have copied to (0, 0), leaving the white pixel at (1, 1).
+            assert_eq!(
+                target.bitmap_data().read().pixels(),
+                [
+                    Color::from(-1),
+                    Color::from(0),
+                    Color::from(0),
+                    Color::from(0)
+                ]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn rectangle_and_point_rounding() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let cases: &[(&[(&'static str, f64)], (i32, i32, i32, i32))] = &[
+                (&[("x", 0.6), ("width", 9.5)], (0, 0, 10, 0)),
+                (
+                    &[("x", -0.5), ("y", -1.5), ("width", 2.0), ("height", 0.6)],
+                    (-1, -2, 2, 1),
+                ),
+                (&[("y", 2.9), ("height", f64::NAN)], (0, 2, 0, 0)),
+                (&[], (0, 0, 0, 0)),
+            ];
+            for (properties, expected) in cases {
+                let rectangle = new_object(activation, properties)?.coerce_to_object(activation);
+                assert_eq!(rectangle_to_i32(activation, rectangle)?, *expected);
+            }
+
+            // Real `flash.geom` objects go through the same rules.
+            let rectangle = activation
+                .context
+                .avm1
+                .prototypes()
+                .rectangle_constructor
+                .construct(
+                    activation,
+                    &[0.5.into(), (-0.5).into(), 1.0.into(), 1.0.into()],
+                )?
+                .coerce_to_object(activation);
+            assert_eq!(rectangle_to_i32(activation, rectangle)?, (0, -1, 1, 1));
+            let point = activation
+                .context
+                .avm1
+                .prototypes()
+                .point_constructor
+                .construct(activation, &[(-0.1).into(), 1.9.into()])?
+                .coerce_to_object(activation);
+            assert_eq!(point_to_i32(activation, point)?, (-1, 1));
+            Ok(())
+        });
+    }
+
+    /// Runs every `BitmapData` method that takes a rectangle over a clear 2x2
+    /// bitmap, returning which of its pixels each method changed.
+    fn pixels_touched<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        rect: &[(&'static str, f64)],
+    ) -> Result<Vec<(&'static str, Vec<bool>)>, Error<'gc>> {
+        let white = new_bitmap_data(activation);
+        operations::fill_rect(
+            &mut activation.context,
+            white.bitmap_data_wrapper(),
+            0,
+            0,
+            2,
+            2,
+            -1,
+        );
+        let clear = new_bitmap_data(activation);
+        let rect = new_object(activation, rect)?;
+        let point = new_object(activation, &[("x", 0.0), ("y", 0.0)])?;
+        let transform = activation
+            .context
+            .avm1
+            .prototypes()
+            .color_transform_constructor
+            .construct(
+                activation,
+                &[
+                    1.into(),
+                    1.into(),
+                    1.into(),
+                    1.into(),
+                    255.into(),
+                    255.into(),
+                    255.into(),
+                    255.into(),
+                ],
+            )?;
+        let equals = AvmString::new_utf8(activation.context.gc_context, "==");
+
+        type Method<'a, 'gc> = fn(
+            &mut Activation<'a, 'gc>,
+            Object<'gc>,
+            &[Value<'gc>],
+        ) -> Result<Value<'gc>, Error<'gc>>;
+        let methods: [(&'static str, Method<'_, 'gc>, Vec<Value<'gc>>); 8] = [
+            ("fillRect", fill_rect, vec![rect, (-1).into()]),
+            ("colorTransform", color_transform, vec![rect, transform]),
+            ("copyPixels", copy_pixels, vec![white.into(), rect, point]),
+            (
+                "copyChannel",
+                copy_channel,
+                vec![white.into(), rect, point, 8.into(), 8.into()],
+            ),
+            (
+                "merge",
+                merge,
+                vec![
+                    white.into(),
+                    rect,
+                    point,
+                    256.into(),
+                    256.into(),
+                    256.into(),
+                    256.into(),
+                ],
+            ),
+            ("paletteMap", palette_map, vec![white.into(), rect, point]),
+            (
+                "pixelDissolve",
+                pixel_dissolve,
+                vec![white.into(), rect, point, 0.into(), 4.into()],
+            ),
+            (
+                "threshold",
+                threshold,
+                vec![
+                    clear.into(),
+                    rect,
+                    point,
+                    equals.into(),
+                    0.into(),
+                    (-1).into(),
+                ],
+            ),
+        ];
+
+        let mut touched = vec![];
+        for (name, method, args) in methods {
+            let target = new_bitmap_data(activation);
+            method(activation, target.into(), &args)?;
+            let pixels = target.bitmap_data().read().pixels().to_vec();
+            touched.push((
+                name,
+                pixels.into_iter().map(|c| c != Color::from(0)).collect(),
+            ));
+        }
+        Ok(touched)
+    }
+
+    #[test]
+    fn fractional_rectangles_in_every_method() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            // Covers the whole top row; truncating each field would only cover (0, 0).
+            let rect = [("x", 0.6), ("y", 0.5), ("width", 1.5), ("height", 1.0)];
+            for (name, touched) in pixels_touched(activation, &rect)? {
+                assert_eq!(touched, [true, true, false, false], "{name}");
+            }
+
+            // Starts at (-1, -1), so only one pixel lands inside the bitmap. Truncating
+            // each field would have covered all four.
+            let rect = [("x", -0.5), ("y", -0.5), ("width", 2.0), ("height", 2.0)];
+            for (name, touched) in pixels_touched(activation, &rect)? {
+                let expected = match name {
+                    // These work on the target alone.
+                    "fillRect" | "colorTransform" => [true, false, false, false],
+                    // The source's (0, 0) lands one pixel in from the destination point.
+                    _ => [false, false, false, true],
+                };
+                assert_eq!(touched, expected, "{name}");
+            }
+            Ok(())
+        });
+    }
+
+    /// Draws a 2x2 bitmap with a white top-left pixel with `matrix`, returning the result.
+    fn draw_with_matrix<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        matrix: Value<'gc>,
+    ) -> Result<Vec<Color>, Error<'gc>> {
+        let source = new_bitmap_data(activation);
+        source
+            .bitmap_data()
+            .write(activation.context.gc_context)
+            .set_pixel32_raw(0, 0, Color::from(-1));
+        let target = new_bitmap_data(activation);
+        draw(activation, target.into(), &[source.into(), matrix])?;
+        let pixels = target.bitmap_data().read().pixels().to_vec();
+        Ok(pixels)
+    }
+
+    #[test]
+    fn plain_object_matrices() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let scale = new_object(activation, &[("a", 2.0), ("d", 2.0)])?;
+            let scale = object_to_matrix(scale.coerce_to_object(activation), activation)?;
+            assert_eq!(scale, Matrix::scale(2.0, 2.0));
+
+            let white = Color::from(-1);
+            let clear = Color::from(0);
+
+            // Missing properties keep their identity values, rather than becoming NaN.
+            let identity = new_object(activation, &[])?;
+            assert_eq!(
+                draw_with_matrix(activation, identity)?,
+                [white, clear, clear, clear]
+            );
+            let translate = new_object(activation, &[("tx", 1.0)])?;
+            assert_eq!(
+                draw_with_matrix(activation, translate)?,
+                [clear, white, clear, clear]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn plain_object_color_transforms() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let blue = Color::from(0xFF0000FFu32 as i32);
+            let green = Color::from(0xFF00FF00u32 as i32);
+            let clear = Color::from(0);
+
+            // `draw` tints with either the `ColorTransform` names or the legacy ones.
+            let identity = new_object(activation, &[])?;
+            for (properties, expected) in [
+                (&[("redMultiplier", 0.0), ("greenOffset", -255.0)], blue),
+                (&[("ra", 0.0), ("bb", -255.0)], green),
+            ] {
+                let source = new_bitmap_data(activation);
+                source
+                    .bitmap_data()
+                    .write(activation.context.gc_context)
+                    .set_pixel32_raw(0, 0, Color::from(-1));
+                let target = new_bitmap_data(activation);
+                let transform = new_object(activation, properties)?;
+                draw(
+                    activation,
+                    target.into(),
+                    &[source.into(), identity, transform],
+                )?;
+                assert_eq!(
+                    target.bitmap_data().read().pixels(),
+                    [expected, clear, clear, clear]
+                );
+            }
+
+            // So does `colorTransform`, which used to reject anything but a real one.
+            let target = new_bitmap_data(activation);
+            operations::fill_rect(
+                &mut activation.context,
+                target.bitmap_data_wrapper(),
+                0,
+                0,
+                2,
+                2,
+                -1,
+            );
+            let rect = new_object(
+                activation,
+                &[("x", 0.0), ("y", 0.0), ("width", 1.0), ("height", 1.0)],
+            )?;
+            let transform = new_object(activation, &[("redMultiplier", 0.0), ("gb", -255.0)])?;
+            color_transform(activation, target.into(), &[rect, transform])?;
+            let white = Color::from(-1);
+            assert_eq!(
+                target.bitmap_data().read().pixels(),
+                [blue, white, white, white]
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn merge_null_source() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            assert_eq!(merge_from(activation, Value::Null)?, (-1).into());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn merge_non_bitmap_source() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let source = new_object(activation, &[])?;
+            assert_eq!(merge_from(activation, source)?, (-1).into());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn merge_disposed_source() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let source = new_bitmap_data(activation);
+            source.dispose(&mut activation.context);
+            assert_eq!(merge_from(activation, source.into())?, (-2).into());
+
+            let source = new_bitmap_data(activation);
+            assert_eq!(merge_from(activation, source.into())?, Value::Undefined);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn draw_renders_text_field_border_and_text() {
+        use crate::avm1::function::ExecutionReason;
+        use crate::avm1::test_utils::with_avm_and_renderer;
+        use crate::bitmap::bitmap_data::BitmapDataWrapper;
+        use crate::test_utils::RecordingRenderer;
+        use gc_arena::GcCell;
+        use ruffle_render::commands::Command;
+        use ruffle_render::quality::StageQuality;
+        use swf::Twips;
+
+        // The null renderer can't draw offscreen, so `draw` would fall back to the CPU.
+        with_avm_and_renderer(
+            8,
+            RecordingRenderer::default(),
+            |activation, root| -> Result<(), Error> {
+                let text_field = root.call_method(
+                    "createTextField".into(),
+                    &[
+                        "field".into(),
+                        1.into(),
+                        0.into(),
+                        0.into(),
+                        100.into(),
+                        20.into(),
+                    ],
+                    activation,
+                    ExecutionReason::Special,
+                )?;
+                let text_field = text_field.coerce_to_object(activation);
+                text_field.set("border", true.into(), activation)?;
+                text_field.set("text", "Hi".into(), activation)?;
+                let size = text_field
+                    .call_method(
+                        "getTextFormat".into(),
+                        &[],
+                        activation,
+                        ExecutionReason::Special,
+                    )?
+                    .coerce_to_object(activation)
+                    .get("size", activation)?
+                    .coerce_to_f64(activation)?;
+                let text_field = text_field.as_display_object().unwrap();
+
+                let context = &mut activation.context;
+                let target = BitmapDataWrapper::new(GcCell::allocate(
+                    context.gc_context,
+                    BitmapData::new_with_pixels(120, 40, true, vec![Color::from(0); 120 * 40]),
+                ));
+                let offset = Twips::from_pixels(4.0);
+                let result = operations::draw(
+                    context,
+                    target,
+                    IBitmapDrawable::DisplayObject(text_field),
+                    Transform {
+                        matrix: Matrix::translate(offset, offset),
+                        color_transform: Default::default(),
+                    },
+                    false,
+                    BlendMode::Normal,
+                    None,
+                    StageQuality::High,
+                    false,
+                );
+                assert!(result.is_ok());
+
+                let renderer = context
+                    .renderer
+                    .downcast_ref::<RecordingRenderer>()
+                    .unwrap();
+                let commands = &renderer.offscreen_renders.last().unwrap().commands;
+
+                // The border is drawn first, where the field was drawn to.
+                match commands.first() {
+                    Some(Command::RenderShape { transform, .. }) => {
+                        assert_eq!(transform.matrix, Matrix::translate(offset, offset));
+                    }
+                    command => panic!("expected the border first, got {command:?}"),
+                }
+
+                // Then the text, inside the field's mask.
+                let text_start = commands
+                    .iter()
+                    .position(|command| matches!(command, Command::ActivateMask))
+                    .expect("the text should be masked");
+                let text_end = commands
+                    .iter()
+                    .position(|command| matches!(command, Command::DeactivateMask))
+                    .expect("the mask should be deactivated");
+                let glyphs: Vec<_> = commands[text_start..text_end]
+                    .iter()
+                    .filter_map(|command| match command {
+                        Command::RenderShape { transform, .. } => Some(transform),
+                        _ => None,
+                    })
+                    .collect();
+                assert_eq!(glyphs.len(), 2);
+
+                // Text starts inside the field's 2 pixel padding, with glyphs sitting on the
+                // baseline.
+                let font = context.library.device_font().unwrap();
+                let baseline = offset
+                    + Twips::from_pixels(2.0)
+                    + font.get_baseline_for_height(Twips::from_pixels(size));
+                for glyph in glyphs {
+                    assert!(
+                        (glyph.matrix.ty - baseline).to_pixels().abs() < 1.0,
+                        "glyph drawn at {}, but the baseline is at {baseline}",
+                        glyph.matrix.ty
+                    );
+                    assert!(glyph.matrix.tx >= offset + Twips::from_pixels(2.0));
+                }
+                Ok(())
+            },
+        );
+    }
+
+    /// Registers `bitmap` in the root movie's library, exported as `name`.
+    fn embed_bitmap<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        root: Object<'gc>,
+        id: u16,
+        name: &'static str,
+        bitmap: ruffle_render::bitmap::Bitmap,
+    ) {
+        let movie = root.as_display_object().unwrap().movie();
+        let bitmap =
+            crate::display_object::Bitmap::new(&mut activation.context, id, bitmap).unwrap();
+        let library = activation.context.library.library_for_movie_mut(movie);
+        library.register_character(id, Character::Bitmap(bitmap));
+        library.register_export(id, AvmString::new_utf8(activation.context.gc_context, name));
+    }
+
+    #[test]
+    fn load_bitmap_keeps_transparency() {
+        use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+
+        fn load<'gc>(
+            activation: &mut Activation<'_, 'gc>,
+            root: Object<'gc>,
+            name: &'static str,
+        ) -> Result<(bool, Color), Error<'gc>> {
+            let bitmap_data = load_bitmap(activation, root, &[name.into()])?;
+            let bitmap_data = bitmap_data.coerce_to_object(activation);
+            let bitmap_data = bitmap_data.as_bitmap_data_object().unwrap().bitmap_data();
+            let bitmap_data = bitmap_data.read();
+            Ok((
+                bitmap_data.transparency(),
+                bitmap_data.get_pixel32_raw(0, 0),
+            ))
+        }
+
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let transparent = Bitmap::new(1, 1, BitmapFormat::Rgba, vec![0x20, 0x40, 0x60, 0x80]);
+            embed_bitmap(activation, root, 1, "transparent", transparent);
+            let opaque = Bitmap::new(1, 1, BitmapFormat::Rgb, vec![0x20, 0x40, 0x60]);
+            embed_bitmap(activation, root, 2, "opaque", opaque);
+
+            // Pixels are stored premultiplied, exactly as the embedded bitmap had them.
+            assert_eq!(
+                load(activation, root, "transparent")?,
+                (true, Color::from(0x80204060u32 as i32))
+            );
+            assert_eq!(
+                load(activation, root, "opaque")?,
+                (false, Color::from(0xFF204060u32 as i32))
+            );
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn load_bitmap_export_names_are_case_sensitive_from_swf7() {
+        use ruffle_render::bitmap::{Bitmap, BitmapFormat};
+
+        for (version, found) in [(6, true), (7, false), (8, false)] {
+            with_avm(version, |activation, root| -> Result<(), Error> {
+                let bitmap = Bitmap::new(1, 1, BitmapFormat::Rgb, vec![0; 3]);
+                embed_bitmap(activation, root, 1, "background", bitmap);
+
+                let exact = load_bitmap(activation, root, &["background".into()])?;
+                assert!(matches!(exact, Value::Object(_)), "SWF{version}");
+                let other_case = load_bitmap(activation, root, &["Background".into()])?;
+                assert_eq!(
+                    matches!(other_case, Value::Object(_)),
+                    found,
+                    "SWF{version}"
+                );
+                Ok(())
+            });
+        }
+    }
+
+    #[test]
+    fn constructor_uses_default_fill_color() {
+        fn first_pixel<'gc>(
+            activation: &mut Activation<'_, 'gc>,
+            args: &[Value<'gc>],
+        ) -> Result<i32, Error<'gc>> {
+            let this = BitmapDataObject::empty_object(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().bitmap_data,
+            );
+            constructor(activation, this.into(), args)?;
+            Ok(operations::get_pixel32(this.bitmap_data_wrapper(), 0, 0))
+        }
+
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            let size = [2.into(), 2.into(), true.into()];
+            assert_eq!(first_pixel(activation, &size)?, -1);
+
+            activation.context.default_bitmap_fill_color = 0x00000000;
+            assert_eq!(first_pixel(activation, &size)?, 0);
+
+            // A color passed by the script still wins.
+            let red = [2.into(), 2.into(), true.into(), 0xFFFF0000u32.into()];
+            assert_eq!(first_pixel(activation, &red)?, 0xFFFF0000u32 as i32);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn stubs_are_recorded_once() {
+        use crate::stub::Stub;
+        use std::cell::Cell;
+        use std::rc::Rc;
+
+        let reported = Rc::new(Cell::new(0));
+        let counter = reported.clone();
+        with_avm(8, move |activation, _root| -> Result<(), Error> {
+            activation
+                .context
+                .stub_tracker
+                .set_on_new_stub(Some(Box::new(move |_| counter.set(counter.get() + 1))));
+
+            let this = new_bitmap_data(activation).into();
+            apply_filter(activation, this, &[])?;
+            apply_filter(activation, this, &[])?;
+
+            let stubs: Vec<_> = activation.context.stub_tracker.iter().collect();
+            assert_eq!(
+                stubs,
+                [&Stub::Avm1Method {
+                    class: "BitmapData",
+                    method: "applyFilter",
+                    specifics: None,
+                }]
+            );
+            Ok(())
+        });
+        assert_eq!(reported.get(), 1);
+    }
+}
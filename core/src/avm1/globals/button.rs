@@ -7,8 +7,6 @@ use crate::avm1::{globals, Object, ScriptObject, TObject, Value};
 use crate::display_object::{Avm1Button, TDisplayObject};
 use crate::string::AvmString;
 use gc_arena::MutationContext;
-use std::str::FromStr;
-use swf::BlendMode;
 
 macro_rules! button_getter {
     ($name:ident) => {
@@ -42,6 +40,8 @@ const PROTO_DECLS: &[Declaration] = declare_properties! {
     "getDepth" => method(globals::get_depth; DONT_ENUM | DONT_DELETE | READ_ONLY | VERSION_6);
     "useHandCursor" => property(button_getter!(use_hand_cursor), button_setter!(set_use_hand_cursor));
     "blendMode" => property(button_getter!(blend_mode), button_setter!(set_blend_mode); DONT_DELETE | DONT_ENUM);
+    "filters" => property(globals::get_filters, globals::set_filters; DONT_DELETE | DONT_ENUM | VERSION_8);
+    "cacheAsBitmap" => property(globals::get_cache_as_bitmap, globals::set_cache_as_bitmap; DONT_DELETE | DONT_ENUM | VERSION_8);
 };
 
 pub fn create_proto<'gc>(
@@ -110,13 +110,10 @@ fn set_blend_mode<'gc>(
     activation: &mut Activation<'_, 'gc>,
     value: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    // No-op if value is not a string.
-    if let Value::String(mode) = value {
-        if let Ok(mode) = BlendMode::from_str(&mode.to_string()) {
-            this.set_blend_mode(activation.context.gc_context, mode);
-        } else {
-            tracing::error!("Unknown blend mode {}", mode);
-        };
+    // Invalid values leave the blend mode as it was.
+    match globals::blend_mode_from_value(activation, value)? {
+        Some(mode) => this.set_blend_mode(activation.context.gc_context, mode),
+        None => tracing::error!("Unknown blend mode {:?}", value),
     }
     Ok(())
 }
@@ -62,6 +62,53 @@ impl<'gc> ColorTransformObject {
         }
         None
     }
+
+    /// Reads a color transform from `value`, which need not be a real
+    /// `ColorTransform`: any object with the same properties will do.
+    ///
+    /// Plain objects may also use the names from `Color.setTransform` (`ra`,
+    /// `rb`, and so on), where the multipliers are percentages. Properties that
+    /// are missing keep their identity values. Returns `None` if `value` isn't
+    /// an object.
+    pub fn from_value(
+        activation: &mut Activation<'_, 'gc>,
+        value: Value<'gc>,
+    ) -> Result<Option<Self>, Error<'gc>> {
+        if let Some(color_transform) = Self::cast(value) {
+            return Ok(Some(color_transform.read().clone()));
+        }
+        let Value::Object(object) = value else {
+            return Ok(None);
+        };
+
+        let mut read = |name: &'static str,
+                        legacy_name: &'static str,
+                        legacy_scale: f64,
+                        default: f64|
+         -> Result<f64, Error<'gc>> {
+            if object.has_property(activation, name.into()) {
+                object.get(name, activation)?.coerce_to_f64(activation)
+            } else if object.has_property(activation, legacy_name.into()) {
+                let value = object
+                    .get(legacy_name, activation)?
+                    .coerce_to_f64(activation)?;
+                Ok(value * legacy_scale)
+            } else {
+                Ok(default)
+            }
+        };
+
+        Ok(Some(Self {
+            red_multiplier: read("redMultiplier", "ra", 0.01, 1.0)?,
+            green_multiplier: read("greenMultiplier", "ga", 0.01, 1.0)?,
+            blue_multiplier: read("blueMultiplier", "ba", 0.01, 1.0)?,
+            alpha_multiplier: read("alphaMultiplier", "aa", 0.01, 1.0)?,
+            red_offset: read("redOffset", "rb", 1.0, 0.0)?,
+            green_offset: read("greenOffset", "gb", 1.0, 0.0)?,
+            blue_offset: read("blueOffset", "bb", 1.0, 0.0)?,
+            alpha_offset: read("alphaOffset", "ab", 1.0, 0.0)?,
+        }))
+    }
 }
 
 impl From<ColorTransformObject> for ColorTransform {
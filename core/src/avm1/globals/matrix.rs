@@ -26,42 +26,6 @@ const PROTO_DECLS: &[Declaration] = declare_properties! {
     "deltaTransformPoint" => method(delta_transform_point);
 };
 
-pub fn value_to_matrix<'gc>(
-    value: Value<'gc>,
-    activation: &mut Activation<'_, 'gc>,
-) -> Result<Matrix, Error<'gc>> {
-    let a = value
-        .coerce_to_object(activation)
-        .get("a", activation)?
-        .coerce_to_f64(activation)? as f32;
-    let b = value
-        .coerce_to_object(activation)
-        .get("b", activation)?
-        .coerce_to_f64(activation)? as f32;
-    let c = value
-        .coerce_to_object(activation)
-        .get("c", activation)?
-        .coerce_to_f64(activation)? as f32;
-    let d = value
-        .coerce_to_object(activation)
-        .get("d", activation)?
-        .coerce_to_f64(activation)? as f32;
-    let tx = Twips::from_pixels(
-        value
-            .coerce_to_object(activation)
-            .get("tx", activation)?
-            .coerce_to_f64(activation)?,
-    );
-    let ty = Twips::from_pixels(
-        value
-            .coerce_to_object(activation)
-            .get("ty", activation)?
-            .coerce_to_f64(activation)?,
-    );
-
-    Ok(Matrix { a, b, c, d, tx, ty })
-}
-
 pub fn gradient_object_to_matrix<'gc>(
     object: Object<'gc>,
     activation: &mut Activation<'_, 'gc>,
@@ -89,18 +53,160 @@ pub fn gradient_object_to_matrix<'gc>(
     }
 }
 
+/// The properties of a `flash.geom.Matrix`, at full precision.
+///
+/// The `Matrix` methods work on these directly. Going through a render `Matrix`
+/// would round the scale to `f32` and the translation to twips, which adds up
+/// when points are mapped through a matrix and back again.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct MatrixProperties {
+    a: f64,
+    b: f64,
+    c: f64,
+    d: f64,
+    tx: f64,
+    ty: f64,
+}
+
+impl MatrixProperties {
+    const IDENTITY: Self = Self {
+        a: 1.0,
+        b: 0.0,
+        c: 0.0,
+        d: 1.0,
+        tx: 0.0,
+        ty: 0.0,
+    };
+
+    /// Reads the properties of `object`, where any that are missing (or
+    /// `undefined`) keep their value from the identity matrix.
+    fn read<'gc>(
+        object: Object<'gc>,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Self, Error<'gc>> {
+        let mut get = |name: &'static str, default: f64| -> Result<f64, Error<'gc>> {
+            match object.get(name, activation)? {
+                Value::Undefined => Ok(default),
+                value => value.coerce_to_f64(activation),
+            }
+        };
+        Ok(Self {
+            a: get("a", 1.0)?,
+            b: get("b", 0.0)?,
+            c: get("c", 0.0)?,
+            d: get("d", 1.0)?,
+            tx: get("tx", 0.0)?,
+            ty: get("ty", 0.0)?,
+        })
+    }
+
+    fn write<'gc>(
+        self,
+        object: Object<'gc>,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<(), Error<'gc>> {
+        object.set("a", self.a.into(), activation)?;
+        object.set("b", self.b.into(), activation)?;
+        object.set("c", self.c.into(), activation)?;
+        object.set("d", self.d.into(), activation)?;
+        object.set("tx", self.tx.into(), activation)?;
+        object.set("ty", self.ty.into(), activation)?;
+        Ok(())
+    }
+
+    fn create_box(scale_x: f64, scale_y: f64, rotation: f64, tx: f64, ty: f64) -> Self {
+        let (sin, cos) = rotation.sin_cos();
+        Self {
+            a: cos * scale_x,
+            b: sin * scale_y,
+            c: -sin * scale_x,
+            d: cos * scale_y,
+            tx,
+            ty,
+        }
+    }
+
+    /// The matrix that applies `self`, then `other`.
+    fn concat(self, other: Self) -> Self {
+        Self {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            tx: self.tx * other.a + self.ty * other.c + other.tx,
+            ty: self.tx * other.b + self.ty * other.d + other.ty,
+        }
+    }
+
+    /// Inverts this matrix the way Flash does.
+    ///
+    /// A matrix that only scales is inverted channel by channel, so a zero
+    /// scale turns into an infinite one. Any other singular matrix becomes
+    /// the identity.
+    fn invert(self) -> Self {
+        if self.b == 0.0 && self.c == 0.0 {
+            let a = 1.0 / self.a;
+            let d = 1.0 / self.d;
+            return Self {
+                a,
+                b: 0.0,
+                c: 0.0,
+                d,
+                tx: -a * self.tx,
+                ty: -d * self.ty,
+            };
+        }
+
+        let determinant = self.a * self.d - self.b * self.c;
+        if determinant == 0.0 {
+            return Self::IDENTITY;
+        }
+        let a = self.d / determinant;
+        let b = -self.b / determinant;
+        let c = -self.c / determinant;
+        let d = self.a / determinant;
+        Self {
+            a,
+            b,
+            c,
+            d,
+            tx: -(a * self.tx + c * self.ty),
+            ty: -(b * self.tx + d * self.ty),
+        }
+    }
+
+    fn delta_transform_point(self, (x, y): (f64, f64)) -> (f64, f64) {
+        (x * self.a + y * self.c, x * self.b + y * self.d)
+    }
+
+    fn transform_point(self, point: (f64, f64)) -> (f64, f64) {
+        let (x, y) = self.delta_transform_point(point);
+        (x + self.tx, y + self.ty)
+    }
+}
+
+impl From<MatrixProperties> for Matrix {
+    fn from(matrix: MatrixProperties) -> Self {
+        Self {
+            a: matrix.a as f32,
+            b: matrix.b as f32,
+            c: matrix.c as f32,
+            d: matrix.d as f32,
+            tx: Twips::from_pixels(matrix.tx),
+            ty: Twips::from_pixels(matrix.ty),
+        }
+    }
+}
+
+/// Returns a `Matrix` with the properties from `object`.
+///
+/// Any of the `a`, `b`, `c`, `d`, `tx` and `ty` properties that are missing (or `undefined`)
+/// keep their value from the identity matrix, so plain objects like `{a: 2, d: 2}` work too.
 pub fn object_to_matrix<'gc>(
     object: Object<'gc>,
     activation: &mut Activation<'_, 'gc>,
 ) -> Result<Matrix, Error<'gc>> {
-    let a = object.get("a", activation)?.coerce_to_f64(activation)? as f32;
-    let b = object.get("b", activation)?.coerce_to_f64(activation)? as f32;
-    let c = object.get("c", activation)?.coerce_to_f64(activation)? as f32;
-    let d = object.get("d", activation)?.coerce_to_f64(activation)? as f32;
-    let tx = Twips::from_pixels(object.get("tx", activation)?.coerce_to_f64(activation)?);
-    let ty = Twips::from_pixels(object.get("ty", activation)?.coerce_to_f64(activation)?);
-
-    Ok(Matrix { a, b, c, d, tx, ty })
+    MatrixProperties::read(object, activation).map(Into::into)
 }
 
 /// Returns a `Matrix` with the properties from `object`.
@@ -111,34 +217,18 @@ pub fn object_to_matrix_or_default<'gc>(
     activation: &mut Activation<'_, 'gc>,
 ) -> Result<Matrix, Error<'gc>> {
     // These lookups do not search the prototype chain and ignore virtual properties.
-    let a = object
-        .get_local_stored("a", activation)
-        .unwrap_or(Value::Undefined)
-        .coerce_to_f64(activation)? as f32;
-    let b = object
-        .get_local_stored("b", activation)
-        .unwrap_or(Value::Undefined)
-        .coerce_to_f64(activation)? as f32;
-    let c = object
-        .get_local_stored("c", activation)
-        .unwrap_or(Value::Undefined)
-        .coerce_to_f64(activation)? as f32;
-    let d = object
-        .get_local_stored("d", activation)
-        .unwrap_or(Value::Undefined)
-        .coerce_to_f64(activation)? as f32;
-    let tx = Twips::from_pixels(
-        object
-            .get_local_stored("tx", activation)
-            .unwrap_or(Value::Undefined)
-            .coerce_to_f64(activation)?,
-    );
-    let ty = Twips::from_pixels(
-        object
-            .get_local_stored("ty", activation)
-            .unwrap_or(Value::Undefined)
-            .coerce_to_f64(activation)?,
-    );
+    let [a, b, c, d, tx, ty] =
+        ["a", "b", "c", "d", "tx", "ty"].map(|name| object.get_local_stored(name, activation));
+    let (Some(a), Some(b), Some(c), Some(d), Some(tx), Some(ty)) = (a, b, c, d, tx, ty) else {
+        return Ok(Matrix::IDENTITY);
+    };
+
+    let a = a.coerce_to_f64(activation)? as f32;
+    let b = b.coerce_to_f64(activation)? as f32;
+    let c = c.coerce_to_f64(activation)? as f32;
+    let d = d.coerce_to_f64(activation)? as f32;
+    let tx = Twips::from_pixels(tx.coerce_to_f64(activation)?);
+    let ty = Twips::from_pixels(ty.coerce_to_f64(activation)?);
     Ok(Matrix { a, b, c, d, tx, ty })
 }
 
@@ -244,9 +334,9 @@ fn scale<'gc>(
         .get(1)
         .unwrap_or(&Value::Undefined)
         .coerce_to_f64(activation)?;
-    let mut matrix = Matrix::scale(scale_x as f32, scale_y as f32);
-    matrix *= object_to_matrix(this, activation)?;
-    apply_matrix_to_object(matrix, this, activation)?;
+    let scale = MatrixProperties::create_box(scale_x, scale_y, 0.0, 0.0, 0.0);
+    let matrix = MatrixProperties::read(this, activation)?.concat(scale);
+    matrix.write(this, activation)?;
 
     Ok(Value::Undefined)
 }
@@ -260,9 +350,9 @@ fn rotate<'gc>(
         .get(0)
         .unwrap_or(&Value::Undefined)
         .coerce_to_f64(activation)?;
-    let mut matrix = Matrix::rotate(angle as f32);
-    matrix *= object_to_matrix(this, activation)?;
-    apply_matrix_to_object(matrix, this, activation)?;
+    let rotation = MatrixProperties::create_box(1.0, 1.0, angle, 0.0, 0.0);
+    let matrix = MatrixProperties::read(this, activation)?.concat(rotation);
+    matrix.write(this, activation)?;
 
     Ok(Value::Undefined)
 }
@@ -280,12 +370,10 @@ fn translate<'gc>(
         .get(1)
         .unwrap_or(&Value::Undefined)
         .coerce_to_f64(activation)?;
-    let mut matrix = Matrix::translate(
-        Twips::from_pixels(translate_x),
-        Twips::from_pixels(translate_y),
-    );
-    matrix *= object_to_matrix(this, activation)?;
-    apply_matrix_to_object(matrix, this, activation)?;
+    let mut matrix = MatrixProperties::read(this, activation)?;
+    matrix.tx += translate_x;
+    matrix.ty += translate_y;
+    matrix.write(this, activation)?;
 
     Ok(Value::Undefined)
 }
@@ -295,10 +383,13 @@ fn concat<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let mut matrix = object_to_matrix(this, activation)?;
-    let other = value_to_matrix(*args.get(0).unwrap_or(&Value::Undefined), activation)?;
-    matrix = other * matrix;
-    apply_matrix_to_object(matrix, this, activation)?;
+    let matrix = MatrixProperties::read(this, activation)?;
+    let other = args
+        .get(0)
+        .unwrap_or(&Value::Undefined)
+        .coerce_to_object(activation);
+    let other = MatrixProperties::read(other, activation)?;
+    matrix.concat(other).write(this, activation)?;
 
     Ok(Value::Undefined)
 }
@@ -308,14 +399,8 @@ fn invert<'gc>(
     this: Object<'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // FIXME:
-    // 1) `invert` and other Matrix methods need to operate on `f64`, not with `ruffle_render::Matrix`.
-    // 2) If non-invertible, we are always setting to an identity matrix. But Flash only return identity
-    //    if `c != 0 && b != 0`? Otherwise it results in a matrix with infinities.
-    let matrix = object_to_matrix(this, activation)?
-        .inverse()
-        .unwrap_or_default();
-    apply_matrix_to_object(matrix, this, activation)?;
+    let matrix = MatrixProperties::read(this, activation)?.invert();
+    matrix.write(this, activation)?;
 
     Ok(Value::Undefined)
 }
@@ -349,14 +434,8 @@ fn create_box<'gc>(
         0.0
     };
 
-    let matrix = Matrix::create_box(
-        scale_x as f32,
-        scale_y as f32,
-        rotation as f32,
-        Twips::from_pixels(translate_x),
-        Twips::from_pixels(translate_y),
-    );
-    apply_matrix_to_object(matrix, this, activation)?;
+    let matrix = MatrixProperties::create_box(scale_x, scale_y, rotation, translate_x, translate_y);
+    matrix.write(this, activation)?;
 
     Ok(Value::Undefined)
 }
@@ -390,14 +469,14 @@ fn create_gradient_box<'gc>(
         0.0
     };
 
-    let matrix = Matrix::create_gradient_box(
-        width as f32,
-        height as f32,
-        rotation as f32,
-        Twips::from_pixels(translate_x),
-        Twips::from_pixels(translate_y),
+    let matrix = MatrixProperties::create_box(
+        width / 1638.4,
+        height / 1638.4,
+        rotation,
+        translate_x + width / 2.0,
+        translate_y + height / 2.0,
     );
-    apply_matrix_to_object(matrix, this, activation)?;
+    matrix.write(this, activation)?;
 
     Ok(Value::Undefined)
 }
@@ -407,15 +486,13 @@ fn transform_point<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let matrix = object_to_matrix(this, activation)?;
+    let matrix = MatrixProperties::read(this, activation)?;
     let point = value_to_point(
         args.get(0).unwrap_or(&Value::Undefined).to_owned(),
         activation,
     )?;
 
-    let x = point.0 * matrix.a as f64 + point.1 * matrix.c as f64 + matrix.tx.to_pixels();
-    let y = point.0 * matrix.b as f64 + point.1 * matrix.d as f64 + matrix.ty.to_pixels();
-    let object = point_to_object((x, y), activation)?;
+    let object = point_to_object(matrix.transform_point(point), activation)?;
     Ok(object)
 }
 
@@ -424,15 +501,13 @@ fn delta_transform_point<'gc>(
     this: Object<'gc>,
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    let matrix = object_to_matrix(this, activation)?;
+    let matrix = MatrixProperties::read(this, activation)?;
     let point = value_to_point(
         args.get(0).unwrap_or(&Value::Undefined).to_owned(),
         activation,
     )?;
 
-    let x = point.0 * matrix.a as f64 + point.1 * matrix.c as f64;
-    let y = point.0 * matrix.b as f64 + point.1 * matrix.d as f64;
-    let object = point_to_object((x, y), activation)?;
+    let object = point_to_object(matrix.delta_transform_point(point), activation)?;
     Ok(object)
 }
 
@@ -486,3 +561,92 @@ pub fn create_proto<'gc>(
     define_properties_on(PROTO_DECLS, gc_context, object, fn_proto);
     object.into()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::globals::point::object_to_point;
+    use crate::avm1::test_utils::with_avm;
+
+    fn new_matrix<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        values: [f64; 6],
+    ) -> Result<Object<'gc>, Error<'gc>> {
+        let constructor = activation.context.avm1.prototypes().matrix_constructor;
+        let args = values.map(Value::from);
+        Ok(constructor
+            .construct(activation, &args)?
+            .coerce_to_object(activation))
+    }
+
+    fn call_transform_point<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        matrix: Object<'gc>,
+        point: (f64, f64),
+    ) -> Result<(f64, f64), Error<'gc>> {
+        let point = point_to_object(point, activation)?;
+        let result = transform_point(activation, matrix, &[point])?.coerce_to_object(activation);
+        object_to_point(result, activation)
+    }
+
+    #[test]
+    fn transform_point_round_trips() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            for scale in [1.0, 37.5, 4096.0] {
+                let matrix = new_matrix(activation, [1.0, 0.0, 0.0, 1.0, 0.0, 0.0])?;
+                create_box(
+                    activation,
+                    matrix,
+                    &[
+                        scale.into(),
+                        (scale * 0.75).into(),
+                        0.3.into(),
+                        12.345.into(),
+                        (-6.789).into(),
+                    ],
+                )?;
+                let inverse = clone(activation, matrix, &[])?.coerce_to_object(activation);
+                invert(activation, inverse, &[])?;
+
+                for point in [(0.0, 0.0), (0.05, -0.05), (123.456, 78.9)] {
+                    let (x, y) = call_transform_point(activation, matrix, point)?;
+                    let back = call_transform_point(activation, inverse, (x, y))?;
+                    assert!(
+                        (back.0 - point.0).abs() < 1e-9 && (back.1 - point.1).abs() < 1e-9,
+                        "scale {scale}: {point:?} came back as {back:?}"
+                    );
+                }
+            }
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn invert_singular_matrices() {
+        with_avm(8, |activation, _root| -> Result<(), Error> {
+            // Singular matrices that rotate or skew become the identity.
+            let matrix = new_matrix(activation, [1.0, 2.0, 2.0, 4.0, 5.0, 6.0])?;
+            invert(activation, matrix, &[])?;
+            assert_eq!(
+                MatrixProperties::read(matrix, activation)?,
+                MatrixProperties::IDENTITY
+            );
+
+            // Pure scales are inverted one axis at a time.
+            let matrix = new_matrix(activation, [0.0, 0.0, 0.0, 2.0, 3.0, 4.0])?;
+            invert(activation, matrix, &[])?;
+            assert_eq!(
+                MatrixProperties::read(matrix, activation)?,
+                MatrixProperties {
+                    a: f64::INFINITY,
+                    b: 0.0,
+                    c: 0.0,
+                    d: 0.5,
+                    tx: f64::NEG_INFINITY,
+                    ty: -2.0,
+                }
+            );
+            Ok(())
+        });
+    }
+}
@@ -10,7 +10,8 @@ use crate::avm_error;
 use crate::avm_warn;
 use crate::backend::navigator::NavigationMethod;
 use crate::display_object::{
-    Bitmap, DisplayObject, EditText, MovieClip, TDisplayObject, TDisplayObjectContainer,
+    Bitmap, DisplayObject, EditText, MovieClip, PixelSnapping, TDisplayObject,
+    TDisplayObjectContainer,
 };
 use crate::ecma_conversions::f64_to_wrapping_i32;
 use crate::prelude::*;
@@ -18,9 +19,8 @@ use crate::string::AvmString;
 use crate::vminterface::Instantiator;
 use gc_arena::MutationContext;
 use ruffle_render::shape_utils::DrawCommand;
-use std::str::FromStr;
 use swf::{
-    BlendMode, FillStyle, Fixed8, Gradient, GradientInterpolation, GradientRecord, GradientSpread,
+    FillStyle, Fixed8, Gradient, GradientInterpolation, GradientRecord, GradientSpread,
     LineCapStyle, LineJoinStyle, LineStyle, Rectangle, Twips,
 };
 
@@ -112,6 +112,8 @@ const PROTO_DECLS: &[Declaration] = declare_properties! {
     "useHandCursor" => property(mc_getter!(use_hand_cursor), mc_setter!(set_use_hand_cursor); DONT_DELETE | DONT_ENUM);
     "blendMode" => property(mc_getter!(blend_mode), mc_setter!(set_blend_mode); DONT_DELETE | DONT_ENUM);
     "scrollRect" => property(mc_getter!(scroll_rect), mc_setter!(set_scroll_rect); DONT_DELETE | DONT_ENUM | VERSION_8);
+    "filters" => property(globals::get_filters, globals::set_filters; DONT_DELETE | DONT_ENUM | VERSION_8);
+    "cacheAsBitmap" => property(globals::get_cache_as_bitmap, globals::set_cache_as_bitmap; DONT_DELETE | DONT_ENUM | VERSION_8);
 };
 
 /// Implements `MovieClip`
@@ -254,11 +256,11 @@ fn attach_bitmap<'gc>(
                     .coerce_to_i32(activation)?
                     .wrapping_add(AVM_DEPTH_BIAS);
 
-                // TODO: Implement pixel snapping
-                let _pixel_snapping = args
-                    .get(2)
-                    .unwrap_or(&Value::Undefined)
-                    .as_bool(activation.swf_version());
+                // Unrecognised modes behave like "auto".
+                let pixel_snapping = match args.get(2) {
+                    Some(Value::String(mode)) => PixelSnapping::from_wstr(mode).unwrap_or_default(),
+                    _ => PixelSnapping::Auto,
+                };
 
                 let smoothing = args
                     .get(3)
@@ -272,6 +274,7 @@ fn attach_bitmap<'gc>(
                     bitmap_data,
                     smoothing,
                 );
+                display_object.set_pixel_snapping(activation.context.gc_context, pixel_snapping);
                 movie_clip.replace_at_depth(&mut activation.context, display_object.into(), depth);
                 display_object.post_instantiation(
                     &mut activation.context,
@@ -398,19 +401,12 @@ fn begin_bitmap_fill<'gc>(
     if let Some(bitmap_data) = args
         .get(0)
         .and_then(|val| val.coerce_to_object(activation).as_bitmap_data_object())
+        // A disposed `BitmapData` acts like `endFill`.
+        .filter(|bitmap_data| !bitmap_data.disposed())
     {
         // Register the bitmap data with the drawing.
-        let bitmap_data = bitmap_data.bitmap_data_wrapper();
-        let handle =
-            bitmap_data.bitmap_handle(activation.context.gc_context, activation.context.renderer);
-        let bitmap = ruffle_render::bitmap::BitmapInfo {
-            handle,
-            width: bitmap_data.width() as u16,
-            height: bitmap_data.height() as u16,
-        };
         let id = movie_clip
-            .drawing(activation.context.gc_context)
-            .add_bitmap(bitmap);
+            .add_drawing_bitmap(&mut activation.context, bitmap_data.bitmap_data_wrapper());
 
         let mut matrix = avm1::globals::matrix::object_to_matrix_or_default(
             args.get(1)
@@ -625,7 +621,7 @@ fn clear<'gc>(
     activation: &mut Activation<'_, 'gc>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    movie_clip.drawing(activation.context.gc_context).clear();
+    movie_clip.clear_drawing(activation.context.gc_context);
     Ok(Value::Undefined)
 }
 
@@ -655,12 +651,15 @@ fn attach_movie<'gc>(
         return Ok(Value::Undefined);
     }
 
+    let case_sensitive = activation.is_case_sensitive();
     if let Ok(new_clip) = activation
         .context
         .library
         .library_for_movie(movie_clip.movie())
         .ok_or("Movie is missing!")
-        .and_then(|l| l.instantiate_by_export_name(export_name, activation.context.gc_context))
+        .and_then(|l| {
+            l.instantiate_by_export_name(export_name, case_sensitive, activation.context.gc_context)
+        })
     {
         // Set name and attach to parent.
         new_clip.set_name(activation.context.gc_context, new_instance_name);
@@ -1492,13 +1491,526 @@ fn set_blend_mode<'gc>(
     activation: &mut Activation<'_, 'gc>,
     value: Value<'gc>,
 ) -> Result<(), Error<'gc>> {
-    // No-op if value is not a string.
-    if let Value::String(mode) = value {
-        if let Ok(mode) = BlendMode::from_str(&mode.to_string()) {
-            this.set_blend_mode(activation.context.gc_context, mode);
-        } else {
-            tracing::error!("Unknown blend mode {}", mode);
-        };
+    // Invalid values leave the blend mode as it was.
+    match globals::blend_mode_from_value(activation, value)? {
+        Some(mode) => this.set_blend_mode(activation.context.gc_context, mode),
+        None => tracing::error!("Unknown blend mode {:?}", value),
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::function::ExecutionReason;
+    use crate::avm1::object::bitmap_data::BitmapDataObject;
+    use crate::avm1::test_utils::{render_commands, render_commands_with, with_avm};
+    use crate::avm1::ArrayObject;
+    use crate::bitmap::bitmap_data::{BitmapData, Color as BitmapColor};
+    use crate::bitmap::operations;
+    use crate::test_utils::RecordingRenderer;
+    use ruffle_render::commands::Command;
+    use ruffle_render::filters::Filter;
+    use swf::BlendMode;
+
+    /// A 2x2 black and white checkerboard.
+    fn checker<'gc>(activation: &mut Activation<'_, 'gc>) -> BitmapDataObject<'gc> {
+        let black = BitmapColor::from(0xFF000000u32 as i32);
+        let white = BitmapColor::from(-1);
+        BitmapDataObject::with_bitmap_data(
+            activation.context.gc_context,
+            activation.context.avm1.prototypes().bitmap_data,
+            BitmapData::new_with_pixels(2, 2, false, vec![black, white, white, black]),
+        )
+    }
+
+    /// Draws a 4x4 square filled with `bitmap`.
+    fn fill_square<'gc>(
+        movie_clip: MovieClip<'gc>,
+        activation: &mut Activation<'_, 'gc>,
+        bitmap: Value<'gc>,
+        repeat: bool,
+    ) -> Result<(), Error<'gc>> {
+        begin_bitmap_fill(
+            movie_clip,
+            activation,
+            &[bitmap, Value::Undefined, repeat.into()],
+        )?;
+        move_to(movie_clip, activation, &[0.into(), 0.into()])?;
+        for (x, y) in [(4, 0), (4, 4), (0, 4), (0, 0)] {
+            line_to(movie_clip, activation, &[x.into(), y.into()])?;
+        }
+        end_fill(movie_clip, activation, &[])?;
+        Ok(())
+    }
+
+    fn is_filled<'gc>(
+        movie_clip: MovieClip<'gc>,
+        activation: &mut Activation<'_, 'gc>,
+        x: f64,
+        y: f64,
+    ) -> bool {
+        movie_clip.drawing(activation.context.gc_context).hit_test(
+            (Twips::from_pixels(x), Twips::from_pixels(y)),
+            &Matrix::IDENTITY,
+        )
+    }
+
+    #[test]
+    fn begin_bitmap_fill_square() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let movie_clip = root
+                .as_display_object()
+                .and_then(|root| root.as_movie_clip())
+                .unwrap();
+            for repeat in [true, false] {
+                let bitmap = checker(activation);
+                fill_square(movie_clip, activation, bitmap.into(), repeat)?;
+                assert!(
+                    is_filled(movie_clip, activation, 3.0, 3.0),
+                    "repeat: {repeat}"
+                );
+
+                // The fill shows later changes to the bitmap, once the clip is rendered.
+                let bitmap_data = bitmap.bitmap_data_wrapper();
+                let texture = bitmap_data
+                    .draw_to_texture(activation.context.gc_context, activation.context.renderer)
+                    .unwrap();
+                operations::set_pixel32(&mut activation.context, bitmap_data, 0, 0, -1);
+                movie_clip.update_drawing_bitmaps(
+                    activation.context.gc_context,
+                    activation.context.renderer,
+                );
+                assert!(!bitmap_data.is_texture_valid(&texture), "repeat: {repeat}");
+
+                clear(movie_clip, activation, &[])?;
+            }
+
+            // A disposed bitmap fills nothing, as if `endFill` had been called.
+            let bitmap = checker(activation);
+            bitmap.dispose(&mut activation.context);
+            fill_square(movie_clip, activation, bitmap.into(), true)?;
+            assert!(!is_filled(movie_clip, activation, 3.0, 3.0));
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn filters_round_trip_as_copies() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let movie_clip = root.as_display_object().unwrap();
+            let blur_filter = activation.context.avm1.prototypes().blur_filter_constructor;
+            let blur = blur_filter.construct(activation, &[8.into(), 2.into(), 3.into()])?;
+            let filters = ArrayObject::new(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().array,
+                [blur, 5.into()],
+            );
+            root.set("filters", filters.into(), activation)?;
+
+            // Anything that isn't a filter is dropped.
+            let filters = movie_clip.filters();
+            let [Filter::BlurFilter(filter)] = &filters[..] else {
+                panic!("expected a single blur: {filters:?}");
+            };
+            assert_eq!(filter.blur_x.to_f64(), 8.0);
+            assert_eq!(filter.blur_y.to_f64(), 2.0);
+            assert_eq!(filter.num_passes(), 3);
+
+            // Each read hands out new copies, which don't affect the clip.
+            let read = root
+                .get("filters", activation)?
+                .coerce_to_object(activation);
+            assert_eq!(read.length(activation)?, 1);
+            let copy = read.get_element(activation, 0).coerce_to_object(activation);
+            assert_ne!(Value::from(copy), blur);
+            assert_eq!(copy.get("blurX", activation)?, 8.into());
+            copy.set("blurX", 1.into(), activation)?;
+            let reread = root
+                .get("filters", activation)?
+                .coerce_to_object(activation);
+            let reread = reread
+                .get_element(activation, 0)
+                .coerce_to_object(activation);
+            assert_eq!(reread.get("blurX", activation)?, 8.into());
+
+            // An empty array removes them again.
+            let empty = ArrayObject::empty(activation);
+            root.set("filters", empty.into(), activation)?;
+            assert!(movie_clip.filters().is_empty());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn scroll_rect_crops_hit_testing() {
+        /// Copies the clip's new scroll rect into place, as rendering a frame would.
+        fn pre_render<'gc>(activation: &mut Activation<'_, 'gc>, object: DisplayObject<'gc>) {
+            render_commands(activation, false, |context| object.pre_render(context));
+        }
+
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let movie_clip = root.as_display_object().unwrap();
+            let rectangle = activation.context.avm1.prototypes().rectangle_constructor;
+            let rect =
+                rectangle.construct(activation, &[10.into(), 10.into(), 20.into(), 20.into()])?;
+            root.set("scrollRect", rect, activation)?;
+
+            // Reads hand out copies of the rectangle.
+            let read = root.get("scrollRect", activation)?;
+            assert_ne!(read, rect);
+            let read = read.coerce_to_object(activation);
+            assert_eq!(read.get("x", activation)?, 10.into());
+            assert_eq!(read.get("width", activation)?, 20.into());
+
+            // The clip's contents are scrolled by (10, 10), so only stage points
+            // within (0, 0)-(20, 20) land inside the rect.
+            pre_render(activation, movie_clip);
+            let pixels = |x, y| (Twips::from_pixels(x), Twips::from_pixels(y));
+            assert!(movie_clip.hit_test_scroll_rect(pixels(5.0, 15.0)));
+            assert!(!movie_clip.hit_test_scroll_rect(pixels(25.0, 15.0)));
+            assert!(!movie_clip.hit_test_scroll_rect(pixels(5.0, -1.0)));
+
+            root.set("scrollRect", Value::Null, activation)?;
+            assert_eq!(root.get("scrollRect", activation)?, Value::Undefined);
+            pre_render(activation, movie_clip);
+            assert!(movie_clip.hit_test_scroll_rect(pixels(25.0, 15.0)));
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn cache_as_bitmap_follows_filters() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            assert_eq!(root.get("cacheAsBitmap", activation)?, false.into());
+            root.set("cacheAsBitmap", true.into(), activation)?;
+            assert_eq!(root.get("cacheAsBitmap", activation)?, true.into());
+            root.set("cacheAsBitmap", false.into(), activation)?;
+
+            // Filters force caching on, even though it was turned off.
+            let blur_filter = activation.context.avm1.prototypes().blur_filter_constructor;
+            let blur = blur_filter.construct(activation, &[])?;
+            let filters = ArrayObject::new(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().array,
+                [blur],
+            );
+            root.set("filters", filters.into(), activation)?;
+            assert_eq!(root.get("cacheAsBitmap", activation)?, true.into());
+
+            let empty = ArrayObject::empty(activation);
+            root.set("filters", empty.into(), activation)?;
+            assert_eq!(root.get("cacheAsBitmap", activation)?, false.into());
+            Ok(())
+        });
+    }
+
+    fn call<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+        name: &'static str,
+        args: &[Value<'gc>],
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        object.call_method(name.into(), args, activation, ExecutionReason::Special)
+    }
+
+    /// Draws a 10x10 square into a new child clip of `root`.
+    fn draw_square<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        root: Object<'gc>,
+    ) -> Result<Object<'gc>, Error<'gc>> {
+        let name = "square".into();
+        let clip = call(activation, root, "createEmptyMovieClip", &[name, 1.into()])?;
+        let clip = clip.coerce_to_object(activation);
+        call(activation, clip, "beginFill", &[0xFF0000.into()])?;
+        for (x, y) in [(10, 0), (10, 10), (0, 10), (0, 0)] {
+            call(activation, clip, "lineTo", &[x.into(), y.into()])?;
+        }
+        call(activation, clip, "endFill", &[])?;
+        Ok(clip)
+    }
+
+    #[test]
+    fn cache_as_bitmap_snaps_to_pixels() {
+        /// Draws a square in a new clip at a fractional position, and returns the horizontal
+        /// position it's rendered at.
+        ///
+        /// The null renderer can't cache the clip, so it's drawn directly, but still where its
+        /// cached bitmap would be.
+        fn render_x<'gc>(
+            activation: &mut Activation<'_, 'gc>,
+            root: Object<'gc>,
+            cache_as_bitmap: bool,
+        ) -> Result<Twips, Error<'gc>> {
+            let clip = draw_square(activation, root)?;
+            clip.set("_x", 10.3.into(), activation)?;
+            clip.set("cacheAsBitmap", cache_as_bitmap.into(), activation)?;
+
+            let clip = clip.as_display_object().unwrap();
+            let commands = render_commands(activation, false, |context| clip.render(context));
+            let Some(Command::RenderShape { transform, .. }) = commands.commands.first() else {
+                panic!("expected a shape: {:?}", commands.commands);
+            };
+            Ok(transform.matrix.tx)
+        }
+
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            assert_eq!(render_x(activation, root, false)?, Twips::from_pixels(10.3));
+            assert_eq!(render_x(activation, root, true)?, Twips::from_pixels(10.0));
+            Ok(())
+        });
+    }
+
+    /// Renders `object` through `renderer`, returning the first command drawn.
+    fn first_command<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        renderer: &mut RecordingRenderer,
+        object: DisplayObject<'gc>,
+    ) -> Option<Command> {
+        let commands = render_commands_with(Some(renderer), activation, false, |context| {
+            object.render(context)
+        });
+        commands.commands.into_iter().next()
+    }
+
+    #[test]
+    fn filters_render_from_a_kept_bitmap() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let mut renderer = RecordingRenderer::default();
+            let clip = draw_square(activation, root)?;
+            let object = clip.as_display_object().unwrap();
+
+            // Without filters, the shape is drawn directly.
+            let first = first_command(activation, &mut renderer, object);
+            assert!(matches!(first, Some(Command::RenderShape { .. })));
+            assert!(renderer.offscreen_renders.is_empty());
+
+            let blur_filter = activation.context.avm1.prototypes().blur_filter_constructor;
+            let blur = blur_filter.construct(activation, &[])?;
+            let filters = ArrayObject::new(
+                activation.context.gc_context,
+                activation.context.avm1.prototypes().array,
+                [blur],
+            );
+            clip.set("filters", filters.into(), activation)?;
+            let Some(Command::RenderBitmap {
+                bitmap: blurred, ..
+            }) = first_command(activation, &mut renderer, object)
+            else {
+                panic!("expected the blurred bitmap");
+            };
+            assert_eq!(renderer.offscreen_renders.len(), 1);
+            assert_eq!(renderer.applied_filters.len(), 1);
+
+            // Nothing about the shape changed, and moving it by a whole pixel doesn't
+            // change the bitmap either.
+            clip.set("_x", 1.into(), activation)?;
+            let Some(Command::RenderBitmap { bitmap, .. }) =
+                first_command(activation, &mut renderer, object)
+            else {
+                panic!("expected the blurred bitmap");
+            };
+            assert_eq!(bitmap, blurred);
+            assert_eq!(renderer.offscreen_renders.len(), 1);
+
+            // Drawing into the clip does.
+            call(activation, clip, "lineStyle", &[1.into()])?;
+            call(activation, clip, "lineTo", &[20.into(), 20.into()])?;
+            let Some(Command::RenderBitmap { bitmap, .. }) =
+                first_command(activation, &mut renderer, object)
+            else {
+                panic!("expected the blurred bitmap");
+            };
+            assert_ne!(bitmap, blurred);
+            assert_eq!(renderer.offscreen_renders.len(), 2);
+
+            // Removing the filters draws the shape directly again.
+            let empty = ArrayObject::empty(activation);
+            clip.set("filters", empty.into(), activation)?;
+            let last = first_command(activation, &mut renderer, object);
+            assert!(matches!(last, Some(Command::RenderShape { .. })));
+            assert_eq!(renderer.offscreen_renders.len(), 2);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn cache_as_bitmap_renders_from_a_kept_bitmap() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let mut renderer = RecordingRenderer::default();
+            let clip = draw_square(activation, root)?;
+            let object = clip.as_display_object().unwrap();
+
+            clip.set("cacheAsBitmap", true.into(), activation)?;
+            let Some(Command::RenderBitmap { bitmap: cached, .. }) =
+                first_command(activation, &mut renderer, object)
+            else {
+                panic!("expected the cached bitmap");
+            };
+            assert_eq!(renderer.offscreen_renders.len(), 1);
+            assert!(renderer.applied_filters.is_empty());
+
+            // The unchanged clip is drawn from the same bitmap, without rendering it again.
+            let Some(Command::RenderBitmap { bitmap, .. }) =
+                first_command(activation, &mut renderer, object)
+            else {
+                panic!("expected the cached bitmap");
+            };
+            assert_eq!(bitmap, cached);
+            assert_eq!(renderer.offscreen_renders.len(), 1);
+
+            // Drawing into the clip renders it again.
+            call(activation, clip, "lineStyle", &[1.into()])?;
+            call(activation, clip, "lineTo", &[20.into(), 20.into()])?;
+            let Some(Command::RenderBitmap { bitmap, .. }) =
+                first_command(activation, &mut renderer, object)
+            else {
+                panic!("expected the cached bitmap");
+            };
+            assert_ne!(bitmap, cached);
+            assert_eq!(renderer.offscreen_renders.len(), 2);
+
+            // Turning cacheAsBitmap off draws the shape directly again.
+            clip.set("cacheAsBitmap", false.into(), activation)?;
+            let last = first_command(activation, &mut renderer, object);
+            assert!(matches!(last, Some(Command::RenderShape { .. })));
+            assert_eq!(renderer.offscreen_renders.len(), 2);
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn attach_bitmap_arguments() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let movie_clip = root
+                .as_display_object()
+                .and_then(|root| root.as_movie_clip())
+                .unwrap();
+            let attached = |movie_clip: MovieClip<'_>| {
+                movie_clip
+                    .child_by_depth(1 + AVM_DEPTH_BIAS)
+                    .and_then(|child| child.as_bitmap())
+                    .unwrap()
+            };
+
+            let bitmap = checker(activation);
+            let always = AvmString::new_utf8(activation.context.gc_context, "always");
+            attach_bitmap(
+                movie_clip,
+                activation,
+                &[bitmap.into(), 1.into(), always.into(), true.into()],
+            )?;
+            let first = attached(movie_clip);
+            assert_eq!(first.pixel_snapping(), PixelSnapping::Always);
+            assert!(first.smoothing());
+
+            // A second bitmap at the same depth replaces the first.
+            let bogus = AvmString::new_utf8(activation.context.gc_context, "sometimes");
+            attach_bitmap(
+                movie_clip,
+                activation,
+                &[bitmap.into(), 1.into(), bogus.into(), false.into()],
+            )?;
+            let second = attached(movie_clip);
+            assert!(!DisplayObject::ptr_eq(first.into(), second.into()));
+            assert_eq!(movie_clip.num_children(), 1);
+            assert_eq!(second.pixel_snapping(), PixelSnapping::Auto);
+            assert!(!second.smoothing());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn blend_mode_accepts_names_and_numbers() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let movie_clip = root.as_display_object().unwrap();
+            for (value, expected) in [
+                (Value::from("ADD"), BlendMode::Add),
+                (Value::from("hardLight"), BlendMode::HardLight),
+                (Value::from("3"), BlendMode::Multiply),
+                (Value::from(12), BlendMode::Erase),
+                (Value::from(1), BlendMode::Normal),
+            ] {
+                root.set("blendMode", value, activation)?;
+                assert_eq!(movie_clip.blend_mode(), expected, "{value:?}");
+            }
+
+            // Anything else leaves the previous blend mode in place.
+            root.set("blendMode", "darken".into(), activation)?;
+            for value in [
+                Value::from("bogus"),
+                Value::from("99"),
+                Value::from(1.5e3),
+                Value::Undefined,
+            ] {
+                root.set("blendMode", value, activation)?;
+                assert_eq!(movie_clip.blend_mode(), BlendMode::Darken, "{value:?}");
+            }
+            assert_eq!(root.get("blendMode", activation)?, "darken".into());
+            Ok(())
+        });
+    }
+
+    #[test]
+    fn pixel_snapping_modes() {
+        let offset = Matrix::translate(Twips::new(28), Twips::new(-8));
+        let scaled = offset * Matrix::scale(16.0, 16.0);
+        for (mode, matrix, expected) in [
+            (PixelSnapping::Never, offset, (28, -8)),
+            (PixelSnapping::Auto, offset, (20, 0)),
+            (PixelSnapping::Auto, scaled, (28, -8)),
+            (PixelSnapping::Always, scaled, (20, 0)),
+        ] {
+            let mut matrix = matrix;
+            mode.apply(&mut matrix);
+            assert_eq!(
+                (matrix.tx.get(), matrix.ty.get()),
+                expected,
+                "{mode:?} {matrix:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn attach_movie_export_names_are_case_sensitive_from_swf7() {
+        use crate::character::Character;
+
+        for (version, found) in [(6, true), (7, false), (8, false)] {
+            with_avm(version, |activation, root| -> Result<(), Error> {
+                let movie_clip = root
+                    .as_display_object()
+                    .and_then(|root| root.as_movie_clip())
+                    .unwrap();
+                let movie = movie_clip.movie();
+                let library = activation
+                    .context
+                    .library
+                    .library_for_movie_mut(movie.clone());
+                let symbol = MovieClip::new(movie, activation.context.gc_context);
+                library.register_character(1, Character::MovieClip(symbol));
+                library.register_export(
+                    1,
+                    AvmString::new_utf8(activation.context.gc_context, "Enemy"),
+                );
+
+                let exact = attach_movie(
+                    movie_clip,
+                    activation,
+                    &["Enemy".into(), "exact".into(), 1.into()],
+                )?;
+                assert!(matches!(exact, Value::Object(_)), "SWF{version}");
+                let other_case = attach_movie(
+                    movie_clip,
+                    activation,
+                    &["enemy".into(), "other_case".into(), 2.into()],
+                )?;
+                assert_eq!(
+                    matches!(other_case, Value::Object(_)),
+                    found,
+                    "SWF{version}"
+                );
+                Ok(())
+            });
+        }
+    }
+}
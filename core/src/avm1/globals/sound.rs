@@ -79,11 +79,12 @@ fn attach_sound<'gc>(
             .owner()
             .unwrap_or_else(|| activation.base_clip().avm1_root())
             .movie();
+        let case_sensitive = activation.is_case_sensitive();
         if let Some(Character::Sound(sound)) = activation
             .context
             .library
             .library_for_movie_mut(movie)
-            .character_by_export_name(name)
+            .character_by_export_name(name, case_sensitive)
         {
             sound_object.set_sound(activation.context.gc_context, Some(*sound));
             sound_object.set_is_streaming(activation.context.gc_context, false);
@@ -439,11 +440,12 @@ fn stop<'gc>(
                 .owner()
                 .unwrap_or_else(|| activation.base_clip().avm1_root())
                 .movie();
+            let case_sensitive = activation.is_case_sensitive();
             if let Some(Character::Sound(sound)) = activation
                 .context
                 .library
                 .library_for_movie_mut(movie)
-                .character_by_export_name(name)
+                .character_by_export_name(name, case_sensitive)
             {
                 // Stop all sounds with the given name.
                 let sound = *sound;
@@ -465,3 +467,51 @@ fn stop<'gc>(
 
     Ok(Value::Undefined)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::backend::audio::AudioBackend;
+    use crate::string::AvmString;
+
+    #[test]
+    fn attach_sound_export_names_are_case_sensitive_from_swf7() {
+        for (version, found) in [(6, true), (7, false), (8, false)] {
+            with_avm(version, |activation, root| -> Result<(), Error> {
+                let sound = activation
+                    .context
+                    .audio
+                    .register_sound(&swf::Sound {
+                        id: 1,
+                        format: swf::SoundFormat {
+                            compression: swf::AudioCompression::Uncompressed,
+                            sample_rate: 44100,
+                            is_stereo: false,
+                            is_16_bit: false,
+                        },
+                        num_samples: 0,
+                        data: &[],
+                    })
+                    .unwrap();
+                let movie = root.as_display_object().unwrap().movie();
+                let library = activation.context.library.library_for_movie_mut(movie);
+                library.register_character(1, Character::Sound(sound));
+                library.register_export(
+                    1,
+                    AvmString::new_utf8(activation.context.gc_context, "Explosion"),
+                );
+
+                for (name, expected) in [("Explosion", true), ("explosion", found)] {
+                    let object = SoundObject::empty_sound(
+                        activation.context.gc_context,
+                        activation.context.avm1.prototypes().sound,
+                    );
+                    attach_sound(activation, object.into(), &[name.into()])?;
+                    assert_eq!(object.sound().is_some(), expected, "SWF{version} {name}");
+                }
+                Ok(())
+            });
+        }
+    }
+}
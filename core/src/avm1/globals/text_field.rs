@@ -65,6 +65,7 @@ const PROTO_DECLS: &[Declaration] = declare_properties! {
     "borderColor" => property(tf_getter!(border_color), tf_setter!(set_border_color));
     "bottomScroll" => property(tf_getter!(bottom_scroll));
     "embedFonts" => property(tf_getter!(embed_fonts), tf_setter!(set_embed_fonts));
+    "filters" => property(globals::get_filters, globals::set_filters; DONT_DELETE | DONT_ENUM | VERSION_8);
     "getDepth" => method(globals::get_depth; DONT_ENUM | DONT_DELETE | READ_ONLY | VERSION_6);
     "hscroll" => property(tf_getter!(hscroll), tf_setter!(set_hscroll));
     "html" => property(tf_getter!(html), tf_setter!(set_html));
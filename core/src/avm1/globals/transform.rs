@@ -167,19 +167,101 @@ fn pixel_bounds<'gc>(
     activation: &mut Activation<'_, 'gc>,
     clip: MovieClip<'gc>,
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // This is equivalent to `clip.getBounds()`.
-    let bounds = clip.world_bounds();
+    // This is `clip.getBounds()`, grown by any filters and rounded out to whole pixels.
+    let mut bounds = clip.world_bounds();
+    if bounds.is_valid() {
+        bounds = clip
+            .filters()
+            .iter()
+            .fold(bounds, |bounds, filter| filter.calculate_dest_rect(bounds));
+    }
+    let (x_min, y_min, x_max, y_max) = if bounds.is_valid() {
+        (
+            bounds.x_min.to_pixels().floor(),
+            bounds.y_min.to_pixels().floor(),
+            bounds.x_max.to_pixels().ceil(),
+            bounds.y_max.to_pixels().ceil(),
+        )
+    } else {
+        Default::default()
+    };
 
     // Return Rectangle object.
     let constructor = activation.context.avm1.prototypes().rectangle_constructor;
     let result = constructor.construct(
         activation,
         &[
-            bounds.x_min.to_pixels().into(),
-            bounds.y_min.to_pixels().into(),
-            bounds.width().to_pixels().into(),
-            bounds.height().to_pixels().into(),
+            x_min.into(),
+            y_min.into(),
+            (x_max - x_min).into(),
+            (y_max - y_min).into(),
         ],
     )?;
     Ok(result)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm1::test_utils::with_avm;
+    use crate::prelude::*;
+    use ruffle_render::filters::Filter;
+    use ruffle_render::matrix::Matrix;
+    use ruffle_render::shape_utils::DrawCommand;
+    use swf::FillStyle;
+
+    fn bounds<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        clip: MovieClip<'gc>,
+    ) -> Result<[f64; 4], Error<'gc>> {
+        let rectangle = pixel_bounds(activation, clip)?.coerce_to_object(activation);
+        let mut out = [0.0; 4];
+        for (name, value) in ["x", "y", "width", "height"].into_iter().zip(&mut out) {
+            *value = rectangle.get(name, activation)?.coerce_to_f64(activation)?;
+        }
+        Ok(out)
+    }
+
+    #[test]
+    fn pixel_bounds_round_out() {
+        with_avm(8, |activation, root| -> Result<(), Error> {
+            let clip = root
+                .as_display_object()
+                .and_then(|root| root.as_movie_clip())
+                .unwrap();
+
+            // An empty clip has no bounds to speak of.
+            assert_eq!(bounds(activation, clip)?, [0.0; 4]);
+
+            {
+                let mut drawing = clip.drawing(activation.context.gc_context);
+                drawing.set_fill_style(Some(FillStyle::Color(Color::BLACK)));
+                drawing.draw_command(DrawCommand::MoveTo {
+                    x: Twips::ZERO,
+                    y: Twips::ZERO,
+                });
+                for (x, y) in [(10, 0), (10, 5), (0, 5), (0, 0)] {
+                    drawing.draw_command(DrawCommand::LineTo {
+                        x: Twips::from_pixels_i32(x),
+                        y: Twips::from_pixels_i32(y),
+                    });
+                }
+            }
+            clip.set_matrix(
+                activation.context.gc_context,
+                Matrix::translate(Twips::from_pixels(0.5), Twips::from_pixels(-1.25)),
+            );
+            assert_eq!(bounds(activation, clip)?, [0.0, -2.0, 11.0, 6.0]);
+
+            // A blur spreads the bounds by half its size on each side.
+            let blur = Filter::BlurFilter(swf::BlurFilter {
+                blur_x: swf::Fixed16::from_f64(4.0),
+                blur_y: swf::Fixed16::from_f64(4.0),
+                flags: swf::BlurFilterFlags::from_passes(1),
+            });
+            clip.set_filters(activation.context.gc_context, vec![blur]);
+            assert_eq!(bounds(activation, clip)?, [-2.0, -4.0, 15.0, 10.0]);
+            Ok(())
+        });
+    }
+}
@@ -3,18 +3,38 @@ use crate::avm1::{
     error::Error,
     Object,
 };
+use crate::context::RenderContext;
 use crate::display_object::TDisplayObject;
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::commands::CommandList;
+use ruffle_render::transform::TransformStack;
 
 pub fn with_avm<F>(swf_version: u8, test: F)
+where
+    F: for<'a, 'gc> FnOnce(&mut Activation<'_, 'gc>, Object<'gc>) -> Result<(), Error<'gc>>,
+{
+    run_with_avm(crate::player::PlayerBuilder::new(), swf_version, test)
+}
+
+/// Like `with_avm`, but the player renders everything with `renderer`, including the shapes
+/// it registers while being built.
+pub fn with_avm_and_renderer<F>(swf_version: u8, renderer: impl 'static + RenderBackend, test: F)
+where
+    F: for<'a, 'gc> FnOnce(&mut Activation<'_, 'gc>, Object<'gc>) -> Result<(), Error<'gc>>,
+{
+    run_with_avm(
+        crate::player::PlayerBuilder::new().with_renderer(renderer),
+        swf_version,
+        test,
+    )
+}
+
+fn run_with_avm<F>(builder: crate::player::PlayerBuilder, swf_version: u8, test: F)
 where
     F: for<'a, 'gc> FnOnce(&mut Activation<'_, 'gc>, Object<'gc>) -> Result<(), Error<'gc>>,
 {
     let movie = crate::tag_utils::SwfMovie::empty(swf_version);
-    let player = crate::player::PlayerBuilder::new()
-        .with_movie(movie)
-        .build();
-    let mut player = player.lock().unwrap();
-    player.mutate_with_update_context(|context| {
+    crate::test_utils::with_player(builder.with_movie(movie), |context| {
         let context = context.reborrow();
         let root = context
             .stage
@@ -30,6 +50,41 @@ where
     })
 }
 
+/// Runs `render` with a `RenderContext` for the player's renderer, returning the commands
+/// it drew.
+pub fn render_commands<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    is_offscreen: bool,
+    render: impl FnOnce(&mut RenderContext<'_, 'gc>),
+) -> CommandList {
+    render_commands_with(None, activation, is_offscreen, render)
+}
+
+/// Like `render_commands`, but renders with `renderer` in place of the player's renderer
+/// if one is given.
+pub fn render_commands_with<'gc>(
+    renderer: Option<&mut dyn RenderBackend>,
+    activation: &mut Activation<'_, 'gc>,
+    is_offscreen: bool,
+    render: impl FnOnce(&mut RenderContext<'_, 'gc>),
+) -> CommandList {
+    let mut transform_stack = TransformStack::new();
+    let mut render_context = RenderContext {
+        renderer: renderer.unwrap_or(&mut *activation.context.renderer),
+        commands: CommandList::new(),
+        gc_context: activation.context.gc_context,
+        library: activation.context.library,
+        transform_stack: &mut transform_stack,
+        is_offscreen,
+        stage: activation.context.stage,
+        quality: activation.context.stage.quality(),
+        allow_mask: true,
+        texture_versions: Vec::new(),
+    };
+    render(&mut render_context);
+    render_context.commands
+}
+
 macro_rules! test_method {
     ( $test: ident, $name: expr, $object: expr, $($versions: expr => { $([$($arg: expr),*] => $out: expr),* }),* ) => {
         #[test]
@@ -24,6 +24,7 @@ macro_rules! avm_debug {
 
 pub mod activation;
 mod amf;
+mod amf3;
 mod array;
 pub mod bytearray;
 mod call_stack;
@@ -135,6 +136,13 @@ pub struct Avm2<'gc> {
     /// strong references around (this matches Flash's behavior).
     orphan_objects: Vec<DisplayObjectWeak<'gc>>,
 
+    /// Class aliases registered with `flash.net.registerClassAlias`, in
+    /// registration order.
+    ///
+    /// AMF uses these to name typed objects when writing them, and to find
+    /// the class to construct when reading them back.
+    class_aliases: Vec<(AvmString<'gc>, ClassObject<'gc>)>,
+
     #[cfg(feature = "avm_debug")]
     pub debug_output: bool,
 }
@@ -172,6 +180,7 @@ impl<'gc> Avm2<'gc> {
             broadcast_list: Default::default(),
 
             orphan_objects: Vec::new(),
+            class_aliases: Vec::new(),
 
             #[cfg(feature = "avm_debug")]
             debug_output: false,
@@ -297,6 +306,31 @@ impl<'gc> Avm2<'gc> {
         });
     }
 
+    /// Register `class` to be written to and read from AMF as `alias`,
+    /// replacing any class previously registered under that alias.
+    pub fn register_class_alias(&mut self, alias: AvmString<'gc>, class: ClassObject<'gc>) {
+        self.class_aliases.retain(|(name, _)| *name != alias);
+        self.class_aliases.push((alias, class));
+    }
+
+    /// Look up the class registered under `alias`.
+    pub fn get_class_by_alias(&self, alias: AvmString<'gc>) -> Option<ClassObject<'gc>> {
+        self.class_aliases
+            .iter()
+            .find(|(name, _)| *name == alias)
+            .map(|(_, class)| *class)
+    }
+
+    /// Look up the alias that `class` is written as, which is the one it was
+    /// most recently registered under.
+    pub fn get_alias_by_class(&self, class: ClassObject<'gc>) -> Option<AvmString<'gc>> {
+        self.class_aliases
+            .iter()
+            .rev()
+            .find(|(_, aliased)| Object::ptr_eq(*aliased, class))
+            .map(|(name, _)| *name)
+    }
+
     /// Dispatch an event on an object.
     ///
     /// This will become its own self contained activation.
@@ -3014,8 +3014,19 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         self.outer.domain()
     }
 
-    fn domain_memory(&self) -> ByteArrayObject<'gc> {
-        self.outer.domain().domain_memory()
+    fn domain_memory(&mut self) -> ByteArrayObject<'gc> {
+        let domain = self.outer.domain();
+        domain.domain_memory(self)
+    }
+
+    /// Store `bytes` at `address` in domain memory, as the `si`/`sf` opcodes
+    /// do.
+    fn store_domain_memory(&mut self, address: i32, bytes: &[u8]) -> Result<(), Error<'gc>> {
+        // Negative addresses are never in range.
+        let address = usize::try_from(address).unwrap_or(usize::MAX);
+        self.domain()
+            .with_domain_memory_mut(self, |memory| memory.write(address, bytes))
+            .map_err(|e| e.to_avm(self))
     }
 
     /// Implements `Op::Si8`
@@ -3023,14 +3034,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_i32(self)?;
         let val = self.pop_stack().coerce_to_i32(self)? as i8;
 
-        let dm = self.domain_memory();
-        let mut dm = dm
-            .as_bytearray_mut(self.context.gc_context)
-            .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
-
-        let address =
-            usize::try_from(address).map_err(|_| "RangeError: The specified range is invalid")?;
-        dm.write_at_nongrowing(&val.to_le_bytes(), address)?;
+        self.store_domain_memory(address, &val.to_le_bytes())?;
 
         Ok(FrameControl::Continue)
     }
@@ -3040,14 +3044,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_i32(self)?;
         let val = self.pop_stack().coerce_to_i32(self)? as i16;
 
-        let dm = self.domain_memory();
-        let mut dm = dm
-            .as_bytearray_mut(self.context.gc_context)
-            .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
-
-        let address =
-            usize::try_from(address).map_err(|_| "RangeError: The specified range is invalid")?;
-        dm.write_at_nongrowing(&val.to_le_bytes(), address)?;
+        self.store_domain_memory(address, &val.to_le_bytes())?;
 
         Ok(FrameControl::Continue)
     }
@@ -3057,14 +3054,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_i32(self)?;
         let val = self.pop_stack().coerce_to_i32(self)?;
 
-        let dm = self.domain_memory();
-        let mut dm = dm
-            .as_bytearray_mut(self.context.gc_context)
-            .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
-
-        let address =
-            usize::try_from(address).map_err(|_| "RangeError: The specified range is invalid")?;
-        dm.write_at_nongrowing(&val.to_le_bytes(), address)?;
+        self.store_domain_memory(address, &val.to_le_bytes())?;
 
         Ok(FrameControl::Continue)
     }
@@ -3074,14 +3064,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_i32(self)?;
         let val = self.pop_stack().coerce_to_number(self)? as f32;
 
-        let dm = self.domain_memory();
-        let mut dm = dm
-            .as_bytearray_mut(self.context.gc_context)
-            .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
-
-        let address =
-            usize::try_from(address).map_err(|_| "RangeError: The specified range is invalid")?;
-        dm.write_at_nongrowing(&val.to_le_bytes(), address)?;
+        self.store_domain_memory(address, &val.to_le_bytes())?;
 
         Ok(FrameControl::Continue)
     }
@@ -3091,14 +3074,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_i32(self)?;
         let val = self.pop_stack().coerce_to_number(self)?;
 
-        let dm = self.domain_memory();
-        let mut dm = dm
-            .as_bytearray_mut(self.context.gc_context)
-            .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
-
-        let address =
-            usize::try_from(address).map_err(|_| "RangeError: The specified range is invalid")?;
-        dm.write_at_nongrowing(&val.to_le_bytes(), address)?;
+        self.store_domain_memory(address, &val.to_le_bytes())?;
 
         Ok(FrameControl::Continue)
     }
@@ -3108,16 +3084,12 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_u32(self)? as usize;
 
         let dm = self.domain_memory();
+        self.domain().check_domain_address(self, address, 1)?;
         let dm = dm
             .as_bytearray()
             .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
-        let val = dm.get(address);
-
-        if let Some(val) = val {
-            self.push_stack(val);
-        } else {
-            return Err("RangeError: The specified range is invalid".into());
-        }
+        let val = dm.read_at(1, address).map_err(|e| e.to_avm(self))?;
+        self.push_stack(u8::from_le_bytes(val.try_into().unwrap()));
 
         Ok(FrameControl::Continue)
     }
@@ -3127,6 +3099,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_u32(self)? as usize;
 
         let dm = self.domain_memory();
+        self.domain().check_domain_address(self, address, 2)?;
         let dm = dm
             .as_bytearray()
             .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
@@ -3141,11 +3114,13 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_u32(self)? as usize;
 
         let dm = self.domain_memory();
+        self.domain().check_domain_address(self, address, 4)?;
         let dm = dm
             .as_bytearray()
             .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
         let val = dm.read_at(4, address).map_err(|e| e.to_avm(self))?;
         self.push_stack(i32::from_le_bytes(val.try_into().unwrap()));
+
         Ok(FrameControl::Continue)
     }
 
@@ -3154,6 +3129,7 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_u32(self)? as usize;
 
         let dm = self.domain_memory();
+        self.domain().check_domain_address(self, address, 4)?;
         let dm = dm
             .as_bytearray()
             .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
@@ -3168,11 +3144,13 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         let address = self.pop_stack().coerce_to_u32(self)? as usize;
 
         let dm = self.domain_memory();
+        self.domain().check_domain_address(self, address, 8)?;
         let dm = dm
             .as_bytearray()
             .ok_or_else(|| "Unable to get bytearray storage".to_string())?;
         let val = dm.read_at(8, address).map_err(|e| e.to_avm(self))?;
         self.push_stack(f64::from_le_bytes(val.try_into().unwrap()));
+
         Ok(FrameControl::Continue)
     }
 
@@ -3295,3 +3273,52 @@ impl<'a, 'gc> Activation<'a, 'gc> {
         Err(Error::AvmError(error_val))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm2::bytearray::Endian;
+    use crate::test_utils::with_avm2_activation;
+
+    #[test]
+    fn memory_opcodes_share_bytearray_storage() {
+        with_avm2_activation(|activation| {
+            activation.max_stack_size = 2;
+            let mc = activation.context.gc_context;
+
+            let bytearray_class = activation.avm2().classes().bytearray;
+            let object = bytearray_class.construct(activation, &[]).unwrap();
+            {
+                let mut storage = object.as_bytearray_mut(mc).unwrap();
+                storage.set_length(16);
+                storage.set_endian(Endian::Little);
+                storage.set_position(8);
+            }
+            activation
+                .domain()
+                .set_domain_memory(mc, object.as_bytearray_object().unwrap());
+
+            // `si32` pops the address, then the value. It writes straight into
+            // the ByteArray, and leaves `position` alone.
+            activation.push_stack(0x11223344);
+            activation.push_stack(4);
+            activation.op_si32().unwrap();
+            let mut storage = object.as_bytearray_mut(mc).unwrap();
+            assert_eq!(storage.position(), 8);
+            storage.set_position(4);
+            assert_eq!(storage.read_int().unwrap(), 0x11223344);
+
+            // And `li32` sees what the ByteArray API wrote.
+            storage.set_position(12);
+            storage.write_int(0x55667788).unwrap();
+            drop(storage);
+            activation.push_stack(12);
+            activation.op_li32().unwrap();
+            assert_eq!(activation.pop_stack(), Value::Integer(0x55667788));
+
+            let storage = object.as_bytearray().unwrap();
+            assert_eq!(storage.position(), 16);
+            assert_eq!(storage.len(), 16);
+        });
+    }
+}
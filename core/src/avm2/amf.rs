@@ -1,231 +1,566 @@
+//! Streaming AMF0 codec used by `ByteArray.readObject` and `ByteArray.writeObject`, and the
+//! Local Shared Object files that `SharedObject` saves.
+//!
+//! AMF0 can't express anything past plain objects, arrays and dates, so every other value is
+//! escaped into the AMF3 codec in `amf3`, as Flash does.
+
+use crate::avm2::amf3::{Amf3Reader, Amf3Writer};
 use crate::avm2::bytearray::ByteArrayStorage;
 use crate::avm2::object::{ByteArrayObject, TObject};
-use crate::avm2::ArrayObject;
-use crate::avm2::ArrayStorage;
-use crate::avm2::{Activation, Error, Object, Value};
+use crate::avm2::{Activation, ArrayObject, ArrayStorage, Error, Object, Value};
 use crate::string::AvmString;
-use enumset::EnumSet;
-use flash_lso::types::{AMFVersion, Element, Lso};
-use flash_lso::types::{Attribute, ClassDefinition, Value as AmfValue};
 
-/// Serialize a Value to an AmfValue
-pub fn serialize_value<'gc>(
+const NUMBER: u8 = 0x00;
+const BOOLEAN: u8 = 0x01;
+const STRING: u8 = 0x02;
+const OBJECT: u8 = 0x03;
+const NULL: u8 = 0x05;
+const UNDEFINED: u8 = 0x06;
+const REFERENCE: u8 = 0x07;
+const ECMA_ARRAY: u8 = 0x08;
+const OBJECT_END: u8 = 0x09;
+const STRICT_ARRAY: u8 = 0x0A;
+const DATE: u8 = 0x0B;
+const LONG_STRING: u8 = 0x0C;
+const UNSUPPORTED: u8 = 0x0D;
+const XML_DOCUMENT: u8 = 0x0F;
+const TYPED_OBJECT: u8 = 0x10;
+const AVMPLUS_OBJECT: u8 = 0x11;
+
+/// The AMF version byte of a Local Shared Object whose body is AMF3.
+const LSO_AMF3: u8 = 3;
+
+/// The enumerable properties of `object`, skipping any functions.
+fn members<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    elem: Value<'gc>,
-    amf_version: AMFVersion,
-) -> Option<AmfValue> {
-    match elem {
-        Value::Undefined => Some(AmfValue::Undefined),
-        Value::Null => Some(AmfValue::Null),
-        Value::Bool(b) => Some(AmfValue::Bool(b)),
-        Value::Number(f) => Some(AmfValue::Number(f)),
-        Value::Integer(num) => {
-            // NOTE - we should really be converting `Value::Integer` to `Value::Number`
-            // whenever it's outside this range, instead of performing this during AMF serialization.
-            // Integers are unsupported in AMF0, and must be converted to Number regardless of whether
-            // it can be represented as an integer.
-            if amf_version == AMFVersion::AMF0 || num >= (1 << 28) || num < -(1 << 28) {
-                Some(AmfValue::Number(num as f64))
-            } else {
-                Some(AmfValue::Integer(num))
-            }
+    object: Object<'gc>,
+) -> Result<Vec<(Value<'gc>, Value<'gc>)>, Error<'gc>> {
+    let mut members = Vec::new();
+    let mut last_index = object.get_next_enumerant(0, activation)?;
+    while let Some(index) = last_index {
+        let name = object.get_enumerant_name(index, activation)?;
+        last_index = object.get_next_enumerant(index, activation)?;
+
+        let value = object.get_public_property(name.coerce_to_string(activation)?, activation)?;
+        if value
+            .as_object()
+            .map_or(false, |o| o.as_executable().is_some())
+        {
+            continue;
         }
-        Value::String(s) => Some(AmfValue::String(s.to_string())),
-        Value::Object(o) => {
-            // TODO: Find a more general rule for which object types should be skipped,
-            // and which turn into undefined.
-            if o.as_executable().is_some() {
-                None
-            } else if o.as_display_object().is_some() {
-                Some(AmfValue::Undefined)
-            } else if let Some(array) = o.as_array_storage() {
-                let mut values = Vec::new();
-                recursive_serialize(activation, o, &mut values, amf_version).unwrap();
-
-                let mut dense = vec![];
-                let mut sparse = vec![];
-                for (i, elem) in (0..array.length()).zip(values.into_iter()) {
-                    if elem.name == i.to_string() {
-                        dense.push(elem.value.clone());
-                    } else {
-                        sparse.push(elem);
-                    }
-                }
+        members.push((name, value));
+    }
+    Ok(members)
+}
 
-                if sparse.is_empty() {
-                    Some(AmfValue::StrictArray(dense))
-                } else {
-                    let len = sparse.len() as u32;
-                    Some(AmfValue::ECMAArray(dense, sparse, len))
-                }
-            } else if let Some(date) = o.as_date_object() {
-                date.date_time()
-                    .map(|date_time| AmfValue::Date(date_time.timestamp_millis() as f64, None))
-            } else {
-                let is_object = o
-                    .instance_of()
-                    .map_or(false, |c| c == activation.avm2().classes().object);
-                if is_object {
-                    let mut object_body = Vec::new();
-                    recursive_serialize(activation, o, &mut object_body, amf_version).unwrap();
-                    Some(AmfValue::Object(
-                        object_body,
-                        Some(ClassDefinition {
-                            name: "".to_string(),
-                            attributes: EnumSet::only(Attribute::Dynamic),
-                            static_properties: Vec::new(),
-                        }),
-                    ))
+/// Writes AMF0 values to a `ByteArray`, keeping the object reference table of a single
+/// `writeObject` call.
+pub struct Amf0Writer<'gc> {
+    target: Object<'gc>,
+    objects: Vec<Object<'gc>>,
+}
+
+impl<'gc> Amf0Writer<'gc> {
+    /// Create a writer that appends to the `ByteArray` `target` at its
+    /// current position.
+    pub fn new(target: Object<'gc>) -> Self {
+        Self {
+            target,
+            objects: Vec::new(),
+        }
+    }
+
+    fn write_bytes(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        bytes: &[u8],
+    ) -> Result<(), Error<'gc>> {
+        self.target
+            .as_bytearray_mut(activation.context.gc_context)
+            .expect("AMF0 target should be a ByteArray")
+            .write_bytes(bytes)
+    }
+
+    /// Write the name of an object member, which has no type marker.
+    fn write_name(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        name: AvmString<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        let utf8 = name.to_utf8_lossy();
+        self.write_bytes(activation, &(utf8.len() as u16).to_be_bytes())?;
+        self.write_bytes(activation, utf8.as_bytes())
+    }
+
+    /// Write a single value, including its type marker.
+    pub fn write_value(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        value: Value<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        match value {
+            Value::Undefined => self.write_bytes(activation, &[UNDEFINED]),
+            Value::Null => self.write_bytes(activation, &[NULL]),
+            Value::Bool(b) => self.write_bytes(activation, &[BOOLEAN, b as u8]),
+            // AMF0 has no integers.
+            Value::Integer(i) => self.write_number(activation, i.into()),
+            Value::Number(n) => self.write_number(activation, n),
+            Value::String(s) => {
+                let utf8 = s.to_utf8_lossy();
+                if let Ok(length) = u16::try_from(utf8.len()) {
+                    self.write_bytes(activation, &[STRING])?;
+                    self.write_bytes(activation, &length.to_be_bytes())?;
                 } else {
-                    tracing::warn!(
-                        "Serialization is not implemented for class other than Object: {:?}",
-                        o
-                    );
-                    None
+                    self.write_bytes(activation, &[LONG_STRING])?;
+                    self.write_bytes(activation, &(utf8.len() as u32).to_be_bytes())?;
                 }
+                self.write_bytes(activation, utf8.as_bytes())
             }
+            Value::Object(o) => self.write_object(activation, o),
         }
     }
-}
 
-/// Serialize an Object and any children to a AMF object
-pub fn recursive_serialize<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    obj: Object<'gc>,
-    elements: &mut Vec<Element>,
-    amf_version: AMFVersion,
-) -> Result<(), Error<'gc>> {
-    let mut last_index = obj.get_next_enumerant(0, activation)?;
-    while let Some(index) = last_index {
-        let name = obj
-            .get_enumerant_name(index, activation)?
-            .coerce_to_string(activation)?;
-        let value = obj.get_public_property(name, activation)?;
+    fn write_number(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        value: f64,
+    ) -> Result<(), Error<'gc>> {
+        self.write_bytes(activation, &[NUMBER])?;
+        self.write_bytes(activation, &value.to_be_bytes())
+    }
+
+    fn write_object(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        // TODO: Find a more general rule for which object types should be skipped,
+        // and which turn into undefined.
+        if object.as_executable().is_some() || object.as_display_object().is_some() {
+            return self.write_bytes(activation, &[UNDEFINED]);
+        }
+
+        if let Some(index) = self.objects.iter().position(|o| Object::ptr_eq(*o, object)) {
+            self.write_bytes(activation, &[REFERENCE])?;
+            return self.write_bytes(activation, &(index as u16).to_be_bytes());
+        }
+
+        if let Some(date) = object.as_date_object() {
+            let time = date
+                .date_time()
+                .map_or(f64::NAN, |date_time| date_time.timestamp_millis() as f64);
+            self.write_bytes(activation, &[DATE])?;
+            self.write_bytes(activation, &time.to_be_bytes())?;
+            // The time zone, which is always written as UTC.
+            return self.write_bytes(activation, &[0, 0]);
+        }
+
+        let object_class = activation.avm2().classes().object;
+        let is_anonymous = object
+            .instance_of()
+            .map_or(false, |class| Object::ptr_eq(class, object_class));
+        if object.as_array_storage().is_some() {
+            self.objects.push(object);
+            self.write_array(activation, object)
+        } else if is_anonymous {
+            self.objects.push(object);
+            self.write_bytes(activation, &[OBJECT])?;
+            let members = members(activation, object)?;
+            self.write_members(activation, members)
+        } else {
+            self.write_bytes(activation, &[AVMPLUS_OBJECT])?;
+            Amf3Writer::new(self.target).write_value(activation, object.into())
+        }
+    }
 
-        if let Some(value) = serialize_value(activation, value, amf_version) {
-            elements.push(Element::new(name.to_utf8_lossy(), value));
+    /// Write an array as a strict array if it's dense, or as an ECMA array of all of its
+    /// members otherwise.
+    fn write_array(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        let length = object
+            .as_array_storage()
+            .expect("Array should have storage")
+            .length();
+        let members = members(activation, object)?;
+        let is_dense = members.len() == length
+            && members.iter().enumerate().all(
+                |(i, (name, _))| matches!(name, Value::Integer(index) if *index as usize == i),
+            );
+
+        if is_dense {
+            self.write_bytes(activation, &[STRICT_ARRAY])?;
+            self.write_bytes(activation, &(length as u32).to_be_bytes())?;
+            for (_, value) in members {
+                self.write_value(activation, value)?;
+            }
+            Ok(())
+        } else {
+            self.write_bytes(activation, &[ECMA_ARRAY])?;
+            self.write_bytes(activation, &(length as u32).to_be_bytes())?;
+            self.write_members(activation, members)
+        }
+    }
+
+    /// Write name-value pairs, followed by the end of the object.
+    fn write_members(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        members: Vec<(Value<'gc>, Value<'gc>)>,
+    ) -> Result<(), Error<'gc>> {
+        for (name, value) in members {
+            let name = name.coerce_to_string(activation)?;
+            self.write_name(activation, name)?;
+            self.write_value(activation, value)?;
         }
-        last_index = obj.get_next_enumerant(index, activation)?;
+        self.write_bytes(activation, &[0, 0, OBJECT_END])
     }
-    Ok(())
 }
 
-/// Deserialize a AmfValue to a Value
-pub fn deserialize_value<'gc>(
-    activation: &mut Activation<'_, 'gc>,
-    val: &AmfValue,
-) -> Result<Value<'gc>, Error<'gc>> {
-    Ok(match val {
-        AmfValue::Null => Value::Null,
-        AmfValue::Undefined => Value::Undefined,
-        AmfValue::Number(f) => (*f).into(),
-        AmfValue::Integer(num) => (*num).into(),
-        AmfValue::String(s) => Value::String(AvmString::new_utf8(activation.context.gc_context, s)),
-        AmfValue::Bool(b) => (*b).into(),
-        AmfValue::ByteArray(bytes) => {
-            let storage = ByteArrayStorage::from_vec(bytes.clone());
-            let bytearray = ByteArrayObject::from_storage(activation, storage)?;
-            bytearray.into()
+/// Reads AMF0 values from a `ByteArray`, keeping the object reference table of a single
+/// `readObject` call.
+pub struct Amf0Reader<'gc> {
+    source: Object<'gc>,
+    objects: Vec<Value<'gc>>,
+}
+
+impl<'gc> Amf0Reader<'gc> {
+    /// Create a reader that reads from the `ByteArray` `source` at its
+    /// current position.
+    pub fn new(source: Object<'gc>) -> Self {
+        Self {
+            source,
+            objects: Vec::new(),
         }
-        AmfValue::ECMAArray(values, elements, _) => {
-            // First let's create an array out of `values` (dense portion), then we add the elements onto it.
-            let mut arr: Vec<Option<Value<'gc>>> = Vec::with_capacity(values.len());
-            for value in values {
-                arr.push(Some(deserialize_value(activation, value)?));
+    }
+
+    fn read_bytes(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        amount: usize,
+    ) -> Result<Vec<u8>, Error<'gc>> {
+        let source = self
+            .source
+            .as_bytearray()
+            .expect("AMF0 source should be a ByteArray");
+        let bytes = source.read_bytes(amount).map(<[u8]>::to_vec);
+        drop(source);
+        bytes.map_err(|e| e.to_avm(activation))
+    }
+
+    fn read_fixed<const N: usize>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<[u8; N], Error<'gc>> {
+        let bytes = self.read_bytes(activation, N)?;
+        Ok(bytes.try_into().expect("Read the requested length"))
+    }
+
+    fn read_u8(&mut self, activation: &mut Activation<'_, 'gc>) -> Result<u8, Error<'gc>> {
+        Ok(self.read_fixed::<1>(activation)?[0])
+    }
+
+    fn read_u16(&mut self, activation: &mut Activation<'_, 'gc>) -> Result<u16, Error<'gc>> {
+        Ok(u16::from_be_bytes(self.read_fixed(activation)?))
+    }
+
+    fn read_u32(&mut self, activation: &mut Activation<'_, 'gc>) -> Result<u32, Error<'gc>> {
+        Ok(u32::from_be_bytes(self.read_fixed(activation)?))
+    }
+
+    fn read_f64(&mut self, activation: &mut Activation<'_, 'gc>) -> Result<f64, Error<'gc>> {
+        Ok(f64::from_be_bytes(self.read_fixed(activation)?))
+    }
+
+    fn read_utf8(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        length: usize,
+    ) -> Result<AvmString<'gc>, Error<'gc>> {
+        let bytes = self.read_bytes(activation, length)?;
+        Ok(AvmString::new_utf8_bytes(
+            activation.context.gc_context,
+            &bytes,
+        ))
+    }
+
+    /// Read the name of an object member, which has no type marker.
+    fn read_name(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<AvmString<'gc>, Error<'gc>> {
+        let length = self.read_u16(activation)?;
+        self.read_utf8(activation, length as usize)
+    }
+
+    /// Read a single value, including its type marker.
+    pub fn read_value(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let marker = self.read_u8(activation)?;
+        Ok(match marker {
+            NUMBER => self.read_f64(activation)?.into(),
+            BOOLEAN => (self.read_u8(activation)? != 0).into(),
+            STRING => self.read_name(activation)?.into(),
+            LONG_STRING => {
+                let length = self.read_u32(activation)?;
+                self.read_utf8(activation, length as usize)?.into()
             }
-            let storage = ArrayStorage::from_storage(arr);
-            let mut array = ArrayObject::from_storage(activation, storage)?;
-            // Now let's add each element as a property
-            for element in elements {
-                array.set_public_property(
-                    AvmString::new_utf8(activation.context.gc_context, element.name()),
-                    deserialize_value(activation, element.value())?,
-                    activation,
-                )?;
+            NULL => Value::Null,
+            UNDEFINED | UNSUPPORTED => Value::Undefined,
+            REFERENCE => {
+                let index = self.read_u16(activation)?;
+                self.objects
+                    .get(index as usize)
+                    .copied()
+                    .ok_or("Error: Invalid object reference")?
             }
-            array.into()
-        }
-        AmfValue::StrictArray(values) => {
-            let mut arr: Vec<Option<Value<'gc>>> = Vec::with_capacity(values.len());
-            for value in values {
-                arr.push(Some(deserialize_value(activation, value)?));
+            OBJECT | TYPED_OBJECT => {
+                // Objects of unknown classes are read as anonymous objects.
+                let class = if marker == TYPED_OBJECT {
+                    let class_name = self.read_name(activation)?;
+                    activation.avm2().get_class_by_alias(class_name)
+                } else {
+                    None
+                };
+                let object = match class {
+                    Some(class) => class.construct(activation, &[])?,
+                    None => activation
+                        .avm2()
+                        .classes()
+                        .object
+                        .construct(activation, &[])?,
+                };
+                self.objects.push(object.into());
+                self.read_members(activation, object)?;
+                object.into()
             }
-            let storage = ArrayStorage::from_storage(arr);
-            let array = ArrayObject::from_storage(activation, storage)?;
-            array.into()
-        }
-        AmfValue::Object(elements, class) => {
-            if let Some(class) = class {
-                if !class.name.is_empty() && class.name != "Object" {
-                    tracing::warn!("Deserializing class {:?} is not supported!", class);
+            ECMA_ARRAY => {
+                // The length isn't needed, as the members end with a marker.
+                self.read_u32(activation)?;
+                let array = ArrayObject::from_storage(activation, ArrayStorage::new(0))?;
+                self.objects.push(array.into());
+                self.read_members(activation, array)?;
+                array.into()
+            }
+            STRICT_ARRAY => {
+                let length = self.read_u32(activation)?;
+                let array = ArrayObject::from_storage(activation, ArrayStorage::new(0))?;
+                self.objects.push(array.into());
+                for index in 0..length as usize {
+                    let value = self.read_value(activation)?;
+                    array
+                        .as_array_storage_mut(activation.context.gc_context)
+                        .expect("Array should have storage")
+                        .set(index, value);
                 }
+                array.into()
+            }
+            DATE => {
+                let time = self.read_f64(activation)?;
+                // The time zone, which Flash ignores.
+                self.read_u16(activation)?;
+                let date = activation.avm2().classes().date;
+                date.construct(activation, &[time.into()])?.into()
             }
+            XML_DOCUMENT => {
+                let length = self.read_u32(activation)?;
+                let string = self.read_utf8(activation, length as usize)?;
+                let xml = activation.avm2().classes().xml;
+                xml.construct(activation, &[string.into()])?.into()
+            }
+            AVMPLUS_OBJECT => Amf3Reader::new(self.source).read_value(activation)?,
+            _ => return Err("Error: Invalid object".into()),
+        })
+    }
 
-            let mut obj = activation
-                .avm2()
-                .classes()
-                .object
-                .construct(activation, &[])?;
-            for entry in elements {
-                let value = deserialize_value(activation, entry.value())?;
-                obj.set_public_property(
-                    AvmString::new_utf8(activation.context.gc_context, entry.name()),
-                    value,
-                    activation,
-                )?;
+    /// Read name-value pairs into `object`, up to the end of the object.
+    fn read_members(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        mut object: Object<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        loop {
+            let name = self.read_name(activation)?;
+            if name.is_empty() {
+                return match self.read_u8(activation)? {
+                    OBJECT_END => Ok(()),
+                    _ => Err("Error: Invalid object".into()),
+                };
             }
-            obj.into()
+            let value = self.read_value(activation)?;
+            object.set_public_property(name, value, activation)?;
         }
-        AmfValue::Date(time, _) => activation
-            .avm2()
-            .classes()
-            .date
-            .construct(activation, &[(*time).into()])?
-            .into(),
-        AmfValue::XML(content, _) => activation
-            .avm2()
-            .classes()
-            .xml
-            .construct(
-                activation,
-                &[Value::String(AvmString::new_utf8(
-                    activation.context.gc_context,
-                    content,
-                ))],
-            )?
-            .into(),
-        AmfValue::VectorDouble(..)
-        | AmfValue::VectorUInt(..)
-        | AmfValue::VectorInt(..)
-        | AmfValue::VectorObject(..)
-        | AmfValue::Dictionary(..)
-        | AmfValue::Custom(..) => {
-            tracing::error!("Deserialization not yet implemented: {:?}", val);
-            Value::Undefined
-        }
-        AmfValue::AMF3(val) => deserialize_value(activation, val)?,
-        AmfValue::Unsupported => Value::Undefined,
-    })
+    }
+
+    /// Read a member of a Local Shared Object: its name, then its value.
+    pub fn read_member(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<(AvmString<'gc>, Value<'gc>), Error<'gc>> {
+        let name = self.read_name(activation)?;
+        let value = self.read_value(activation)?;
+        Ok((name, value))
+    }
+}
+
+/// Serializes the enumerable properties of `data` into an AMF3 Local Shared Object named
+/// `name`, as `SharedObject.flush` saves it.
+pub fn serialize_lso<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    name: &str,
+    data: Object<'gc>,
+) -> Result<Vec<u8>, Error<'gc>> {
+    // The whole body shares the reference tables of one writer.
+    let body = ByteArrayObject::from_storage(activation, ByteArrayStorage::new())?;
+    let mut writer = Amf3Writer::new(body);
+    for (member_name, value) in members(activation, data)? {
+        let member_name = member_name.coerce_to_string(activation)?;
+        writer.write_member(activation, member_name, value)?;
+        // Each member is followed by a padding byte.
+        body.as_bytearray_mut(activation.context.gc_context)
+            .expect("LSO body should be a ByteArray")
+            .write_bytes(&[0])?;
+    }
+    let body = body
+        .as_bytearray()
+        .expect("LSO body should be a ByteArray")
+        .bytes()
+        .to_vec();
+
+    let mut contents = b"TCSO".to_vec();
+    contents.extend_from_slice(&[0, 4, 0, 0, 0, 0]);
+    contents.extend_from_slice(&(name.len() as u16).to_be_bytes());
+    contents.extend_from_slice(name.as_bytes());
+    contents.extend_from_slice(&[0, 0, 0, LSO_AMF3]);
+    contents.extend(body);
+
+    let mut bytes = vec![0x00, 0xBF];
+    bytes.extend_from_slice(&(contents.len() as u32).to_be_bytes());
+    bytes.extend(contents);
+    Ok(bytes)
+}
+
+/// The length of the header of the Local Shared Object `bytes`, which ends with the AMF version
+/// of its body, or `None` if it isn't one.
+fn lso_header_length(bytes: &[u8]) -> Option<usize> {
+    // The signature and the length of the rest of the file, then its type and some padding.
+    if bytes.get(..2)? != [0x00, 0xBF] || bytes.get(6..10)? != b"TCSO" {
+        return None;
+    }
+    let name_length = u16::from_be_bytes(bytes.get(16..18)?.try_into().ok()?) as usize;
+    // The name is followed by more padding, then the AMF version.
+    let length = 18 + name_length + 4;
+    (bytes.len() >= length).then_some(length)
 }
 
-/// Deserializes a Lso into an object containing the properties stored
+/// Deserializes the members of the Local Shared Object `bytes` into a new object, or returns
+/// `None` if `bytes` isn't one.
 pub fn deserialize_lso<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    lso: &Lso,
-) -> Result<Object<'gc>, Error<'gc>> {
-    let mut obj = activation
+    bytes: &[u8],
+) -> Result<Option<Object<'gc>>, Error<'gc>> {
+    let Some(header_length) = lso_header_length(bytes) else {
+        return Ok(None);
+    };
+    let is_amf3 = bytes[header_length - 1] == LSO_AMF3;
+    let storage = ByteArrayStorage::from_vec(bytes[header_length..].to_vec());
+    let source = ByteArrayObject::from_storage(activation, storage)?;
+
+    let mut data = activation
         .avm2()
         .classes()
         .object
         .construct(activation, &[])?;
+    let mut amf0 = Amf0Reader::new(source);
+    let mut amf3 = Amf3Reader::new(source);
+    while source.as_bytearray().unwrap().bytes_available() > 0 {
+        let (name, value) = if is_amf3 {
+            amf3.read_member(activation)?
+        } else {
+            amf0.read_member(activation)?
+        };
+        // Each member is followed by a padding byte.
+        let padding = source.as_bytearray().unwrap().read_bytes(1).map(|_| ());
+        padding.map_err(|e| e.to_avm(activation))?;
+        data.set_public_property(name, value, activation)?;
+    }
+    Ok(Some(data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::with_avm2_activation;
 
-    for child in &lso.body {
-        obj.set_public_property(
-            AvmString::new_utf8(activation.context.gc_context, &child.name),
-            deserialize_value(activation, child.value())?,
-            activation,
-        )?;
+    #[test]
+    fn lso_round_trips_shared_and_cyclic_objects() {
+        use crate::avm2::ArrayStorage;
+
+        with_avm2_activation(|activation| {
+            let object_class = activation.avm2().classes().object;
+            let mut inner = object_class.construct(activation, &[]).unwrap();
+            inner
+                .set_public_property("self", inner.into(), activation)
+                .unwrap();
+            let storage = ArrayStorage::from_args(&[inner.into(), inner.into()]);
+            let list = ArrayObject::from_storage(activation, storage).unwrap();
+            let mut data = object_class.construct(activation, &[]).unwrap();
+            data.set_public_property("list", list.into(), activation)
+                .unwrap();
+
+            let bytes = serialize_lso(activation, "save", data).unwrap();
+            assert_eq!(&bytes[..2], [0x00u8, 0xBF]);
+            assert_eq!(&bytes[2..6], (bytes.len() as u32 - 6).to_be_bytes());
+            #[rustfmt::skip]
+            let expected = [
+                b'T', b'C', b'S', b'O', 0x00, 0x04, 0x00, 0x00, 0x00, 0x00,
+                0x00, 0x04, b's', b'a', b'v', b'e', 0x00, 0x00, 0x00, 0x03,
+                // `list`, an array of two references to object 1, whose `self` refers to itself.
+                0x09, b'l', b'i', b's', b't',
+                0x09, 0x05, 0x01,
+                0x0A, 0x0B, 0x01, 0x09, b's', b'e', b'l', b'f', 0x0A, 0x02, 0x01,
+                0x0A, 0x02,
+                0x00,
+            ];
+            assert_eq!(&bytes[6..], expected);
+
+            let read = deserialize_lso(activation, &bytes).unwrap().unwrap();
+            let list = read.get_public_property("list", activation).unwrap();
+            let list = list.as_object().unwrap();
+            let storage = list.as_array_storage().unwrap();
+            let (first, second) = (storage.get(0).unwrap(), storage.get(1).unwrap());
+            drop(storage);
+            let first = first.as_object().unwrap();
+            let inner = first.get_public_property("self", activation).unwrap();
+            assert!(Object::ptr_eq(second.as_object().unwrap(), first));
+            assert!(Object::ptr_eq(inner.as_object().unwrap(), first));
+        });
     }
 
-    Ok(obj)
+    #[test]
+    fn reads_amf0_lso() {
+        #[rustfmt::skip]
+        let bytes = [
+            0x00, 0xBF, 0x00, 0x00, 0x00, 0x25,
+            b'T', b'C', b'S', b'O', 0x00, 0x04, 0x00, 0x00, 0x00, 0x00,
+            0x00, 0x04, b's', b'a', b'v', b'e', 0x00, 0x00, 0x00, 0x00,
+            // `score`, the number 2.
+            0x00, 0x05, b's', b'c', b'o', b'r', b'e',
+            0x00, 0x40, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x00,
+        ];
+        with_avm2_activation(|activation| {
+            let read = deserialize_lso(activation, &bytes).unwrap().unwrap();
+            let score = read.get_public_property("score", activation).unwrap();
+            assert_eq!(score.coerce_to_number(activation).unwrap(), 2.0);
+
+            assert!(deserialize_lso(activation, b"not an LSO")
+                .unwrap()
+                .is_none());
+        });
+    }
 }
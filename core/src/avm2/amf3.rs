@@ -0,0 +1,904 @@
+//! Streaming AMF3 codec used by `ByteArray.readObject` and `ByteArray.writeObject`, the
+//! AMF0 codec in `amf` for the values AMF0 can't express, and `SharedObject`.
+//!
+//! This reads and writes the `ByteArray` directly, which lets `IExternalizable`
+//! objects interleave their own data with the rest of the stream.
+
+use crate::avm2::bytearray::ByteArrayStorage;
+use crate::avm2::e4x::to_xml_string;
+use crate::avm2::error::argument_error;
+use crate::avm2::object::{ByteArrayObject, ClassObject, E4XOrXml, TObject, VectorObject};
+use crate::avm2::property::Property;
+use crate::avm2::vector::VectorStorage;
+use crate::avm2::{Activation, ArrayObject, ArrayStorage, Error, Namespace, Object, QName, Value};
+use crate::string::AvmString;
+use fnv::FnvHashMap;
+
+const UNDEFINED: u8 = 0x00;
+const NULL: u8 = 0x01;
+const FALSE: u8 = 0x02;
+const TRUE: u8 = 0x03;
+const INTEGER: u8 = 0x04;
+const DOUBLE: u8 = 0x05;
+const STRING: u8 = 0x06;
+const XML_DOCUMENT: u8 = 0x07;
+const DATE: u8 = 0x08;
+const ARRAY: u8 = 0x09;
+const OBJECT: u8 = 0x0A;
+const XML: u8 = 0x0B;
+const BYTE_ARRAY: u8 = 0x0C;
+const VECTOR_INT: u8 = 0x0D;
+const VECTOR_UINT: u8 = 0x0E;
+const VECTOR_DOUBLE: u8 = 0x0F;
+const VECTOR_OBJECT: u8 = 0x10;
+const DICTIONARY: u8 = 0x11;
+
+/// The range of integers that fit in an AMF3 `integer`.
+const INTEGER_RANGE: std::ops::Range<i32> = -(1 << 28)..(1 << 28);
+
+/// Encode a variable-length 29-bit unsigned integer.
+fn encode_u29(value: u32) -> Vec<u8> {
+    let value = value & 0x1FFF_FFFF;
+    if value < 0x80 {
+        vec![value as u8]
+    } else if value < 0x4000 {
+        vec![(value >> 7) as u8 | 0x80, value as u8 & 0x7F]
+    } else if value < 0x20_0000 {
+        vec![
+            (value >> 14) as u8 | 0x80,
+            (value >> 7) as u8 | 0x80,
+            value as u8 & 0x7F,
+        ]
+    } else {
+        vec![
+            (value >> 22) as u8 | 0x80,
+            (value >> 15) as u8 | 0x80,
+            (value >> 8) as u8 | 0x80,
+            value as u8,
+        ]
+    }
+}
+
+/// Returns the `flash.utils.IExternalizable` interface.
+fn externalizable_interface<'gc>(activation: &mut Activation<'_, 'gc>) -> Option<ClassObject<'gc>> {
+    let name = QName::new(
+        Namespace::package("flash.utils", activation.context.gc_context),
+        "IExternalizable",
+    );
+    let globals = activation.avm2().globals;
+    globals
+        .get_defined_value(activation, name)
+        .ok()
+        .and_then(|v| v.as_object())
+        .and_then(|o| o.as_class_object())
+}
+
+fn is_externalizable<'gc>(activation: &mut Activation<'_, 'gc>, class: ClassObject<'gc>) -> bool {
+    externalizable_interface(activation).map_or(false, |i| class.has_class_in_chain(i))
+}
+
+/// The public variables and read-write accessors of `class`, which are
+/// written as the sealed members of its instances.
+fn sealed_members<'gc>(class: ClassObject<'gc>) -> Vec<AvmString<'gc>> {
+    let mut names: Vec<_> = class
+        .instance_vtable()
+        .public_properties()
+        .into_iter()
+        .filter(|(_, property)| {
+            matches!(
+                property,
+                Property::Slot { .. }
+                    | Property::Virtual {
+                        get: Some(_),
+                        set: Some(_),
+                    }
+            )
+        })
+        .map(|(name, _)| name)
+        .collect();
+    names.sort();
+    names
+}
+
+/// Writes AMF3 values to a `ByteArray`, keeping the string, object and
+/// traits reference tables of a single `writeObject` call.
+pub struct Amf3Writer<'gc> {
+    target: Object<'gc>,
+    strings: FnvHashMap<AvmString<'gc>, u32>,
+    objects: Vec<Object<'gc>>,
+    traits: Vec<Option<ClassObject<'gc>>>,
+}
+
+impl<'gc> Amf3Writer<'gc> {
+    /// Create a writer that appends to the `ByteArray` `target` at its
+    /// current position.
+    pub fn new(target: Object<'gc>) -> Self {
+        Self {
+            target,
+            strings: Default::default(),
+            objects: Vec::new(),
+            traits: Vec::new(),
+        }
+    }
+
+    fn write_bytes(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        bytes: &[u8],
+    ) -> Result<(), Error<'gc>> {
+        self.target
+            .as_bytearray_mut(activation.context.gc_context)
+            .expect("AMF3 target should be a ByteArray")
+            .write_bytes(bytes)
+    }
+
+    fn write_u29(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        value: u32,
+    ) -> Result<(), Error<'gc>> {
+        self.write_bytes(activation, &encode_u29(value))
+    }
+
+    /// Write the header of an inline value of `length`.
+    fn write_inline_length(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        length: usize,
+    ) -> Result<(), Error<'gc>> {
+        self.write_u29(activation, ((length as u32) << 1) | 1)
+    }
+
+    fn write_string(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        string: AvmString<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        // The empty string is never sent by reference.
+        if string.is_empty() {
+            return self.write_u29(activation, 1);
+        }
+
+        if let Some(&index) = self.strings.get(&string) {
+            return self.write_u29(activation, index << 1);
+        }
+
+        self.strings.insert(string, self.strings.len() as u32);
+        let utf8 = string.to_utf8_lossy();
+        self.write_inline_length(activation, utf8.len())?;
+        self.write_bytes(activation, utf8.as_bytes())
+    }
+
+    /// Write a single value, including its type marker.
+    pub fn write_value(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        value: Value<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        match value {
+            Value::Undefined => self.write_bytes(activation, &[UNDEFINED]),
+            Value::Null => self.write_bytes(activation, &[NULL]),
+            Value::Bool(false) => self.write_bytes(activation, &[FALSE]),
+            Value::Bool(true) => self.write_bytes(activation, &[TRUE]),
+            Value::Integer(i) if INTEGER_RANGE.contains(&i) => {
+                self.write_bytes(activation, &[INTEGER])?;
+                self.write_u29(activation, i as u32)
+            }
+            Value::Integer(i) => self.write_double(activation, i.into()),
+            Value::Number(n) => self.write_double(activation, n),
+            Value::String(s) => {
+                self.write_bytes(activation, &[STRING])?;
+                self.write_string(activation, s)
+            }
+            Value::Object(o) => self.write_object(activation, o),
+        }
+    }
+
+    /// Write a member of a Local Shared Object: its name, then its value.
+    pub fn write_member(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        name: AvmString<'gc>,
+        value: Value<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        self.write_string(activation, name)?;
+        self.write_value(activation, value)
+    }
+
+    fn write_double(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        value: f64,
+    ) -> Result<(), Error<'gc>> {
+        self.write_bytes(activation, &[DOUBLE])?;
+        self.write_bytes(activation, &value.to_be_bytes())
+    }
+
+    fn write_object(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        // TODO: Find a more general rule for which object types should be skipped,
+        // and which turn into undefined.
+        if object.as_executable().is_some() || object.as_display_object().is_some() {
+            return self.write_bytes(activation, &[UNDEFINED]);
+        }
+
+        if let Some(date) = object.as_date_object() {
+            if self.write_marker_or_reference(activation, DATE, object)? {
+                return Ok(());
+            }
+            let time = date
+                .date_time()
+                .map_or(f64::NAN, |date_time| date_time.timestamp_millis() as f64);
+            self.write_inline_length(activation, 0)?;
+            self.write_bytes(activation, &time.to_be_bytes())
+        } else if let Some(xml) = object.as_xml_object() {
+            if self.write_marker_or_reference(activation, XML, object)? {
+                return Ok(());
+            }
+            let xml = to_xml_string(E4XOrXml::Xml(xml), activation)?;
+            let xml = xml.to_utf8_lossy();
+            self.write_inline_length(activation, xml.len())?;
+            self.write_bytes(activation, xml.as_bytes())
+        } else if object.as_bytearray().is_some() {
+            if self.write_marker_or_reference(activation, BYTE_ARRAY, object)? {
+                return Ok(());
+            }
+            // Copy the bytes out first, as `object` may be the target itself.
+            let bytes = object.as_bytearray().unwrap().bytes().to_vec();
+            self.write_inline_length(activation, bytes.len())?;
+            self.write_bytes(activation, &bytes)
+        } else if object.as_array_storage().is_some() {
+            self.write_array(activation, object)
+        } else if object.as_vector_storage().is_some() {
+            self.write_vector(activation, object)
+        } else if let Some(dictionary) = object.as_dictionary_object() {
+            if self.write_marker_or_reference(activation, DICTIONARY, object)? {
+                return Ok(());
+            }
+            let mut pairs = Vec::new();
+            let mut last_index = dictionary.get_next_enumerant(0, activation)?;
+            while let Some(index) = last_index {
+                let key = dictionary.get_enumerant_name(index, activation)?;
+                let value = dictionary.get_enumerant_value(index, activation)?;
+                pairs.push((key, value));
+                last_index = dictionary.get_next_enumerant(index, activation)?;
+            }
+
+            self.write_inline_length(activation, pairs.len())?;
+            // We don't support weak keys, so dictionaries are always written as strong.
+            self.write_bytes(activation, &[0])?;
+            for (key, value) in pairs {
+                self.write_value(activation, key)?;
+                self.write_value(activation, value)?;
+            }
+            Ok(())
+        } else {
+            self.write_typed_object(activation, object)
+        }
+    }
+
+    /// Write the type marker of `object`, followed by a reference to it if it
+    /// has already been written.
+    ///
+    /// Returns `true` if a reference was written, in which case the body of
+    /// the object must not be.
+    fn write_marker_or_reference(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        marker: u8,
+        object: Object<'gc>,
+    ) -> Result<bool, Error<'gc>> {
+        self.write_bytes(activation, &[marker])?;
+        let known = self.objects.iter().position(|o| Object::ptr_eq(*o, object));
+        if let Some(index) = known {
+            self.write_u29(activation, (index as u32) << 1)?;
+            return Ok(true);
+        }
+
+        self.objects.push(object);
+        Ok(false)
+    }
+
+    fn write_array(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        if self.write_marker_or_reference(activation, ARRAY, object)? {
+            return Ok(());
+        }
+
+        // The dense portion ends at the first hole; everything after it is
+        // written as an associative member.
+        let dense: Vec<Value<'gc>> = object
+            .as_array_storage()
+            .expect("Array should have storage")
+            .iter()
+            .map_while(|value| value)
+            .collect();
+
+        self.write_inline_length(activation, dense.len())?;
+        self.write_dynamic_members(activation, object, dense.len())?;
+        for value in dense {
+            self.write_value(activation, value)?;
+        }
+        Ok(())
+    }
+
+    fn write_vector(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        let vector = object
+            .as_vector_storage()
+            .expect("Vector should have storage");
+        let value_type = vector.value_type();
+        let is_fixed = vector.is_fixed();
+        let values: Vec<Value<'gc>> = vector.iter().collect();
+        drop(vector);
+
+        let classes = activation.avm2().classes();
+        let (int, uint, number) = (classes.int, classes.uint, classes.number);
+        let object_class = classes.object;
+        let marker = if Object::ptr_eq(value_type, int) {
+            VECTOR_INT
+        } else if Object::ptr_eq(value_type, uint) {
+            VECTOR_UINT
+        } else if Object::ptr_eq(value_type, number) {
+            VECTOR_DOUBLE
+        } else {
+            VECTOR_OBJECT
+        };
+
+        if self.write_marker_or_reference(activation, marker, object)? {
+            return Ok(());
+        }
+        self.write_inline_length(activation, values.len())?;
+        self.write_bytes(activation, &[is_fixed as u8])?;
+        match marker {
+            VECTOR_INT => {
+                for value in values {
+                    let value = value.coerce_to_i32(activation)?;
+                    self.write_bytes(activation, &value.to_be_bytes())?;
+                }
+            }
+            VECTOR_UINT => {
+                for value in values {
+                    let value = value.coerce_to_u32(activation)?;
+                    self.write_bytes(activation, &value.to_be_bytes())?;
+                }
+            }
+            VECTOR_DOUBLE => {
+                for value in values {
+                    let value = value.coerce_to_number(activation)?;
+                    self.write_bytes(activation, &value.to_be_bytes())?;
+                }
+            }
+            _ => {
+                let type_name = if Object::ptr_eq(value_type, object_class) {
+                    "".into()
+                } else if let Some(alias) = activation.avm2().get_alias_by_class(value_type) {
+                    alias
+                } else {
+                    value_type
+                        .inner_class_definition()
+                        .read()
+                        .name()
+                        .to_qualified_name(activation.context.gc_context)
+                };
+                self.write_string(activation, type_name)?;
+                for value in values {
+                    self.write_value(activation, value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Write an object with its traits: an anonymous dynamic object, an
+    /// instance of a class (named by its alias, if it has one), or an
+    /// `IExternalizable`.
+    fn write_typed_object(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+    ) -> Result<(), Error<'gc>> {
+        let object_class = activation.avm2().classes().object;
+        let class = object
+            .instance_of()
+            .filter(|class| !Object::ptr_eq(*class, object_class));
+
+        let (is_externalizable, is_dynamic, sealed) = match class {
+            Some(class) if is_externalizable(activation, class) => (true, false, Vec::new()),
+            Some(class) => (
+                false,
+                !class.inner_class_definition().read().is_sealed(),
+                sealed_members(class),
+            ),
+            None => (false, true, Vec::new()),
+        };
+
+        if self.write_marker_or_reference(activation, OBJECT, object)? {
+            return Ok(());
+        }
+        let known_traits = self.traits.iter().position(|known| match (known, class) {
+            (Some(known), Some(class)) => Object::ptr_eq(*known, class),
+            (None, None) => true,
+            _ => false,
+        });
+        if let Some(index) = known_traits {
+            self.write_u29(activation, ((index as u32) << 2) | 0b01)?;
+        } else {
+            self.traits.push(class);
+            let alias = class
+                .and_then(|class| activation.avm2().get_alias_by_class(class))
+                .unwrap_or_default();
+            let header = if is_externalizable {
+                0b0111
+            } else {
+                ((sealed.len() as u32) << 4) | ((is_dynamic as u32) << 3) | 0b011
+            };
+            self.write_u29(activation, header)?;
+            self.write_string(activation, alias)?;
+            for name in &sealed {
+                self.write_string(activation, *name)?;
+            }
+        }
+
+        if is_externalizable {
+            object.call_public_property("writeExternal", &[self.target.into()], activation)?;
+            return Ok(());
+        }
+
+        for name in sealed {
+            let value = object.get_public_property(name, activation)?;
+            self.write_value(activation, value)?;
+        }
+        if is_dynamic {
+            self.write_dynamic_members(activation, object, 0)?;
+        }
+        Ok(())
+    }
+
+    /// Write the enumerable properties of `object` as name-value pairs,
+    /// skipping array indices below `dense_length` and any functions.
+    fn write_dynamic_members(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        object: Object<'gc>,
+        dense_length: usize,
+    ) -> Result<(), Error<'gc>> {
+        let mut last_index = object.get_next_enumerant(0, activation)?;
+        while let Some(index) = last_index {
+            let name = object.get_enumerant_name(index, activation)?;
+            last_index = object.get_next_enumerant(index, activation)?;
+
+            if matches!(name, Value::Integer(i) if (i as usize) < dense_length) {
+                continue;
+            }
+            let name = name.coerce_to_string(activation)?;
+            let value = object.get_public_property(name, activation)?;
+            if value
+                .as_object()
+                .map_or(false, |o| o.as_executable().is_some())
+            {
+                continue;
+            }
+
+            self.write_string(activation, name)?;
+            self.write_value(activation, value)?;
+        }
+        self.write_string(activation, "".into())
+    }
+}
+
+/// The traits of an object read from an AMF3 stream.
+#[derive(Clone)]
+struct Traits<'gc> {
+    class_name: AvmString<'gc>,
+    sealed: Vec<AvmString<'gc>>,
+    is_dynamic: bool,
+    is_externalizable: bool,
+}
+
+/// Reads AMF3 values from a `ByteArray`, keeping the reference tables of a
+/// single `readObject` call.
+pub struct Amf3Reader<'gc> {
+    source: Object<'gc>,
+    strings: Vec<AvmString<'gc>>,
+    objects: Vec<Value<'gc>>,
+    traits: Vec<Traits<'gc>>,
+}
+
+/// The header of a value that may be sent by reference.
+enum Header<'gc> {
+    Reference(Value<'gc>),
+    Inline(u32),
+}
+
+impl<'gc> Amf3Reader<'gc> {
+    /// Create a reader that reads from the `ByteArray` `source` at its
+    /// current position.
+    pub fn new(source: Object<'gc>) -> Self {
+        Self {
+            source,
+            strings: Vec::new(),
+            objects: Vec::new(),
+            traits: Vec::new(),
+        }
+    }
+
+    fn read_bytes(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        amount: usize,
+    ) -> Result<Vec<u8>, Error<'gc>> {
+        let source = self
+            .source
+            .as_bytearray()
+            .expect("AMF3 source should be a ByteArray");
+        let bytes = source.read_bytes(amount).map(<[u8]>::to_vec);
+        drop(source);
+        bytes.map_err(|e| e.to_avm(activation))
+    }
+
+    fn read_fixed<const N: usize>(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<[u8; N], Error<'gc>> {
+        let bytes = self.read_bytes(activation, N)?;
+        Ok(bytes.try_into().expect("Read the requested length"))
+    }
+
+    fn read_u8(&mut self, activation: &mut Activation<'_, 'gc>) -> Result<u8, Error<'gc>> {
+        Ok(self.read_fixed::<1>(activation)?[0])
+    }
+
+    fn read_f64(&mut self, activation: &mut Activation<'_, 'gc>) -> Result<f64, Error<'gc>> {
+        Ok(f64::from_be_bytes(self.read_fixed(activation)?))
+    }
+
+    fn read_u29(&mut self, activation: &mut Activation<'_, 'gc>) -> Result<u32, Error<'gc>> {
+        let mut value = 0;
+        for _ in 0..3 {
+            let byte = self.read_u8(activation)?;
+            value = (value << 7) | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Ok((value << 8) | self.read_u8(activation)? as u32)
+    }
+
+    fn read_string(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<AvmString<'gc>, Error<'gc>> {
+        let header = self.read_u29(activation)?;
+        if header & 1 == 0 {
+            return self
+                .strings
+                .get((header >> 1) as usize)
+                .copied()
+                .ok_or_else(|| "Error: Invalid string reference".into());
+        }
+
+        let bytes = self.read_bytes(activation, (header >> 1) as usize)?;
+        let string = AvmString::new_utf8_bytes(activation.context.gc_context, &bytes);
+        if !string.is_empty() {
+            self.strings.push(string);
+        }
+        Ok(string)
+    }
+
+    /// Read the header of a value that is stored in the object table.
+    fn read_header(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Header<'gc>, Error<'gc>> {
+        let header = self.read_u29(activation)?;
+        if header & 1 == 0 {
+            return self
+                .objects
+                .get((header >> 1) as usize)
+                .map(|object| Header::Reference(*object))
+                .ok_or_else(|| "Error: Invalid object reference".into());
+        }
+        Ok(Header::Inline(header >> 1))
+    }
+
+    /// Read a single value, including its type marker.
+    pub fn read_value(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let marker = self.read_u8(activation)?;
+        let length = match marker {
+            UNDEFINED => return Ok(Value::Undefined),
+            NULL => return Ok(Value::Null),
+            FALSE => return Ok(false.into()),
+            TRUE => return Ok(true.into()),
+            INTEGER => {
+                // Sign-extend the 29-bit value.
+                let value = (self.read_u29(activation)? << 3) as i32 >> 3;
+                return Ok(value.into());
+            }
+            DOUBLE => return Ok(self.read_f64(activation)?.into()),
+            STRING => return Ok(self.read_string(activation)?.into()),
+            XML_DOCUMENT | DATE | ARRAY | XML | BYTE_ARRAY | VECTOR_INT | VECTOR_UINT
+            | VECTOR_DOUBLE | VECTOR_OBJECT | DICTIONARY => match self.read_header(activation)? {
+                Header::Reference(object) => return Ok(object),
+                Header::Inline(length) => length as usize,
+            },
+            OBJECT => return self.read_object(activation),
+            _ => return Err("Error: Invalid object".into()),
+        };
+
+        let value = match marker {
+            XML_DOCUMENT | XML => {
+                let bytes = self.read_bytes(activation, length)?;
+                let string = AvmString::new_utf8_bytes(activation.context.gc_context, &bytes);
+                let xml = activation.avm2().classes().xml;
+                xml.construct(activation, &[string.into()])?.into()
+            }
+            DATE => {
+                let time = self.read_f64(activation)?;
+                let date = activation.avm2().classes().date;
+                date.construct(activation, &[time.into()])?.into()
+            }
+            ARRAY => return self.read_array_body(activation, length),
+            BYTE_ARRAY => {
+                let bytes = self.read_bytes(activation, length)?;
+                let storage = ByteArrayStorage::from_vec(bytes);
+                ByteArrayObject::from_storage(activation, storage)?.into()
+            }
+            VECTOR_INT | VECTOR_UINT | VECTOR_DOUBLE | VECTOR_OBJECT => {
+                return self.read_vector_body(activation, marker, length)
+            }
+            _ => return self.read_dictionary_body(activation, length),
+        };
+        self.objects.push(value);
+        Ok(value)
+    }
+
+    /// Read a member of a Local Shared Object: its name, then its value.
+    pub fn read_member(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<(AvmString<'gc>, Value<'gc>), Error<'gc>> {
+        let name = self.read_string(activation)?;
+        let value = self.read_value(activation)?;
+        Ok((name, value))
+    }
+
+    fn read_array_body(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        dense_length: usize,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let mut array = ArrayObject::from_storage(activation, ArrayStorage::new(0))?;
+        self.objects.push(array.into());
+
+        loop {
+            let name = self.read_string(activation)?;
+            if name.is_empty() {
+                break;
+            }
+            let value = self.read_value(activation)?;
+            array.set_public_property(name, value, activation)?;
+        }
+
+        for index in 0..dense_length {
+            let value = self.read_value(activation)?;
+            array
+                .as_array_storage_mut(activation.context.gc_context)
+                .expect("Array should have storage")
+                .set(index, value);
+        }
+        Ok(array.into())
+    }
+
+    fn read_vector_body(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        marker: u8,
+        length: usize,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let is_fixed = self.read_u8(activation)? != 0;
+        let classes = activation.avm2().classes();
+        let value_type = match marker {
+            VECTOR_INT => classes.int,
+            VECTOR_UINT => classes.uint,
+            VECTOR_DOUBLE => classes.number,
+            _ => {
+                let object_class = classes.object;
+                let type_name = self.read_string(activation)?;
+                self.class_by_name(activation, type_name)
+                    .unwrap_or(object_class)
+            }
+        };
+
+        let storage = VectorStorage::new(0, false, value_type, activation);
+        let vector = VectorObject::from_vector(storage, activation)?;
+        self.objects.push(vector.into());
+
+        for _ in 0..length {
+            let value = match marker {
+                VECTOR_INT => i32::from_be_bytes(self.read_fixed(activation)?).into(),
+                VECTOR_UINT => u32::from_be_bytes(self.read_fixed(activation)?).into(),
+                VECTOR_DOUBLE => self.read_f64(activation)?.into(),
+                _ => self
+                    .read_value(activation)?
+                    .coerce_to_type(activation, value_type)?,
+            };
+            vector
+                .as_vector_storage_mut(activation.context.gc_context)
+                .expect("Vector should have storage")
+                .push(value, activation)?;
+        }
+        vector
+            .as_vector_storage_mut(activation.context.gc_context)
+            .expect("Vector should have storage")
+            .set_is_fixed(is_fixed);
+        Ok(vector.into())
+    }
+
+    fn read_dictionary_body(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        length: usize,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let is_weak = self.read_u8(activation)? != 0;
+        let mut dictionary = activation
+            .avm2()
+            .classes()
+            .dictionary
+            .construct(activation, &[is_weak.into()])?;
+        self.objects.push(dictionary.into());
+
+        for _ in 0..length {
+            let key = self.read_value(activation)?;
+            let value = self.read_value(activation)?;
+            match (key.as_object(), dictionary.as_dictionary_object()) {
+                (Some(key), Some(dictionary)) => {
+                    dictionary.set_property_by_object(key, value, activation.context.gc_context);
+                }
+                _ => {
+                    let key = key.coerce_to_string(activation)?;
+                    dictionary.set_public_property(key, value, activation)?;
+                }
+            }
+        }
+        Ok(dictionary.into())
+    }
+
+    fn read_traits(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+        header: u32,
+    ) -> Result<Traits<'gc>, Error<'gc>> {
+        if header & 0b10 == 0 {
+            return self
+                .traits
+                .get((header >> 2) as usize)
+                .cloned()
+                .ok_or_else(|| "Error: Invalid traits reference".into());
+        }
+
+        let class_name = self.read_string(activation)?;
+        let traits = if header & 0b100 != 0 {
+            Traits {
+                class_name,
+                sealed: Vec::new(),
+                is_dynamic: false,
+                is_externalizable: true,
+            }
+        } else {
+            let mut sealed = Vec::with_capacity((header >> 4) as usize);
+            for _ in 0..header >> 4 {
+                sealed.push(self.read_string(activation)?);
+            }
+            Traits {
+                class_name,
+                sealed,
+                is_dynamic: header & 0b1000 != 0,
+                is_externalizable: false,
+            }
+        };
+        self.traits.push(traits.clone());
+        Ok(traits)
+    }
+
+    /// Look up a class by its alias, or failing that, its qualified name.
+    fn class_by_name(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+        name: AvmString<'gc>,
+    ) -> Option<ClassObject<'gc>> {
+        if name.is_empty() {
+            return None;
+        }
+        if let Some(class) = activation.avm2().get_class_by_alias(name) {
+            return Some(class);
+        }
+
+        let name = QName::from_qualified_name(name, activation);
+        activation
+            .caller_domain()
+            .get_defined_value(activation, name)
+            .ok()
+            .and_then(|v| v.as_object())
+            .and_then(|o| o.as_class_object())
+    }
+
+    fn read_object(
+        &mut self,
+        activation: &mut Activation<'_, 'gc>,
+    ) -> Result<Value<'gc>, Error<'gc>> {
+        let header = match self.read_header(activation)? {
+            Header::Reference(object) => return Ok(object),
+            Header::Inline(header) => header,
+        };
+        let traits = self.read_traits(activation, header)?;
+
+        // Objects of unknown classes are read as anonymous objects.
+        let class = if traits.class_name.is_empty() {
+            None
+        } else {
+            activation.avm2().get_class_by_alias(traits.class_name)
+        };
+
+        let mut object = match class {
+            Some(class) => class.construct(activation, &[])?,
+            None => activation
+                .avm2()
+                .classes()
+                .object
+                .construct(activation, &[])?,
+        };
+        self.objects.push(object.into());
+
+        if traits.is_externalizable {
+            match class {
+                Some(class) if is_externalizable(activation, class) => {
+                    object.call_public_property(
+                        "readExternal",
+                        &[self.source.into()],
+                        activation,
+                    )?;
+                }
+                _ => {
+                    return Err(Error::AvmError(argument_error(
+                        activation,
+                        &format!(
+                            "Error #2173: Unable to read object in stream. The class {} does not implement flash.utils.IExternalizable but is aliased to an externalizable class.",
+                            traits.class_name
+                        ),
+                        2173,
+                    )?));
+                }
+            }
+            return Ok(object.into());
+        }
+
+        for name in traits.sealed {
+            let value = self.read_value(activation)?;
+            object.set_public_property(name, value, activation)?;
+        }
+        if traits.is_dynamic {
+            loop {
+                let name = self.read_string(activation)?;
+                if name.is_empty() {
+                    break;
+                }
+                let value = self.read_value(activation)?;
+                object.set_public_property(name, value, activation)?;
+            }
+        }
+        Ok(object.into())
+    }
+}
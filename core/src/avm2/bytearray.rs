@@ -1,9 +1,9 @@
 use crate::avm2::error::eof_error;
 use crate::avm2::Activation;
 use crate::avm2::Error;
-use crate::string::{FromWStr, WStr};
+use crate::string::{FromWStr, WStr, WString};
 use flate2::read::*;
-use flate2::Compression;
+use flate2::{Compression, Decompress, FlushDecompress, Status};
 use gc_arena::Collect;
 use std::cell::Cell;
 use std::cmp;
@@ -11,6 +11,46 @@ use std::fmt::{self, Display, Formatter};
 use std::io::prelude::*;
 use std::io::{self, Read, SeekFrom};
 
+/// Inflate a complete zlib (`zlib_header == true`) or raw deflate stream.
+///
+/// Unlike `flate2::read::*Decoder`, this is strict about the container:
+/// the stream must end exactly at the end of `input`, so truncated streams
+/// and trailing garbage are both reported as errors, as they are in Flash.
+fn inflate(
+    input: &[u8],
+    zlib_header: bool,
+    output: &mut Vec<u8>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let mut decompress = Decompress::new(zlib_header);
+    loop {
+        if output.len() == output.capacity() {
+            output.reserve(cmp::max(input.len(), 64));
+        }
+        let total_in = decompress.total_in();
+        let total_out = decompress.total_out();
+        let status = decompress.decompress_vec(
+            &input[total_in as usize..],
+            output,
+            FlushDecompress::Finish,
+        )?;
+        match status {
+            Status::StreamEnd => break,
+            // No progress with room left in the output means we ran out of input.
+            _ if decompress.total_in() == total_in
+                && decompress.total_out() == total_out
+                && output.len() < output.capacity() =>
+            {
+                return Err("Compressed stream is truncated".into());
+            }
+            _ => {}
+        }
+    }
+    if decompress.total_in() as usize != input.len() {
+        return Err("Unexpected data after the end of the compressed stream".into());
+    }
+    Ok(())
+}
+
 #[derive(Clone, Collect, Debug, Copy, PartialEq, Eq)]
 #[collect(no_drop)]
 pub enum Endian {
@@ -25,6 +65,7 @@ pub enum CompressionAlgorithm {
     Lzma,
 }
 
+#[derive(Debug)]
 pub struct EofError;
 
 impl EofError {
@@ -90,6 +131,11 @@ pub struct ByteArrayStorage {
 
     /// The encoding used when serializing/deserializing using readObject/writeObject
     object_encoding: ObjectEncoding,
+
+    /// Whether this ByteArray may be shared with other workers.
+    ///
+    /// We don't support workers yet, so this only affects the `shareable` property.
+    shareable: bool,
 }
 
 impl ByteArrayStorage {
@@ -100,6 +146,7 @@ impl ByteArrayStorage {
             position: Cell::new(0),
             endian: Endian::Big,
             object_encoding: ObjectEncoding::Amf3,
+            shareable: false,
         }
     }
 
@@ -110,6 +157,7 @@ impl ByteArrayStorage {
             position: Cell::new(0),
             endian: Endian::Big,
             object_encoding: ObjectEncoding::Amf3,
+            shareable: false,
         }
     }
 
@@ -229,9 +277,18 @@ impl ByteArrayStorage {
                 encoder.read_to_end(&mut buffer).err().map(|e| e.into())
             }
             #[cfg(feature = "lzma")]
-            CompressionAlgorithm::Lzma => lzma_rs::lzma_compress(&mut &*self.bytes, &mut buffer)
-                .err()
-                .map(|e| e.into()),
+            CompressionAlgorithm::Lzma => {
+                // Flash always writes the uncompressed length into the header,
+                // rather than the "unknown length" marker.
+                let options = lzma_rs::compress::Options {
+                    unpacked_size: lzma_rs::compress::UnpackedSize::WriteToHeader(Some(
+                        self.bytes.len() as u64,
+                    )),
+                };
+                lzma_rs::lzma_compress_with_options(&mut &*self.bytes, &mut buffer, &options)
+                    .err()
+                    .map(|e| e.into())
+            }
             #[cfg(not(feature = "lzma"))]
             CompressionAlgorithm::Lzma => Some("Ruffle was not compiled with LZMA support".into()),
         };
@@ -247,14 +304,8 @@ impl ByteArrayStorage {
     pub fn decompress(&mut self, algorithm: CompressionAlgorithm) -> Option<Vec<u8>> {
         let mut buffer = Vec::new();
         let error: Option<Box<dyn std::error::Error>> = match algorithm {
-            CompressionAlgorithm::Zlib => {
-                let mut decoder = ZlibDecoder::new(&*self.bytes);
-                decoder.read_to_end(&mut buffer).err().map(|e| e.into())
-            }
-            CompressionAlgorithm::Deflate => {
-                let mut decoder = DeflateDecoder::new(&*self.bytes);
-                decoder.read_to_end(&mut buffer).err().map(|e| e.into())
-            }
+            CompressionAlgorithm::Zlib => inflate(&self.bytes, true, &mut buffer).err(),
+            CompressionAlgorithm::Deflate => inflate(&self.bytes, false, &mut buffer).err(),
             #[cfg(feature = "lzma")]
             CompressionAlgorithm::Lzma => lzma_rs::lzma_decompress(&mut &*self.bytes, &mut buffer)
                 .err()
@@ -284,14 +335,14 @@ impl ByteArrayStorage {
         Ok(self.read_bytes(1)? != [0])
     }
 
-    // Writes a UTF String into the buffer, with its length as a prefix
+    /// Writes a UTF-8 string into the buffer, prefixed with its length in bytes.
+    ///
+    /// The prefix is 16 bits, so the string must be at most 65535 bytes long; `writeUTF`
+    /// throws a RangeError for anything longer before calling this.
     pub fn write_utf<'gc>(&mut self, utf_string: &str) -> Result<(), Error<'gc>> {
-        if let Ok(str_size) = u16::try_from(utf_string.len()) {
-            self.write_unsigned_short(str_size)?;
-            self.write_bytes(utf_string.as_bytes())
-        } else {
-            Err("RangeError: UTF String length must fit into a short".into())
-        }
+        debug_assert!(utf_string.len() <= u16::MAX as usize);
+        self.write_unsigned_short(utf_string.len() as u16)?;
+        self.write_bytes(utf_string.as_bytes())
     }
 
     #[inline]
@@ -306,8 +357,14 @@ impl ByteArrayStorage {
     }
 
     #[inline]
+    /// Resizes the ByteArray, zero-filling any new bytes.
+    ///
+    /// Like Flash, the position is moved back to the new end if it would be past it.
     pub fn set_length(&mut self, new_len: usize) {
         self.bytes.resize(new_len, 0);
+        if self.position.get() > new_len {
+            self.position.set(new_len);
+        }
     }
 
     pub fn get(&self, pos: usize) -> Option<u8> {
@@ -373,6 +430,78 @@ impl ByteArrayStorage {
         self.object_encoding = new_object_encoding;
     }
 
+    #[inline]
+    pub fn shareable(&self) -> bool {
+        self.shareable
+    }
+
+    #[inline]
+    pub fn set_shareable(&mut self, shareable: bool) {
+        self.shareable = shareable;
+    }
+
+    /// Replaces the int at `index` with `new` if it is currently `expected`,
+    /// returning the previous value.
+    ///
+    /// Like domain memory, this always uses little-endian, regardless of `endian`.
+    /// Returns `None` if `index` is not 4-byte aligned, or out of bounds.
+    pub fn compare_and_swap_int_at(
+        &mut self,
+        index: usize,
+        expected: i32,
+        new: i32,
+    ) -> Option<i32> {
+        if index % 4 != 0 {
+            return None;
+        }
+        let bytes = self.bytes.get_mut(index..index.checked_add(4)?)?;
+        let previous = i32::from_le_bytes(bytes.try_into().unwrap());
+        if previous == expected {
+            bytes.copy_from_slice(&new.to_le_bytes());
+        }
+        Some(previous)
+    }
+
+    /// Resizes this ByteArray to `new_len` if its length is currently `expected`,
+    /// returning the previous length.
+    pub fn compare_and_swap_length(&mut self, expected: usize, new_len: usize) -> usize {
+        let previous = self.len();
+        if previous == expected {
+            self.set_length(new_len);
+        }
+        previous
+    }
+
+    /// Decodes the whole ByteArray as text, as done by `ByteArray.toString`.
+    ///
+    /// A leading byte order mark selects UTF-8, UTF-16BE or UTF-16LE, and is stripped.
+    /// Without one, the bytes are decoded as UTF-8, or with the system code page
+    /// if `use_codepage` is set.
+    pub fn decode_text(&self, use_codepage: bool) -> WString {
+        let utf16 = |bytes: &[u8], from_bytes: fn([u8; 2]) -> u16| {
+            let units: Vec<u16> = bytes
+                .chunks_exact(2)
+                .map(|unit| from_bytes([unit[0], unit[1]]))
+                .collect();
+            WString::from_buf(units)
+        };
+
+        let bytes = self.bytes();
+        if let Some(bytes) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
+            WString::from_utf8_bytes(bytes.to_vec())
+        } else if let Some(bytes) = bytes.strip_prefix(&[0xFE, 0xFF]) {
+            utf16(bytes, u16::from_be_bytes)
+        } else if let Some(bytes) = bytes.strip_prefix(&[0xFF, 0xFE]) {
+            utf16(bytes, u16::from_le_bytes)
+        } else if use_codepage {
+            // We don't know the real system code page, so assume the most common one.
+            let (text, _) = encoding_rs::WINDOWS_1252.decode_without_bom_handling(bytes);
+            WString::from_utf8(&text)
+        } else {
+            WString::from_utf8_bytes(bytes.to_vec())
+        }
+    }
+
     #[inline]
     pub fn bytes_available(&self) -> usize {
         self.len().saturating_sub(self.position.get())
@@ -481,3 +610,283 @@ impl Default for ByteArrayStorage {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_endian(endian: Endian, bytes: Vec<u8>) -> ByteArrayStorage {
+        let mut storage = ByteArrayStorage::from_vec(bytes);
+        storage.set_endian(endian);
+        storage
+    }
+
+    fn decode_text(bytes: &[u8], use_codepage: bool) -> String {
+        ByteArrayStorage::from_vec(bytes.to_vec())
+            .decode_text(use_codepage)
+            .to_utf8_lossy()
+            .into_owned()
+    }
+
+    #[test]
+    fn decode_text_boms() {
+        assert_eq!(decode_text(b"\xEF\xBB\xBFh\xC3\xA9", false), "h\u{E9}");
+        assert_eq!(decode_text(b"\xFE\xFF\x00h\x00\xE9", false), "h\u{E9}");
+        assert_eq!(decode_text(b"\xFF\xFEh\x00\xE9\x00", false), "h\u{E9}");
+        // A UTF-16 BOM wins over the code page.
+        assert_eq!(decode_text(b"\xFF\xFEh\x00", true), "h");
+    }
+
+    #[test]
+    fn decode_text_code_page() {
+        assert_eq!(decode_text(b"h\xE9\x80", true), "h\u{E9}\u{20AC}");
+        assert_eq!(decode_text(b"h\xC3\xA9", false), "h\u{E9}");
+    }
+
+    #[test]
+    fn decode_text_invalid_continuation_byte() {
+        // Like Flash, a stray continuation byte is decoded as if it were Latin-1.
+        assert_eq!(decode_text(b"a\x80b", false), "a\u{80}b");
+    }
+
+    #[test]
+    fn write_utf_length_prefix_follows_endian() {
+        let mut storage = ByteArrayStorage::new();
+        storage.set_endian(Endian::Little);
+        storage.write_utf("ab").unwrap();
+        assert_eq!(storage.bytes(), [0x02, 0x00, b'a', b'b']);
+    }
+
+    #[test]
+    fn read_utf_stops_at_nul() {
+        let storage = ByteArrayStorage::from_vec(vec![0x00, 0x05, b'a', b'b', 0, b'c', b'd', b'e']);
+        assert_eq!(storage.read_utf().unwrap(), b"ab");
+        // The whole prefixed length is consumed, even after the NUL.
+        assert_eq!(storage.position(), 7);
+        assert_eq!(storage.read_unsigned_byte().unwrap(), b'e');
+    }
+
+    #[test]
+    fn write_utf_rejects_long_strings() {
+        let mut storage = ByteArrayStorage::new();
+        assert!(storage.write_utf(&"a".repeat(65536)).is_err());
+        assert_eq!(storage.len(), 0);
+        assert!(storage.write_utf(&"a".repeat(65535)).is_ok());
+        assert_eq!(storage.len(), 65537);
+    }
+
+    #[test]
+    fn set_length_clamps_position() {
+        let mut storage = ByteArrayStorage::from_vec(vec![1, 2, 3, 4]);
+        storage.set_position(3);
+        storage.set_length(2);
+        assert_eq!(storage.position(), 2);
+        assert!(storage.read_byte().is_err());
+
+        storage.set_length(4);
+        assert_eq!(storage.bytes(), [1, 2, 0, 0]);
+        assert_eq!(storage.position(), 2);
+        assert_eq!(storage.read_byte().unwrap(), 0);
+    }
+
+    #[test]
+    fn clear_keeps_settings() {
+        let mut storage = ByteArrayStorage::from_vec(vec![1, 2, 3, 4]);
+        storage.set_endian(Endian::Little);
+        storage.set_object_encoding(ObjectEncoding::Amf0);
+        storage.set_position(2);
+        storage.clear();
+        storage.shrink_to_fit();
+
+        assert_eq!(storage.len(), 0);
+        assert_eq!(storage.position(), 0);
+        assert_eq!(storage.bytes.capacity(), 0);
+        assert_eq!(storage.endian(), Endian::Little);
+        assert_eq!(storage.object_encoding(), ObjectEncoding::Amf0);
+    }
+
+    #[test]
+    fn compare_and_swap_int_at() {
+        let mut storage = ByteArrayStorage::from_vec(vec![0; 8]);
+        storage.set_endian(Endian::Big);
+        assert_eq!(storage.compare_and_swap_int_at(4, 0, 0x01020304), Some(0));
+        assert_eq!(storage.bytes(), [0, 0, 0, 0, 4, 3, 2, 1]);
+
+        // A mismatched expected value leaves the bytes alone.
+        assert_eq!(storage.compare_and_swap_int_at(4, 5, 6), Some(0x01020304));
+        assert_eq!(storage.bytes(), [0, 0, 0, 0, 4, 3, 2, 1]);
+
+        assert_eq!(storage.compare_and_swap_int_at(2, 0, 1), None);
+        assert_eq!(storage.compare_and_swap_int_at(8, 0, 1), None);
+        assert_eq!(storage.compare_and_swap_int_at(usize::MAX - 3, 0, 1), None);
+    }
+
+    #[test]
+    fn compare_and_swap_length() {
+        let mut storage = ByteArrayStorage::from_vec(vec![1, 2, 3]);
+        assert_eq!(storage.compare_and_swap_length(2, 8), 3);
+        assert_eq!(storage.len(), 3);
+        assert_eq!(storage.compare_and_swap_length(3, 5), 3);
+        assert_eq!(storage.bytes(), [1, 2, 3, 0, 0]);
+    }
+
+    #[test]
+    fn endian_swaps_integers() {
+        let mut storage = ByteArrayStorage::new();
+        storage.set_endian(Endian::Little);
+        storage.write_short(0x0102).unwrap();
+        storage.write_int(0x01020304).unwrap();
+        storage.write_unsigned_int(0xAABBCCDD).unwrap();
+        assert_eq!(
+            storage.bytes(),
+            [0x02, 0x01, 0x04, 0x03, 0x02, 0x01, 0xDD, 0xCC, 0xBB, 0xAA]
+        );
+
+        let storage = with_endian(Endian::Big, storage.bytes().to_vec());
+        assert_eq!(storage.read_short().unwrap(), 0x0201);
+        assert_eq!(storage.read_int().unwrap(), 0x04030201);
+        assert_eq!(storage.read_unsigned_int().unwrap(), 0xDDCCBBAA);
+    }
+
+    #[test]
+    fn endian_swaps_floats() {
+        let mut storage = ByteArrayStorage::new();
+        storage.write_float(1.5).unwrap();
+        storage.write_double(-2.25).unwrap();
+
+        let swapped = with_endian(Endian::Little, storage.bytes().to_vec());
+        assert_eq!(
+            swapped.read_float().unwrap().to_bits(),
+            1.5f32.to_bits().swap_bytes()
+        );
+        assert_eq!(
+            swapped.read_double().unwrap().to_bits(),
+            (-2.25f64).to_bits().swap_bytes()
+        );
+
+        let same = with_endian(Endian::Big, storage.bytes().to_vec());
+        assert_eq!(same.read_float().unwrap(), 1.5);
+        assert_eq!(same.read_double().unwrap(), -2.25);
+    }
+
+    #[test]
+    fn endian_does_not_affect_raw_bytes() {
+        let mut storage = ByteArrayStorage::new();
+        storage.set_endian(Endian::Little);
+        storage.write_bytes(&[1, 2, 3, 4]).unwrap();
+        storage.set_position(0);
+        assert_eq!(storage.read_bytes(4).unwrap(), [1, 2, 3, 4]);
+        assert_eq!(storage.read_at(2, 1).unwrap(), [2, 3]);
+    }
+
+    const DATA: &[u8] = b"Lorem ipsum dolor sit amet, lorem ipsum dolor sit amet";
+
+    fn round_trip(algorithm: CompressionAlgorithm) {
+        let mut storage = ByteArrayStorage::from_vec(DATA.to_vec());
+        let compressed = storage.compress(algorithm);
+        assert!(!compressed.is_empty());
+        assert_ne!(compressed, DATA);
+
+        let mut storage = ByteArrayStorage::from_vec(compressed);
+        assert_eq!(storage.decompress(algorithm).as_deref(), Some(DATA));
+    }
+
+    #[test]
+    fn compress_round_trip_zlib() {
+        round_trip(CompressionAlgorithm::Zlib);
+    }
+
+    #[test]
+    fn compress_round_trip_deflate() {
+        round_trip(CompressionAlgorithm::Deflate);
+    }
+
+    #[cfg(feature = "lzma")]
+    #[test]
+    fn compress_round_trip_lzma() {
+        round_trip(CompressionAlgorithm::Lzma);
+    }
+
+    #[cfg(feature = "lzma")]
+    #[test]
+    fn compress_lzma_header() {
+        let mut storage = ByteArrayStorage::from_vec(DATA.to_vec());
+        let compressed = storage.compress(CompressionAlgorithm::Lzma);
+
+        // 5 bytes of properties, followed by the uncompressed length as a little-endian u64.
+        let length = u64::from_le_bytes(compressed[5..13].try_into().unwrap());
+        assert_eq!(length, DATA.len() as u64);
+    }
+
+    #[cfg(feature = "lzma")]
+    #[test]
+    fn decompress_flash_lzma() {
+        // The LZMA stream that Flash wrote into the `ZWS` SWF of the `avm2/function_proto` test,
+        // framed like the output of `ByteArray.compress("lzma")`. The expected bytes come from
+        // xz's decoder for the same framing.
+        let compressed = include_bytes!("../../tests/bytearray/flash_lzma.bin");
+        let expected = include_bytes!("../../tests/bytearray/flash_lzma_uncompressed.bin");
+
+        let mut storage = ByteArrayStorage::from_vec(compressed.to_vec());
+        assert_eq!(
+            storage.decompress(CompressionAlgorithm::Lzma).as_deref(),
+            Some(&expected[..])
+        );
+    }
+
+    #[cfg(feature = "lzma")]
+    #[test]
+    fn decompress_malformed_lzma_fails() {
+        let mut storage = ByteArrayStorage::from_vec(vec![0xFF; 16]);
+        assert_eq!(storage.decompress(CompressionAlgorithm::Lzma), None);
+    }
+
+    #[test]
+    fn decompress_deflate_as_zlib_fails() {
+        let mut storage = ByteArrayStorage::from_vec(DATA.to_vec());
+        let compressed = storage.compress(CompressionAlgorithm::Deflate);
+
+        let mut storage = ByteArrayStorage::from_vec(compressed);
+        assert_eq!(storage.decompress(CompressionAlgorithm::Zlib), None);
+    }
+
+    #[test]
+    fn decompress_zlib_as_deflate_fails() {
+        let mut storage = ByteArrayStorage::from_vec(DATA.to_vec());
+        let compressed = storage.compress(CompressionAlgorithm::Zlib);
+
+        let mut storage = ByteArrayStorage::from_vec(compressed);
+        assert_eq!(storage.decompress(CompressionAlgorithm::Deflate), None);
+    }
+
+    #[test]
+    fn decompress_truncated_fails() {
+        for algorithm in [CompressionAlgorithm::Zlib, CompressionAlgorithm::Deflate] {
+            let mut storage = ByteArrayStorage::from_vec(DATA.to_vec());
+            let mut compressed = storage.compress(algorithm);
+            compressed.truncate(compressed.len() - 4);
+
+            let mut storage = ByteArrayStorage::from_vec(compressed);
+            assert_eq!(storage.decompress(algorithm), None);
+        }
+    }
+
+    #[test]
+    fn decompress_trailing_garbage_fails() {
+        for algorithm in [CompressionAlgorithm::Zlib, CompressionAlgorithm::Deflate] {
+            let mut storage = ByteArrayStorage::from_vec(DATA.to_vec());
+            let mut compressed = storage.compress(algorithm);
+            compressed.extend_from_slice(b"garbage");
+
+            let mut storage = ByteArrayStorage::from_vec(compressed.clone());
+            assert_eq!(storage.decompress(algorithm), None);
+            assert_eq!(storage.bytes(), compressed);
+        }
+    }
+
+    #[test]
+    fn decompress_malformed_fails() {
+        let mut storage = ByteArrayStorage::from_vec(vec![0x78, 0x9C, 0xFF, 0xFF, 0xFF]);
+        assert_eq!(storage.decompress(CompressionAlgorithm::Zlib), None);
+    }
+}
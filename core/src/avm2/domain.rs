@@ -1,18 +1,25 @@
 //! Application Domains
 
 use crate::avm2::activation::Activation;
-use crate::avm2::object::{ByteArrayObject, TObject};
+use crate::avm2::error::range_error;
+use crate::avm2::object::{ByteArrayObject, Object, TObject};
 use crate::avm2::property_map::PropertyMap;
 use crate::avm2::script::Script;
 use crate::avm2::value::Value;
 use crate::avm2::Error;
 use crate::avm2::Multiname;
+use crate::avm2::Namespace;
 use crate::avm2::QName;
+use crate::string::WStr;
 use gc_arena::{Collect, GcCell, MutationContext};
+use std::fmt::Write;
 
 use super::class::Class;
 use super::string::AvmString;
 
+/// The size, in bytes, of the memory every domain starts out with.
+const DEFAULT_DOMAIN_MEMORY_LENGTH: usize = 1024;
+
 /// Represents a set of scripts and movies that share traits across different
 /// script-global scopes.
 #[derive(Copy, Clone, Collect)]
@@ -29,9 +36,26 @@ struct DomainData<'gc> {
     /// to perform early interface resolution.
     classes: PropertyMap<'gc, GcCell<'gc, Class<'gc>>>,
 
+    /// The script that exported each class in `classes`, by class name.
+    class_scripts: PropertyMap<'gc, Script<'gc>>,
+
     /// The parent domain.
     parent: Option<Domain<'gc>>,
 
+    /// A cache of classes previously resolved by `get_defined_value`.
+    ///
+    /// This is only used by the global domain, whose definitions do not change
+    /// once player globals have been set up. Child domains can gain new
+    /// definitions at any time, so they always resolve from scratch. Other
+    /// values are never cached, as a `var` can be reassigned.
+    defined_value_cache: PropertyMap<'gc, Value<'gc>>,
+
+    /// The `ApplicationDomain` object that represents this domain in AVM2.
+    ///
+    /// Flash compares `ApplicationDomain`s by the domain they wrap, so we
+    /// hand out the same object every time instead of allocating new ones.
+    domain_object: Option<Object<'gc>>,
+
     /// The bytearray used for storing domain memory
     ///
     /// Note: While this property is optional, it is not recommended to set it
@@ -56,7 +80,10 @@ impl<'gc> Domain<'gc> {
             DomainData {
                 defs: PropertyMap::new(),
                 classes: PropertyMap::new(),
+                class_scripts: PropertyMap::new(),
                 parent: None,
+                defined_value_cache: PropertyMap::new(),
+                domain_object: None,
                 domain_memory: None,
             },
         ))
@@ -71,19 +98,127 @@ impl<'gc> Domain<'gc> {
     /// This function must not be called before the player globals have been
     /// fully allocated.
     pub fn movie_domain(activation: &mut Activation<'_, 'gc>, parent: Domain<'gc>) -> Domain<'gc> {
-        let this = Self(GcCell::allocate(
+        let this = Self::movie_domain_lazy(activation, parent);
+
+        this.init_default_domain_memory(activation).unwrap();
+
+        this
+    }
+
+    /// Create a new domain with a given parent, already holding `defs`.
+    ///
+    /// This inserts every definition at once, without the lookups through
+    /// the parent domains that `export_definition` makes for each one. The
+    /// caller must make sure that no name appears twice in `defs`, and that
+    /// none is already defined by a parent domain: unlike with
+    /// `export_definition`, such a definition would shadow the parent's one
+    /// (or, within `defs`, the last one would win).
+    ///
+    /// This function must not be called before the player globals have been
+    /// fully allocated.
+    pub fn movie_domain_with_defs(
+        activation: &mut Activation<'_, 'gc>,
+        parent: Domain<'gc>,
+        defs: Vec<(QName<'gc>, Script<'gc>)>,
+    ) -> Domain<'gc> {
+        let this = Self::movie_domain(activation, parent);
+
+        let mut write = this.0.write(activation.context.gc_context);
+        for (name, script) in defs {
+            write.defs.insert(name, script);
+        }
+        drop(write);
+
+        this
+    }
+
+    /// Create a new domain with a given parent, without allocating its
+    /// default domain memory.
+    ///
+    /// The memory will instead be allocated the first time `domain_memory` is
+    /// called, which saves the allocation for domains that never use it.
+    pub fn movie_domain_lazy(
+        activation: &mut Activation<'_, 'gc>,
+        parent: Domain<'gc>,
+    ) -> Domain<'gc> {
+        Self(GcCell::allocate(
             activation.context.gc_context,
             DomainData {
                 defs: PropertyMap::new(),
                 classes: PropertyMap::new(),
+                class_scripts: PropertyMap::new(),
                 parent: Some(parent),
+                defined_value_cache: PropertyMap::new(),
+                domain_object: None,
                 domain_memory: None,
             },
-        ));
+        ))
+    }
 
-        this.init_default_domain_memory(activation).unwrap();
+    /// Describe the definitions and classes of this domain and each of its
+    /// parents, for debugging class loading.
+    ///
+    /// Each parent is indented one level further than its child, and names
+    /// are sorted so that the output is stable.
+    pub fn debug_tree(self) -> String {
+        fn write_names<V>(out: &mut String, indent: &str, label: &str, map: &PropertyMap<'_, V>) {
+            let mut names: Vec<_> = map
+                .iter()
+                .map(|(name, ns, _)| QName::new(ns, name).to_qualified_name_no_mc().to_string())
+                .collect();
+            names.sort();
+            let names = if names.is_empty() {
+                "(none)".to_string()
+            } else {
+                names.join(", ")
+            };
+            let _ = writeln!(out, "{indent}  {label}: {names}");
+        }
 
-        this
+        let mut out = String::new();
+        let mut visited = Vec::new();
+        let mut domain = Some(self);
+        let mut indent = String::new();
+        while let Some(current) = domain {
+            if visited.contains(&current.0.as_ptr()) {
+                let _ = writeln!(out, "{indent}(cycle)");
+                break;
+            }
+            visited.push(current.0.as_ptr());
+
+            let read = current.0.read();
+            let _ = writeln!(out, "{indent}Domain");
+            write_names(&mut out, &indent, "definitions", &read.defs);
+            write_names(&mut out, &indent, "classes", &read.classes);
+
+            domain = read.parent;
+            if domain.is_some() {
+                let _ = writeln!(out, "{indent}  parent:");
+                indent.push_str("    ");
+            }
+        }
+        out
+    }
+
+    /// Get every namespace under which a definition with the given local name
+    /// is exported, in this domain or any of its parents.
+    ///
+    /// This is intended for explaining failed `getDefinitionByName` lookups
+    /// and for tooling that needs to disambiguate names. Each namespace is
+    /// listed once; this domain's namespaces come before its parents'.
+    pub fn candidate_namespaces_for(&self, local_name: &WStr) -> Vec<Namespace<'gc>> {
+        let mut namespaces = Vec::new();
+        let mut domain = Some(*self);
+        while let Some(current) = domain {
+            let read = current.0.read();
+            for (name, ns, _) in read.defs.iter() {
+                if &*name == local_name && !namespaces.contains(&ns) {
+                    namespaces.push(ns);
+                }
+            }
+            domain = read.parent;
+        }
+        namespaces
     }
 
     /// Get the parent of this domain
@@ -91,6 +226,44 @@ impl<'gc> Domain<'gc> {
         self.0.read().parent
     }
 
+    /// Get the topmost ancestor of this domain, which is the player globals domain.
+    pub fn root_domain(self) -> Domain<'gc> {
+        let mut domain = self;
+        while let Some(parent) = domain.parent_domain() {
+            domain = parent;
+        }
+        domain
+    }
+
+    /// Determine if this domain is `other`, or one of its parents.
+    pub fn is_self_or_parent_of(self, other: Domain<'gc>) -> bool {
+        let mut domain = Some(other);
+        while let Some(current) = domain {
+            if GcCell::ptr_eq(self.0, current.0) {
+                return true;
+            }
+            domain = current.parent_domain();
+        }
+        false
+    }
+
+    /// Remove every definition and class exported into this domain, so that the
+    /// scripts of an unloaded movie can be collected even if something still
+    /// holds on to the domain itself.
+    ///
+    /// The global domain holds the player globals, and is never cleared.
+    pub fn clear_definitions(self, mc: MutationContext<'gc, '_>) {
+        let mut write = self.0.write(mc);
+        if write.parent.is_none() {
+            tracing::warn!("Attempted to clear the definitions of the global domain");
+            return;
+        }
+
+        write.defs = PropertyMap::new();
+        write.classes = PropertyMap::new();
+        write.class_scripts = PropertyMap::new();
+    }
+
     /// Determine if something has been defined within the current domain.
     pub fn has_definition(self, name: QName<'gc>) -> bool {
         let read = self.0.read();
@@ -146,6 +319,45 @@ impl<'gc> Domain<'gc> {
         Ok(None)
     }
 
+    /// Find the classes exported into this domain that directly implement the
+    /// interface named by `interface`, also searching ancestor domains if
+    /// `include_parents` is set.
+    ///
+    /// Interface names are resolved against the domain each class was
+    /// exported into, as early interface resolution does. Nothing is returned
+    /// if `interface` itself cannot be resolved.
+    pub fn classes_implementing(
+        self,
+        interface: &Multiname<'gc>,
+        include_parents: bool,
+    ) -> Vec<GcCell<'gc, Class<'gc>>> {
+        let Ok(Some(interface)) = self.get_class(interface) else {
+            return Vec::new();
+        };
+
+        let mut classes = Vec::new();
+        let mut domain = Some(self);
+        while let Some(current) = domain {
+            let read = current.0.read();
+            for (_, _, class) in read.classes.iter() {
+                let implements = class.read().direct_interfaces().iter().any(|name| {
+                    current
+                        .get_class(name)
+                        .ok()
+                        .flatten()
+                        .map_or(false, |resolved| GcCell::ptr_eq(resolved, interface))
+                });
+                if implements {
+                    classes.push(*class);
+                }
+            }
+
+            domain = read.parent.filter(|_| include_parents);
+        }
+
+        classes
+    }
+
     /// Resolve a Multiname and return the script that provided it.
     ///
     /// If a name does not exist or cannot be resolved, an error will be thrown.
@@ -169,16 +381,52 @@ impl<'gc> Domain<'gc> {
         }
     }
 
+    /// Resolve a name in the public namespace and return the script that
+    /// provided it.
+    ///
+    /// If the name does not exist, an error will be thrown.
+    pub fn find_public_definition(
+        self,
+        activation: &mut Activation<'_, 'gc>,
+        local_name: AvmString<'gc>,
+    ) -> Result<(QName<'gc>, Script<'gc>), Error<'gc>> {
+        let multiname = Multiname::new(activation.avm2().public_namespace, local_name);
+        self.find_defining_script(activation, &multiname)
+    }
+
     /// Retrieve a value from this domain.
+    ///
+    /// Classes looked up on the global domain are cached, as the same player
+    /// globals tend to be resolved over and over again.
     pub fn get_defined_value(
         self,
         activation: &mut Activation<'_, 'gc>,
         name: QName<'gc>,
     ) -> Result<Value<'gc>, Error<'gc>> {
-        let (name, mut script) = self.find_defining_script(activation, &name.into())?;
+        let is_global = self.0.read().parent.is_none();
+        if is_global {
+            if let Some(value) = self.0.read().defined_value_cache.get(name) {
+                return Ok(*value);
+            }
+        }
+
+        let (defined_name, mut script) = self.find_defining_script(activation, &name.into())?;
         let globals = script.globals(&mut activation.context)?;
+        let value = globals.get_property(&defined_name.into(), activation)?;
+
+        // Only classes are cached, as they can't be reassigned once defined.
+        let is_class = value
+            .as_object()
+            .and_then(|object| object.as_class_object())
+            .is_some();
+        if is_global && is_class {
+            self.0
+                .write(activation.context.gc_context)
+                .defined_value_cache
+                .insert(name, value);
+        }
 
-        globals.get_property(&name.into(), activation)
+        Ok(value)
     }
 
     /// Retrieve a value from this domain, with special handling for 'Vector.<SomeType>'.
@@ -235,17 +483,163 @@ impl<'gc> Domain<'gc> {
         self.0.write(mc).defs.insert(name, script);
     }
 
-    pub fn export_class(&self, class: GcCell<'gc, Class<'gc>>, mc: MutationContext<'gc, '_>) {
-        self.0.write(mc).classes.insert(class.read().name(), class);
+    /// Copy the definitions and classes exported directly into `other` into
+    /// this domain.
+    ///
+    /// Unlike making `other` a parent, this flattens the two domains into one.
+    /// Only `other`'s own exports are copied, not those of its parents, and
+    /// names that this domain (or one of its parents) already defines are
+    /// skipped, so nothing from the global domain ends up duplicated here.
+    /// Neither domain may be the global domain.
+    pub fn merge_from(&mut self, other: Domain<'gc>, mc: MutationContext<'gc, '_>) {
+        if GcCell::ptr_eq(self.0, other.0) {
+            return;
+        }
+        if self.0.read().parent.is_none() || other.0.read().parent.is_none() {
+            tracing::warn!("Attempted to merge definitions into or out of the global domain");
+            return;
+        }
+
+        let other = other.0.read();
+        for (name, ns, script) in other.defs.iter() {
+            self.export_definition(QName::new(ns, name), *script, mc);
+        }
+
+        for (name, ns, class) in other.classes.iter() {
+            let name = QName::new(ns, name);
+            if matches!(self.get_class(&name.into()), Ok(Some(_))) {
+                continue;
+            }
+
+            let mut write = self.0.write(mc);
+            write.classes.insert(name, *class);
+            if let Some(script) = other.class_scripts.get(name) {
+                write.class_scripts.insert(name, *script);
+            }
+        }
+    }
+
+    /// Export a class, defined by `script`, into the current application domain.
+    ///
+    /// If a class with the same name was already exported, it is replaced, to
+    /// match Flash Player. Returns `true` when that happens.
+    pub fn export_class(
+        &self,
+        class: GcCell<'gc, Class<'gc>>,
+        script: Script<'gc>,
+        mc: MutationContext<'gc, '_>,
+    ) -> bool {
+        let name = class.read().name();
+        let mut write = self.0.write(mc);
+        write.class_scripts.insert(name, script);
+        let overwritten = write.classes.insert(name, class).is_some();
+
+        #[cfg(feature = "avm_debug")]
+        {
+            if overwritten {
+                tracing::warn!(
+                    "Class {} was exported more than once; the last definition wins",
+                    name.to_qualified_name(mc)
+                );
+            }
+        }
+
+        overwritten
+    }
+
+    /// Find the script that exported `class` into this domain or one of its
+    /// parents.
+    ///
+    /// Classes that were replaced by a later export of the same name are no
+    /// longer found.
+    pub fn script_for_class(self, class: GcCell<'gc, Class<'gc>>) -> Option<Script<'gc>> {
+        let name = class.read().name();
+        let mut domain = Some(self);
+        while let Some(current) = domain {
+            let read = current.0.read();
+            if let Some(exported) = read.classes.get(name) {
+                if GcCell::ptr_eq(*exported, class) {
+                    return read.class_scripts.get(name).copied();
+                }
+            }
+            domain = read.parent;
+        }
+        None
+    }
+
+    /// Get the `ApplicationDomain` object for this domain, if one exists.
+    pub fn domain_object(&self) -> Option<Object<'gc>> {
+        self.0.read().domain_object
+    }
+
+    pub fn set_domain_object(&self, mc: MutationContext<'gc, '_>, domain_object: Object<'gc>) {
+        self.0.write(mc).domain_object = Some(domain_object);
     }
 
-    pub fn domain_memory(&self) -> ByteArrayObject<'gc> {
+    /// Get the domain memory of this domain, allocating the default memory
+    /// first if this domain was created by `movie_domain_lazy`.
+    pub fn domain_memory(&self, activation: &mut Activation<'_, 'gc>) -> ByteArrayObject<'gc> {
+        if self.0.read().domain_memory.is_none() {
+            self.init_default_domain_memory(activation)
+                .expect("Failed to allocate default domain memory");
+        }
         self.0
             .read()
             .domain_memory
             .expect("Domain must have valid memory at all times")
     }
 
+    /// Get the length of this domain's memory, in bytes.
+    ///
+    /// Domains whose memory has not been allocated yet report the size of the
+    /// default memory they will receive on first access.
+    pub fn domain_memory_length(&self) -> usize {
+        self.0
+            .read()
+            .domain_memory
+            .and_then(|memory| memory.as_bytearray().map(|storage| storage.len()))
+            .unwrap_or(DEFAULT_DOMAIN_MEMORY_LENGTH)
+    }
+
+    /// Check that `size` bytes starting at `address` lie within this domain's
+    /// memory, throwing the `RangeError` that the memory opcodes raise
+    /// otherwise.
+    pub(crate) fn check_domain_address(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+        address: usize,
+        size: usize,
+    ) -> Result<(), Error<'gc>> {
+        let in_range = address
+            .checked_add(size)
+            .map_or(false, |end| end <= self.domain_memory_length());
+        if in_range {
+            Ok(())
+        } else {
+            Err(DomainRangeError.to_avm(activation))
+        }
+    }
+
+    /// Run `f` with write access to this domain's memory, allocating the
+    /// default memory first if needed.
+    ///
+    /// The memory stays borrowed for the whole call, so a caller storing many
+    /// values pays for the lookup once. `f` gets no activation, which means no
+    /// script can swap out `domainMemory` while it runs.
+    pub fn with_domain_memory_mut<R>(
+        &self,
+        activation: &mut Activation<'_, 'gc>,
+        f: impl FnOnce(&mut DomainMemoryWriter<'_>) -> R,
+    ) -> R {
+        let memory = self.domain_memory(activation);
+        let mut storage = memory
+            .as_bytearray_mut(activation.context.gc_context)
+            .expect("Domain memory must be a ByteArray");
+        f(&mut DomainMemoryWriter {
+            bytes: storage.bytes_mut(),
+        })
+    }
+
     pub fn set_domain_memory(
         &self,
         mc: MutationContext<'gc, '_>,
@@ -270,7 +664,7 @@ impl<'gc> Domain<'gc> {
         domain_memory
             .as_bytearray_mut(activation.context.gc_context)
             .unwrap()
-            .set_length(1024);
+            .set_length(DEFAULT_DOMAIN_MEMORY_LENGTH);
 
         let mut write = self.0.write(activation.context.gc_context);
         write
@@ -281,6 +675,73 @@ impl<'gc> Domain<'gc> {
     }
 }
 
+/// Bounds-checked, little-endian stores into a domain's memory.
+///
+/// Obtained from `Domain::with_domain_memory_mut`. Stores never grow the
+/// memory; anything that doesn't fit is rejected without writing a byte.
+pub struct DomainMemoryWriter<'a> {
+    bytes: &'a mut [u8],
+}
+
+impl DomainMemoryWriter<'_> {
+    /// The length of the memory, in bytes.
+    pub fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// Copy `bytes` into the memory starting at `address`.
+    pub fn write(&mut self, address: usize, bytes: &[u8]) -> Result<(), DomainRangeError> {
+        let end = address
+            .checked_add(bytes.len())
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(DomainRangeError)?;
+        self.bytes[address..end].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    pub fn write_i8(&mut self, address: usize, val: i8) -> Result<(), DomainRangeError> {
+        self.write(address, &val.to_le_bytes())
+    }
+
+    pub fn write_i16(&mut self, address: usize, val: i16) -> Result<(), DomainRangeError> {
+        self.write(address, &val.to_le_bytes())
+    }
+
+    pub fn write_i32(&mut self, address: usize, val: i32) -> Result<(), DomainRangeError> {
+        self.write(address, &val.to_le_bytes())
+    }
+
+    pub fn write_f32(&mut self, address: usize, val: f32) -> Result<(), DomainRangeError> {
+        self.write(address, &val.to_le_bytes())
+    }
+
+    pub fn write_f64(&mut self, address: usize, val: f64) -> Result<(), DomainRangeError> {
+        self.write(address, &val.to_le_bytes())
+    }
+}
+
+/// An access that falls outside of a domain's memory.
+#[derive(Debug)]
+pub struct DomainRangeError;
+
+impl DomainRangeError {
+    #[inline(never)]
+    pub fn to_avm<'gc>(&self, activation: &mut Activation<'_, 'gc>) -> Error<'gc> {
+        match range_error(
+            activation,
+            "Error #1506: The specified range is invalid.",
+            1506,
+        ) {
+            Ok(e) => Error::AvmError(e),
+            Err(e) => e,
+        }
+    }
+}
+
 impl<'gc> PartialEq for Domain<'gc> {
     fn eq(&self, other: &Self) -> bool {
         self.0.as_ptr() == other.0.as_ptr()
@@ -288,3 +749,463 @@ impl<'gc> PartialEq for Domain<'gc> {
 }
 
 impl<'gc> Eq for Domain<'gc> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::avm2::method::Method;
+    use crate::test_utils::with_avm2_activation;
+    use gc_arena::rootless_arena;
+
+    fn new_class<'gc>(mc: MutationContext<'gc, '_>, name: &'static str) -> GcCell<'gc, Class<'gc>> {
+        let init = Method::from_builtin(|_, _, _| Ok(Value::Undefined), "<test init>", mc);
+        Class::new(
+            QName::new(Namespace::package("", mc), name),
+            None,
+            init.clone(),
+            init,
+            mc,
+        )
+    }
+
+    fn new_script<'gc>(mc: MutationContext<'gc, '_>, domain: Domain<'gc>) -> Script<'gc> {
+        let globals = crate::avm2::object::ScriptObject::custom_object(mc, None, None);
+        Script::empty_script(mc, globals, domain)
+    }
+
+    /// Creates an empty domain under `parent`, for tests that run in a bare arena without the
+    /// `Activation` that `Domain::movie_domain` needs.
+    fn child_domain<'gc>(mc: MutationContext<'gc, '_>, parent: Domain<'gc>) -> Domain<'gc> {
+        let domain = Domain::global_domain(mc);
+        domain.0.write(mc).parent = Some(parent);
+        domain
+    }
+
+    #[test]
+    fn export_class_detects_overwrite() {
+        rootless_arena(|mc| {
+            let domain = Domain::global_domain(mc);
+            let script = new_script(mc, domain);
+            let first = new_class(mc, "Duplicate");
+            let second = new_class(mc, "Duplicate");
+
+            assert!(!domain.export_class(first, script, mc));
+            assert!(domain.export_class(second, script, mc));
+            assert!(!domain.export_class(new_class(mc, "Unique"), script, mc));
+
+            let name = QName::new(Namespace::package("", mc), "Duplicate");
+            let exported = domain.get_class(&name.into()).ok().flatten().unwrap();
+            assert!(GcCell::ptr_eq(exported, second));
+        });
+    }
+
+    #[test]
+    fn root_domain_is_global_domain() {
+        with_avm2_activation(|activation| {
+            let global = activation.avm2().global_domain();
+            let child = Domain::movie_domain(activation, global);
+            let grandchild = Domain::movie_domain(activation, child);
+
+            assert!(grandchild.root_domain() == global);
+            assert!(child.root_domain() == global);
+            assert!(global.root_domain() == global);
+        });
+    }
+
+    #[test]
+    fn clear_definitions_only_affects_child_domains() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let global = activation.avm2().global_domain();
+            let child = Domain::movie_domain(activation, global);
+
+            let class = new_class(mc, "ChildClass");
+            let name = QName::new(Namespace::package("", mc), "ChildClass");
+            child.export_class(class, new_script(mc, child), mc);
+            assert!(child.get_class(&name.into()).ok().flatten().is_some());
+
+            child.clear_definitions(mc);
+            assert!(child.get_class(&name.into()).ok().flatten().is_none());
+
+            let object = QName::new(Namespace::package("", mc), "Object");
+            global.clear_definitions(mc);
+            assert!(global.has_definition(object));
+        });
+    }
+
+    #[test]
+    fn is_self_or_parent_of_follows_parents() {
+        with_avm2_activation(|activation| {
+            let global = activation.avm2().global_domain();
+            let parent = Domain::movie_domain(activation, global);
+            let child = Domain::movie_domain(activation, parent);
+            let sibling = Domain::movie_domain(activation, parent);
+
+            assert!(child.is_self_or_parent_of(child));
+            assert!(parent.is_self_or_parent_of(child));
+            assert!(global.is_self_or_parent_of(child));
+            assert!(!child.is_self_or_parent_of(parent));
+            assert!(!sibling.is_self_or_parent_of(child));
+        });
+    }
+
+    #[test]
+    fn movie_domain_with_defs_resolves_definitions() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let global = activation.avm2().global_domain();
+            let library = Domain::movie_domain(activation, global);
+            let first_script = new_script(mc, library);
+            let second_script = new_script(mc, library);
+            let first = QName::new(Namespace::package("lib", mc), "First");
+            let second = QName::new(Namespace::package("lib", mc), "Second");
+
+            let domain = Domain::movie_domain_with_defs(
+                activation,
+                global,
+                vec![(first, first_script), (second, second_script)],
+            );
+
+            assert!(domain.has_definition(first));
+            assert!(domain.has_definition(second));
+            let (name, script) = domain.get_defining_script(&second.into()).unwrap().unwrap();
+            assert!(name == second);
+            assert!(Script::ptr_eq(script, second_script));
+
+            // Definitions from the parent domain still resolve too.
+            let object = QName::new(Namespace::package("", mc), "Object");
+            assert!(domain.has_definition(object));
+            let missing = QName::new(Namespace::package("lib", mc), "Missing");
+            assert!(!domain.has_definition(missing));
+        });
+    }
+
+    #[test]
+    fn merge_from_flattens_sibling_definitions() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let global = activation.avm2().global_domain();
+            let mut host = Domain::movie_domain(activation, global);
+            let mut plugin = Domain::movie_domain(activation, global);
+
+            let host_script = new_script(mc, host);
+            let plugin_script = new_script(mc, plugin);
+            let shared = QName::new(Namespace::package("", mc), "Shared");
+            let added = QName::new(Namespace::package("plugin", mc), "Added");
+            host.export_definition(shared, host_script, mc);
+            plugin.export_definition(shared, plugin_script, mc);
+            plugin.export_definition(added, plugin_script, mc);
+            let class = new_class(mc, "PluginClass");
+            plugin.export_class(class, plugin_script, mc);
+            let class_name = QName::new(Namespace::package("", mc), "PluginClass");
+
+            host.merge_from(plugin, mc);
+
+            // The plugin's own exports now resolve from the host...
+            let (_, script) = host.get_defining_script(&added.into()).unwrap().unwrap();
+            assert!(Script::ptr_eq(script, plugin_script));
+            let merged = host.get_class(&class_name.into()).unwrap().unwrap();
+            assert!(GcCell::ptr_eq(merged, class));
+            assert!(Script::ptr_eq(
+                host.script_for_class(class).unwrap(),
+                plugin_script
+            ));
+
+            // ...but names the host already had keep their definitions.
+            let (_, script) = host.get_defining_script(&shared.into()).unwrap().unwrap();
+            assert!(Script::ptr_eq(script, host_script));
+
+            // Nothing inherited from the global domain was copied.
+            let object = QName::new(Namespace::package("", mc), "Object");
+            assert!(!host.0.read().defs.contains_key(object));
+        });
+    }
+
+    #[test]
+    fn lazy_domain_allocates_memory_on_first_access() {
+        with_avm2_activation(|activation| {
+            let global = activation.avm2().global_domain();
+
+            let eager = Domain::movie_domain(activation, global);
+            assert!(eager.0.read().domain_memory.is_some());
+
+            let lazy = Domain::movie_domain_lazy(activation, global);
+            assert!(lazy.0.read().domain_memory.is_none());
+
+            let memory = lazy.domain_memory(activation);
+            assert_eq!(memory.as_bytearray().unwrap().len(), 1024);
+            let again = lazy.domain_memory(activation);
+            assert!(Object::ptr_eq(memory.into(), again.into()));
+        });
+    }
+
+    #[test]
+    fn domain_memory_address_checks() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let global = activation.avm2().global_domain();
+
+            // Unallocated memory reports the size it will be created with.
+            let domain = Domain::movie_domain_lazy(activation, global);
+            assert_eq!(domain.domain_memory_length(), 1024);
+            assert!(domain.check_domain_address(activation, 1020, 4).is_ok());
+
+            let bytearray_class = activation.avm2().classes().bytearray;
+            let object = bytearray_class.construct(activation, &[]).unwrap();
+            object.as_bytearray_mut(mc).unwrap().set_length(16);
+            domain.set_domain_memory(mc, object.as_bytearray_object().unwrap());
+            assert_eq!(domain.domain_memory_length(), 16);
+
+            assert!(domain.check_domain_address(activation, 0, 16).is_ok());
+            assert!(domain.check_domain_address(activation, 15, 1).is_ok());
+
+            for (address, size) in [(13, 4), (16, 1), (usize::MAX, 8)] {
+                let Err(Error::AvmError(error)) =
+                    domain.check_domain_address(activation, address, size)
+                else {
+                    panic!("{size} bytes at {address} should be out of range");
+                };
+                let error = error.as_object().unwrap();
+                let id = error
+                    .get_public_property("errorID", activation)
+                    .unwrap()
+                    .coerce_to_i32(activation)
+                    .unwrap();
+                assert_eq!(id, 1506);
+            }
+        });
+    }
+
+    #[test]
+    fn domain_memory_writer_fills_sequentially() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let global = activation.avm2().global_domain();
+            let domain = Domain::movie_domain_lazy(activation, global);
+
+            let bytearray_class = activation.avm2().classes().bytearray;
+            let object = bytearray_class.construct(activation, &[]).unwrap();
+            object.as_bytearray_mut(mc).unwrap().set_length(0x10000);
+            domain.set_domain_memory(mc, object.as_bytearray_object().unwrap());
+
+            // A 64KB fill, one `si32` at a time, under a single borrow.
+            domain.with_domain_memory_mut(activation, |memory| {
+                assert_eq!(memory.len(), 0x10000);
+                for i in 0..0x4000 {
+                    memory.write_i32(i * 4, i as i32).unwrap();
+                }
+            });
+
+            let storage = object.as_bytearray().unwrap();
+            assert_eq!(storage.read_at(4, 0).unwrap(), 0i32.to_le_bytes());
+            assert_eq!(
+                storage.read_at(4, 0x1234 * 4).unwrap(),
+                0x1234i32.to_le_bytes()
+            );
+            assert_eq!(storage.read_at(4, 0xfffc).unwrap(), 0x3fffi32.to_le_bytes());
+            drop(storage);
+
+            // Stores that don't fit are rejected whole.
+            domain.with_domain_memory_mut(activation, |memory| {
+                assert!(memory.write_f64(0xfffc, 1.0).is_err());
+                assert!(memory.write_i8(0x10000, 1).is_err());
+                assert!(memory.write(usize::MAX, &[1, 2]).is_err());
+                assert!(memory.write_i8(0xffff, -1).is_ok());
+            });
+            let storage = object.as_bytearray().unwrap();
+            assert_eq!(storage.read_at(4, 0xfffc).unwrap(), [0xff, 0x3f, 0, 0xff]);
+        });
+    }
+
+    #[test]
+    fn repeated_global_lookups_return_the_same_class() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let global = activation.avm2().global_domain();
+            let child = Domain::movie_domain_lazy(activation, global);
+            let name = QName::new(Namespace::package("flash.display", mc), "Sprite");
+
+            let sprite = global.get_defined_value(activation, name).unwrap();
+            assert!(sprite
+                .as_object()
+                .and_then(|o| o.as_class_object())
+                .is_some());
+            for _ in 0..1000 {
+                assert_eq!(global.get_defined_value(activation, name).unwrap(), sprite);
+                assert_eq!(child.get_defined_value(activation, name).unwrap(), sprite);
+            }
+        });
+    }
+
+    #[test]
+    fn global_lookups_see_reassigned_variables() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let mut global = activation.avm2().global_domain();
+            let object = QName::new(Namespace::package("", mc), "Object");
+            let (_, mut script) = global.get_defining_script(&object.into()).unwrap().unwrap();
+
+            // A `var` of the toplevel script, as if it were declared next to `Object`.
+            let score = QName::new(Namespace::package("", mc), "score");
+            global.export_definition(score, script, mc);
+            let globals = script.globals(&mut activation.context).unwrap();
+            for value in [1, 2] {
+                globals
+                    .set_property(&score.into(), Value::Integer(value), activation)
+                    .unwrap();
+                assert_eq!(
+                    global.get_defined_value(activation, score).unwrap(),
+                    Value::Integer(value)
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn classes_implementing_interface() {
+        rootless_arena(|mc| {
+            let parent = Domain::global_domain(mc);
+            let child = child_domain(mc, parent);
+
+            let package = Namespace::package("", mc);
+            let script = new_script(mc, child);
+            let interface_name = Multiname::new(package, "IRenderable");
+            let interface = new_class(mc, "IRenderable");
+            parent.export_class(interface, script, mc);
+
+            let sprite = new_class(mc, "Sprite");
+            sprite.write(mc).implements(interface_name.clone());
+            let shape = new_class(mc, "Shape");
+            let tile = new_class(mc, "Tile");
+            tile.write(mc).implements(interface_name.clone());
+            child.export_class(sprite, script, mc);
+            child.export_class(shape, script, mc);
+            parent.export_class(tile, script, mc);
+
+            let found = child.classes_implementing(&interface_name, false);
+            assert_eq!(found.len(), 1);
+            assert!(GcCell::ptr_eq(found[0], sprite));
+
+            let found = child.classes_implementing(&interface_name, true);
+            assert_eq!(found.len(), 2);
+            assert!(found.iter().any(|class| GcCell::ptr_eq(*class, tile)));
+
+            let missing = Multiname::new(package, "IMissing");
+            assert!(child.classes_implementing(&missing, true).is_empty());
+        });
+    }
+
+    #[test]
+    fn find_public_definition_ignores_private_names() {
+        with_avm2_activation(|activation| {
+            let mc = activation.context.gc_context;
+            let global = activation.avm2().global_domain();
+            let mut child = Domain::movie_domain(activation, global);
+
+            let (name, script) = child
+                .find_public_definition(activation, "Object".into())
+                .unwrap();
+            assert_eq!(
+                name,
+                QName::new(activation.avm2().public_namespace, "Object")
+            );
+
+            let secret = QName::new(Namespace::private("", mc), "Secret");
+            child.export_definition(secret, script, mc);
+            assert!(child.has_definition(secret));
+            assert!(child
+                .find_public_definition(activation, "Secret".into())
+                .is_err());
+        });
+    }
+
+    #[test]
+    fn script_for_class_finds_exporting_script() {
+        rootless_arena(|mc| {
+            let parent = Domain::global_domain(mc);
+            let child = child_domain(mc, parent);
+            let parent_script = new_script(mc, parent);
+            let child_script = new_script(mc, child);
+
+            let base = new_class(mc, "Base");
+            parent.export_class(base, parent_script, mc);
+            let first = new_class(mc, "Main");
+            let second = new_class(mc, "Main");
+            child.export_class(first, child_script, mc);
+            child.export_class(second, parent_script, mc);
+
+            let script = child.script_for_class(base).unwrap();
+            assert!(Script::ptr_eq(script, parent_script));
+            let script = child.script_for_class(second).unwrap();
+            assert!(Script::ptr_eq(script, parent_script));
+            // `first` was replaced, and isn't exported anywhere any more.
+            assert!(child.script_for_class(first).is_none());
+            // Children aren't searched.
+            assert!(parent.script_for_class(second).is_none());
+        });
+    }
+
+    #[test]
+    fn candidate_namespaces_include_parents() {
+        use crate::avm2::object::ScriptObject;
+
+        rootless_arena(|mc| {
+            let mut parent = Domain::global_domain(mc);
+            let mut child = child_domain(mc, parent);
+
+            let globals = ScriptObject::custom_object(mc, None, None);
+            let script = Script::empty_script(mc, globals, child);
+            let ui = Namespace::package("game.ui", mc);
+            let world = Namespace::package("game.world", mc);
+            child.export_definition(QName::new(ui, "Button"), script, mc);
+            parent.export_definition(QName::new(world, "Button"), script, mc);
+            parent.export_definition(QName::new(ui, "Button"), script, mc);
+            parent.export_definition(QName::new(world, "Tile"), script, mc);
+
+            let button = WStr::from_units(b"Button");
+            assert_eq!(child.candidate_namespaces_for(button), [ui, world]);
+            assert_eq!(parent.candidate_namespaces_for(button).len(), 2);
+            assert!(child
+                .candidate_namespaces_for(WStr::from_units(b"Missing"))
+                .is_empty());
+        });
+    }
+
+    #[test]
+    fn debug_tree_snapshot() {
+        use crate::avm2::object::ScriptObject;
+
+        rootless_arena(|mc| {
+            let mut parent = Domain::global_domain(mc);
+            let mut child = child_domain(mc, parent);
+
+            let globals = ScriptObject::custom_object(mc, None, None);
+            let script = Script::empty_script(mc, globals, child);
+            parent.export_definition(QName::new(Namespace::package("", mc), "Zeta"), script, mc);
+            parent.export_definition(
+                QName::new(Namespace::package("flash.display", mc), "Sprite"),
+                script,
+                mc,
+            );
+            parent.export_class(new_class(mc, "Zeta"), script, mc);
+            child.export_definition(QName::new(Namespace::package("", mc), "Main"), script, mc);
+            child.export_definition(
+                QName::new(Namespace::package("game", mc), "Level"),
+                script,
+                mc,
+            );
+
+            assert_eq!(
+                child.debug_tree(),
+                "Domain
+  definitions: Main, game::Level
+  classes: (none)
+  parent:
+    Domain
+      definitions: Zeta, flash.display::Sprite
+      classes: Zeta
+"
+            );
+        });
+    }
+}
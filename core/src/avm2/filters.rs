@@ -1,12 +1,16 @@
-use ruffle_render::filters::{DisplacementMapFilter, DisplacementMapFilterMode, Filter};
+use ruffle_render::filters::{
+    DisplacementMapFilter, DisplacementMapFilterMode, Filter, ShaderFilter,
+};
 use swf::{
     BevelFilter, BevelFilterFlags, BlurFilter, BlurFilterFlags, Color, ColorMatrixFilter,
     ConvolutionFilter, ConvolutionFilterFlags, DropShadowFilter, DropShadowFilterFlags, Fixed16,
     Fixed8, GlowFilter, GlowFilterFlags, GradientFilter, GradientFilterFlags, GradientRecord,
 };
 
+use crate::avm2::bytearray::ByteArrayStorage;
 use crate::avm2::error::{argument_error, type_error};
-use crate::avm2::{Activation, ArrayObject, ClassObject, Error, Object, TObject, Value};
+use crate::avm2::object::ByteArrayObject;
+use crate::avm2::{Activation, ArrayObject, ClassObject, Error, Multiname, Object, TObject, Value};
 
 pub trait FilterAvm2Ext {
     fn from_avm2_object<'gc>(
@@ -74,6 +78,11 @@ impl FilterAvm2Ext for Filter {
             )?));
         }
 
+        let shader_filter = activation.avm2().classes().shaderfilter;
+        if object.is_of_type(shader_filter, activation) {
+            return avm2_to_shader_filter(activation, object);
+        }
+
         Err(Error::AvmError(type_error(
             activation,
             &format!(
@@ -105,6 +114,7 @@ impl FilterAvm2Ext for Filter {
                 let gradientglowfilter = activation.avm2().classes().gradientglowfilter;
                 gradient_filter_to_avm2(activation, filter, gradientglowfilter)
             }
+            Filter::ShaderFilter(filter) => shader_filter_to_avm2(activation, filter),
         }
     }
 }
@@ -758,3 +768,43 @@ fn get_gradient_colors<'gc>(
     }
     Ok(colors)
 }
+
+fn avm2_to_shader_filter<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    object: Object<'gc>,
+) -> Result<Filter, Error<'gc>> {
+    // A filter without a shader, or a shader without bytecode, runs no operations at all.
+    let mut bytecode = Vec::new();
+    if let Value::Object(shader) = object.get_public_property("shader", activation)? {
+        let byte_code = shader.get_property(
+            &Multiname::new(activation.avm2().flash_display_internal, "_byteCode"),
+            activation,
+        )?;
+        if let Some(storage) = byte_code.as_object().and_then(|o| o.as_bytearray()) {
+            bytecode = storage.bytes().to_vec();
+        }
+    }
+    Ok(Filter::ShaderFilter(ShaderFilter {
+        bytecode: bytecode.into(),
+    }))
+}
+
+fn shader_filter_to_avm2<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    filter: &ShaderFilter,
+) -> Result<Object<'gc>, Error<'gc>> {
+    let byte_code = ByteArrayObject::from_storage(
+        activation,
+        ByteArrayStorage::from_vec(filter.bytecode.to_vec()),
+    )?;
+    let shader = activation
+        .avm2()
+        .classes()
+        .shader
+        .construct(activation, &[byte_code.into()])?;
+    activation
+        .avm2()
+        .classes()
+        .shaderfilter
+        .construct(activation, &[shader.into()])
+}
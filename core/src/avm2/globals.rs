@@ -65,6 +65,7 @@ pub struct SystemClasses<'gc> {
     pub xml: ClassObject<'gc>,
     pub xml_list: ClassObject<'gc>,
     pub display_object: ClassObject<'gc>,
+    pub shader: ClassObject<'gc>,
     pub shape: ClassObject<'gc>,
     pub textfield: ClassObject<'gc>,
     pub textformat: ClassObject<'gc>,
@@ -79,6 +80,7 @@ pub struct SystemClasses<'gc> {
     pub graphicsstroke: ClassObject<'gc>,
     pub loaderinfo: ClassObject<'gc>,
     pub bytearray: ClassObject<'gc>,
+    pub dictionary: ClassObject<'gc>,
     pub stage: ClassObject<'gc>,
     pub sprite: ClassObject<'gc>,
     pub simplebutton: ClassObject<'gc>,
@@ -131,6 +133,7 @@ pub struct SystemClasses<'gc> {
     pub glowfilter: ClassObject<'gc>,
     pub gradientbevelfilter: ClassObject<'gc>,
     pub gradientglowfilter: ClassObject<'gc>,
+    pub shaderfilter: ClassObject<'gc>,
     pub texture: ClassObject<'gc>,
     pub cubetexture: ClassObject<'gc>,
     pub rectangletexture: ClassObject<'gc>,
@@ -173,6 +176,7 @@ impl<'gc> SystemClasses<'gc> {
             xml: object,
             xml_list: object,
             display_object: object,
+            shader: object,
             shape: object,
             textfield: object,
             textformat: object,
@@ -187,6 +191,7 @@ impl<'gc> SystemClasses<'gc> {
             graphicsstroke: object,
             loaderinfo: object,
             bytearray: object,
+            dictionary: object,
             stage: object,
             sprite: object,
             simplebutton: object,
@@ -239,6 +244,7 @@ impl<'gc> SystemClasses<'gc> {
             glowfilter: object,
             gradientbevelfilter: object,
             gradientglowfilter: object,
+            shaderfilter: object,
             texture: object,
             cubetexture: object,
             rectangletexture: object,
@@ -336,7 +342,7 @@ fn class<'gc>(
         activation.avm2().classes().class,
     );
     domain.export_definition(class_name, script, activation.context.gc_context);
-    domain.export_class(class_def, activation.context.gc_context);
+    domain.export_class(class_def, script, activation.context.gc_context);
 
     Ok(class_object)
 }
@@ -388,24 +394,24 @@ pub fn load_player_globals<'gc>(
     let object_classdef = object::create_class(activation);
     let object_class = ClassObject::from_class_partial(activation, object_classdef, None)?;
     let object_proto = ScriptObject::custom_object(mc, Some(object_class), None);
-    domain.export_class(object_classdef, mc);
+    domain.export_class(object_classdef, script, mc);
 
     let fn_classdef = function::create_class(activation);
     let fn_class = ClassObject::from_class_partial(activation, fn_classdef, Some(object_class))?;
     let fn_proto = ScriptObject::custom_object(mc, Some(fn_class), Some(object_proto));
-    domain.export_class(fn_classdef, mc);
+    domain.export_class(fn_classdef, script, mc);
 
     let class_classdef = class::create_class(activation);
     let class_class =
         ClassObject::from_class_partial(activation, class_classdef, Some(object_class))?;
     let class_proto = ScriptObject::custom_object(mc, Some(object_class), Some(object_proto));
-    domain.export_class(class_classdef, mc);
+    domain.export_class(class_classdef, script, mc);
 
     let global_classdef = global_scope::create_class(activation);
     let global_class =
         ClassObject::from_class_partial(activation, global_classdef, Some(object_class))?;
     let global_proto = ScriptObject::custom_object(mc, Some(object_class), Some(object_proto));
-    domain.export_class(global_classdef, mc);
+    domain.export_class(global_classdef, script, mc);
 
     // Now to weave the Gordian knot...
     object_class.link_prototype(activation, object_proto)?;
@@ -632,6 +638,7 @@ fn load_playerglobal<'gc>(
             ("flash.display", "Graphics", graphics),
             ("flash.display", "LoaderInfo", loaderinfo),
             ("flash.display", "MovieClip", movieclip),
+            ("flash.display", "Shader", shader),
             ("flash.display", "Shape", shape),
             ("flash.display", "SimpleButton", simplebutton),
             ("flash.display", "Sprite", sprite),
@@ -675,6 +682,7 @@ fn load_playerglobal<'gc>(
             ("flash.media", "SoundTransform", soundtransform),
             ("flash.net", "URLVariables", urlvariables),
             ("flash.utils", "ByteArray", bytearray),
+            ("flash.utils", "Dictionary", dictionary),
             ("flash.system", "ApplicationDomain", application_domain),
             ("flash.text", "StaticText", statictext),
             ("flash.text", "TextFormat", textformat),
@@ -694,6 +702,7 @@ fn load_playerglobal<'gc>(
             ("flash.filters", "GlowFilter", glowfilter),
             ("flash.filters", "GradientBevelFilter", gradientbevelfilter),
             ("flash.filters", "GradientGlowFilter", gradientglowfilter),
+            ("flash.filters", "ShaderFilter", shaderfilter),
         ]
     );
 
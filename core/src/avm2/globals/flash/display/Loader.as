@@ -31,15 +31,15 @@ package flash.display {
 
 		public native function loadBytes(data: ByteArray, context: LoaderContext = null):void;
 		
+		private native function unloadContent():void;
+
 		public function unload():void {
-			stub_method("flash.display.Loader", "unload");
-			// Content seems to prefer an error here, over an empty implementation.
-			// https://github.com/ruffle-rs/ruffle/pull/8909
-			throw new Error("flash.display.Loader.unload - not yet implemented");
+			this.unloadContent();
 		}
 
 		public function unloadAndStop(gc:Boolean = true):void {
-			stub_method("flash.display.Loader", "unloadAndStop");
+			stub_method("flash.display.Loader", "unloadAndStop", "stopping sounds, timers and streams");
+			this.unloadContent();
 		}
 		
 		public function close():void {
@@ -0,0 +1,19 @@
+package flash.display {
+	import flash.utils.ByteArray;
+
+	public class Shader {
+		internal var _byteCode: ByteArray;
+
+		public var precisionHint: String = ShaderPrecision.FULL;
+
+		public function Shader(code:ByteArray = null) {
+			if (code) {
+				this.byteCode = code;
+			}
+		}
+
+		public function set byteCode(code:ByteArray):void {
+			this._byteCode = code;
+		}
+	}
+}
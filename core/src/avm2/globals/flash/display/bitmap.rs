@@ -1,6 +1,7 @@
 //! `flash.display.Bitmap` builtin/prototype
 
 use crate::avm2::activation::Activation;
+use crate::avm2::error::argument_error;
 use crate::avm2::globals::flash::display::bitmap_data::fill_bitmap_data_from_symbol;
 use crate::avm2::object::{BitmapDataObject, Object, TObject};
 use crate::avm2::value::Value;
@@ -9,8 +10,8 @@ use crate::avm2::Error;
 use crate::avm2::parameters::ParametersExt;
 use crate::bitmap::bitmap_data::{BitmapData, BitmapDataWrapper};
 use crate::character::Character;
-use crate::display_object::{Bitmap, TDisplayObject};
-use crate::{avm2_stub_getter, avm2_stub_setter};
+use crate::display_object::{Bitmap, PixelSnapping, TDisplayObject};
+use crate::string::AvmString;
 use gc_arena::GcCell;
 
 /// Implements `flash.display.Bitmap`'s `init` method, which is called from the constructor
@@ -25,8 +26,8 @@ pub fn init<'gc>(
         let bitmap_data = args
             .try_get_object(activation, 0)
             .and_then(|o| o.as_bitmap_data_wrapper());
-        //TODO: Pixel snapping is not supported
-        let _pixel_snapping = args.get_string(activation, 1);
+        let pixel_snapping = args.get_string(activation, 1)?;
+        let pixel_snapping = parse_pixel_snapping(activation, pixel_snapping)?;
         let smoothing = args.get_bool(2);
 
         if let Some(bitmap) = this.as_display_object().and_then(|dobj| dobj.as_bitmap()) {
@@ -59,7 +60,7 @@ pub fn init<'gc>(
                         let new_bitmap_data =
                             GcCell::allocate(activation.context.gc_context, BitmapData::default());
 
-                        fill_bitmap_data_from_symbol(activation, bitmap, new_bitmap_data);
+                        fill_bitmap_data_from_symbol(activation, bitmap, new_bitmap_data)?;
                         BitmapDataObject::from_bitmap_data(
                             activation,
                             new_bitmap_data,
@@ -84,6 +85,7 @@ pub fn init<'gc>(
             this.set_public_property("bitmapData", bd_object.into(), activation)?;
 
             bitmap.set_smoothing(activation.context.gc_context, smoothing);
+            bitmap.set_pixel_snapping(activation.context.gc_context, pixel_snapping);
         } else {
             //We are being initialized by AVM2 (and aren't associated with a
             //Bitmap subclass).
@@ -97,6 +99,7 @@ pub fn init<'gc>(
 
             let bitmap =
                 Bitmap::new_with_bitmap_data(&mut activation.context, 0, bitmap_data, smoothing);
+            bitmap.set_pixel_snapping(activation.context.gc_context, pixel_snapping);
 
             this.init_display_object(&mut activation.context, bitmap.into());
         }
@@ -139,11 +142,14 @@ pub fn set_bitmap_data<'gc>(
     {
         let bitmap_data = args.get(0).unwrap_or(&Value::Null);
         let bitmap_data = if matches!(bitmap_data, Value::Null) {
-            GcCell::allocate(activation.context.gc_context, BitmapData::dummy())
+            BitmapDataWrapper::new(GcCell::allocate(
+                activation.context.gc_context,
+                BitmapData::dummy(),
+            ))
         } else {
             bitmap_data
                 .coerce_to_object(activation)?
-                .as_bitmap_data()
+                .as_bitmap_data_wrapper()
                 .ok_or_else(|| Error::RustError("Argument was not a BitmapData".into()))?
         };
         bitmap.set_bitmap_data(&mut activation.context, bitmap_data);
@@ -152,23 +158,51 @@ pub fn set_bitmap_data<'gc>(
     Ok(Value::Undefined)
 }
 
-/// Stub `Bitmap.pixelSnapping`'s getter
-pub fn get_pixel_snapping<'gc>(
+fn parse_pixel_snapping<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
+    pixel_snapping: AvmString<'gc>,
+) -> Result<PixelSnapping, Error<'gc>> {
+    match PixelSnapping::from_wstr(&pixel_snapping) {
+        Some(pixel_snapping) => Ok(pixel_snapping),
+        None => Err(Error::AvmError(argument_error(
+            activation,
+            "Error #2008: Parameter pixelSnapping must be one of the accepted values.",
+            2008,
+        )?)),
+    }
+}
+
+/// Implement `Bitmap.pixelSnapping`'s getter
+pub fn get_pixel_snapping<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_getter!(activation, "flash.display.Bitmap", "pixelSnapping");
-    Ok("auto".into())
+    if let Some(bitmap) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|dobj| dobj.as_bitmap())
+    {
+        return Ok(bitmap.pixel_snapping().name().into());
+    }
+
+    Ok(Value::Undefined)
 }
 
-/// Stub `Bitmap.pixelSnapping`'s setter
+/// Implement `Bitmap.pixelSnapping`'s setter
 pub fn set_pixel_snapping<'gc>(
     activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    avm2_stub_setter!(activation, "flash.display.Bitmap", "pixelSnapping");
+    if let Some(bitmap) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|dobj| dobj.as_bitmap())
+    {
+        let pixel_snapping = args.get_string(activation, 0)?;
+        let pixel_snapping = parse_pixel_snapping(activation, pixel_snapping)?;
+        bitmap.set_pixel_snapping(activation.context.gc_context, pixel_snapping);
+    }
+
     Ok(Value::Undefined)
 }
 
@@ -204,3 +238,40 @@ pub fn set_smoothing<'gc>(
 
     Ok(Value::Undefined)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::with_avm2_activation;
+
+    #[test]
+    fn pixel_snapping_round_trips() {
+        with_avm2_activation(|activation| {
+            let class = activation.avm2().classes().bitmap;
+            let args = [Value::Null, "always".into(), false.into()];
+            let mut bitmap = class.construct(activation, &args).unwrap();
+            let display_object = bitmap.as_display_object().unwrap();
+            assert_eq!(
+                display_object.as_bitmap().unwrap().pixel_snapping(),
+                PixelSnapping::Always
+            );
+
+            bitmap
+                .set_public_property("pixelSnapping", "never".into(), activation)
+                .unwrap();
+            let pixel_snapping = bitmap
+                .get_public_property("pixelSnapping", activation)
+                .unwrap()
+                .coerce_to_string(activation)
+                .unwrap();
+            assert_eq!(pixel_snapping.to_string(), "never");
+
+            assert!(bitmap
+                .set_public_property("pixelSnapping", "sometimes".into(), activation)
+                .is_err());
+            assert!(class
+                .construct(activation, &[Value::Null, "sometimes".into()])
+                .is_err());
+        });
+    }
+}
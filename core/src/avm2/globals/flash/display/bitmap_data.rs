@@ -9,14 +9,14 @@ use crate::avm2::vector::VectorStorage;
 use crate::avm2::Error;
 use crate::bitmap::bitmap_data::{BitmapData, ChannelOptions, ThresholdOperation};
 use crate::bitmap::bitmap_data::{BitmapDataDrawError, IBitmapDrawable};
-use crate::bitmap::{is_size_valid, operations};
+use crate::bitmap::{is_size_valid, operations, BitmapAllocation, BitmapMemoryBudget};
 use crate::character::Character;
 use crate::display_object::Bitmap;
 use crate::swf::BlendMode;
 use gc_arena::GcCell;
+use ruffle_render::bitmap::PixelRegion;
 use ruffle_render::filters::Filter;
 use ruffle_render::transform::Transform;
-use std::str::FromStr;
 
 pub use crate::avm2::object::bitmap_data_allocator;
 use crate::avm2::parameters::{null_parameter_error, ParametersExt};
@@ -30,15 +30,32 @@ pub fn fill_bitmap_data_from_symbol<'gc>(
     activation: &mut Activation<'_, 'gc>,
     bd: Bitmap<'gc>,
     new_bitmap_data: GcCell<'gc, BitmapData<'gc>>,
-) {
-    new_bitmap_data
-        .write(activation.context.gc_context)
-        .set_pixels(
-            bd.width().into(),
-            bd.height().into(),
-            true,
-            bd.bitmap_data().read().pixels().to_vec(),
-        );
+) -> Result<(), Error<'gc>> {
+    let (width, height) = (bd.width().into(), bd.height().into());
+    let memory = allocate_memory(activation, width, height)?;
+
+    let mut write = new_bitmap_data.write(activation.context.gc_context);
+    write.set_pixels(width, height, true, bd.bitmap_data().read().shared_pixels());
+    write.set_memory(memory);
+    Ok(())
+}
+
+/// Reserves the pixel memory of a `width` by `height` bitmap from the player's budget,
+/// throwing if that would exceed its limit.
+fn allocate_memory<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    width: u32,
+    height: u32,
+) -> Result<BitmapAllocation, Error<'gc>> {
+    let bytes = BitmapMemoryBudget::bytes_for(width, height);
+    match activation.context.bitmap_memory.try_allocate(bytes) {
+        Some(memory) => Ok(memory),
+        None => Err(Error::AvmError(argument_error(
+            activation,
+            "Error #2015: Invalid BitmapData.",
+            2015,
+        )?)),
+    }
 }
 
 /// Implements `flash.display.BitmapData`'s 'init' method (invoked from the AS3 constructor)
@@ -75,7 +92,7 @@ pub fn init<'gc>(
 
             if let Some(Character::Bitmap(bitmap)) = character {
                 // Instantiating BitmapData from an Animate-style bitmap asset
-                fill_bitmap_data_from_symbol(activation, bitmap, new_bitmap_data);
+                fill_bitmap_data_from_symbol(activation, bitmap, new_bitmap_data)?;
             } else {
                 if character.is_some() {
                     //TODO: Determine if mismatched symbols will still work as a
@@ -95,9 +112,11 @@ pub fn init<'gc>(
                     return Err("Bitmap size is not valid".into());
                 }
 
-                new_bitmap_data
-                    .write(activation.context.gc_context)
-                    .init_pixels(width, height, transparency, fill_color as i32);
+                let memory = allocate_memory(activation, width, height)?;
+
+                let mut write = new_bitmap_data.write(activation.context.gc_context);
+                write.init_pixels(width, height, transparency, fill_color as i32);
+                write.set_memory(memory);
             }
 
             new_bitmap_data
@@ -509,7 +528,8 @@ pub fn flood_fill<'gc>(
             let y = args.get_u32(activation, 1)?;
             let color = args.get_i32(activation, 2)?;
 
-            operations::flood_fill(&mut activation.context, bitmap_data, x, y, color);
+            let limit = activation.context.flood_fill_limit;
+            operations::flood_fill(&mut activation.context, bitmap_data, x, y, color, limit);
         }
     }
 
@@ -622,8 +642,8 @@ pub fn get_color_bounds_rect<'gc>(
 }
 
 pub fn lock<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     // `BitmapData.lock` tells Flash Player to temporarily stop updating the player's
@@ -636,16 +656,46 @@ pub fn lock<'gc>(
     // instances on the stage, based on how the player decides to update its dirty region
     // ("Show Redraw Regions" in Flash Player debugger context menu).
     //
-    // Ruffle has no concept of a player dirty region for now, so this has no effect.
+    // Ruffle has no player dirty region, so we approximate this by holding back
+    // texture uploads for this BitmapData until `unlock` is called.
+    if let Some(bitmap_data) = this.and_then(|t| t.as_bitmap_data_wrapper()) {
+        if !bitmap_data.disposed() {
+            bitmap_data.lock(activation.context.gc_context);
+        }
+    }
+
     Ok(Value::Undefined)
 }
 
 pub fn unlock<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
-    _this: Option<Object<'gc>>,
-    _args: &[Value<'gc>],
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
-    // No effect (see comments for `lock`).
+    if let Some(bitmap_data) = this.and_then(|t| t.as_bitmap_data_wrapper()) {
+        if !bitmap_data.disposed() {
+            let change_rect = if let Some(rectangle) = args.try_get_object(activation, 0) {
+                let x = rectangle
+                    .get_public_property("x", activation)?
+                    .coerce_to_i32(activation)?;
+                let y = rectangle
+                    .get_public_property("y", activation)?
+                    .coerce_to_i32(activation)?;
+                let width = rectangle
+                    .get_public_property("width", activation)?
+                    .coerce_to_i32(activation)?;
+                let height = rectangle
+                    .get_public_property("height", activation)?
+                    .coerce_to_i32(activation)?;
+                Some(PixelRegion::for_region_i32(x, y, width, height))
+            } else {
+                None
+            };
+
+            bitmap_data.unlock(activation.context.gc_context, change_rect);
+        }
+    }
+
     Ok(Value::Undefined)
 }
 
@@ -796,7 +846,7 @@ pub fn draw<'gc>(
         }
 
         if let Some(mode) = args.try_get_string(activation, 3)? {
-            if let Ok(mode) = BlendMode::from_str(&mode.to_string()) {
+            if let Some(mode) = BlendMode::from_name_or_number(&mode.to_string()) {
                 blend_mode = mode;
             } else {
                 tracing::error!("Unknown blend mode {:?}", mode);
@@ -841,6 +891,7 @@ pub fn draw<'gc>(
             blend_mode,
             clip_rect,
             quality,
+            false,
         ) {
             Ok(()) => {}
             Err(BitmapDataDrawError::Unimplemented) => {
@@ -875,7 +926,7 @@ pub fn draw_with_quality<'gc>(
         }
 
         if let Some(mode) = args.try_get_string(activation, 3)? {
-            if let Ok(mode) = BlendMode::from_str(&mode.to_string()) {
+            if let Some(mode) = BlendMode::from_name_or_number(&mode.to_string()) {
                 blend_mode = mode;
             } else {
                 tracing::error!("Unknown blend mode {:?}", mode);
@@ -929,6 +980,7 @@ pub fn draw_with_quality<'gc>(
             blend_mode,
             clip_rect,
             quality,
+            false,
         ) {
             Ok(()) => {}
             Err(BitmapDataDrawError::Unimplemented) => {
@@ -1070,7 +1122,9 @@ pub fn clone<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(bitmap_data) = this.and_then(|this| this.as_bitmap_data_wrapper()) {
         if !bitmap_data.disposed() {
-            let new_bitmap_data = operations::clone(bitmap_data);
+            let memory = allocate_memory(activation, bitmap_data.width(), bitmap_data.height())?;
+            let mut new_bitmap_data = operations::clone(bitmap_data);
+            new_bitmap_data.set_memory(memory);
 
             let class = activation.avm2().classes().bitmapdata;
             let new_bitmap_data_object = BitmapDataObject::from_bitmap_data(
@@ -1289,3 +1343,134 @@ pub fn threshold<'gc>(
 
     Ok(Value::Undefined)
 }
+
+/// Implement `BitmapData.pixelDissolve`
+pub fn pixel_dissolve<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(bitmap_data) = this.and_then(|this| this.as_bitmap_data_wrapper()) {
+        bitmap_data.check_valid(activation)?;
+        let src_bitmap = args.get_object(activation, 0, "sourceBitmapData")?;
+        let source_rect = args.get_object(activation, 1, "sourceRect")?;
+        let dest_point = args.get_object(activation, 2, "destPoint")?;
+        let dest_point = (
+            dest_point
+                .get_public_property("x", activation)?
+                .coerce_to_i32(activation)?,
+            dest_point
+                .get_public_property("y", activation)?
+                .coerce_to_i32(activation)?,
+        );
+        let random_seed = args.get_i32(activation, 3)?;
+        let num_pixels = args.get_i32(activation, 4)?;
+        let fill_color = args.get_u32(activation, 5)?;
+
+        let src_min_x = source_rect
+            .get_public_property("x", activation)?
+            .coerce_to_i32(activation)?;
+        let src_min_y = source_rect
+            .get_public_property("y", activation)?
+            .coerce_to_i32(activation)?;
+        let src_width = source_rect
+            .get_public_property("width", activation)?
+            .coerce_to_i32(activation)?;
+        let src_height = source_rect
+            .get_public_property("height", activation)?
+            .coerce_to_i32(activation)?;
+
+        if let Some(src_bitmap) = src_bitmap.as_bitmap_data_wrapper() {
+            src_bitmap.check_valid(activation)?;
+
+            return Ok(operations::pixel_dissolve(
+                &mut activation.context,
+                bitmap_data,
+                Some(src_bitmap),
+                (src_min_x, src_min_y, src_width, src_height),
+                dest_point,
+                random_seed,
+                num_pixels,
+                fill_color,
+            )
+            .into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::with_player;
+
+    fn error_id<'gc>(activation: &mut Activation<'_, 'gc>, error: Error<'gc>) -> i32 {
+        match error {
+            Error::AvmError(error) => error
+                .as_object()
+                .unwrap()
+                .get_public_property("errorID", activation)
+                .unwrap()
+                .coerce_to_i32(activation)
+                .unwrap(),
+            Error::RustError(error) => panic!("Unexpected internal error: {error}"),
+        }
+    }
+
+    #[test]
+    fn constructor_respects_memory_budget() {
+        with_player(
+            crate::player::PlayerBuilder::new()
+                .with_movie(crate::tag_utils::SwfMovie::empty(10))
+                .with_bitmap_memory_limit(Some(1000)),
+            |context| {
+                let mut activation = Activation::from_nothing(context.reborrow());
+                let class = activation.avm2().classes().bitmapdata;
+                let args = [10.into(), 10.into()];
+
+                let first = class.construct(&mut activation, &args).unwrap();
+                class.construct(&mut activation, &args).unwrap();
+                assert_eq!(activation.context.bitmap_memory.used(), 800);
+
+                let Err(error) = class.construct(&mut activation, &args) else {
+                    panic!("BitmapData past the memory limit was constructed");
+                };
+                assert_eq!(error_id(&mut activation, error), 2015);
+                assert_eq!(activation.context.bitmap_memory.used(), 800);
+
+                first
+                    .as_bitmap_data_wrapper()
+                    .unwrap()
+                    .dispose(activation.context.gc_context);
+                assert_eq!(activation.context.bitmap_memory.used(), 400);
+                class.construct(&mut activation, &args).unwrap();
+                assert_eq!(activation.context.bitmap_memory.used(), 800);
+            },
+        );
+    }
+
+    #[test]
+    fn clone_respects_memory_budget() {
+        with_player(
+            crate::player::PlayerBuilder::new()
+                .with_movie(crate::tag_utils::SwfMovie::empty(10))
+                .with_bitmap_memory_limit(Some(1000)),
+            |context| {
+                let mut activation = Activation::from_nothing(context.reborrow());
+                let class = activation.avm2().classes().bitmapdata;
+                let original = class
+                    .construct(&mut activation, &[10.into(), 10.into()])
+                    .unwrap();
+
+                let copy = clone(&mut activation, Some(original), &[]).unwrap();
+                assert!(copy.as_object().is_some());
+                assert_eq!(activation.context.bitmap_memory.used(), 800);
+
+                let error = clone(&mut activation, Some(original), &[]).unwrap_err();
+                assert_eq!(error_id(&mut activation, error), 2015);
+                assert_eq!(activation.context.bitmap_memory.used(), 800);
+            },
+        );
+    }
+}
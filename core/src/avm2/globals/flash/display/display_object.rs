@@ -17,7 +17,6 @@ use crate::types::{Degrees, Percent};
 use crate::vminterface::Instantiator;
 use crate::{avm2_stub_getter, avm2_stub_setter};
 use ruffle_render::filters::Filter;
-use std::str::FromStr;
 use swf::BlendMode;
 
 pub use crate::avm2::object::stage_allocator as display_object_allocator;
@@ -758,7 +757,7 @@ pub fn set_blend_mode<'gc>(
     if let Some(dobj) = this.and_then(|this| this.as_display_object()) {
         let mode = args.get_string(activation, 0)?;
 
-        if let Ok(mode) = BlendMode::from_str(&mode.to_string()) {
+        if let Some(mode) = BlendMode::from_name_or_number(&mode.to_string()) {
             dobj.set_blend_mode(activation.context.gc_context, mode);
         } else {
             tracing::error!("Unknown blend mode {}", mode);
@@ -10,6 +10,7 @@ use crate::avm2::{Error, Object};
 use crate::backend::navigator::Request;
 use crate::display_object::LoaderDisplay;
 use crate::display_object::MovieClip;
+use crate::display_object::{TDisplayObject, TDisplayObjectContainer};
 use crate::loader::{Avm2LoaderData, MovieLoaderEventHandler};
 use crate::tag_utils::SwfMovie;
 use std::sync::Arc;
@@ -136,3 +137,40 @@ pub fn load_bytes<'gc>(
     }
     Ok(Value::Undefined)
 }
+
+/// Implements the shared part of `Loader.unload` and `Loader.unloadAndStop`.
+///
+/// This removes the loaded content, and clears the definitions of the domain it
+/// was loaded into so that its scripts can be collected. The domain is kept if
+/// the calling code also resolves definitions through it, as it does when the
+/// content was loaded into `ApplicationDomain.currentDomain`.
+pub fn unload_content<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let Some(mut loader) = this
+        .and_then(|this| this.as_display_object())
+        .and_then(|this| this.as_container())
+    else {
+        return Ok(Value::Undefined);
+    };
+    let Some(content) = loader.child_by_index(0) else {
+        return Ok(Value::Undefined);
+    };
+
+    loader.remove_child(&mut activation.context, content);
+
+    let domain = activation
+        .context
+        .library
+        .library_for_movie(content.movie())
+        .map(|library| library.avm2_domain());
+    if let Some(domain) = domain {
+        if !domain.is_self_or_parent_of(activation.caller_domain()) {
+            domain.clear_definitions(activation.context.gc_context);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
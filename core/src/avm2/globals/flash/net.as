@@ -5,9 +5,9 @@ package flash.net {
 
     public native function navigateToURL(request:URLRequest, window:String = null):void;
 
-    public function registerClassAlias(a:String, b:Object):void {
-        stub_method("flash.net", "registerClassAlias");
-    }
+    public native function registerClassAlias(aliasName:String, classObject:Class):void;
+
+    public native function getClassByAlias(aliasName:String):Class;
 
     public function sendToURL(request:URLRequest):void {
         stub_method("flash.net", "sendToURL");
@@ -1,6 +1,8 @@
 //! `flash.net` namespace
 
+use crate::avm2::error::reference_error;
 use crate::avm2::object::TObject;
+use crate::avm2::parameters::{null_parameter_error, ParametersExt};
 use crate::avm2::{Activation, Error, Object, Value};
 
 pub mod local_connection;
@@ -36,3 +38,42 @@ pub fn navigate_to_url<'gc>(
 
     Ok(Value::Undefined)
 }
+
+/// Implements `flash.net.registerClassAlias`
+pub fn register_class_alias<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let alias = args
+        .try_get_string(activation, 0)?
+        .ok_or_else(|| null_parameter_error(activation, "aliasName"))?;
+    let class = args
+        .get_object(activation, 1, "classObject")?
+        .as_class_object()
+        .ok_or("registerClassAlias: classObject is not a class")?;
+
+    activation.context.avm2.register_class_alias(alias, class);
+
+    Ok(Value::Undefined)
+}
+
+/// Implements `flash.net.getClassByAlias`
+pub fn get_class_by_alias<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    let alias = args
+        .try_get_string(activation, 0)?
+        .ok_or_else(|| null_parameter_error(activation, "aliasName"))?;
+
+    match activation.avm2().get_class_by_alias(alias) {
+        Some(class) => Ok(class.into()),
+        None => Err(Error::AvmError(reference_error(
+            activation,
+            &format!("Error #1014: Class {alias} could not be found."),
+            1014,
+        )?)),
+    }
+}
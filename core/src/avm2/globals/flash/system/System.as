@@ -12,6 +12,9 @@ package flash.system {
 
         public static native function setClipboard(string:String): void;
 
+        public static native function get useCodePage(): Boolean;
+        public static native function set useCodePage(value:Boolean): void;
+
         public static function disposeXML(node:XML):void {
             stub_method("flash.system.System", "disposeXML");
         }
@@ -28,6 +28,7 @@ pub fn init<'gc>(
         };
         let fresh_domain = Domain::movie_domain(activation, parent_domain);
         this.init_application_domain(activation.context.gc_context, fresh_domain);
+        fresh_domain.set_domain_object(activation.context.gc_context, this);
     }
 
     Ok(Value::Undefined)
@@ -121,14 +122,52 @@ pub fn set_domain_memory<'gc>(
 
 /// `domainMemory` property getter
 pub fn get_domain_memory<'gc>(
-    _activation: &mut Activation<'_, 'gc>,
+    activation: &mut Activation<'_, 'gc>,
     this: Option<Object<'gc>>,
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(appdomain) = this.and_then(|this| this.as_application_domain()) {
-        let bytearray_object: Object<'gc> = appdomain.domain_memory().into();
+        let bytearray_object: Object<'gc> = appdomain.domain_memory(activation).into();
         return Ok(bytearray_object.into());
     }
 
     Ok(Value::Undefined)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::context::UpdateContext;
+    use crate::test_utils::with_update_context;
+
+    /// Evaluates `ApplicationDomain.currentDomain` in code running in `domain`.
+    fn current_domain<'gc>(
+        context: &mut UpdateContext<'_, 'gc>,
+        domain: Domain<'gc>,
+    ) -> Value<'gc> {
+        let mut activation = Activation::from_domain(context.reborrow(), domain);
+        get_current_domain(&mut activation, None, &[]).unwrap()
+    }
+
+    #[test]
+    fn application_domains_are_identical_per_domain() {
+        with_update_context(|context| {
+            let global = context.avm2.global_domain();
+            let mut activation = Activation::from_nothing(context.reborrow());
+            let first = Domain::movie_domain_lazy(&mut activation, global);
+            let second = Domain::movie_domain_lazy(&mut activation, global);
+            drop(activation);
+
+            let first_object = current_domain(context, first);
+            assert!(first_object.strict_eq(&current_domain(context, first)));
+            assert!(!first_object.strict_eq(&current_domain(context, second)));
+            assert!(!first_object.strict_eq(&current_domain(context, global)));
+
+            // `parentDomain` hands out the same object as `currentDomain` does.
+            let mut activation = Activation::from_domain(context.reborrow(), first);
+            let parent = get_parent_domain(&mut activation, first_object.as_object(), &[]).unwrap();
+            drop(activation);
+            assert!(parent.strict_eq(&current_domain(context, global)));
+        });
+    }
+}
@@ -23,3 +23,24 @@ pub fn set_clipboard<'gc>(
 
     Ok(Value::Undefined)
 }
+
+/// Implements `flash.system.System.useCodePage`'s getter
+pub fn get_use_code_page<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    Ok(activation.context.system.use_codepage.into())
+}
+
+/// Implements `flash.system.System.useCodePage`'s setter
+pub fn set_use_code_page<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    _this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    activation.context.system.use_codepage =
+        args.get(0).unwrap_or(&Value::Undefined).coerce_to_boolean();
+
+    Ok(Value::Undefined)
+}
@@ -24,6 +24,9 @@ package flash.utils {
 		public native function get position():uint;
 		public native function set position(value:uint):void;
 
+		public native function get shareable():Boolean;
+		public native function set shareable(value:Boolean):void;
+
 		public function ByteArray() {
 			this.init();
 			this.objectEncoding = _defaultObjectEncoding;
@@ -33,6 +36,9 @@ package flash.utils {
 
 		public native function clear():void;
 
+		public native function atomicCompareAndSwapIntAt(byteIndex:int, expectedValue:int, newValue:int):int;
+		public native function atomicCompareAndSwapLength(expectedLength:int, newLength:int):int;
+
 		public function deflate(): void {
 			this.compress("deflate");
 		}
@@ -1,5 +1,8 @@
 use crate::avm2::activation::Activation;
-use crate::avm2::bytearray::{Endian, ObjectEncoding};
+use crate::avm2::amf::{Amf0Reader, Amf0Writer};
+use crate::avm2::amf3::{Amf3Reader, Amf3Writer};
+use crate::avm2::bytearray::{Endian, EofError, ObjectEncoding};
+use crate::avm2::error::{argument_error, range_error};
 pub use crate::avm2::object::byte_array_allocator;
 use crate::avm2::object::{Object, TObject};
 use crate::avm2::value::Value;
@@ -8,9 +11,6 @@ use crate::character::Character;
 use crate::string::AvmString;
 use encoding_rs::Encoding;
 use encoding_rs::UTF_8;
-use flash_lso::amf0::read::AMF0Decoder;
-use flash_lso::amf3::read::AMF3Decoder;
-use flash_lso::types::{AMFVersion, Element};
 
 /// Implements `flash.utils.ByteArray`'s instance constructor.
 pub fn init<'gc>(
@@ -85,6 +85,20 @@ pub fn write_bytes<'gc>(
             .get(2)
             .unwrap_or(&Value::Integer(0))
             .coerce_to_u32(activation)? as usize;
+
+        // Flash never throws on a bad range here; it clips both the offset and
+        // the length to the source, and a length of 0 means "everything after `offset`".
+        let source_len = bytearray
+            .as_bytearray()
+            .ok_or("ArgumentError: Parameter must be a bytearray")?
+            .len();
+        let offset = offset.min(source_len);
+        let length = if length == 0 || offset.saturating_add(length) > source_len {
+            source_len - offset
+        } else {
+            length
+        };
+
         if !Object::ptr_eq(this, bytearray) {
             // The ByteArray we are reading from is different than the ByteArray we are writing to,
             // so we are allowed to borrow both at the same time without worrying about a panic
@@ -93,15 +107,7 @@ pub fn write_bytes<'gc>(
                 .as_bytearray()
                 .ok_or("ArgumentError: Parameter must be a bytearray")?;
             let to_write = ba_read
-                .read_at(
-                    // If length is 0, lets read the remaining bytes of ByteArray from the supplied offset
-                    if length != 0 {
-                        length
-                    } else {
-                        ba_read.len().saturating_sub(offset)
-                    },
-                    offset,
-                )
+                .read_at(length, offset)
                 .map_err(|e| e.to_avm(activation))?;
 
             if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
@@ -110,12 +116,7 @@ pub fn write_bytes<'gc>(
         } else if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
             // The ByteArray we are reading from is the same as the ByteArray we are writing to,
             // so we only need to borrow once, and we can use `write_bytes_within` to write bytes from our own ByteArray
-            let amnt = if length != 0 {
-                length
-            } else {
-                bytearray.len().saturating_sub(offset)
-            };
-            bytearray.write_bytes_within(offset, amnt)?;
+            bytearray.write_bytes_within(offset, length)?;
         }
     }
 
@@ -142,17 +143,37 @@ pub fn read_bytes<'gc>(
             .unwrap_or(&Value::Integer(0))
             .coerce_to_u32(activation)? as usize;
 
+        if bytearray.as_bytearray().is_none() {
+            return Err("ArgumentError: Parameter must be a bytearray".into());
+        }
+
+        let available = this
+            .as_bytearray()
+            .map(|bytearray| bytearray.bytes_available())
+            .unwrap_or_default();
+        // If length is 0, lets read the remaining bytes of ByteArray
+        let length = if length != 0 { length } else { available };
+
+        if offset as u64 + length as u64 > u32::MAX as u64 {
+            return Err(Error::AvmError(range_error(
+                activation,
+                "Error #2006: The supplied index is out of bounds.",
+                2006,
+            )?));
+        }
+
+        if length > available {
+            return Err(EofError.to_avm(activation));
+        }
+
+        if length == 0 {
+            return Ok(Value::Undefined);
+        }
+
         if !Object::ptr_eq(this, bytearray) {
             if let Some(bytearray_read) = this.as_bytearray() {
                 let to_write = bytearray_read
-                    .read_bytes(
-                        // If length is 0, lets read the remaining bytes of ByteArray
-                        if length != 0 {
-                            length
-                        } else {
-                            bytearray_read.bytes_available()
-                        },
-                    )
+                    .read_bytes(length)
                     .map_err(|e| e.to_avm(activation))?;
 
                 let mut ba_write = bytearray
@@ -162,13 +183,9 @@ pub fn read_bytes<'gc>(
                 ba_write.write_at(to_write, offset)?;
             }
         } else if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
-            let amnt = if length != 0 {
-                length
-            } else {
-                bytearray.bytes_available()
-            };
             let pos = bytearray.position();
-            bytearray.write_at_within(pos, amnt, offset)?;
+            bytearray.write_at_within(pos, length, offset)?;
+            bytearray.set_position(pos + length);
         }
     }
     Ok(Value::Undefined)
@@ -179,14 +196,23 @@ pub fn write_utf<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this {
-        if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
-            if let Some(utf_string) = args.get(0) {
-                let utf_string = utf_string.coerce_to_string(activation)?;
-                // NOTE: there is a bug on old Flash Player (e.g. v11.3); if the string to
-                // write ends with an unpaired high surrogate, the routine bails out and nothing
-                // is written.
-                // The bug is fixed on newer FP versions (e.g. v32), but the fix isn't SWF-version-gated.
-                bytearray.write_utf(&utf_string.to_utf8_lossy())?;
+        if let Some(utf_string) = args.get(0) {
+            let utf_string = utf_string.coerce_to_string(activation)?;
+            // NOTE: there is a bug on old Flash Player (e.g. v11.3); if the string to
+            // write ends with an unpaired high surrogate, the routine bails out and nothing
+            // is written.
+            // The bug is fixed on newer FP versions (e.g. v32), but the fix isn't SWF-version-gated.
+            let utf_string = utf_string.to_utf8_lossy();
+            if utf_string.len() > u16::MAX as usize {
+                return Err(Error::AvmError(range_error(
+                    activation,
+                    "Error #2006: The supplied index is out of bounds.",
+                    2006,
+                )?));
+            }
+
+            if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
+                bytearray.write_utf(&utf_string)?;
             }
         }
     }
@@ -218,11 +244,8 @@ pub fn to_string<'gc>(
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this {
         if let Some(bytearray) = this.as_bytearray() {
-            let mut bytes = bytearray.bytes();
-            if let Some(without_bom) = bytes.strip_prefix(&[0xEF, 0xBB, 0xBF]) {
-                bytes = without_bom;
-            }
-            return Ok(AvmString::new_utf8_bytes(activation.context.gc_context, bytes).into());
+            let text = bytearray.decode_text(activation.context.system.use_codepage);
+            return Ok(AvmString::new(activation.context.gc_context, text).into());
         }
     }
 
@@ -322,6 +345,108 @@ pub fn set_length<'gc>(
     Ok(Value::Undefined)
 }
 
+pub fn get_shareable<'gc>(
+    _activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    _args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this {
+        if let Some(bytearray) = this.as_bytearray() {
+            return Ok(bytearray.shareable().into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn set_shareable<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this {
+        if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
+            let shareable = args.get(0).unwrap_or(&Value::Undefined).coerce_to_boolean();
+            bytearray.set_shareable(shareable);
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn atomic_compare_and_swap_int_at<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this {
+        let index = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_i32(activation)?;
+        let expected = args
+            .get(1)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_i32(activation)?;
+        let new = args
+            .get(2)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_i32(activation)?;
+
+        let previous = this
+            .as_bytearray_mut(activation.context.gc_context)
+            .and_then(|mut bytearray| {
+                let index = usize::try_from(index).ok()?;
+                bytearray.compare_and_swap_int_at(index, expected, new)
+            });
+        return match previous {
+            Some(previous) => Ok(previous.into()),
+            None => Err(Error::AvmError(range_error(
+                activation,
+                "Error #1506: The specified range is invalid.",
+                1506,
+            )?)),
+        };
+    }
+
+    Ok(Value::Undefined)
+}
+
+pub fn atomic_compare_and_swap_length<'gc>(
+    activation: &mut Activation<'_, 'gc>,
+    this: Option<Object<'gc>>,
+    args: &[Value<'gc>],
+) -> Result<Value<'gc>, Error<'gc>> {
+    if let Some(this) = this {
+        let expected = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_i32(activation)?;
+        let new_len = args
+            .get(1)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_i32(activation)?;
+        if new_len < 0 {
+            return Err(Error::AvmError(range_error(
+                activation,
+                "Error #1506: The specified range is invalid.",
+                1506,
+            )?));
+        }
+
+        if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
+            let previous = match usize::try_from(expected) {
+                Ok(expected) => bytearray.compare_and_swap_length(expected, new_len as usize),
+                // A negative length can never match.
+                Err(_) => bytearray.len(),
+            };
+            return Ok(previous.into());
+        }
+    }
+
+    Ok(Value::Undefined)
+}
+
 pub fn get_endian<'gc>(
     _activation: &mut Activation<'_, 'gc>,
     this: Option<Object<'gc>>,
@@ -345,18 +470,24 @@ pub fn set_endian<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this {
+        let endian = args
+            .get(0)
+            .unwrap_or(&Value::Undefined)
+            .coerce_to_string(activation)?;
+        let endian = if &endian == b"bigEndian" {
+            Endian::Big
+        } else if &endian == b"littleEndian" {
+            Endian::Little
+        } else {
+            return Err(Error::AvmError(argument_error(
+                activation,
+                "Error #2008: Parameter type must be one of the accepted values.",
+                2008,
+            )?));
+        };
+
         if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
-            let endian = args
-                .get(0)
-                .unwrap_or(&Value::Undefined)
-                .coerce_to_string(activation)?;
-            if &endian == b"bigEndian" {
-                bytearray.set_endian(Endian::Big);
-            } else if &endian == b"littleEndian" {
-                bytearray.set_endian(Endian::Little);
-            } else {
-                return Err("Parameter type must be one of the accepted values.".into());
-            }
+            bytearray.set_endian(endian);
         }
     }
 
@@ -740,6 +871,11 @@ pub fn compress<'gc>(
                     )?))
                 }
             };
+            // Flash leaves empty ByteArrays untouched instead of writing an
+            // empty compressed stream.
+            if bytearray.len() == 0 {
+                return Ok(Value::Undefined);
+            }
             let buffer = bytearray.compress(algorithm);
             bytearray.clear();
             bytearray.write_bytes(&buffer)?;
@@ -771,6 +907,9 @@ pub fn uncompress<'gc>(
                     )?))
                 }
             };
+            if bytearray.len() == 0 {
+                return Ok(Value::Undefined);
+            }
             let buffer = match bytearray.decompress(algorithm) {
                 Some(buffer) => buffer,
                 None => {
@@ -796,36 +935,24 @@ pub fn read_object<'gc>(
     _args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this {
-        if let Some(bytearray) = this.as_bytearray() {
-            let bytes = bytearray
-                .read_at(bytearray.bytes_available(), bytearray.position())
-                .map_err(|e| e.to_avm(activation))?;
-            let (bytes_left, value) = match bytearray.object_encoding() {
-                ObjectEncoding::Amf0 => {
-                    let mut decoder = AMF0Decoder::default();
-                    let (extra, amf) = decoder
-                        .parse_single_element(bytes)
-                        .map_err(|_| "Error: Invalid object")?;
-                    (
-                        extra.len(),
-                        crate::avm2::amf::deserialize_value(activation, &amf)?,
-                    )
-                }
-                ObjectEncoding::Amf3 => {
-                    let mut decoder = AMF3Decoder::default();
-                    let (extra, amf) = decoder
-                        .parse_single_element(bytes)
-                        .map_err(|_| "Error: Invalid object")?;
-                    (
-                        extra.len(),
-                        crate::avm2::amf::deserialize_value(activation, &amf)?,
-                    )
-                }
-            };
-
-            bytearray.set_position(bytearray.len() - bytes_left);
-            return Ok(value);
+        let Some(bytearray) = this.as_bytearray() else {
+            return Ok(Value::Undefined);
+        };
+
+        // The reader borrows the ByteArray as it goes, as `readExternal`
+        // may read from it too.
+        let object_encoding = bytearray.object_encoding();
+        let position = bytearray.position();
+        drop(bytearray);
+        let result = if object_encoding == ObjectEncoding::Amf3 {
+            Amf3Reader::new(this).read_value(activation)
+        } else {
+            Amf0Reader::new(this).read_value(activation)
+        };
+        if result.is_err() {
+            this.as_bytearray().unwrap().set_position(position);
         }
+        return result;
     }
 
     Ok(Value::Undefined)
@@ -837,28 +964,15 @@ pub fn write_object<'gc>(
     args: &[Value<'gc>],
 ) -> Result<Value<'gc>, Error<'gc>> {
     if let Some(this) = this {
-        if let Some(mut bytearray) = this.as_bytearray_mut(activation.context.gc_context) {
-            let obj = args.get(0).cloned().unwrap_or(Value::Undefined);
-            let amf_version = match bytearray.object_encoding() {
-                ObjectEncoding::Amf0 => AMFVersion::AMF0,
-                ObjectEncoding::Amf3 => AMFVersion::AMF3,
-            };
-            if let Some(amf) = crate::avm2::amf::serialize_value(activation, obj, amf_version) {
-                let element = Element::new("", amf);
-                let mut lso = flash_lso::types::Lso::new(vec![element], "", amf_version);
-                let bytes = flash_lso::write::write_to_bytes(&mut lso)
-                    .map_err(|_| "Failed to serialize object")?;
-                // This is kind of hacky: We need to strip out the header and any padding so that we only write
-                // the value. In the future, there should be a method to do this in the flash_lso crate.
-                let element_padding = match amf_version {
-                    AMFVersion::AMF0 => 8,
-                    AMFVersion::AMF3 => 7,
-                };
-                bytearray.write_bytes(
-                    &bytes[flash_lso::write::header_length(&lso.header) + element_padding
-                        ..bytes.len() - 1],
-                )?;
-            }
+        let Some(object_encoding) = this.as_bytearray().map(|b| b.object_encoding()) else {
+            return Ok(Value::Undefined);
+        };
+        let obj = args.get(0).cloned().unwrap_or(Value::Undefined);
+
+        if object_encoding == ObjectEncoding::Amf3 {
+            Amf3Writer::new(this).write_value(activation, obj)?;
+        } else {
+            Amf0Writer::new(this).write_value(activation, obj)?;
         }
     }
     Ok(Value::Undefined)
@@ -899,3 +1013,467 @@ pub fn set_object_encoding<'gc>(
 
     Ok(Value::Undefined)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::with_avm2_activation;
+
+    fn new_bytearray<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        bytes: &[u8],
+        position: usize,
+    ) -> Object<'gc> {
+        let class = activation.avm2().classes().bytearray;
+        let object = class.construct(activation, &[]).unwrap();
+        let mut storage = object
+            .as_bytearray_mut(activation.context.gc_context)
+            .unwrap();
+        storage.write_bytes(bytes).unwrap();
+        storage.set_position(position);
+        drop(storage);
+        object
+    }
+
+    fn contents(object: Object<'_>) -> (Vec<u8>, usize) {
+        let storage = object.as_bytearray().unwrap();
+        (storage.bytes().to_vec(), storage.position())
+    }
+
+    fn error_id<'gc>(activation: &mut Activation<'_, 'gc>, error: Error<'gc>) -> i32 {
+        match error {
+            Error::AvmError(error) => error
+                .as_object()
+                .unwrap()
+                .get_public_property("errorID", activation)
+                .unwrap()
+                .coerce_to_i32(activation)
+                .unwrap(),
+            Error::RustError(error) => panic!("Unexpected internal error: {error}"),
+        }
+    }
+
+    type Outcome = Result<(Vec<u8>, usize), i32>;
+
+    #[test]
+    fn read_bytes_edge_cases() {
+        // (source, source position, destination, offset, length) => (destination, source position)
+        #[allow(clippy::type_complexity)]
+        let cases: &[(&[u8], usize, &[u8], u32, u32, Outcome)] = &[
+            (&[1, 2, 3, 4], 1, &[], 0, 0, Ok((vec![2, 3, 4], 4))),
+            (&[1, 2, 3, 4], 0, &[9], 3, 2, Ok((vec![9, 0, 0, 1, 2], 2))),
+            (&[1, 2, 3, 4], 4, &[9], 0, 0, Ok((vec![9], 4))),
+            (&[1, 2, 3, 4], 4, &[9], 5, 0, Ok((vec![9], 4))),
+            (&[1, 2, 3, 4], 2, &[], 0, 3, Err(2030)),
+            (&[1, 2, 3, 4], 0, &[], u32::MAX, 1, Err(2006)),
+        ];
+        with_avm2_activation(|activation| {
+            for (source, position, dest, offset, length, expected) in cases {
+                let source = new_bytearray(activation, source, *position);
+                let dest = new_bytearray(activation, dest, 0);
+                let args = [dest.into(), (*offset).into(), (*length).into()];
+                let result = read_bytes(activation, Some(source), &args)
+                    .map(|_| (contents(dest).0, contents(source).1))
+                    .map_err(|e| error_id(activation, e));
+                assert_eq!(&result, expected, "offset {offset}, length {length}");
+            }
+        });
+    }
+
+    #[test]
+    fn read_bytes_into_self() {
+        with_avm2_activation(|activation| {
+            let bytearray = new_bytearray(activation, &[1, 2, 3, 4], 0);
+            let args = [bytearray.into(), 4.into(), 2.into()];
+            read_bytes(activation, Some(bytearray), &args).unwrap();
+            assert_eq!(contents(bytearray), (vec![1, 2, 3, 4, 1, 2], 2));
+        });
+    }
+
+    #[test]
+    fn write_bytes_edge_cases() {
+        // (offset, length) into an empty destination => written bytes
+        let cases: &[(u32, u32, &[u8])] = &[
+            (0, 0, &[1, 2, 3, 4]),
+            (1, 2, &[2, 3]),
+            (2, 10, &[3, 4]),
+            (4, 0, &[]),
+            (10, 1, &[]),
+            (0, u32::MAX, &[1, 2, 3, 4]),
+        ];
+        with_avm2_activation(|activation| {
+            for (offset, length, expected) in cases {
+                let source = new_bytearray(activation, &[1, 2, 3, 4], 0);
+                let dest = new_bytearray(activation, &[], 0);
+                let args = [source.into(), (*offset).into(), (*length).into()];
+                write_bytes(activation, Some(dest), &args).unwrap();
+                assert_eq!(
+                    contents(dest),
+                    (expected.to_vec(), expected.len()),
+                    "offset {offset}, length {length}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn write_bytes_from_self() {
+        with_avm2_activation(|activation| {
+            let bytearray = new_bytearray(activation, &[1, 2, 3], 3);
+            let args = [bytearray.into(), 0.into(), 0.into()];
+            write_bytes(activation, Some(bytearray), &args).unwrap();
+            assert_eq!(contents(bytearray), (vec![1, 2, 3, 1, 2, 3], 6));
+        });
+    }
+
+    fn position_and_available<'gc>(
+        activation: &mut Activation<'_, 'gc>,
+        bytearray: Object<'gc>,
+    ) -> (Value<'gc>, Value<'gc>) {
+        (
+            get_position(activation, Some(bytearray), &[]).unwrap(),
+            get_bytes_available(activation, Some(bytearray), &[]).unwrap(),
+        )
+    }
+
+    #[test]
+    fn position_after_destructive_operations() {
+        with_avm2_activation(|activation| {
+            let bytearray = new_bytearray(activation, &[1, 2, 3, 4, 5, 6, 7, 8], 6);
+
+            set_length(activation, Some(bytearray), &[4.into()]).unwrap();
+            assert_eq!(
+                position_and_available(activation, bytearray),
+                (4.into(), 0.into())
+            );
+            let error = read_byte(activation, Some(bytearray), &[]).unwrap_err();
+            assert_eq!(error_id(activation, error), 2030);
+
+            set_position(activation, Some(bytearray), &[10.into()]).unwrap();
+            assert_eq!(
+                position_and_available(activation, bytearray),
+                (10.into(), 0.into())
+            );
+
+            clear(activation, Some(bytearray), &[]).unwrap();
+            assert_eq!(
+                position_and_available(activation, bytearray),
+                (0.into(), 0.into())
+            );
+
+            let data = b"Lorem ipsum dolor sit amet, lorem ipsum dolor sit amet";
+            let bytearray = new_bytearray(activation, data, 3);
+            compress(activation, Some(bytearray), &["zlib".into()]).unwrap();
+            let compressed_len = contents(bytearray).0.len();
+            assert_eq!(
+                position_and_available(activation, bytearray),
+                (compressed_len.into(), 0.into())
+            );
+
+            set_position(activation, Some(bytearray), &[2.into()]).unwrap();
+            uncompress(activation, Some(bytearray), &["zlib".into()]).unwrap();
+            assert_eq!(
+                position_and_available(activation, bytearray),
+                (0.into(), data.len().into())
+            );
+
+            // A failed uncompress leaves both the contents and the position alone.
+            set_position(activation, Some(bytearray), &[5.into()]).unwrap();
+            assert!(uncompress(activation, Some(bytearray), &["zlib".into()]).is_err());
+            assert_eq!(contents(bytearray), (data.to_vec(), 5));
+        });
+    }
+
+    #[test]
+    fn write_utf_encodes_surrogate_pairs_as_four_bytes() {
+        use crate::string::WString;
+
+        with_avm2_activation(|activation| {
+            // U+1F600, stored in the string as a surrogate pair.
+            let string = WString::from_buf(vec![0xD83Du16, 0xDE00]);
+            let string = AvmString::new(activation.context.gc_context, string);
+            let bytearray = new_bytearray(activation, &[], 0);
+            write_utf(activation, Some(bytearray), &[string.into()]).unwrap();
+            assert_eq!(
+                contents(bytearray),
+                (vec![0x00, 0x04, 0xF0, 0x9F, 0x98, 0x80], 6)
+            );
+
+            set_position(activation, Some(bytearray), &[0.into()]).unwrap();
+            let read = read_utf(activation, Some(bytearray), &[]).unwrap();
+            assert_eq!(read.coerce_to_string(activation).unwrap(), string);
+        });
+    }
+
+    #[test]
+    fn write_utf_rejects_strings_over_65535_bytes() {
+        with_avm2_activation(|activation| {
+            let bytearray = new_bytearray(activation, &[], 0);
+            let fits = AvmString::new_utf8(activation.context.gc_context, "a".repeat(0xFFFF));
+            write_utf(activation, Some(bytearray), &[fits.into()]).unwrap();
+            assert_eq!(contents(bytearray).1, 0x10001);
+
+            // A 3-byte character takes it over the limit, and nothing is written.
+            let too_long = format!("{}\u{20AC}", "a".repeat(0xFFFD));
+            let too_long = AvmString::new_utf8(activation.context.gc_context, too_long);
+            let error = write_utf(activation, Some(bytearray), &[too_long.into()]).unwrap_err();
+            assert_eq!(error_id(activation, error), 2006);
+            assert_eq!(contents(bytearray).1, 0x10001);
+        });
+    }
+
+    #[test]
+    fn write_object_vector_uint_above_i32_max() {
+        use crate::avm2::object::VectorObject;
+        use crate::avm2::vector::VectorStorage;
+
+        with_avm2_activation(|activation| {
+            let uint = activation.avm2().classes().uint;
+            let values = vec![u32::MAX.into(), 3_000_000_000u32.into()];
+            let storage = VectorStorage::from_values(values, false, uint);
+            let vector = VectorObject::from_vector(storage, activation).unwrap();
+
+            let bytearray = new_bytearray(activation, &[], 0);
+            write_object(activation, Some(bytearray), &[vector.into()]).unwrap();
+            assert_eq!(
+                contents(bytearray),
+                (
+                    vec![0x0E, 0x05, 0x00, 0xFF, 0xFF, 0xFF, 0xFF, 0xB2, 0xD0, 0x5E, 0x00],
+                    11
+                )
+            );
+
+            set_position(activation, Some(bytearray), &[0.into()]).unwrap();
+            let read = read_object(activation, Some(bytearray), &[]).unwrap();
+            let read: Vec<_> = read
+                .as_object()
+                .unwrap()
+                .as_vector_storage()
+                .unwrap()
+                .iter()
+                .collect();
+            assert_eq!(read.len(), 2);
+            assert_eq!(read[0].coerce_to_u32(activation).unwrap(), u32::MAX);
+            assert_eq!(read[1].coerce_to_u32(activation).unwrap(), 3_000_000_000);
+        });
+    }
+
+    #[test]
+    fn aliased_object_round_trips() {
+        use crate::avm2::globals::flash::net::{get_class_by_alias, register_class_alias};
+
+        with_avm2_activation(|activation| {
+            let point = activation.avm2().classes().point;
+            register_class_alias(activation, None, &["pt".into(), point.into()]).unwrap();
+            let found = get_class_by_alias(activation, None, &["pt".into()]).unwrap();
+            assert!(Object::ptr_eq(found.as_object().unwrap(), point));
+
+            let value = point.construct(activation, &[1.into(), 2.into()]).unwrap();
+            let bytearray = new_bytearray(activation, &[], 0);
+            write_object(activation, Some(bytearray), &[value.into()]).unwrap();
+            // A sealed, non-dynamic object with two members, named by its alias.
+            let (bytes, _) = contents(bytearray);
+            assert_eq!(
+                &bytes[..9],
+                &[0x0A, 0x23, 0x05, b'p', b't', 0x03, b'x', 0x03, b'y']
+            );
+
+            set_position(activation, Some(bytearray), &[0.into()]).unwrap();
+            let read = read_object(activation, Some(bytearray), &[]).unwrap();
+            let read = read.as_object().unwrap();
+            assert!(Object::ptr_eq(read.instance_of().unwrap(), point));
+            let x = read.get_public_property("x", activation).unwrap();
+            let y = read.get_public_property("y", activation).unwrap();
+            assert_eq!(x.coerce_to_number(activation).unwrap(), 1.0);
+            assert_eq!(y.coerce_to_number(activation).unwrap(), 2.0);
+            assert_eq!(contents(bytearray).1, bytes.len());
+        });
+    }
+
+    #[test]
+    fn unknown_alias_reads_as_object() {
+        // A dynamic object of class "Foo" with `a = 5`.
+        let bytes = [
+            0x0A, 0x0B, 0x07, b'F', b'o', b'o', 0x03, b'a', 0x04, 0x05, 0x01,
+        ];
+        with_avm2_activation(|activation| {
+            let bytearray = new_bytearray(activation, &bytes, 0);
+            let read = read_object(activation, Some(bytearray), &[]).unwrap();
+            let read = read.as_object().unwrap();
+            let object = activation.avm2().classes().object;
+            assert!(Object::ptr_eq(read.instance_of().unwrap(), object));
+            let a = read.get_public_property("a", activation).unwrap();
+            assert_eq!(a.coerce_to_i32(activation).unwrap(), 5);
+            assert_eq!(contents(bytearray).1, bytes.len());
+        });
+    }
+
+    #[test]
+    fn externalizable_without_externalizable_class_is_an_error() {
+        use crate::avm2::globals::flash::net::{get_class_by_alias, register_class_alias};
+
+        // An externalizable object of class "pt".
+        let bytes = [0x0A, 0x07, 0x05, b'p', b't', 0x00];
+        with_avm2_activation(|activation| {
+            // Neither an unknown alias...
+            let bytearray = new_bytearray(activation, &bytes, 0);
+            let error = read_object(activation, Some(bytearray), &[]).unwrap_err();
+            assert_eq!(error_id(activation, error), 2173);
+            assert_eq!(contents(bytearray).1, 0);
+
+            // ...nor an alias for a class that isn't `IExternalizable` can read it.
+            let point = activation.avm2().classes().point;
+            register_class_alias(activation, None, &["pt".into(), point.into()]).unwrap();
+            let error = read_object(activation, Some(bytearray), &[]).unwrap_err();
+            assert_eq!(error_id(activation, error), 2173);
+            assert_eq!(contents(bytearray).1, 0);
+
+            let error = get_class_by_alias(activation, None, &["missing".into()]).unwrap_err();
+            assert_eq!(error_id(activation, error), 1014);
+        });
+    }
+
+    #[test]
+    fn write_object_references_cycles() {
+        with_avm2_activation(|activation| {
+            let object_class = activation.avm2().classes().object;
+            let mut object = object_class.construct(activation, &[]).unwrap();
+            object
+                .set_public_property("self", object.into(), activation)
+                .unwrap();
+
+            let bytearray = new_bytearray(activation, &[], 0);
+            write_object(activation, Some(bytearray), &[object.into()]).unwrap();
+            // The inner `self` is written as a reference to object 0.
+            let expected = vec![
+                0x0A, 0x0B, 0x01, 0x09, b's', b'e', b'l', b'f', 0x0A, 0x00, 0x01,
+            ];
+            assert_eq!(contents(bytearray), (expected, 11));
+
+            set_position(activation, Some(bytearray), &[0.into()]).unwrap();
+            let read = read_object(activation, Some(bytearray), &[]).unwrap();
+            let read = read.as_object().unwrap();
+            let inner = read.get_public_property("self", activation).unwrap();
+            assert!(Object::ptr_eq(inner.as_object().unwrap(), read));
+        });
+    }
+
+    #[test]
+    fn write_object_references_shared_objects() {
+        use crate::avm2::{ArrayObject, ArrayStorage};
+
+        with_avm2_activation(|activation| {
+            let object_class = activation.avm2().classes().object;
+            let shared = object_class.construct(activation, &[]).unwrap();
+            let storage = ArrayStorage::from_args(&[shared.into(), shared.into()]);
+            let array = ArrayObject::from_storage(activation, storage).unwrap();
+
+            let bytearray = new_bytearray(activation, &[], 0);
+            write_object(activation, Some(bytearray), &[array.into()]).unwrap();
+            // The array is object 0, so the second element refers to object 1.
+            let expected = vec![0x09, 0x05, 0x01, 0x0A, 0x0B, 0x01, 0x01, 0x0A, 0x02];
+            assert_eq!(contents(bytearray), (expected, 9));
+
+            set_position(activation, Some(bytearray), &[0.into()]).unwrap();
+            let read = read_object(activation, Some(bytearray), &[]).unwrap();
+            let read = read.as_object().unwrap();
+            let storage = read.as_array_storage().unwrap();
+            let (first, second) = (storage.get(0).unwrap(), storage.get(1).unwrap());
+            drop(storage);
+            assert!(Object::ptr_eq(
+                first.as_object().unwrap(),
+                second.as_object().unwrap()
+            ));
+            assert!(!Object::ptr_eq(first.as_object().unwrap(), read));
+        });
+    }
+
+    #[test]
+    fn amf0_switches_to_amf3_for_amf3_only_types() {
+        with_avm2_activation(|activation| {
+            let content = new_bytearray(activation, &[1, 2, 3], 0);
+            let bytearray = new_bytearray(activation, &[], 0);
+            set_object_encoding(activation, Some(bytearray), &[0.into()]).unwrap();
+            write_object(activation, Some(bytearray), &[content.into()]).unwrap();
+            // The avmplus-object marker, followed by an AMF3 ByteArray.
+            let expected = vec![0x11, 0x0C, 0x07, 1, 2, 3];
+            assert_eq!(contents(bytearray), (expected, 6));
+
+            set_position(activation, Some(bytearray), &[0.into()]).unwrap();
+            let read = read_object(activation, Some(bytearray), &[]).unwrap();
+            let read = read.as_object().unwrap();
+            assert_eq!(read.as_bytearray().unwrap().bytes(), [1, 2, 3]);
+            assert_eq!(contents(bytearray).1, 6);
+        });
+    }
+
+    #[test]
+    fn amf0_references_cycles() {
+        with_avm2_activation(|activation| {
+            let object_class = activation.avm2().classes().object;
+            let mut object = object_class.construct(activation, &[]).unwrap();
+            object
+                .set_public_property("self", object.into(), activation)
+                .unwrap();
+
+            let bytearray = new_bytearray(activation, &[], 0);
+            set_object_encoding(activation, Some(bytearray), &[0.into()]).unwrap();
+            write_object(activation, Some(bytearray), &[object.into()]).unwrap();
+            // The inner `self` is written as a reference to object 0.
+            let expected = vec![
+                0x03, 0x00, 0x04, b's', b'e', b'l', b'f', 0x07, 0x00, 0x00, 0x00, 0x00, 0x09,
+            ];
+            assert_eq!(contents(bytearray), (expected, 13));
+
+            set_position(activation, Some(bytearray), &[0.into()]).unwrap();
+            let read = read_object(activation, Some(bytearray), &[]).unwrap();
+            let read = read.as_object().unwrap();
+            let inner = read.get_public_property("self", activation).unwrap();
+            assert!(Object::ptr_eq(inner.as_object().unwrap(), read));
+        });
+    }
+
+    #[test]
+    fn read_object_amf0_with_amf3_values() {
+        #[rustfmt::skip]
+        let bytes = [
+            // An anonymous object, whose `v` is an AMF3 `Vector.<int>` of 1 and 2.
+            0x03,
+            0x00, 0x01, b'v',
+            0x11, 0x0D, 0x05, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x02,
+            0x00, 0x00, 0x09,
+            // An AMF3 string, then an AMF0 number, each read by its own call.
+            0x11, 0x06, 0x07, b'a', b'b', b'c',
+            0x00, 0x3F, 0xF8, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ];
+        with_avm2_activation(|activation| {
+            let bytearray = new_bytearray(activation, &bytes, 0);
+            set_object_encoding(activation, Some(bytearray), &[0.into()]).unwrap();
+
+            let object = read_object(activation, Some(bytearray), &[]).unwrap();
+            let vector = object
+                .as_object()
+                .unwrap()
+                .get_public_property("v", activation)
+                .unwrap();
+            let vector: Vec<_> = vector
+                .as_object()
+                .unwrap()
+                .as_vector_storage()
+                .unwrap()
+                .iter()
+                .collect();
+            assert_eq!(vector, [1.into(), 2.into()]);
+            assert_eq!(contents(bytearray).1, 19);
+
+            let string = read_object(activation, Some(bytearray), &[]).unwrap();
+            let string = string.coerce_to_string(activation).unwrap();
+            assert_eq!(string.to_utf8_lossy(), "abc");
+            assert_eq!(contents(bytearray).1, 25);
+
+            let number = read_object(activation, Some(bytearray), &[]).unwrap();
+            assert_eq!(number.coerce_to_number(activation).unwrap(), 1.5);
+            assert_eq!(contents(bytearray).1, bytes.len());
+        });
+    }
+}
@@ -81,6 +81,7 @@ include "flash/display/NativeMenuItem.as"
 include "flash/display/PixelSnapping.as"
 include "flash/display/PNGEncoderOptions.as"
 include "flash/display/Scene.as"
+include "flash/display/Shader.as"
 include "flash/display/ShaderParameterType.as"
 include "flash/display/ShaderPrecision.as"
 include "flash/display/Shape.as"
@@ -189,6 +190,7 @@ include "flash/filters/DisplacementMapFilterMode.as"
 include "flash/filters/DropShadowFilter.as"
 include "flash/filters/GlowFilter.as"
 include "flash/filters/GradientGlowFilter.as"
+include "flash/filters/ShaderFilter.as"
 
 include "flash/geom/ColorTransform.as"
 include "flash/geom/Matrix.as"
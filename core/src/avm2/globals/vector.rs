@@ -101,12 +101,7 @@ pub fn class_init<'gc>(
         //We have to grab Object's defining script instead of our own, because
         //at this point Vector hasn't actually been defined yet. It doesn't
         //matter because we only have one script for our globals.
-        let (_, script) = domain
-            .get_defining_script(&Multiname::new(
-                activation.avm2().public_namespace,
-                "Object",
-            ))?
-            .unwrap();
+        let (_, script) = domain.find_public_definition(activation, "Object".into())?;
 
         let class_class = activation.avm2().classes().class;
         let int_class = activation.avm2().classes().int;
@@ -48,7 +48,10 @@ pub struct DomainObjectData<'gc> {
 }
 
 impl<'gc> DomainObject<'gc> {
-    /// Create a new object for a given domain.
+    /// Get the object for a given domain, creating it if necessary.
+    ///
+    /// Each domain only ever has one object, so that `ApplicationDomain`s
+    /// wrapping the same domain compare equal.
     ///
     /// This function will call instance initializers. You do not need to do so
     /// yourself.
@@ -56,6 +59,10 @@ impl<'gc> DomainObject<'gc> {
         activation: &mut Activation<'_, 'gc>,
         domain: Domain<'gc>,
     ) -> Result<Object<'gc>, Error<'gc>> {
+        if let Some(this) = domain.domain_object() {
+            return Ok(this);
+        }
+
         let class = activation.avm2().classes().application_domain;
         let base = ScriptObjectData::new(class);
         let mut this: Object<'gc> = DomainObject(GcCell::allocate(
@@ -71,6 +78,7 @@ impl<'gc> DomainObject<'gc> {
             .superclass_object()
             .unwrap()
             .call_native_init(Some(this), &[], activation)?;
+        domain.set_domain_object(activation.context.gc_context, this);
         Ok(this)
     }
 }
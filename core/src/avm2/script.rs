@@ -380,6 +380,10 @@ pub struct ScriptData<'gc> {
 }
 
 impl<'gc> Script<'gc> {
+    pub fn ptr_eq(a: Script<'gc>, b: Script<'gc>) -> bool {
+        GcCell::ptr_eq(a.0, b.0)
+    }
+
     /// Create an empty script.
     ///
     /// This method is intended for builtin script initialization, such as our
@@ -487,7 +491,7 @@ impl<'gc> Script<'gc> {
             if let TraitKind::Class { class, .. } = newtrait.kind() {
                 write
                     .domain
-                    .export_class(*class, activation.context.gc_context);
+                    .export_class(*class, *self, activation.context.gc_context);
             }
 
             write.traits.push(newtrait);
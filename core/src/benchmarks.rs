@@ -0,0 +1,265 @@
+//! Benchmarks of code that isn't reachable through the public API.
+//!
+//! This module only exists to feed the criterion benches in `benches/core.rs`, and isn't
+//! part of the crate's API. Run them with `cargo bench -p ruffle_core --features benchmarks`.
+
+use crate::avm2::object::TObject;
+use crate::avm2::{Domain, Namespace, QName};
+use crate::bitmap::bitmap_data::{
+    BitmapData, BitmapDataWrapper, Color, IBitmapDrawable, LehmerRng, ThresholdOperation,
+};
+use crate::bitmap::operations;
+use crate::player::PlayerBuilder;
+use crate::tag_utils::SwfMovie;
+use crate::test_utils::{
+    with_avm2_activation, with_player, with_update_context, RecordingRenderer,
+};
+use criterion::Criterion;
+use gc_arena::{GcCell, MutationContext};
+use ruffle_render::backend::null::NullRenderer;
+use ruffle_render::backend::{RenderBackend, ViewportDimensions};
+use ruffle_render::bitmap::PixelRegion;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::transform::Transform;
+use swf::BlendMode;
+
+fn new_bitmap_data<'gc>(
+    mc: MutationContext<'gc, '_>,
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+) -> BitmapDataWrapper<'gc> {
+    let bitmap_data = BitmapData::new_with_pixels(width, height, true, pixels);
+    BitmapDataWrapper::new(GcCell::allocate(mc, bitmap_data))
+}
+
+/// Uploads a 2048x2048 bitmap after editing a small part of it, and after editing all of it.
+pub fn bitmap_upload(c: &mut Criterion) {
+    let mut renderer = RecordingRenderer::default();
+    let mut bitmap_data =
+        BitmapData::new_with_pixels(2048, 2048, true, vec![Color::from(0); 2048 * 2048]);
+    bitmap_data.bitmap_handle(&mut renderer);
+
+    let mut group = c.benchmark_group("bitmap_upload_2048x2048");
+    for (name, region) in [
+        ("small_edit", PixelRegion::for_region(1000, 1000, 16, 16)),
+        ("full_edit", PixelRegion::for_whole_size(2048, 2048)),
+    ] {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                bitmap_data.set_cpu_dirty(region);
+                bitmap_data.update_dirty_texture(&mut renderer);
+                renderer.texture_updates.clear();
+            })
+        });
+    }
+    group.finish();
+}
+
+/// Plots 1000 pixels into a 2048x2048 bitmap and uploads it, as a game drawing into a
+/// canvas does every frame.
+///
+/// The null renderer can't update part of a texture, so its run shows the cost of
+/// uploading the whole bitmap instead.
+pub fn bitmap_plot_pixels(c: &mut Criterion) {
+    with_update_context(|context| {
+        let mut recording_renderer = RecordingRenderer::default();
+        let mut null_renderer = NullRenderer::new(ViewportDimensions {
+            width: 550,
+            height: 400,
+            scale_factor: 1.0,
+        });
+        let renderers: [(&str, &mut dyn RenderBackend); 2] = [
+            ("partial_upload", &mut recording_renderer),
+            ("full_upload", &mut null_renderer),
+        ];
+
+        let mut group = c.benchmark_group("plot_1k_pixels_2048x2048");
+        for (name, renderer) in renderers {
+            let target = new_bitmap_data(
+                context.gc_context,
+                2048,
+                2048,
+                vec![Color::from(0); 2048 * 2048],
+            );
+            target.bitmap_handle(context.gc_context, renderer);
+            let mut rng = LehmerRng::with_seed(1);
+            group.bench_function(name, |b| {
+                b.iter(|| {
+                    for _ in 0..1000 {
+                        let (x, y) = (rng.gen() % 2048, rng.gen() % 2048);
+                        operations::set_pixel32(context, target, x, y, -1);
+                    }
+                    target.bitmap_handle(context.gc_context, renderer);
+                })
+            });
+        }
+        group.finish();
+    });
+}
+
+/// Alternates a `draw` into a bitmap with a `getPixel32` of the drawn area, as games
+/// probing one pixel per frame for collisions do.
+///
+/// Perf note: the recording renderer's readbacks can't be partial, so this measures the
+/// fallback that copies the whole drawn area back to the CPU. On backends that support
+/// `retrieve_offscreen_region`, `getPixel32` reads back only the pixel it returns, and
+/// its cost no longer grows with the size of the draw.
+pub fn bitmap_draw_and_get_pixel(c: &mut Criterion) {
+    with_player(
+        PlayerBuilder::new()
+            .with_renderer(RecordingRenderer::default())
+            .with_movie(SwfMovie::empty(10)),
+        |context| {
+            let source = new_bitmap_data(
+                context.gc_context,
+                256,
+                256,
+                vec![Color::from(-1); 256 * 256],
+            );
+            let target = new_bitmap_data(
+                context.gc_context,
+                1024,
+                1024,
+                vec![Color::from(0); 1024 * 1024],
+            );
+            c.bench_function("draw_then_get_pixel_256x256", |b| {
+                b.iter(|| {
+                    operations::draw(
+                        context,
+                        target,
+                        IBitmapDrawable::BitmapData(source),
+                        Transform::default(),
+                        false,
+                        BlendMode::Normal,
+                        None,
+                        StageQuality::Low,
+                        false,
+                    )
+                    .unwrap();
+                    let pixel = operations::get_pixel32(target, 128, 128);
+                    if let Some(renderer) = context.renderer.downcast_mut::<RecordingRenderer>() {
+                        renderer.offscreen_renders.clear();
+                    }
+                    pixel
+                })
+            });
+        },
+    );
+}
+
+/// Clears a 1920x1080 bitmap with `fillRect`.
+pub fn bitmap_fill_rect(c: &mut Criterion) {
+    with_update_context(|context| {
+        let target = new_bitmap_data(
+            context.gc_context,
+            1920,
+            1080,
+            vec![Color::from(-1); 1920 * 1080],
+        );
+        c.bench_function("fill_rect_clear_1920x1080", |b| {
+            b.iter(|| operations::fill_rect(context, target, 0, 0, 1920, 1080, 0))
+        });
+    });
+}
+
+/// Runs `threshold` over a 1920x1080 region, with about half of the pixels passing.
+pub fn bitmap_threshold(c: &mut Criterion) {
+    with_update_context(|context| {
+        let pixels = (0..1920 * 1080)
+            .map(|i: u32| Color::argb(0xFF, i as u8, (i >> 8) as u8, (i >> 16) as u8))
+            .collect();
+        let source = new_bitmap_data(context.gc_context, 1920, 1080, pixels);
+        let target = new_bitmap_data(
+            context.gc_context,
+            1920,
+            1080,
+            vec![Color::from(0); 1920 * 1080],
+        );
+        c.bench_function("threshold_1920x1080", |b| {
+            b.iter(|| {
+                operations::threshold(
+                    context,
+                    target,
+                    source,
+                    (0, 0, 1920, 1080),
+                    (0, 0),
+                    ThresholdOperation::LessThan,
+                    0x00000080,
+                    -1,
+                    0x000000FF,
+                    false,
+                )
+            })
+        });
+    });
+}
+
+/// Compares two identical 2048x2048 bitmaps.
+pub fn bitmap_compare(c: &mut Criterion) {
+    with_update_context(|context| {
+        let pixels: Vec<_> = (0..2048 * 2048).map(Color::from).collect();
+        let left = new_bitmap_data(context.gc_context, 2048, 2048, pixels.clone());
+        let right = new_bitmap_data(context.gc_context, 2048, 2048, pixels);
+        c.bench_function("compare_identical_2048x2048", |b| {
+            b.iter(|| operations::compare(left, right))
+        });
+    });
+}
+
+/// Fills 64KB of domain memory one `si32` store at a time, under one borrow of the memory
+/// and with a borrow per store.
+pub fn domain_memory_fill(c: &mut Criterion) {
+    with_avm2_activation(|activation| {
+        let mc = activation.context.gc_context;
+        let global = activation.avm2().global_domain();
+        let domain = Domain::movie_domain_lazy(activation, global);
+
+        let bytearray_class = activation.avm2().classes().bytearray;
+        let object = bytearray_class.construct(activation, &[]).unwrap();
+        object.as_bytearray_mut(mc).unwrap().set_length(0x10000);
+        domain.set_domain_memory(mc, object.as_bytearray_object().unwrap());
+
+        let mut group = c.benchmark_group("domain_memory_fill_64k");
+        group.bench_function("one_borrow", |b| {
+            b.iter(|| {
+                domain.with_domain_memory_mut(activation, |memory| {
+                    for i in 0..0x4000 {
+                        memory.write_i32(i * 4, i as i32).unwrap();
+                    }
+                })
+            })
+        });
+        group.bench_function("borrow_per_store", |b| {
+            b.iter(|| {
+                for i in 0..0x4000 {
+                    domain
+                        .with_domain_memory_mut(activation, |memory| {
+                            memory.write_i32(i * 4, i as i32)
+                        })
+                        .unwrap();
+                }
+            })
+        });
+        group.finish();
+    });
+}
+
+/// Looks up the same player global over and over, from the global domain, which caches it,
+/// and from a movie domain, which resolves it through its parent every time.
+pub fn domain_global_lookup(c: &mut Criterion) {
+    with_avm2_activation(|activation| {
+        let mc = activation.context.gc_context;
+        let global = activation.avm2().global_domain();
+        let child = Domain::movie_domain_lazy(activation, global);
+        let name = QName::new(Namespace::package("flash.display", mc), "Sprite");
+
+        let mut group = c.benchmark_group("lookup_flash_display_sprite");
+        for (label, domain) in [("global_domain", global), ("movie_domain", child)] {
+            group.bench_function(label, |b| {
+                b.iter(|| domain.get_defined_value(activation, name).unwrap())
+            });
+        }
+        group.finish();
+    });
+}
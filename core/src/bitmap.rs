@@ -1,7 +1,11 @@
 pub mod bitmap_data;
 pub mod operations;
+pub mod pixel_bender;
 pub mod turbulence;
 
+use std::cell::Cell;
+use std::rc::Rc;
+
 /// Determine if a particular bitmap data size is valid.
 ///
 /// This enforces limits on BitmapData as specified in the Flash documentation.
@@ -47,3 +51,112 @@ pub fn is_size_valid(swf_version: u8, width: u32, height: u32) -> bool {
     }
     true
 }
+
+/// Tracks how much pixel memory is held by the `BitmapData`s of a player, optionally
+/// capping it so that a movie can't exhaust the host's memory.
+///
+/// Memory is reserved through `try_allocate`, and returned to the budget once the
+/// resulting `BitmapAllocation` is dropped (when its `BitmapData` is disposed or collected).
+#[derive(Clone, Debug, Default)]
+pub struct BitmapMemoryBudget(Rc<BudgetState>);
+
+#[derive(Debug, Default)]
+struct BudgetState {
+    limit: Option<usize>,
+    used: Cell<usize>,
+}
+
+impl BitmapMemoryBudget {
+    /// Creates a budget allowing up to `limit` bytes of pixel data, or unlimited if `None`.
+    pub fn new(limit: Option<usize>) -> Self {
+        Self(Rc::new(BudgetState {
+            limit,
+            used: Cell::new(0),
+        }))
+    }
+
+    /// The number of bytes needed for the pixels of a bitmap of the given size.
+    pub fn bytes_for(width: u32, height: u32) -> usize {
+        width as usize * height as usize * 4
+    }
+
+    pub fn limit(&self) -> Option<usize> {
+        self.0.limit
+    }
+
+    /// The number of bytes currently reserved by live allocations.
+    pub fn used(&self) -> usize {
+        self.0.used.get()
+    }
+
+    /// Reserves `bytes` of pixel memory, or returns `None` if that would exceed the limit.
+    pub fn try_allocate(&self, bytes: usize) -> Option<BitmapAllocation> {
+        let used = self.used().checked_add(bytes)?;
+        if self.limit().map_or(false, |limit| used > limit) {
+            return None;
+        }
+        self.0.used.set(used);
+        Some(BitmapAllocation {
+            budget: self.0.clone(),
+            bytes,
+        })
+    }
+}
+
+/// Pixel memory reserved from a `BitmapMemoryBudget`, which is returned when this is dropped.
+#[derive(Debug)]
+pub struct BitmapAllocation {
+    budget: Rc<BudgetState>,
+    bytes: usize,
+}
+
+impl BitmapAllocation {
+    /// Updates the size of this reservation.
+    ///
+    /// This never fails, as it's only used to keep the accounting accurate when a bitmap
+    /// is resized after it was constructed.
+    pub fn resize(&mut self, bytes: usize) {
+        let used = self.budget.used.get() - self.bytes + bytes;
+        self.budget.used.set(used);
+        self.bytes = bytes;
+    }
+}
+
+impl Drop for BitmapAllocation {
+    fn drop(&mut self) {
+        let used = self.budget.used.get() - self.bytes;
+        self.budget.used.set(used);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_is_returned_on_drop() {
+        let budget = BitmapMemoryBudget::new(Some(100));
+        let first = budget.try_allocate(60).unwrap();
+        assert!(budget.try_allocate(50).is_none());
+        let second = budget.try_allocate(40).unwrap();
+        assert_eq!(budget.used(), 100);
+
+        drop(first);
+        assert_eq!(budget.used(), 40);
+        let mut third = budget.try_allocate(60).unwrap();
+        third.resize(10);
+        assert_eq!(budget.used(), 50);
+
+        drop((second, third));
+        assert_eq!(budget.used(), 0);
+    }
+
+    #[test]
+    fn unlimited_budget_still_counts() {
+        let budget = BitmapMemoryBudget::default();
+        let allocation = budget.try_allocate(usize::MAX / 2).unwrap();
+        assert_eq!(budget.used(), usize::MAX / 2);
+        drop(allocation);
+        assert_eq!(budget.used(), 0);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::avm2::{Object as Avm2Object, Value as Avm2Value};
+use crate::bitmap::{BitmapAllocation, BitmapMemoryBudget};
 use crate::display_object::{DisplayObject, TDisplayObject};
 use bitflags::bitflags;
 use core::fmt;
@@ -6,10 +7,26 @@ use gc_arena::Collect;
 use ruffle_render::backend::RenderBackend;
 use ruffle_render::bitmap::{Bitmap, BitmapFormat, BitmapHandle, PixelRegion, SyncHandle};
 use ruffle_wstr::WStr;
+use std::cell::{Ref, RefCell};
 use std::ops::Range;
-use swf::{Rectangle, Twips};
+use std::rc::Rc;
+use swf::{BlendMode, Rectangle, Twips};
 use tracing::instrument;
 
+thread_local! {
+    /// Scratch space for converting pixels to RGBA when uploading them to the render backend.
+    ///
+    /// This is reused between uploads, so that editing a large bitmap every frame doesn't
+    /// allocate (and zero) a whole bitmap's worth of memory every frame.
+    static UPLOAD_BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::new());
+}
+
+/// The most memory `UPLOAD_BUFFER` holds on to between uploads, enough for a 2048x2048 bitmap.
+///
+/// Bigger bitmaps still upload through it, but it's freed afterwards instead of pinning that
+/// much memory for the rest of the thread's life.
+const UPLOAD_BUFFER_RETAINED_CAPACITY: usize = 2048 * 2048 * 4;
+
 /// An implementation of the Lehmer/Park-Miller random number generator
 /// Uses the fixed parameters m = 2,147,483,647 and a = 16,807
 pub struct LehmerRng {
@@ -28,6 +45,26 @@ impl LehmerRng {
         self.x
     }
 
+    /// Advance the sequence by `n` values at once, as if `gen` had been called `n` times.
+    ///
+    /// Since X_(k+n) = a^n * X_k mod m, this only needs O(log n) multiplications.
+    pub fn skip(&mut self, mut n: u64) {
+        const M: u64 = 2_147_483_647;
+        if n == 0 {
+            return;
+        }
+        let mut multiplier = 1;
+        let mut base = 16_807;
+        while n > 0 {
+            if n & 1 == 1 {
+                multiplier = multiplier * base % M;
+            }
+            base = base * base % M;
+            n >>= 1;
+        }
+        self.x = (self.x as u64 * multiplier % M) as u32;
+    }
+
     pub fn gen_range(&mut self, rng: Range<u8>) -> u8 {
         rng.start + (self.gen() % ((rng.end - rng.start) as u32 + 1)) as u8
     }
@@ -126,6 +163,58 @@ impl Color {
         Self::argb(alpha, self.red(), self.green(), self.blue())
     }
 
+    /// Converts the (un-multiplied) RGB channels of this color to hue, saturation and value.
+    ///
+    /// Hue is in degrees, in the range `0.0..360.0`, and is 0 for grays.
+    /// Saturation and value are in the range `0.0..=1.0`.
+    pub fn to_hsv(&self) -> (f64, f64, f64) {
+        let [r, g, b] = [self.red(), self.green(), self.blue()].map(|c| c as f64 / 255.0);
+        let max = r.max(g).max(b);
+        let chroma = max - r.min(g).min(b);
+
+        let hue = if chroma == 0.0 {
+            0.0
+        } else if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        let saturation = if max == 0.0 { 0.0 } else { chroma / max };
+
+        (hue, saturation, max)
+    }
+
+    /// Creates an un-multiplied color from hue, saturation and value, as returned by `to_hsv`.
+    ///
+    /// Hue wraps around every 360 degrees, and saturation and value are clamped to `0.0..=1.0`.
+    #[must_use]
+    pub fn from_hsv(alpha: u8, hue: f64, saturation: f64, value: f64) -> Self {
+        let hue = if hue.is_finite() {
+            hue.rem_euclid(360.0) / 60.0
+        } else {
+            0.0
+        };
+        let saturation = saturation.clamp(0.0, 1.0);
+        let value = value.clamp(0.0, 1.0);
+
+        let chroma = value * saturation;
+        let x = chroma * (1.0 - (hue % 2.0 - 1.0).abs());
+        let (r, g, b) = match hue as u32 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let m = value - chroma;
+        let to_u8 = |c: f64| ((c + m) * 255.0).round() as u8;
+
+        Self::argb(alpha, to_u8(r), to_u8(g), to_u8(b))
+    }
+
     #[must_use]
     pub fn blend_over(&self, source: &Self) -> Self {
         let sa = source.alpha();
@@ -136,6 +225,93 @@ impl Color {
         let a = source.alpha() + ((self.alpha() as u16 * (255 - sa as u16)) >> 8) as u8;
         Self::argb(a, r, g, b)
     }
+
+    /// Composites `source` onto this color using the given blend mode.
+    ///
+    /// Both colors are premultiplied. The math mirrors the blend shaders used
+    /// by the GPU render backends, so CPU and GPU compositing agree.
+    #[must_use]
+    pub fn blend(&self, source: &Self, blend_mode: BlendMode) -> Self {
+        let to_f32 = |c: u8| c as f32 / 255.0;
+        let src = [
+            to_f32(source.red()),
+            to_f32(source.green()),
+            to_f32(source.blue()),
+        ];
+        let dst = [
+            to_f32(self.red()),
+            to_f32(self.green()),
+            to_f32(self.blue()),
+        ];
+        let sa = to_f32(source.alpha());
+        let da = to_f32(self.alpha());
+
+        // The shaders for the complex blend modes discard fragments with no
+        // source alpha, leaving the destination untouched.
+        let is_trivial = matches!(
+            blend_mode,
+            BlendMode::Normal
+                | BlendMode::Layer
+                | BlendMode::Multiply
+                | BlendMode::Screen
+                | BlendMode::Add
+                | BlendMode::Subtract
+        );
+        if !is_trivial && source.alpha() == 0 {
+            return *self;
+        }
+
+        let over_alpha = sa + da * (1.0 - sa);
+
+        let channels =
+            |f: &dyn Fn(f32, f32) -> f32| [f(src[0], dst[0]), f(src[1], dst[1]), f(src[2], dst[2])];
+
+        // Non-trivial blend modes operate on un-premultiplied colors, and are
+        // then composited like a normal "over" operation.
+        let separable = |f: &dyn Fn(f32, f32) -> f32| {
+            channels(&|s, d| {
+                let s_unmul = if sa > 0.0 { s / sa } else { 0.0 };
+                let d_unmul = if da > 0.0 { d / da } else { 0.0 };
+                s * (1.0 - da) + d * (1.0 - sa) + sa * da * f(s_unmul, d_unmul)
+            })
+        };
+
+        let hard_light = |s: f32, d: f32| {
+            if s <= 0.5 {
+                2.0 * s * d
+            } else {
+                1.0 - 2.0 * (1.0 - d) * (1.0 - s)
+            }
+        };
+
+        let (rgb, alpha) = match blend_mode {
+            BlendMode::Normal | BlendMode::Layer => {
+                (channels(&|s, d| s + d * (1.0 - sa)), over_alpha)
+            }
+            BlendMode::Multiply => (channels(&|s, d| s * d), over_alpha),
+            BlendMode::Screen => (channels(&|s, d| s + d * (1.0 - s)), over_alpha),
+            BlendMode::Add => (channels(&|s, d| s + d), over_alpha),
+            BlendMode::Subtract => (channels(&|s, d| d - s), over_alpha),
+            BlendMode::Lighten => (separable(&|s, d| s.max(d)), over_alpha),
+            BlendMode::Darken => (separable(&|s, d| s.min(d)), over_alpha),
+            BlendMode::Difference => (separable(&|s, d| (d - s).abs()), over_alpha),
+            BlendMode::Invert => (separable(&|_, d| 1.0 - d), over_alpha),
+            BlendMode::Overlay => (separable(&|s, d| hard_light(d, s)), over_alpha),
+            BlendMode::HardLight => (separable(&hard_light), over_alpha),
+            BlendMode::Alpha => (channels(&|_, d| d * sa), sa * da),
+            BlendMode::Erase => (channels(&|_, d| d * (1.0 - sa)), da * (1.0 - sa)),
+        };
+
+        // Keep the result a valid premultiplied color.
+        let alpha = alpha.clamp(0.0, 1.0);
+        let to_u8 = |c: f32| (c.clamp(0.0, alpha) * 255.0).round() as u8;
+        Self::argb(
+            (alpha * 255.0).round() as u8,
+            to_u8(rgb[0]),
+            to_u8(rgb[1]),
+            to_u8(rgb[2]),
+        )
+    }
 }
 
 impl std::fmt::Display for Color {
@@ -188,11 +364,23 @@ bitflags! {
     }
 }
 
-#[derive(Clone, Collect, Default)]
+#[derive(Collect, Default)]
 #[collect(no_drop)]
 pub struct BitmapData<'gc> {
     /// The pixels in the bitmap, stored as a array of pre-multiplied ARGB colour values
-    pixels: Vec<Color>,
+    ///
+    /// The buffer is shared copy-on-write between clones of this `BitmapData`
+    /// (and bitmaps created with `loadBitmap`), so that it is only copied on the first write.
+    #[collect(require_static)]
+    pixels: Rc<Vec<Color>>,
+
+    /// If set, `pixels` hasn't been allocated yet, and every pixel is this color.
+    ///
+    /// Bitmaps that are only drawn to and displayed never need their pixels on the CPU,
+    /// so `init_pixels` leaves them unallocated until something accesses them.
+    /// See `materialize_pixels`.
+    #[collect(require_static)]
+    pending_fill: Option<Color>,
     width: u32,
     height: u32,
     transparency: bool,
@@ -216,6 +404,84 @@ pub struct BitmapData<'gc> {
     avm2_object: Option<Avm2Object<'gc>>,
 
     dirty_state: DirtyState,
+
+    /// Incremented whenever the contents of `bitmap_handle` are replaced or
+    /// the handle is released, invalidating any outstanding `BitmapDataTexture`.
+    texture_generation: u64,
+
+    /// The pixel memory this bitmap has reserved from the player's budget, if any.
+    ///
+    /// Every `BitmapData` created by a script is accounted for, whether it was
+    /// constructed, cloned, or copied from a library symbol.
+    #[collect(require_static)]
+    memory: Option<BitmapAllocation>,
+
+    /// Whether `BitmapData.lock` is in effect.
+    ///
+    /// While locked, `Bitmap` instances keep displaying the last uploaded texture,
+    /// and CPU-side changes are accumulated until `unlock` is called.
+    locked: bool,
+
+    /// The `changeRect` given to the last `unlock`, limiting the next texture upload.
+    #[collect(require_static)]
+    change_rect: Option<PixelRegion>,
+}
+
+/// Converts premultiplied ARGB colors to RGBA bytes, one pixel per four bytes of `output`.
+///
+/// This is written without any per-pixel branches, so that it can be vectorized.
+fn pixels_to_rgba(pixels: &[Color], output: &mut [u8]) {
+    for (pixel, output) in pixels.iter().zip(output.chunks_exact_mut(4)) {
+        let [b, g, r, a] = pixel.0.to_le_bytes();
+        output.copy_from_slice(&[r, g, b, a]);
+    }
+}
+
+/// The GPU texture backing a `BitmapData`, as returned by `BitmapDataWrapper::draw_to_texture`.
+///
+/// This allows embedders to bind the texture in their own rendering.
+/// The texture is only valid until the `BitmapData` is next uploaded to, drawn to on the GPU,
+/// or disposed. Use `BitmapDataWrapper::is_texture_valid` to check this before each use.
+#[derive(Clone, Debug)]
+pub struct BitmapDataTexture {
+    handle: BitmapHandle,
+    generation: u64,
+}
+
+impl BitmapDataTexture {
+    pub fn handle(&self) -> &BitmapHandle {
+        &self.handle
+    }
+}
+
+/// An iterator over the pixels of a `BitmapData` within a region, as returned by
+/// `BitmapDataWrapper::pixels_in_rect`.
+///
+/// Yields `(x, y, color)` in row-major order, with `color` in the same premultiplied
+/// form as `BitmapData::get_pixel32_raw`. The `BitmapData` stays borrowed for reading
+/// until the iterator is dropped.
+pub struct PixelsInRect<'a, 'gc> {
+    read: Ref<'a, BitmapData<'gc>>,
+    region: PixelRegion,
+    x: u32,
+    y: u32,
+}
+
+impl<'a, 'gc> Iterator for PixelsInRect<'a, 'gc> {
+    type Item = (u32, u32, Color);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.y >= self.region.y_max || self.region.width() == 0 {
+            return None;
+        }
+        let item = (self.x, self.y, self.read.get_pixel32_raw(self.x, self.y));
+        self.x += 1;
+        if self.x >= self.region.x_max {
+            self.x = self.region.x_min;
+            self.y += 1;
+        }
+        Some(item)
+    }
 }
 
 #[derive(Clone, Collect, Default, Debug)]
@@ -237,9 +503,12 @@ mod wrapper {
     use ruffle_render::backend::RenderBackend;
     use ruffle_render::bitmap::{BitmapHandle, PixelRegion};
     use ruffle_render::commands::CommandHandler;
+    use ruffle_render::transform::Transform;
     use std::cell::Ref;
 
-    use super::{copy_pixels_to_bitmapdata, BitmapData, DirtyState};
+    use super::{
+        copy_pixels_to_bitmapdata, BitmapData, BitmapDataTexture, Color, DirtyState, PixelsInRect,
+    };
 
     #[derive(Collect, Copy, Clone)]
     #[collect(no_drop)]
@@ -287,6 +556,7 @@ mod wrapper {
             // `BitmapData` doesn't contain any gc pointers, allowing us to use a normal
             // `RefCell` instead of a `GcCell`.
             let mut write = unsafe { self.0.borrow_mut() };
+            write.materialize_pixels();
             match std::mem::replace(&mut write.dirty_state, DirtyState::Clean) {
                 DirtyState::GpuModified(sync_handle, bounds) => {
                     sync_handle
@@ -317,10 +587,37 @@ mod wrapper {
             bitmap_data.bitmap_handle(renderer).unwrap()
         }
 
+        /// Uploads any pending CPU changes, and returns the GPU texture holding this `BitmapData`.
+        /// Returns `None` if this `BitmapData` has been disposed.
+        ///
+        /// See `BitmapDataTexture` for how long the returned texture remains valid.
+        pub fn draw_to_texture(
+            &self,
+            gc_context: MutationContext<'gc, '_>,
+            renderer: &mut dyn RenderBackend,
+        ) -> Option<BitmapDataTexture> {
+            let mut bitmap_data = self.0.write(gc_context);
+            if bitmap_data.disposed() {
+                return None;
+            }
+            bitmap_data.update_dirty_texture(renderer);
+            Some(BitmapDataTexture {
+                handle: bitmap_data.bitmap_handle(renderer)?,
+                generation: bitmap_data.texture_generation,
+            })
+        }
+
+        /// Checks whether a texture from `draw_to_texture` still reflects this `BitmapData`.
+        pub fn is_texture_valid(&self, texture: &BitmapDataTexture) -> bool {
+            let read = self.0.read();
+            !read.disposed() && read.texture_generation == texture.generation
+        }
+
         /// Provides access to the underlying `BitmapData`.
         /// This should only be used when you will be overwriting the entire
         /// `pixels` vec without reading from it. Cancels any in-progress GPU -> CPU sync.
-        /// This does not sync from cpu to gpu.
+        /// This does not sync from cpu to gpu, and doesn't allocate the CPU pixels if they
+        /// haven't been yet (`pixels_mut` takes care of that).
         #[allow(clippy::type_complexity)]
         pub fn overwrite_cpu_pixels_from_gpu(
             &self,
@@ -337,25 +634,156 @@ mod wrapper {
             (self.0, dirty_rect)
         }
 
+        /// Provides access to the underlying `BitmapData`, for an operation that will
+        /// replace every pixel in `overwrite` without reading them.
+        ///
+        /// Only the GPU-modified pixels outside of `overwrite` are read back, and nothing
+        /// at all if it covers the whole GPU-modified area. Backends that can't read back
+        /// part of a texture fall back to a full sync.
+        pub fn sync_for_overwrite(&self, overwrite: PixelRegion) -> GcCell<'gc, BitmapData<'gc>> {
+            self.materialize_pixels();
+            let modified_area = match self.0.read().dirty_state {
+                DirtyState::GpuModified(_, area) => area,
+                _ => return self.0,
+            };
+
+            let remaining = modified_area.subtract(overwrite);
+            if overwrite.contains(modified_area)
+                || (remaining != [modified_area] && self.read_back_regions(&remaining))
+            {
+                // SAFETY: See `sync`.
+                unsafe { self.0.borrow_mut() }.dirty_state = DirtyState::Clean;
+                return self.0;
+            }
+            self.sync()
+        }
+
         /// Provides read access to the BitmapData pixels.
         /// Only the provided region is guaranteed to be up-to-date.
         /// It is an error to access any other pixels outside of that region.
+        ///
+        /// If the region only covers part of the GPU-modified area, just that part is read
+        /// back, and the rest of the GPU -> CPU sync is left pending.
         pub fn read_area(&self, read_area: PixelRegion) -> Ref<'_, BitmapData<'gc>> {
+            self.materialize_pixels();
             let needs_update = if let DirtyState::GpuModified(_, area) = self.0.read().dirty_state {
-                area.intersects(read_area)
+                area.intersects(read_area).then(|| {
+                    let mut overlap = read_area;
+                    overlap.intersect(area);
+                    (overlap, area)
+                })
             } else {
-                false
+                None
             };
-            if needs_update {
-                self.sync();
+            if let Some((overlap, area)) = needs_update {
+                let is_partial = overlap != area && overlap.width() > 0 && overlap.height() > 0;
+                if !is_partial || !self.read_back_regions(&[overlap]) {
+                    self.sync();
+                }
             }
             self.0.read()
         }
 
+        /// Allocates the CPU pixels, if they haven't been yet. This doesn't wait for any
+        /// GPU -> CPU sync, so it's safe to call before reading back part of the texture.
+        fn materialize_pixels(&self) {
+            if self.0.read().pending_fill.is_some() {
+                // SAFETY: See `sync`.
+                unsafe { self.0.borrow_mut() }.materialize_pixels();
+            }
+        }
+
+        /// Copies `regions` of the pending GPU -> CPU sync into the CPU pixels, leaving the
+        /// sync itself pending. Returns `false` if the backend couldn't read back part of
+        /// the texture, in which case some regions may not have been updated.
+        fn read_back_regions(&self, regions: &[PixelRegion]) -> bool {
+            // SAFETY: See `sync`.
+            let mut write = unsafe { self.0.borrow_mut() };
+            let DirtyState::GpuModified(sync_handle, area) =
+                std::mem::replace(&mut write.dirty_state, DirtyState::Clean)
+            else {
+                unreachable!("Only called while a GPU -> CPU sync is pending");
+            };
+
+            let read_back = regions.iter().all(|region| {
+                sync_handle
+                    .retrieve_offscreen_region(
+                        *region,
+                        Box::new(|buffer, buffer_width| {
+                            copy_pixels_to_bitmapdata(&mut write, buffer, buffer_width, *region)
+                        }),
+                    )
+                    .is_ok()
+            });
+            write.dirty_state = DirtyState::GpuModified(sync_handle, area);
+            read_back
+        }
+
+        /// Reads a single pixel, in the same form as `BitmapData::get_pixel32_raw`.
+        ///
+        /// If that pixel has been modified on the GPU, only it is read back from the
+        /// backend, and the full GPU -> CPU sync is left pending. Backends that can't
+        /// read back part of a texture fall back to a full sync.
+        pub fn read_pixel_raw(&self, x: u32, y: u32) -> Color {
+            let region = PixelRegion::for_pixel(x, y);
+            {
+                let read = self.0.read();
+                if let DirtyState::GpuModified(sync_handle, area) = &read.dirty_state {
+                    if (area.x_min..area.x_max).contains(&x)
+                        && (area.y_min..area.y_max).contains(&y)
+                    {
+                        let transparency = read.transparency;
+                        let mut pixel = None;
+                        let result = sync_handle.retrieve_offscreen_region(
+                            region,
+                            Box::new(|buffer: &[u8], _buffer_width: u32| {
+                                let a = if transparency { buffer[3] } else { 255 };
+                                pixel = Some(Color::argb(a, buffer[0], buffer[1], buffer[2]));
+                            }),
+                        );
+                        if let (Ok(()), Some(pixel)) = (result, pixel) {
+                            return pixel;
+                        }
+                    }
+                }
+            }
+            self.read_area(region).get_pixel32_raw(x, y)
+        }
+
+        /// Iterates over the pixels in `region`, clamped to the bounds of this `BitmapData`,
+        /// without copying them. See `PixelsInRect`.
+        pub fn pixels_in_rect(&self, mut region: PixelRegion) -> PixelsInRect<'_, 'gc> {
+            region.clamp(self.width(), self.height());
+            PixelsInRect {
+                read: self.read_area(region),
+                region,
+                x: region.x_min,
+                y: region.y_min,
+            }
+        }
+
         // These methods do not require a sync to complete, as they do not depend on the
         // CPU-side pixels. They are implemented directly on `BitmapDataWrapper`, allowing
         // callers to avoid calling sync()
 
+        /// Returns whether any pixels in `region` have been modified on the GPU, and not yet
+        /// read back. Reading those pixels would have to wait for the GPU.
+        pub fn is_gpu_modified(&self, region: PixelRegion) -> bool {
+            match self.0.read().dirty_state {
+                DirtyState::GpuModified(_, area) => area.intersects(region),
+                DirtyState::CpuModified(_) | DirtyState::Clean => false,
+            }
+        }
+
+        /// Returns the color of every pixel in `region`, if the CPU pixels haven't been
+        /// allocated yet and none of those pixels have been modified on the GPU since.
+        pub fn pending_fill(&self, region: PixelRegion) -> Option<Color> {
+            if self.is_gpu_modified(region) {
+                return None;
+            }
+            self.0.read().pending_fill
+        }
+
         pub fn height(&self) -> u32 {
             self.0.read().height
         }
@@ -396,7 +824,27 @@ mod wrapper {
             self.0.write(mc).dispose();
         }
 
+        pub fn lock(&self, mc: MutationContext<'gc, '_>) {
+            self.0.write(mc).lock();
+        }
+
+        pub fn unlock(&self, mc: MutationContext<'gc, '_>, change_rect: Option<PixelRegion>) {
+            self.0.write(mc).unlock(change_rect);
+        }
+
         pub fn render(&self, smoothing: bool, context: &mut RenderContext<'_, 'gc>) {
+            let transform = context.transform_stack.transform();
+            self.render_with_transform(smoothing, transform, context)
+        }
+
+        /// Render this bitmap with `transform` in place of the top of the
+        /// transform stack.
+        pub fn render_with_transform(
+            &self,
+            smoothing: bool,
+            transform: Transform,
+            context: &mut RenderContext<'_, 'gc>,
+        ) {
             let mut inner_bitmap_data = self.0.write(context.gc_context);
             if inner_bitmap_data.disposed() {
                 return;
@@ -404,14 +852,19 @@ mod wrapper {
 
             // Note - we do a CPU -> GPU sync, but we do *not* do a GPU -> CPU sync
             // (rendering is done on the GPU, so the CPU pixels don't need to be up-to-date).
-            inner_bitmap_data.update_dirty_texture(context.renderer);
+            // While locked, we keep showing the previous texture, unless we've never uploaded one.
+            if !inner_bitmap_data.locked || inner_bitmap_data.bitmap_handle.is_none() {
+                inner_bitmap_data.update_dirty_texture(context.renderer);
+            }
             let handle = inner_bitmap_data
                 .bitmap_handle(context.renderer)
                 .expect("Missing bitmap handle");
 
+            let smoothing = smoothing && context.quality.allows_bitmap_smoothing();
+            context.commands.render_bitmap(handle, transform, smoothing);
             context
-                .commands
-                .render_bitmap(handle, context.transform_stack.transform(), smoothing);
+                .texture_versions
+                .push(inner_bitmap_data.texture_generation);
         }
 
         pub fn is_point_in_bounds(&self, x: i32, y: i32) -> bool {
@@ -434,11 +887,37 @@ impl fmt::Debug for BitmapData<'_> {
             .field("height", &self.height)
             .field("transparency", &self.transparency)
             .field("disposed", &self.disposed)
+            .field("texture_generation", &self.texture_generation)
+            .field("locked", &self.locked)
+            .field("change_rect", &self.change_rect)
             .field("bitmap_handle", &self.bitmap_handle)
             .finish()
     }
 }
 
+impl Clone for BitmapData<'_> {
+    /// A clone shares its pixels copy-on-write, but not the memory reservation: that
+    /// has to be made separately, with `set_memory`, so that it's checked against the
+    /// budget.
+    fn clone(&self) -> Self {
+        Self {
+            pixels: self.pixels.clone(),
+            pending_fill: self.pending_fill,
+            width: self.width,
+            height: self.height,
+            transparency: self.transparency,
+            disposed: self.disposed,
+            bitmap_handle: self.bitmap_handle.clone(),
+            avm2_object: self.avm2_object,
+            dirty_state: self.dirty_state.clone(),
+            texture_generation: self.texture_generation,
+            memory: None,
+            locked: self.locked,
+            change_rect: self.change_rect,
+        }
+    }
+}
+
 impl<'gc> BitmapData<'gc> {
     // Creates a dummy BitmapData with no pixels or handle, marked as disposed.
     // This is used for AS3 `Bitmap` instances without a corresponding AS3 `BitmapData` instance.
@@ -446,7 +925,8 @@ impl<'gc> BitmapData<'gc> {
     // be inaccessible to AS3 code.
     pub fn dummy() -> Self {
         BitmapData {
-            pixels: Vec::new(),
+            pixels: Rc::default(),
+            pending_fill: None,
             width: 0,
             height: 0,
             transparency: false,
@@ -454,6 +934,10 @@ impl<'gc> BitmapData<'gc> {
             bitmap_handle: None,
             avm2_object: None,
             dirty_state: DirtyState::Clean,
+            texture_generation: 0,
+            memory: None,
+            locked: false,
+            change_rect: None,
         }
     }
 
@@ -461,11 +945,37 @@ impl<'gc> BitmapData<'gc> {
         self.width = width;
         self.height = height;
         self.transparency = transparency;
-        self.pixels = vec![
-            Color(fill_color).to_premultiplied_alpha(self.transparency());
-            width as usize * height as usize
-        ];
-        self.set_cpu_dirty(PixelRegion::for_whole_size(width, height));
+        // The pixels are only allocated once something needs them, and the texture is
+        // created directly from the fill color.
+        self.pixels = Rc::default();
+        self.pending_fill = Some(Color(fill_color).to_premultiplied_alpha(self.transparency()));
+        self.resize_memory();
+        self.bitmap_handle = None;
+        self.dirty_state = DirtyState::Clean;
+        self.texture_generation += 1;
+    }
+
+    /// Allocates the CPU pixels, if `init_pixels` hasn't done so yet.
+    ///
+    /// This is done by everything that accesses the pixels through `BitmapDataWrapper`,
+    /// as well as by `pixels_mut` and `set_pixel32_raw`.
+    fn materialize_pixels(&mut self) {
+        if let Some(fill) = self.pending_fill.take() {
+            self.pixels = Rc::new(vec![fill; self.width as usize * self.height as usize]);
+        }
+    }
+
+    /// Charges this bitmap's pixels to the given reservation from the player's budget.
+    pub fn set_memory(&mut self, memory: BitmapAllocation) {
+        self.memory = Some(memory);
+        self.resize_memory();
+    }
+
+    fn resize_memory(&mut self) {
+        let bytes = BitmapMemoryBudget::bytes_for(self.width, self.height);
+        if let Some(memory) = &mut self.memory {
+            memory.resize(bytes);
+        }
     }
 
     pub fn new_with_pixels(
@@ -475,7 +985,8 @@ impl<'gc> BitmapData<'gc> {
         pixels: Vec<Color>,
     ) -> Self {
         Self {
-            pixels,
+            pixels: Rc::new(pixels),
+            pending_fill: None,
             width,
             height,
             transparency,
@@ -483,6 +994,10 @@ impl<'gc> BitmapData<'gc> {
             avm2_object: None,
             disposed: false,
             dirty_state: DirtyState::Clean,
+            texture_generation: 0,
+            memory: None,
+            locked: false,
+            change_rect: None,
         }
     }
 
@@ -493,21 +1008,26 @@ impl<'gc> BitmapData<'gc> {
     pub fn dispose(&mut self) {
         self.width = 0;
         self.height = 0;
-        self.pixels.clear();
+        self.pixels = Rc::default();
+        self.pending_fill = None;
         self.bitmap_handle = None;
         // There's no longer a handle to update
         self.dirty_state = DirtyState::Clean;
+        self.texture_generation += 1;
+        self.memory = None;
         self.disposed = true;
     }
 
     pub fn bitmap_handle(&mut self, renderer: &mut dyn RenderBackend) -> Option<BitmapHandle> {
         if self.bitmap_handle.is_none() {
-            let bitmap = Bitmap::new(
-                self.width(),
-                self.height(),
-                BitmapFormat::Rgba,
-                self.pixels_rgba(),
-            );
+            let rgba = match self.pending_fill {
+                Some(fill) => {
+                    let [b, g, r, a] = fill.0.to_le_bytes();
+                    [r, g, b, a].repeat(self.width as usize * self.height as usize)
+                }
+                None => self.pixels_rgba(),
+            };
+            let bitmap = Bitmap::new(self.width(), self.height(), BitmapFormat::Rgba, rgba);
             let bitmap_handle = renderer.register_bitmap(bitmap);
             if let Err(e) = &bitmap_handle {
                 tracing::warn!("Failed to register raw bitmap for BitmapData: {:?}", e);
@@ -524,6 +1044,7 @@ impl<'gc> BitmapData<'gc> {
 
     pub fn set_gpu_dirty(&mut self, sync_handle: Box<dyn SyncHandle>, region: PixelRegion) {
         self.dirty_state = DirtyState::GpuModified(sync_handle, region);
+        self.texture_generation += 1;
     }
 
     pub fn set_cpu_dirty(&mut self, region: PixelRegion) {
@@ -538,36 +1059,105 @@ impl<'gc> BitmapData<'gc> {
         }
     }
 
+    pub fn lock(&mut self) {
+        self.locked = true;
+    }
+
+    /// Ends a `lock`, allowing the accumulated changes to be uploaded on the next render.
+    ///
+    /// If `change_rect` is provided, the next upload only covers the part of the dirty
+    /// region inside of it, mirroring how Flash only redraws the given area of any `Bitmap`s.
+    /// The rest stays dirty, and is uploaded on the render after that.
+    pub fn unlock(&mut self, change_rect: Option<PixelRegion>) {
+        if !self.locked {
+            return;
+        }
+        self.locked = false;
+        self.change_rect = change_rect;
+    }
+
     pub fn pixels(&self) -> &[Color] {
+        debug_assert!(
+            self.pending_fill.is_none(),
+            "Pixels must be accessed through `BitmapDataWrapper`"
+        );
         &self.pixels
     }
 
-    pub fn set_pixels(&mut self, width: u32, height: u32, transparency: bool, pixels: Vec<Color>) {
+    /// Direct access to the pixel storage. Callers are responsible for marking what they
+    /// change as dirty.
+    ///
+    /// If the pixels are shared with another `BitmapData`, they are copied first.
+    pub fn pixels_mut(&mut self) -> &mut [Color] {
+        self.materialize_pixels();
+        Rc::make_mut(&mut self.pixels)
+    }
+
+    /// Returns a handle to the pixel storage, which can be passed to `set_pixels`
+    /// to share it with another `BitmapData` without copying.
+    ///
+    /// If the pixels haven't been allocated yet, a new buffer is filled instead of
+    /// sharing one. Going through `BitmapDataWrapper::sync` first avoids this.
+    pub fn shared_pixels(&self) -> Rc<Vec<Color>> {
+        match self.pending_fill {
+            Some(fill) => Rc::new(vec![fill; self.width as usize * self.height as usize]),
+            None => self.pixels.clone(),
+        }
+    }
+
+    /// Returns whether this `BitmapData` currently shares its pixel storage with `other`.
+    pub fn shares_pixels_with(&self, other: &BitmapData<'_>) -> bool {
+        Rc::ptr_eq(&self.pixels, &other.pixels)
+    }
+
+    pub fn set_pixels(
+        &mut self,
+        width: u32,
+        height: u32,
+        transparency: bool,
+        pixels: impl Into<Rc<Vec<Color>>>,
+    ) {
         self.width = width;
         self.height = height;
         self.transparency = transparency;
-        self.pixels = pixels;
+        self.pixels = pixels.into();
+        self.pending_fill = None;
+        self.resize_memory();
         self.set_cpu_dirty(PixelRegion::for_whole_size(width, height));
     }
 
     pub fn pixels_rgba(&self) -> Vec<u8> {
-        // TODO: This could have been implemented as follows:
-        //
-        // self.pixels
-        //     .iter()
-        //     .flat_map(|p| [p.red(), p.green(), p.blue(), p.alpha()])
-        //     .collect()
-        //
-        // But currently Rust emits suboptimal code in that case. For now we use
-        // `Vec::with_capacity` manually to avoid unnecessary re-allocations.
-
-        let mut output = Vec::with_capacity(self.pixels.len() * 4);
-        for p in &self.pixels {
-            output.extend_from_slice(&[p.red(), p.green(), p.blue(), p.alpha()])
-        }
+        let mut output = vec![0; self.pixels.len() * 4];
+        pixels_to_rgba(&self.pixels, &mut output);
         output
     }
 
+    /// Like `pixels_rgba`, but only converts the pixels inside of `region`.
+    /// The rest of the buffer is left zeroed.
+    pub fn pixels_rgba_in_region(&self, region: PixelRegion) -> Vec<u8> {
+        let mut output = Vec::new();
+        self.write_rgba_in_region(region, &mut output);
+        output
+    }
+
+    /// Converts the pixels inside of `region` to RGBA, storing them at the same place in
+    /// `output` as they would be in the result of `pixels_rgba`.
+    ///
+    /// `output` is resized to fit the whole bitmap, but anything outside of `region` is
+    /// left as it was.
+    pub fn write_rgba_in_region(&self, region: PixelRegion, output: &mut Vec<u8>) {
+        output.resize(self.pixels.len() * 4, 0);
+        let width = self.width as usize;
+        let columns = region.x_min as usize..region.x_max as usize;
+        for y in region.y_min as usize..region.y_max as usize {
+            let row = y * width;
+            pixels_to_rgba(
+                &self.pixels[row + columns.start..row + columns.end],
+                &mut output[(row + columns.start) * 4..(row + columns.end) * 4],
+            );
+        }
+    }
+
     pub fn width(&self) -> u32 {
         self.width
     }
@@ -581,11 +1171,15 @@ impl<'gc> BitmapData<'gc> {
 
     #[inline]
     pub fn set_pixel32_raw(&mut self, x: u32, y: u32, color: Color) {
-        self.pixels[(x + y * self.width) as usize] = color;
+        let index = (x + y * self.width) as usize;
+        self.pixels_mut()[index] = color;
     }
 
     #[inline]
     pub fn get_pixel32_raw(&self, x: u32, y: u32) -> Color {
+        if let Some(fill) = self.pending_fill {
+            return fill;
+        }
         self.pixels[(x + y * self.width()) as usize]
     }
 
@@ -595,10 +1189,44 @@ impl<'gc> BitmapData<'gc> {
         let handle = self.bitmap_handle(renderer).unwrap();
         match &self.dirty_state {
             DirtyState::CpuModified(region) => {
-                if let Err(e) = renderer.update_texture(&handle, self.pixels_rgba(), *region) {
+                let region = *region;
+                let mut upload_region = region;
+                if let Some(change_rect) = self.change_rect.take() {
+                    upload_region.intersect(change_rect);
+                    if upload_region.width() == 0 || upload_region.height() == 0 {
+                        return;
+                    }
+                }
+
+                self.materialize_pixels();
+                let converted = if renderer.supports_partial_texture_update() {
+                    upload_region
+                } else {
+                    PixelRegion::for_whole_size(self.width, self.height)
+                };
+                let result = UPLOAD_BUFFER.with(|buffer| {
+                    let mut buffer = buffer.borrow_mut();
+                    self.write_rgba_in_region(converted, &mut buffer);
+                    let result = renderer.update_texture(&handle, &buffer, upload_region);
+                    if buffer.capacity() > UPLOAD_BUFFER_RETAINED_CAPACITY {
+                        *buffer = Vec::new();
+                    }
+                    result
+                });
+                if let Err(e) = result {
                     tracing::error!("Failed to update dirty bitmap {:?}: {:?}", handle, e);
                 }
-                self.dirty_state = DirtyState::Clean;
+
+                // Anything left out by an `unlock` change rect is uploaded next time.
+                let remaining = region.subtract(converted).into_iter().reduce(|mut a, b| {
+                    a.union(b);
+                    a
+                });
+                self.dirty_state = match remaining {
+                    Some(remaining) => DirtyState::CpuModified(remaining),
+                    None => DirtyState::Clean,
+                };
+                self.texture_generation += 1;
             }
             DirtyState::Clean | DirtyState::GpuModified(_, _) => {}
         }
@@ -709,3 +1337,551 @@ impl ThresholdOperation {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{with_update_context, RecordingRenderer};
+
+    fn dirty_bitmap_data<'gc>(width: u32, height: u32) -> BitmapData<'gc> {
+        let mut bitmap_data = BitmapData::new_with_pixels(
+            width,
+            height,
+            true,
+            vec![Color(0); (width * height) as usize],
+        );
+        bitmap_data.set_cpu_dirty(PixelRegion::for_whole_size(width, height));
+        bitmap_data
+    }
+
+    /// Blends premultiplied `src` onto `dst` the way the wgpu backend does: with
+    /// the blend states in `blend.rs` for trivial modes, and the shaders in
+    /// `shaders/blend` for the rest. Returns `None` where a shader discards.
+    fn gpu_blend(dst: [f32; 4], src: [f32; 4], blend_mode: BlendMode) -> Option<[f32; 4]> {
+        let [dr, dg, db, da] = dst;
+        let [sr, sg, sb, sa] = src;
+        let over_alpha = sa + da * (1.0 - sa);
+        let per_channel = |f: &dyn Fn(f32, f32) -> f32| [f(sr, dr), f(sg, dg), f(sb, db)];
+        let complex = |f: &dyn Fn(f32, f32) -> f32| {
+            per_channel(&|s, d| s * (1.0 - da) + d * (1.0 - sa) + sa * da * f(s / sa, d / da))
+        };
+
+        let ([r, g, b], a) = match blend_mode {
+            BlendMode::Normal | BlendMode::Layer => {
+                (per_channel(&|s, d| s + d * (1.0 - sa)), over_alpha)
+            }
+            BlendMode::Multiply => (per_channel(&|s, d| d * s), over_alpha),
+            BlendMode::Add => (per_channel(&|s, d| s + d), over_alpha),
+            BlendMode::Screen => (per_channel(&|s, d| s + d * (1.0 - s)), over_alpha),
+            BlendMode::Subtract => (per_channel(&|s, d| d - s), over_alpha),
+            _ if sa <= 0.0 => return None,
+            BlendMode::Lighten => (complex(&|s, d| s.max(d)), over_alpha),
+            BlendMode::Darken => (complex(&|s, d| s.min(d)), over_alpha),
+            BlendMode::Difference => (complex(&|s, d| (d - s).abs()), over_alpha),
+            BlendMode::Invert => (complex(&|_, d| 1.0 - d), over_alpha),
+            BlendMode::Overlay => (
+                complex(&|s, d| {
+                    if d <= 0.5 {
+                        2.0 * s * d
+                    } else {
+                        1.0 - 2.0 * (1.0 - d) * (1.0 - s)
+                    }
+                }),
+                over_alpha,
+            ),
+            BlendMode::HardLight => (
+                complex(&|s, d| {
+                    if s <= 0.5 {
+                        2.0 * s * d
+                    } else {
+                        1.0 - 2.0 * (1.0 - d) * (1.0 - s)
+                    }
+                }),
+                over_alpha,
+            ),
+            BlendMode::Alpha => ([dr * sa, dg * sa, db * sa], sa * da),
+            BlendMode::Erase => (
+                [dr * (1.0 - sa), dg * (1.0 - sa), db * (1.0 - sa)],
+                (1.0 - sa) * da,
+            ),
+        };
+        Some([r, g, b, a])
+    }
+
+    #[test]
+    fn blend_matches_gpu() {
+        let to_f32 = |color: Color| {
+            [color.red(), color.green(), color.blue(), color.alpha()].map(|c| c as f32 / 255.0)
+        };
+        // The render target clamps each channel, and reading pixels back from the GPU
+        // un-premultiplies them, which saturates any channel brighter than alpha.
+        let to_color = |[r, g, b, a]: [f32; 4]| {
+            let a = a.clamp(0.0, 1.0);
+            let c = |c: f32| (c.clamp(0.0, a) * 255.0).round() as u8;
+            Color::argb((a * 255.0).round() as u8, c(r), c(g), c(b))
+        };
+
+        let destinations = [
+            Color::argb(0xFF, 0x80, 0x80, 0x80),
+            Color::argb(0xFF, 0x10, 0xE0, 0x60),
+            Color::argb(0x80, 0x40, 0x20, 0x7F),
+            Color::argb(0x01, 0x01, 0x00, 0x01),
+        ];
+        let sources = [
+            Color::argb(0xFF, 0x40, 0x80, 0xFF),
+            Color::argb(0xFF, 0xF0, 0x20, 0x00),
+            Color::argb(0x60, 0x30, 0x60, 0x10),
+            Color::argb(0x00, 0x00, 0x00, 0x00),
+        ];
+        let blend_modes = [
+            BlendMode::Normal,
+            BlendMode::Layer,
+            BlendMode::Multiply,
+            BlendMode::Screen,
+            BlendMode::Lighten,
+            BlendMode::Darken,
+            BlendMode::Difference,
+            BlendMode::Add,
+            BlendMode::Subtract,
+            BlendMode::Invert,
+            BlendMode::Alpha,
+            BlendMode::Erase,
+            BlendMode::Overlay,
+            BlendMode::HardLight,
+        ];
+
+        for blend_mode in blend_modes {
+            for dst in destinations {
+                for src in sources {
+                    let cpu = dst.blend(&src, blend_mode);
+                    let gpu = gpu_blend(to_f32(dst), to_f32(src), blend_mode).map_or(dst, to_color);
+                    let channels = |c: Color| [c.red(), c.green(), c.blue(), c.alpha()];
+                    for (cpu_channel, gpu_channel) in channels(cpu).into_iter().zip(channels(gpu)) {
+                        assert!(
+                            cpu_channel.abs_diff(gpu_channel) <= 1,
+                            "{blend_mode:?}: {src} onto {dst} is {cpu} on the CPU, {gpu} on the GPU"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn dispose_invalidates_texture() {
+        use gc_arena::{rootless_arena, GcCell};
+        use ruffle_render::backend::null::NullRenderer;
+        use ruffle_render::backend::ViewportDimensions;
+
+        let mut renderer = NullRenderer::new(ViewportDimensions {
+            width: 1,
+            height: 1,
+            scale_factor: 1.0,
+        });
+        rootless_arena(|mc| {
+            let bitmap_data = BitmapDataWrapper::new(GcCell::allocate(mc, dirty_bitmap_data(2, 2)));
+            let texture = bitmap_data.draw_to_texture(mc, &mut renderer).unwrap();
+            assert!(bitmap_data.is_texture_valid(&texture));
+
+            bitmap_data.dispose(mc);
+            assert!(!bitmap_data.is_texture_valid(&texture));
+            assert!(bitmap_data.draw_to_texture(mc, &mut renderer).is_none());
+        });
+    }
+
+    #[test]
+    fn upload_invalidates_texture() {
+        use gc_arena::{rootless_arena, GcCell};
+        use ruffle_render::backend::null::NullRenderer;
+        use ruffle_render::backend::ViewportDimensions;
+
+        let mut renderer = NullRenderer::new(ViewportDimensions {
+            width: 1,
+            height: 1,
+            scale_factor: 1.0,
+        });
+        rootless_arena(|mc| {
+            let bitmap_data = BitmapDataWrapper::new(GcCell::allocate(mc, dirty_bitmap_data(2, 2)));
+            let texture = bitmap_data.draw_to_texture(mc, &mut renderer).unwrap();
+
+            bitmap_data
+                .sync()
+                .write(mc)
+                .set_cpu_dirty(PixelRegion::for_pixel(0, 0));
+            // Not uploaded yet.
+            assert!(bitmap_data.is_texture_valid(&texture));
+
+            let new_texture = bitmap_data.draw_to_texture(mc, &mut renderer).unwrap();
+            assert!(!bitmap_data.is_texture_valid(&texture));
+            assert!(bitmap_data.is_texture_valid(&new_texture));
+        });
+    }
+
+    #[test]
+    fn unlock_limits_upload_to_change_rect() {
+        let mut renderer = RecordingRenderer::default();
+        let mut bitmap_data = dirty_bitmap_data(10, 10);
+        bitmap_data.lock();
+        bitmap_data.unlock(Some(PixelRegion::for_region(2, 3, 4, 1)));
+        assert!(!bitmap_data.locked);
+
+        bitmap_data.update_dirty_texture(&mut renderer);
+        assert_eq!(
+            renderer.texture_updates,
+            [PixelRegion::for_region(2, 3, 4, 1)]
+        );
+        // The rest of the changes are still waiting to be uploaded.
+        assert!(matches!(
+            bitmap_data.dirty_state,
+            DirtyState::CpuModified(region) if region == PixelRegion::for_whole_size(10, 10)
+        ));
+
+        bitmap_data.update_dirty_texture(&mut renderer);
+        assert_eq!(
+            renderer.texture_updates[1],
+            PixelRegion::for_whole_size(10, 10)
+        );
+        assert!(matches!(bitmap_data.dirty_state, DirtyState::Clean));
+    }
+
+    #[test]
+    fn unlock_without_change_rect_keeps_dirty_region() {
+        let mut renderer = RecordingRenderer::default();
+        let mut bitmap_data = dirty_bitmap_data(10, 10);
+        bitmap_data.lock();
+        bitmap_data.unlock(None);
+        assert!(matches!(
+            bitmap_data.dirty_state,
+            DirtyState::CpuModified(region) if region == PixelRegion::for_whole_size(10, 10)
+        ));
+
+        bitmap_data.update_dirty_texture(&mut renderer);
+        assert_eq!(
+            renderer.texture_updates,
+            [PixelRegion::for_whole_size(10, 10)]
+        );
+        assert!(matches!(bitmap_data.dirty_state, DirtyState::Clean));
+    }
+
+    #[test]
+    fn unlock_with_disjoint_change_rect_keeps_changes_dirty() {
+        let mut renderer = RecordingRenderer::default();
+        let mut bitmap_data = dirty_bitmap_data(10, 10);
+        bitmap_data.dirty_state = DirtyState::CpuModified(PixelRegion::for_region(0, 0, 2, 2));
+        bitmap_data.lock();
+        bitmap_data.unlock(Some(PixelRegion::for_region(5, 5, 2, 2)));
+
+        bitmap_data.update_dirty_texture(&mut renderer);
+        assert!(renderer.texture_updates.is_empty());
+        assert!(matches!(
+            bitmap_data.dirty_state,
+            DirtyState::CpuModified(region) if region == PixelRegion::for_region(0, 0, 2, 2)
+        ));
+
+        bitmap_data.update_dirty_texture(&mut renderer);
+        assert_eq!(
+            renderer.texture_updates,
+            [PixelRegion::for_region(0, 0, 2, 2)]
+        );
+    }
+
+    #[test]
+    fn pixels_rgba_in_region_only_converts_region() {
+        let pixels = (1..=6).map(|i| Color::argb(0xFF, i, i, i)).collect();
+        let bitmap_data = BitmapData::new_with_pixels(3, 2, true, pixels);
+        let rgba = bitmap_data.pixels_rgba_in_region(PixelRegion::for_region(1, 1, 2, 1));
+        assert_eq!(rgba.len(), 3 * 2 * 4);
+        assert!(rgba[..16].iter().all(|b| *b == 0));
+        assert_eq!(&rgba[16..], [5, 5, 5, 0xFF, 6, 6, 6, 0xFF]);
+        assert_eq!(&rgba[16..], &bitmap_data.pixels_rgba()[16..]);
+    }
+
+    #[test]
+    fn hsv_round_trips_rgb_cube() {
+        let corners = [0, 255];
+        let mut colors: Vec<_> = corners
+            .iter()
+            .flat_map(|r| {
+                corners
+                    .iter()
+                    .flat_map(move |g| corners.map(|b| (*r, *g, b)))
+            })
+            .collect();
+        colors.extend([(128, 128, 128), (255, 128, 0), (12, 200, 97), (1, 0, 254)]);
+
+        for (r, g, b) in colors {
+            let color = Color::argb(0x80, r, g, b);
+            let (hue, saturation, value) = color.to_hsv();
+            assert!((0.0..360.0).contains(&hue), "{color:?}: hue {hue}");
+            assert!(
+                (0.0..=1.0).contains(&saturation),
+                "{color:?}: saturation {saturation}"
+            );
+            assert!((0.0..=1.0).contains(&value), "{color:?}: value {value}");
+            assert_eq!(Color::from_hsv(0x80, hue, saturation, value), color);
+        }
+
+        assert_eq!(Color::argb(0xFF, 255, 0, 0).to_hsv(), (0.0, 1.0, 1.0));
+        assert_eq!(Color::argb(0xFF, 0, 0, 255).to_hsv(), (240.0, 1.0, 1.0));
+        assert_eq!(Color::argb(0xFF, 255, 255, 255).to_hsv(), (0.0, 0.0, 1.0));
+        assert_eq!(
+            Color::from_hsv(0xFF, -120.0, 1.0, 1.0),
+            Color::argb(0xFF, 0, 0, 255)
+        );
+    }
+
+    #[test]
+    fn write_rgba_in_region_reuses_buffer() {
+        let pixels = (1..=6)
+            .map(|i| Color::argb(0xFF, i, 2 * i, 3 * i))
+            .collect();
+        let bitmap_data = BitmapData::new_with_pixels(3, 2, true, pixels);
+        let mut buffer = vec![0xAA; 3 * 2 * 4];
+        let allocation = buffer.as_ptr();
+
+        bitmap_data.write_rgba_in_region(PixelRegion::for_region(1, 0, 1, 2), &mut buffer);
+        assert_eq!(buffer.as_ptr(), allocation);
+        assert_eq!(&buffer[..4], [0xAA; 4]);
+        assert_eq!(&buffer[4..8], [2, 4, 6, 0xFF]);
+        assert_eq!(&buffer[16..20], [5, 10, 15, 0xFF]);
+        assert_eq!(&buffer[20..], [0xAA; 4]);
+
+        bitmap_data.write_rgba_in_region(PixelRegion::for_whole_size(3, 2), &mut buffer);
+        assert_eq!(buffer.as_ptr(), allocation);
+        assert_eq!(buffer, bitmap_data.pixels_rgba());
+    }
+
+    #[test]
+    fn upload_buffer_is_freed_after_large_uploads() {
+        let mut renderer = RecordingRenderer::default();
+        let mut upload = |width: u32, height: u32| {
+            let pixels = vec![Color(0); (width * height) as usize];
+            let mut bitmap_data = BitmapData::new_with_pixels(width, height, true, pixels);
+            bitmap_data.bitmap_handle(&mut renderer);
+            bitmap_data.set_cpu_dirty(PixelRegion::for_pixel(0, 0));
+            bitmap_data.update_dirty_texture(&mut renderer);
+            UPLOAD_BUFFER.with(|buffer| buffer.borrow().capacity())
+        };
+
+        assert!(upload(64, 64) >= 64 * 64 * 4);
+        assert_eq!(upload(2049, 2048), 0);
+        assert!(upload(2048, 2048) >= UPLOAD_BUFFER_RETAINED_CAPACITY);
+    }
+
+    #[test]
+    fn set_pixel_only_dirties_that_pixel() {
+        use crate::bitmap::operations;
+        use gc_arena::GcCell;
+
+        with_update_context(|context| {
+            let bitmap_data = BitmapData::new_with_pixels(4, 4, true, vec![Color(0); 16]);
+            let bitmap_data =
+                BitmapDataWrapper::new(GcCell::allocate(context.gc_context, bitmap_data));
+            operations::set_pixel(context, bitmap_data, 2, 3, Color(-1));
+            operations::set_pixel32(context, bitmap_data, 1, 1, -1);
+
+            let expected = PixelRegion::encompassing_pixels((1, 1), (2, 3));
+            assert!(matches!(
+                bitmap_data.sync().read().dirty_state,
+                DirtyState::CpuModified(region) if region == expected
+            ));
+        });
+    }
+
+    /// Stands in for a GPU render, holding the RGBA pixels of the rendered area.
+    #[derive(Debug)]
+    struct FakeSyncHandle {
+        rgba: Vec<u8>,
+        width: u32,
+        partial_reads: std::rc::Rc<std::cell::Cell<u32>>,
+    }
+
+    impl SyncHandle for FakeSyncHandle {
+        fn retrieve_offscreen_texture(
+            self: Box<Self>,
+            with_rgba: ruffle_render::bitmap::RgbaBufRead,
+        ) -> Result<(), ruffle_render::error::Error> {
+            with_rgba(&self.rgba, self.width * 4);
+            Ok(())
+        }
+
+        fn retrieve_offscreen_region(
+            &self,
+            region: PixelRegion,
+            with_rgba: ruffle_render::bitmap::RgbaBufRead,
+        ) -> Result<(), ruffle_render::error::Error> {
+            self.partial_reads.set(self.partial_reads.get() + 1);
+            let start = ((region.x_min + region.y_min * self.width) * 4) as usize;
+            with_rgba(&self.rgba[start..], self.width * 4);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn read_pixel_matches_full_sync() {
+        use gc_arena::{rootless_arena, GcCell};
+
+        let partial_reads = std::rc::Rc::new(std::cell::Cell::new(0));
+        rootless_arena(|mc| {
+            let cell = GcCell::allocate(
+                mc,
+                BitmapData::new_with_pixels(3, 3, true, vec![Color(0); 9]),
+            );
+            let bitmap_data = BitmapDataWrapper::new(cell);
+
+            for frame in 0..4u8 {
+                // Simulate a `draw` that leaves the result on the GPU.
+                let rgba = (0..9u8)
+                    .flat_map(|i| [i * 10, frame * 20, 0x80, 0xFF])
+                    .collect();
+                cell.write(mc).dirty_state = DirtyState::GpuModified(
+                    Box::new(FakeSyncHandle {
+                        rgba,
+                        width: 3,
+                        partial_reads: partial_reads.clone(),
+                    }),
+                    PixelRegion::for_whole_size(3, 3),
+                );
+
+                let pixel = bitmap_data.read_pixel_raw(2, 1);
+                assert!(matches!(
+                    cell.read().dirty_state,
+                    DirtyState::GpuModified(..)
+                ));
+                assert_eq!(pixel, Color::argb(0xFF, 50, frame * 20, 0x80));
+                assert_eq!(bitmap_data.sync().read().get_pixel32_raw(2, 1), pixel);
+            }
+        });
+        assert_eq!(partial_reads.get(), 4);
+    }
+
+    #[test]
+    fn draw_target_only_allocates_pixels_when_read() {
+        use gc_arena::{rootless_arena, GcCell};
+        use ruffle_render::backend::null::NullRenderer;
+        use ruffle_render::backend::ViewportDimensions;
+
+        let mut renderer = NullRenderer::new(ViewportDimensions {
+            width: 1,
+            height: 1,
+            scale_factor: 1.0,
+        });
+        rootless_arena(|mc| {
+            let cell = GcCell::allocate(mc, BitmapData::default());
+            cell.write(mc).init_pixels(4, 4, true, 0x80FF0000u32 as i32);
+            let bitmap_data = BitmapDataWrapper::new(cell);
+            let allocated = || cell.read().pixels.capacity() * std::mem::size_of::<Color>();
+
+            // Displaying it and drawing to it only ever touches the texture.
+            for frame in 0..3u8 {
+                assert!(bitmap_data.draw_to_texture(mc, &mut renderer).is_some());
+                cell.write(mc).set_gpu_dirty(
+                    Box::new(FakeSyncHandle {
+                        rgba: [frame, 0, 0, 0xFF].repeat(16),
+                        width: 4,
+                        partial_reads: Default::default(),
+                    }),
+                    PixelRegion::for_region(0, 0, 2, 2),
+                );
+                assert_eq!(allocated(), 0);
+            }
+
+            // Reading it allocates the pixels, filled in from the fill color and the GPU.
+            let read = bitmap_data.sync();
+            assert_eq!(allocated(), 4 * 4 * std::mem::size_of::<Color>());
+            assert_eq!(
+                read.read().get_pixel32_raw(1, 1),
+                Color::argb(0xFF, 2, 0, 0)
+            );
+            assert_eq!(
+                read.read().get_pixel32_raw(3, 3),
+                Color::argb(0x80, 0xFF, 0, 0).to_premultiplied_alpha(true)
+            );
+
+            bitmap_data.dispose(mc);
+            assert_eq!(allocated(), 0);
+            assert!(cell.read().bitmap_handle.is_none());
+        });
+    }
+
+    #[test]
+    fn cpu_edits_after_gpu_draw_only_read_back_untouched_pixels() {
+        use crate::bitmap::operations;
+        use gc_arena::GcCell;
+
+        let partial_reads = std::rc::Rc::new(std::cell::Cell::new(0));
+        with_update_context(|context| {
+            let mc = context.gc_context;
+            let cell = GcCell::allocate(
+                mc,
+                BitmapData::new_with_pixels(4, 4, true, vec![Color(0); 16]),
+            );
+            let bitmap_data = BitmapDataWrapper::new(cell);
+            let gpu_pixel = |x: u32, y: u32| Color::argb(0xFF, x as u8, y as u8, 0x80);
+            // Simulate a `draw` that leaves its result for the middle 2x2 pixels on the GPU.
+            let draw = || {
+                let rgba = (0..16u32)
+                    .flat_map(|i| [(i % 4) as u8, (i / 4) as u8, 0x80, 0xFF])
+                    .collect();
+                cell.write(mc).dirty_state = DirtyState::GpuModified(
+                    Box::new(FakeSyncHandle {
+                        rgba,
+                        width: 4,
+                        partial_reads: partial_reads.clone(),
+                    }),
+                    PixelRegion::for_region(1, 1, 2, 2),
+                );
+            };
+            let pixel = |x, y| cell.read().get_pixel32_raw(x, y);
+
+            // Reading part of the drawn area leaves the rest on the GPU.
+            draw();
+            assert_eq!(
+                bitmap_data
+                    .read_area(PixelRegion::for_pixel(2, 2))
+                    .get_pixel32_raw(2, 2),
+                gpu_pixel(2, 2)
+            );
+            assert!(matches!(
+                cell.read().dirty_state,
+                DirtyState::GpuModified(..)
+            ));
+            assert_eq!(partial_reads.get(), 1);
+
+            // Setting one pixel reads back the other three.
+            operations::set_pixel32(context, bitmap_data, 1, 1, -1);
+            assert_eq!(partial_reads.get(), 3);
+            assert_eq!(pixel(1, 1), Color(-1));
+            for (x, y) in [(2, 1), (1, 2), (2, 2)] {
+                assert_eq!(pixel(x, y), gpu_pixel(x, y));
+            }
+            // Pixels outside the drawn area were never touched by the GPU.
+            assert_eq!(pixel(0, 0), Color(0));
+            assert_eq!(pixel(3, 3), Color(0));
+
+            // Filling over the whole drawn area needs no readback at all.
+            draw();
+            operations::fill_rect(context, bitmap_data, 0, 1, 3, 3, 0xFF112233u32 as i32);
+            assert_eq!(partial_reads.get(), 3);
+            assert!(matches!(
+                cell.read().dirty_state,
+                DirtyState::CpuModified(..)
+            ));
+            assert_eq!(pixel(2, 2), Color::argb(0xFF, 0x11, 0x22, 0x33));
+            assert_eq!(pixel(3, 1), Color(0));
+
+            // Copying over the left column of the drawn area reads back the right one.
+            draw();
+            let source = BitmapDataWrapper::new(GcCell::allocate(
+                mc,
+                BitmapData::new_with_pixels(4, 4, true, vec![Color(-1); 16]),
+            ));
+            operations::copy_pixels(context, bitmap_data, source, (0, 0, 2, 4), (0, 0), false);
+            assert_eq!(partial_reads.get(), 4);
+            assert_eq!(pixel(1, 2), Color(-1));
+            assert_eq!(pixel(2, 1), gpu_pixel(2, 1));
+            assert_eq!(pixel(2, 2), gpu_pixel(2, 2));
+            assert_eq!(pixel(3, 2), Color(0));
+        });
+    }
+}
@@ -4,16 +4,19 @@ use crate::bitmap::bitmap_data::{
     BitmapData, BitmapDataDrawError, BitmapDataWrapper, ChannelOptions, Color, IBitmapDrawable,
     LehmerRng, ThresholdOperation,
 };
+use crate::bitmap::pixel_bender::Shader;
 use crate::bitmap::turbulence::Turbulence;
 use crate::context::{RenderContext, UpdateContext};
-use crate::display_object::TDisplayObject;
-use ruffle_render::bitmap::PixelRegion;
+use crate::display_object::{DisplayObject, MovieClip, TDisplayObject, TDisplayObjectContainer};
+use gc_arena::MutationContext;
+use ruffle_render::bitmap::{BitmapHandle, PixelRegion};
 use ruffle_render::commands::{CommandHandler, CommandList};
-use ruffle_render::filters::Filter;
+use ruffle_render::filters::{Filter, ShaderFilter};
 use ruffle_render::matrix::Matrix;
 use ruffle_render::quality::StageQuality;
 use ruffle_render::transform::Transform;
-use swf::{BlendMode, ColorTransform, Fixed8, Rectangle, Twips};
+use std::ops::Range;
+use swf::{BlendMode, ColorTransform, Fixed8, GradientFilter, GradientRecord, Rectangle, Twips};
 
 /// AVM1 and AVM2 have a shared set of operations they can perform on BitmapDatas.
 /// Instead of directly manipulating the BitmapData in each place, they should call
@@ -38,24 +41,77 @@ pub fn fill_rect<'gc>(
         return;
     }
 
-    let target = if rect.width() == target.width() && rect.height() == target.height() {
-        // If we're filling the whole region, we can discard the gpu data
-        target.overwrite_cpu_pixels_from_gpu(context).0
-    } else {
-        // If we're filling a partial region, finish any gpu->cpu sync
-        target.sync()
-    };
+    // Filling a region that already has this color would only cause a needless upload.
+    // Pixels that are still on the GPU aren't checked, as reading them back costs more
+    // than the fill itself.
+    let color = Color::from(color).to_premultiplied_alpha(target.transparency());
+    if !target.is_gpu_modified(rect) && region_is_uniform(target, rect) == Some(color.into()) {
+        return;
+    }
+
+    let is_whole_surface = rect.width() == target.width() && rect.height() == target.height();
+    // Any GPU changes inside the filled region are about to be replaced anyway.
+    let target = target.sync_for_overwrite(rect);
     let mut write = target.write(context.gc_context);
-    let color = Color::from(color).to_premultiplied_alpha(write.transparency());
 
-    for x in rect.x_min..rect.x_max {
-        for y in rect.y_min..rect.y_max {
-            write.set_pixel32_raw(x, y, color);
+    let width = write.width() as usize;
+    let pixels = write.pixels_mut();
+    if is_whole_surface {
+        // Clearing the whole bitmap is by far the most common use of this, often every frame.
+        pixels.fill(color);
+    } else {
+        let columns = rect.x_min as usize..rect.x_max as usize;
+        for row in pixels
+            .chunks_exact_mut(width)
+            .take(rect.y_max as usize)
+            .skip(rect.y_min as usize)
+        {
+            row[columns.clone()].fill(color);
         }
     }
     write.set_cpu_dirty(rect);
 }
 
+/// Returns the color of every pixel in `rect` (clamped to the bounds of `target`), if they
+/// are all the same, or `None` if they differ or `rect` is empty.
+///
+/// The color is returned premultiplied, as stored; see `BitmapData::get_pixel32_raw`.
+/// Operations that treat each pixel independently can use this to process a single pixel
+/// and fill the region with the result.
+pub fn region_is_uniform(target: BitmapDataWrapper<'_>, mut rect: PixelRegion) -> Option<u32> {
+    rect.clamp(target.width(), target.height());
+    if rect.width() == 0 || rect.height() == 0 {
+        return None;
+    }
+
+    // Reading the pixels would allocate them just to compare them all to the fill.
+    if let Some(fill) = target.pending_fill(rect) {
+        return Some(fill.into());
+    }
+
+    let read = target.read_area(rect);
+    let first = read.get_pixel32_raw(rect.x_min, rect.y_min);
+    let columns = rect.x_min as usize..rect.x_max as usize;
+    read.pixels()
+        .chunks_exact(read.width() as usize)
+        .take(rect.y_max as usize)
+        .skip(rect.y_min as usize)
+        .all(|row| row[columns.clone()].iter().all(|pixel| *pixel == first))
+        .then(|| first.into())
+}
+
+/// Fills the whole of `target` with `color`.
+///
+/// The alpha of `color` is ignored for opaque bitmaps.
+pub fn clear<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    color: i32,
+) {
+    let (width, height) = (target.width() as i32, target.height() as i32);
+    fill_rect(context, target, 0, 0, width, height, color);
+}
+
 pub fn set_pixel32<'gc>(
     context: &mut UpdateContext<'_, 'gc>,
     target: BitmapDataWrapper<'gc>,
@@ -66,7 +122,7 @@ pub fn set_pixel32<'gc>(
     if x >= target.width() || y >= target.height() {
         return;
     }
-    let target = target.sync();
+    let target = target.sync_for_overwrite(PixelRegion::for_pixel(x, y));
     let mut write = target.write(context.gc_context);
     let transparency = write.transparency();
     write.set_pixel32_raw(
@@ -77,12 +133,82 @@ pub fn set_pixel32<'gc>(
     write.set_cpu_dirty(PixelRegion::for_pixel(x, y));
 }
 
+/// Returns the ARGB color of a pixel, or 0 if it is out of bounds.
+///
+/// Pixels of an opaque bitmap always have full alpha, whatever is held in storage.
 pub fn get_pixel32(target: BitmapDataWrapper, x: u32, y: u32) -> i32 {
     if x >= target.width() || y >= target.height() {
         return 0;
     }
-    let read = target.read_area(PixelRegion::for_pixel(x, y));
-    read.get_pixel32_raw(x, y).to_un_multiplied_alpha().into()
+    let pixel = target.read_pixel_raw(x, y);
+    if target.transparency() {
+        pixel.to_un_multiplied_alpha().into()
+    } else {
+        pixel.with_alpha(0xFF).into()
+    }
+}
+
+/// Returns the hue, saturation and value of a pixel, as in `Color::to_hsv`.
+///
+/// Like `get_pixel`, alpha is ignored and out of bounds pixels are black.
+pub fn get_pixel_hsv(target: BitmapDataWrapper, x: u32, y: u32) -> (f64, f64, f64) {
+    Color::from(get_pixel(target, x, y)).to_hsv()
+}
+
+/// Sets the color of a pixel from hue, saturation and value, as in `Color::from_hsv`.
+///
+/// Like `set_pixel`, the existing alpha of the pixel is kept.
+pub fn set_pixel_hsv<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    x: u32,
+    y: u32,
+    (hue, saturation, value): (f64, f64, f64),
+) {
+    let color = Color::from_hsv(0, hue, saturation, value);
+    set_pixel(context, target, x, y, color);
+}
+
+/// Calls `f` with each pixel of `target` within `rect`, clamped to its bounds.
+///
+/// The pixels are borrowed rather than copied, so this is suited to read-only analysis.
+/// Colors are passed in their premultiplied form.
+pub fn for_each_pixel_in_rect<F>(target: BitmapDataWrapper, rect: PixelRegion, mut f: F)
+where
+    F: FnMut(u32, u32, Color),
+{
+    for (x, y, color) in target.pixels_in_rect(rect) {
+        f(x, y, color);
+    }
+}
+
+/// Computes a stable 64-bit FNV-1a hash of the pixels of `target` within `rect`, clamped to
+/// its bounds.
+///
+/// This is not exposed to ActionScript, but makes it easy to pin down the exact output of
+/// other operations in tests. Pixels are hashed in row-major order in their stored
+/// (premultiplied) form, along with the size of the region and the transparency flag.
+pub fn checksum(target: BitmapDataWrapper, rect: PixelRegion) -> u64 {
+    const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+    const FNV_PRIME: u64 = 0x100000001b3;
+
+    let mut hash = FNV_OFFSET_BASIS;
+    let mut write = |bytes: &[u8]| {
+        for byte in bytes {
+            hash = (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME);
+        }
+    };
+
+    let mut region = rect;
+    region.clamp(target.width(), target.height());
+    write(&[u8::from(target.transparency())]);
+    write(&region.width().to_le_bytes());
+    write(&region.height().to_le_bytes());
+    for (_, _, color) in target.pixels_in_rect(region) {
+        write(&i32::from(color).to_le_bytes());
+    }
+
+    hash
 }
 
 pub fn set_pixel<'gc>(
@@ -105,15 +231,15 @@ pub fn set_pixel<'gc>(
     } else {
         write.set_pixel32_raw(x, y, color.with_alpha(0xFF));
     }
-    write.set_cpu_dirty(PixelRegion::for_whole_size(x, y));
+    write.set_cpu_dirty(PixelRegion::for_pixel(x, y));
 }
 
 pub fn get_pixel(target: BitmapDataWrapper, x: u32, y: u32) -> i32 {
     if x >= target.width() || y >= target.height() {
         return 0;
     }
-    let read = target.read_area(PixelRegion::for_pixel(x, y));
-    read.get_pixel32_raw(x, y)
+    target
+        .read_pixel_raw(x, y)
         .to_un_multiplied_alpha()
         .with_alpha(0x0)
         .into()
@@ -132,36 +258,61 @@ pub fn flood_fill<'gc>(
     x: u32,
     y: u32,
     color: i32,
+    max_pixels: Option<usize>,
 ) {
     if x >= target.width() || y >= target.height() {
         return;
     }
     let target = target.sync();
     let mut write = target.write(context.gc_context);
+    let mut remaining = max_pixels.unwrap_or(usize::MAX);
     let expected_color = write.get_pixel32_raw(x, y);
     let replace_color = Color::from(color).to_premultiplied_alpha(write.transparency());
+    if expected_color == replace_color {
+        return;
+    }
 
+    let (width, height) = (write.width(), write.height());
     let mut pending = vec![(x, y)];
     let mut dirty_region = PixelRegion::for_pixel(x, y);
 
-    while !pending.is_empty() {
-        if let Some((x, y)) = pending.pop() {
-            let old_color = write.get_pixel32_raw(x, y);
-            if old_color == expected_color {
-                if x > 0 {
-                    pending.push((x - 1, y));
-                }
-                if y > 0 {
-                    pending.push((x, y - 1));
-                }
-                if x < write.width() - 1 {
-                    pending.push((x + 1, y))
-                }
-                if y < write.height() - 1 {
-                    pending.push((x, y + 1));
+    // Scanline fill: extend each pending seed into the full horizontal run it's part of,
+    // fill that run, then queue one seed for every run touching it in the rows above and below.
+    // Once `max_pixels` have been filled we stop, leaving whatever has been filled so far.
+    while let Some((x, y)) = pending.pop() {
+        if remaining == 0 {
+            break;
+        }
+        if write.get_pixel32_raw(x, y) != expected_color {
+            continue;
+        }
+
+        let mut x_min = x;
+        while x_min > 0 && write.get_pixel32_raw(x_min - 1, y) == expected_color {
+            x_min -= 1;
+        }
+        let mut x_max = x + 1;
+        while x_max < width && write.get_pixel32_raw(x_max, y) == expected_color {
+            x_max += 1;
+        }
+
+        let run_length = (x_max - x_min).min(remaining.try_into().unwrap_or(u32::MAX));
+        for x in x_min..x_min + run_length {
+            write.set_pixel32_raw(x, y, replace_color);
+        }
+        remaining -= run_length as usize;
+        dirty_region.union(PixelRegion::for_region(x_min, y, run_length, 1));
+
+        let above = y.checked_sub(1);
+        let below = Some(y + 1).filter(|y| *y < height);
+        for y in [above, below].into_iter().flatten() {
+            let mut in_run = false;
+            for x in x_min..x_max {
+                let matches = write.get_pixel32_raw(x, y) == expected_color;
+                if matches && !in_run {
+                    pending.push((x, y));
                 }
-                write.set_pixel32_raw(x, y, replace_color);
-                dirty_region.encompass(x, y);
+                in_run = matches;
             }
         }
     }
@@ -180,25 +331,69 @@ pub fn noise<'gc>(
     let (target, _) = target.overwrite_cpu_pixels_from_gpu(context);
     let mut write = target.write(context.gc_context);
 
+    let parallel = write.width() as usize * write.height() as usize >= PARALLEL_PIXEL_THRESHOLD;
+    write_noise(
+        &mut write,
+        parallel,
+        seed,
+        low,
+        high,
+        channel_options,
+        gray_scale,
+    );
+
+    let region = PixelRegion::for_whole_size(write.width(), write.height());
+    write.set_cpu_dirty(region);
+}
+
+fn write_noise(
+    bitmap_data: &mut BitmapData,
+    parallel: bool,
+    seed: i32,
+    low: u8,
+    high: u8,
+    channel_options: ChannelOptions,
+    gray_scale: bool,
+) {
     let true_seed = if seed <= 0 {
         (-seed + 1) as u32
     } else {
         seed as u32
     };
 
-    let mut rng = LehmerRng::with_seed(true_seed);
-
-    for y in 0..write.height() {
-        for x in 0..write.width() {
-            let pixel_color = if gray_scale {
-                let gray = rng.gen_range(low..high);
-                let alpha = if channel_options.contains(ChannelOptions::ALPHA) {
-                    rng.gen_range(low..high)
+    // Every pixel consumes a fixed number of values from the generator, so the state
+    // at the start of any row can be found by skipping ahead, and rows filled independently.
+    let values_per_pixel = if gray_scale {
+        1 + channel_options.contains(ChannelOptions::ALPHA) as u64
+    } else {
+        channel_options.bits().count_ones() as u64
+    };
+    let width = bitmap_data.width();
+    let transparency = bitmap_data.transparency();
+
+    for_each_row(bitmap_data.pixels_mut(), width, parallel, |y, row| {
+        let mut rng = LehmerRng::with_seed(true_seed);
+        rng.skip(y as u64 * width as u64 * values_per_pixel);
+
+        // Opaque bitmaps stay opaque, but still draw the alpha value so that the
+        // colors match those of a transparent bitmap with the same seed.
+        let alpha = |rng: &mut LehmerRng| {
+            if channel_options.contains(ChannelOptions::ALPHA) {
+                let alpha = rng.gen_range(low..high);
+                if transparency {
+                    alpha
                 } else {
                     255
-                };
+                }
+            } else {
+                255
+            }
+        };
 
-                Color::argb(alpha, gray, gray, gray)
+        for pixel in row {
+            *pixel = if gray_scale {
+                let gray = rng.gen_range(low..high);
+                Color::argb(alpha(&mut rng), gray, gray, gray)
             } else {
                 let r = if channel_options.contains(ChannelOptions::RED) {
                     rng.gen_range(low..high)
@@ -218,20 +413,44 @@ pub fn noise<'gc>(
                     0
                 };
 
-                let a = if channel_options.contains(ChannelOptions::ALPHA) {
-                    rng.gen_range(low..high)
-                } else {
-                    255
-                };
-
-                Color::argb(a, r, g, b)
+                Color::argb(alpha(&mut rng), r, g, b)
             };
-
-            write.set_pixel32_raw(x, y, pixel_color);
         }
+    });
+}
+
+/// Bitmaps with at least this many pixels have their rows generated on a thread pool,
+/// where the platform supports it.
+const PARALLEL_PIXEL_THRESHOLD: usize = 256 * 256;
+
+/// Calls `f` with the index and pixels of every row of `pixels`.
+///
+/// If `parallel` is set, rows are processed on a thread pool. `f` only ever sees a single row,
+/// so the output is identical to the serial path as long as `f` is a pure function of its row.
+/// Targets without threads always use the serial path.
+fn for_each_row<F>(pixels: &mut [Color], width: u32, parallel: bool, f: F)
+where
+    F: Fn(u32, &mut [Color]) + Send + Sync,
+{
+    if width == 0 {
+        return;
+    }
+
+    #[cfg(not(target_family = "wasm"))]
+    if parallel {
+        use rayon::prelude::*;
+        pixels
+            .par_chunks_mut(width as usize)
+            .enumerate()
+            .for_each(|(y, row)| f(y as u32, row));
+        return;
+    }
+    #[cfg(target_family = "wasm")]
+    let _ = parallel;
+
+    for (y, row) in pixels.chunks_mut(width as usize).enumerate() {
+        f(y as u32, row);
     }
-    let region = PixelRegion::for_whole_size(write.width(), write.height());
-    write.set_cpu_dirty(region);
 }
 
 #[allow(clippy::too_many_arguments)]
@@ -250,100 +469,135 @@ pub fn perlin_noise<'gc>(
     let (target, _) = target.overwrite_cpu_pixels_from_gpu(context);
     let mut write = target.write(context.gc_context);
 
-    let turb = Turbulence::from_seed(random_seed);
+    let parallel = write.width() as usize * write.height() as usize >= PARALLEL_PIXEL_THRESHOLD;
+    write_perlin_noise(
+        &mut write,
+        parallel,
+        base,
+        num_octaves,
+        random_seed,
+        stitch,
+        fractal_noise,
+        channel_options,
+        grayscale,
+        &offsets,
+    );
+
+    let region = PixelRegion::for_whole_size(write.width(), write.height());
+    write.set_cpu_dirty(region);
+}
 
-    for y in 0..write.height() {
-        for x in 0..write.width() {
-            let px = x as f64;
-            let py = y as f64;
+#[allow(clippy::too_many_arguments)]
+fn write_perlin_noise(
+    bitmap_data: &mut BitmapData,
+    parallel: bool,
+    base: (f64, f64),
+    num_octaves: usize,
+    random_seed: i64,
+    stitch: bool,
+    fractal_noise: bool,
+    channel_options: ChannelOptions,
+    grayscale: bool,
+    offsets: &[(f64, f64)],
+) {
+    let turb = Turbulence::from_seed(random_seed);
+    let (width, height) = (bitmap_data.width(), bitmap_data.height());
+    let transparency = bitmap_data.transparency();
+
+    let pixel_at = |x: u32, y: u32| {
+        let px = x as f64;
+        let py = y as f64;
+
+        let mut noise = [0.0; 4];
+
+        // grayscale mode is different enough to warrant its own branch
+        if grayscale {
+            noise[0] = turb.turbulence(
+                0,
+                (px, py),
+                (1.0 / base.0, 1.0 / base.1),
+                num_octaves,
+                fractal_noise,
+                stitch,
+                (0.0, 0.0),
+                (width as f64, height as f64),
+                offsets,
+            );
 
-            let mut noise = [0.0; 4];
+            noise[1] = noise[0];
+            noise[2] = noise[0];
 
-            // grayscale mode is different enough to warrant its own branch
-            if grayscale {
-                noise[0] = turb.turbulence(
-                    0,
+            noise[3] = if channel_options.contains(ChannelOptions::ALPHA) {
+                turb.turbulence(
+                    1,
                     (px, py),
                     (1.0 / base.0, 1.0 / base.1),
                     num_octaves,
                     fractal_noise,
                     stitch,
                     (0.0, 0.0),
-                    (write.width() as f64, write.height() as f64),
-                    &offsets,
-                );
-
-                noise[1] = noise[0];
-                noise[2] = noise[0];
-
-                noise[3] = if channel_options.contains(ChannelOptions::ALPHA) {
-                    turb.turbulence(
-                        1,
+                    (width as f64, height as f64),
+                    offsets,
+                )
+            } else {
+                1.0
+            };
+        } else {
+            // Flash seems to pass the `color_channel` parameter to `turbulence`
+            // somewhat strangely. It's not always r=0, g=1, b=2, a=3; instead,
+            // it skips incrementing the parameter after channels that are
+            // not included in `channel_options`.
+            let mut channel = 0;
+
+            for (c, noise_c) in noise.iter_mut().enumerate() {
+                // this will work both in fractal_sum and turbulence "modes",
+                // because of the saturating conversion to u8
+                *noise_c = if c == 3 { 1.0 } else { -1.0 };
+
+                // `c` is always in 0..4, so `1 << c` is never actually truncated here
+                let c = ChannelOptions::from_bits_truncate(1 << c);
+                if channel_options.contains(c) {
+                    *noise_c = turb.turbulence(
+                        channel,
                         (px, py),
                         (1.0 / base.0, 1.0 / base.1),
                         num_octaves,
                         fractal_noise,
                         stitch,
                         (0.0, 0.0),
-                        (write.width() as f64, write.height() as f64),
-                        &offsets,
-                    )
-                } else {
-                    1.0
-                };
-            } else {
-                // Flash seems to pass the `color_channel` parameter to `turbulence`
-                // somewhat strangely. It's not always r=0, g=1, b=2, a=3; instead,
-                // it skips incrementing the parameter after channels that are
-                // not included in `channel_options`.
-                let mut channel = 0;
-
-                for (c, noise_c) in noise.iter_mut().enumerate() {
-                    // this will work both in fractal_sum and turbulence "modes",
-                    // because of the saturating conversion to u8
-                    *noise_c = if c == 3 { 1.0 } else { -1.0 };
-
-                    // `c` is always in 0..4, so `1 << c` is never actually truncated here
-                    let c = ChannelOptions::from_bits_truncate(1 << c);
-                    if channel_options.contains(c) {
-                        *noise_c = turb.turbulence(
-                            channel,
-                            (px, py),
-                            (1.0 / base.0, 1.0 / base.1),
-                            num_octaves,
-                            fractal_noise,
-                            stitch,
-                            (0.0, 0.0),
-                            (write.width() as f64, write.height() as f64),
-                            &offsets,
-                        );
-                        channel += 1;
-                    }
+                        (width as f64, height as f64),
+                        offsets,
+                    );
+                    channel += 1;
                 }
             }
+        }
 
-            let mut color = [0_u8; 4];
-            for chan in 0..4 {
-                // This is precisely how Adobe Flash converts the -1..1 or 0..1 floats to u8.
-                // Please don't touch, it was difficult to figure out the exact method. :)
-                color[chan] = (if fractal_noise {
-                    // Yes, the + 0.5 for correct (nearest) rounding is done before the division by 2.0,
-                    // making it technically less correct (I think), but this is how it is!
-                    ((noise[chan] * 255.0 + 255.0) + 0.5) / 2.0
-                } else {
-                    (noise[chan] * 255.0) + 0.5
-                }) as u8;
-            }
+        let mut color = [0_u8; 4];
+        for chan in 0..4 {
+            // This is precisely how Adobe Flash converts the -1..1 or 0..1 floats to u8.
+            // Please don't touch, it was difficult to figure out the exact method. :)
+            color[chan] = (if fractal_noise {
+                // Yes, the + 0.5 for correct (nearest) rounding is done before the division by 2.0,
+                // making it technically less correct (I think), but this is how it is!
+                ((noise[chan] * 255.0 + 255.0) + 0.5) / 2.0
+            } else {
+                (noise[chan] * 255.0) + 0.5
+            }) as u8;
+        }
 
-            if !write.transparency() {
-                color[3] = 255;
-            }
+        if !transparency {
+            color[3] = 255;
+        }
+
+        Color::argb(color[3], color[0], color[1], color[2])
+    };
 
-            write.set_pixel32_raw(x, y, Color::argb(color[3], color[0], color[1], color[2]));
+    for_each_row(bitmap_data.pixels_mut(), width, parallel, |y, row| {
+        for (x, pixel) in row.iter_mut().enumerate() {
+            *pixel = pixel_at(x as u32, y);
         }
-    }
-    let region = PixelRegion::for_whole_size(write.width(), write.height());
-    write.set_cpu_dirty(region);
+    });
 }
 
 pub fn copy_channel<'gc>(
@@ -459,25 +713,52 @@ pub fn color_transform<'gc>(
     let x_max = x_max.min(target.width());
     let y_max = y_max.min(target.height());
 
-    if x_max == 0 || y_max == 0 {
+    if x_min >= x_max || y_min >= y_max {
         return;
     }
 
+    // Every channel is transformed independently of the others, so the 8.8
+    // fixed-point math only has to be done once per possible channel value.
+    let mut tables = [[0u8; 256]; 4];
+    for value in 0..=255u8 {
+        let color = color_transform
+            * swf::Color {
+                r: value,
+                g: value,
+                b: value,
+                // Fully transparent pixels are never transformed, see below.
+                a: value.max(1),
+            };
+        tables[0][usize::from(value)] = color.r;
+        tables[1][usize::from(value)] = color.g;
+        tables[2][usize::from(value)] = color.b;
+        tables[3][usize::from(value)] = color.a;
+    }
+
     let target = target.sync();
     let mut write = target.write(context.gc_context);
     let transparency = write.transparency();
-
-    for x in x_min..x_max {
-        for y in y_min..y_max {
-            let color = write.get_pixel32_raw(x, y).to_un_multiplied_alpha();
-
-            let color = color_transform * swf::Color::from(color);
-
-            write.set_pixel32_raw(
-                x,
-                y,
-                Color::from(color).to_premultiplied_alpha(transparency),
-            )
+    let width = write.width() as usize;
+
+    let rows = write
+        .pixels_mut()
+        .chunks_exact_mut(width)
+        .skip(y_min as usize)
+        .take((y_max - y_min) as usize);
+    for row in rows {
+        for pixel in &mut row[x_min as usize..x_max as usize] {
+            let color = pixel.to_un_multiplied_alpha();
+            let color = if color.alpha() == 0 {
+                color
+            } else {
+                Color::argb(
+                    tables[3][usize::from(color.alpha())],
+                    tables[0][usize::from(color.red())],
+                    tables[1][usize::from(color.green())],
+                    tables[2][usize::from(color.blue())],
+                )
+            };
+            *pixel = color.to_premultiplied_alpha(transparency);
         }
     }
     write.set_cpu_dirty(PixelRegion::encompassing_pixels(
@@ -499,18 +780,18 @@ pub fn threshold<'gc>(
     mask: u32,
     copy_source: bool,
 ) -> u32 {
-    // Pre-compute the masked threshold
-    let masked_threshold = threshold & mask;
+    let test = ThresholdTest {
+        operation,
+        // Pre-compute the masked threshold
+        masked_threshold: threshold & mask,
+        mask,
+        colour: Color::from(colour),
+        copy_source,
+    };
 
-    // Extract coords
     let (src_min_x, src_min_y, src_width, src_height) = src_rect;
     let (dest_min_x, dest_min_y) = dest_point;
 
-    // The number of modified pixels
-    // This doesn't seem to include pixels changed due to copy_source
-    let mut modified_count = 0;
-    let mut dirty_area: Option<PixelRegion> = None;
-
     let mut source_region =
         PixelRegion::for_region_i32(src_min_x, src_min_y, src_width, src_height);
     source_region.clamp(source_bitmap.width(), source_bitmap.height());
@@ -523,6 +804,53 @@ pub fn threshold<'gc>(
     let target = target.sync();
     let mut write = target.write(context.gc_context);
 
+    // When thresholding a bitmap onto an overlapping part of itself, later pixels are
+    // tested against the results for earlier ones, so they have to be done one at a time.
+    let mut dest_region =
+        PixelRegion::for_region_i32(dest_min_x, dest_min_y, src_width, src_height);
+    dest_region.clamp(write.width(), write.height());
+    let (modified_count, dirty_area) = if source.is_none() && dest_region.intersects(source_region)
+    {
+        threshold_pixelwise(&mut write, None, src_rect, dest_point, test)
+    } else {
+        threshold_chunked(&mut write, source.as_deref(), src_rect, dest_point, test)
+    };
+
+    if let Some(dirty_area) = dirty_area {
+        write.set_cpu_dirty(dirty_area);
+    }
+
+    modified_count
+}
+
+/// The test `threshold` performs on each pixel, and what it replaces the pixel with.
+#[derive(Clone, Copy)]
+struct ThresholdTest {
+    operation: ThresholdOperation,
+    masked_threshold: u32,
+    mask: u32,
+    colour: Color,
+    copy_source: bool,
+}
+
+/// Applies `test` to `src_rect` of `source` (or `target` itself, if `None`) one pixel at a time.
+///
+/// Returns the number of pixels that passed, and the area of `target` that was written to.
+fn threshold_pixelwise(
+    write: &mut BitmapData,
+    source: Option<&BitmapData>,
+    src_rect: (i32, i32, i32, i32),
+    dest_point: (i32, i32),
+    test: ThresholdTest,
+) -> (u32, Option<PixelRegion>) {
+    let (src_min_x, src_min_y, src_width, src_height) = src_rect;
+    let (dest_min_x, dest_min_y) = dest_point;
+
+    // The number of modified pixels
+    // This doesn't seem to include pixels changed due to copy_source
+    let mut modified_count = 0;
+    let mut dirty_area: Option<PixelRegion> = None;
+
     // Check each pixel
     for src_y in src_min_y..(src_min_y + src_height) {
         for src_x in src_min_x..(src_min_x + src_width) {
@@ -551,12 +879,15 @@ pub fn threshold<'gc>(
             };
 
             // If the test, as defined by the operation pass then set to input colour
-            if operation.matches(i32::from(source_color) as u32 & mask, masked_threshold) {
+            if test.operation.matches(
+                i32::from(source_color) as u32 & test.mask,
+                test.masked_threshold,
+            ) {
                 modified_count += 1;
-                write.set_pixel32_raw(dest_x as u32, dest_y as u32, Color::from(colour));
+                write.set_pixel32_raw(dest_x as u32, dest_y as u32, test.colour);
             } else {
                 // If the test fails, but copy_source is true then take the colour from the source
-                if copy_source {
+                if test.copy_source {
                     let new_color = if let Some(source) = &source {
                         source
                             .get_pixel32_raw(dest_x as u32, dest_y as u32)
@@ -578,85 +909,297 @@ pub fn threshold<'gc>(
         }
     }
 
-    if let Some(dirty_area) = dirty_area {
-        write.set_cpu_dirty(dirty_area);
-    }
-
-    modified_count
+    (modified_count, dirty_area)
 }
 
-pub fn scroll<'gc>(
-    context: &mut UpdateContext<'_, 'gc>,
-    target: BitmapDataWrapper<'gc>,
-    x: i32,
-    y: i32,
-) {
-    let width = target.width() as i32;
-    let height = target.height() as i32;
-
-    if (x == 0 && y == 0) || x.abs() >= width || y.abs() >= height {
-        return; // no-op
-    }
-
-    // since this is an "in-place copy", we have to iterate from bottom to top
-    // when scrolling downwards - so if y is positive
-    let reverse_y = y > 0;
-    // and if only scrolling horizontally, we have to iterate from right to left
-    // when scrolling right - so if x is positive
-    let reverse_x = y == 0 && x > 0;
-
-    // iteration ranges to use as source for the copy, from is inclusive, to is exclusive
-    let y_from = if reverse_y { height - y - 1 } else { -y };
-    let y_to = if reverse_y { -1 } else { height };
-    let dy = if reverse_y { -1 } else { 1 };
+/// Applies `test` like `threshold_pixelwise`, but a row at a time in fixed-size chunks, which
+/// the compiler can vectorize.
+///
+/// If `source` is `None`, `src_rect` must not overlap the destination.
+fn threshold_chunked(
+    write: &mut BitmapData,
+    source: Option<&BitmapData>,
+    src_rect: (i32, i32, i32, i32),
+    dest_point: (i32, i32),
+    test: ThresholdTest,
+) -> (u32, Option<PixelRegion>) {
+    const LANES: usize = 8;
 
-    let x_from = if reverse_x {
-        // we know x > 0
-        width - x - 1
-    } else {
-        // x can be any sign
-        (-x).max(0)
+    let source_size = source.map_or((write.width(), write.height()), |source| {
+        (source.width(), source.height())
+    });
+    let Some((columns, rows, (dx, dy))) = clip_copy(
+        src_rect,
+        dest_point,
+        source_size,
+        (write.width(), write.height()),
+    ) else {
+        return (0, None);
     };
-    let x_to = if reverse_x { -1 } else { width.min(width - x) };
-    let dx = if reverse_x { -1 } else { 1 };
-
-    let target = target.sync();
-    let mut write = target.write(context.gc_context);
 
-    let mut src_y = y_from;
-    while src_y != y_to {
-        let mut src_x = x_from;
-        while src_x != x_to {
-            let color = write.get_pixel32_raw(src_x as u32, src_y as u32);
-            write.set_pixel32_raw((src_x + x) as u32, (src_y + y) as u32, color);
-            src_x += dx;
+    let mut modified_count = 0;
+    for y in rows.clone() {
+        let dest_y = (y + dy) as u32;
+        let mut x = columns.start;
+        while x < columns.end {
+            let len = LANES.min((columns.end - x) as usize);
+
+            let mut values = [0; LANES];
+            let source_row = source.unwrap_or(&*write).pixels();
+            let start = (y * source_size.0 as i64 + x) as usize;
+            for (value, color) in values.iter_mut().zip(&source_row[start..start + len]) {
+                *value = i32::from(color.to_un_multiplied_alpha()) as u32 & test.mask;
+            }
+            let passed = threshold_lanes(test.operation, &values, test.masked_threshold);
+
+            for (i, passed) in passed.into_iter().take(len).enumerate() {
+                let dest_x = (x + dx) as u32 + i as u32;
+                if passed {
+                    modified_count += 1;
+                    write.set_pixel32_raw(dest_x, dest_y, test.colour);
+                } else if test.copy_source {
+                    let new_color = source
+                        .unwrap_or(&*write)
+                        .get_pixel32_raw(dest_x, dest_y)
+                        .to_un_multiplied_alpha();
+                    write.set_pixel32_raw(dest_x, dest_y, new_color);
+                }
+            }
+            x += len as i64;
         }
-        src_y += dy;
     }
 
-    let region = PixelRegion::for_whole_size(write.width(), write.height());
-    write.set_cpu_dirty(region);
+    let dirty_area = PixelRegion::for_region(
+        (columns.start + dx) as u32,
+        (rows.start + dy) as u32,
+        (columns.end - columns.start) as u32,
+        (rows.end - rows.start) as u32,
+    );
+    (modified_count, Some(dirty_area))
 }
 
-pub fn palette_map<'gc>(
-    context: &mut UpdateContext<'_, 'gc>,
-    target: BitmapDataWrapper<'gc>,
-    source_bitmap: BitmapDataWrapper<'gc>,
+/// Compares every lane of `values` against `masked_threshold`.
+///
+/// The operation is matched once for the whole chunk, so each arm is a simple loop over a
+/// fixed-size array.
+fn threshold_lanes<const N: usize>(
+    operation: ThresholdOperation,
+    values: &[u32; N],
+    masked_threshold: u32,
+) -> [bool; N] {
+    match operation {
+        ThresholdOperation::Equals => values.map(|v| v == masked_threshold),
+        ThresholdOperation::NotEquals => values.map(|v| v != masked_threshold),
+        ThresholdOperation::LessThan => values.map(|v| v < masked_threshold),
+        ThresholdOperation::LessThanOrEquals => values.map(|v| v <= masked_threshold),
+        ThresholdOperation::GreaterThan => values.map(|v| v > masked_threshold),
+        ThresholdOperation::GreaterThanOrEquals => values.map(|v| v >= masked_threshold),
+    }
+}
+
+/// Clips a copy of `src_rect` in a bitmap of `source_size` to `dest_point` in a bitmap of
+/// `target_size`.
+///
+/// Returns the source columns and rows that lie within both bitmaps once copied, along with
+/// the offset from each source pixel to its destination, or `None` if nothing is copied.
+fn clip_copy(
     src_rect: (i32, i32, i32, i32),
     dest_point: (i32, i32),
-    channel_arrays: ([u32; 256], [u32; 256], [u32; 256], [u32; 256]),
-) {
+    source_size: (u32, u32),
+    target_size: (u32, u32),
+) -> Option<(Range<i64>, Range<i64>, (i64, i64))> {
     let (src_min_x, src_min_y, src_width, src_height) = src_rect;
-    let (dest_min_x, dest_min_y) = dest_point;
+    let dx = dest_point.0 as i64 - src_min_x as i64;
+    let dy = dest_point.1 as i64 - src_min_y as i64;
+
+    let x_min = (src_min_x as i64).max(0).max(-dx);
+    let x_max = (src_min_x as i64 + src_width as i64)
+        .min(source_size.0 as i64)
+        .min(target_size.0 as i64 - dx);
+    let y_min = (src_min_y as i64).max(0).max(-dy);
+    let y_max = (src_min_y as i64 + src_height as i64)
+        .min(source_size.1 as i64)
+        .min(target_size.1 as i64 - dy);
+
+    (x_min < x_max && y_min < y_max).then_some((x_min..x_max, y_min..y_max, (dx, dy)))
+}
 
-    let mut source_region =
-        PixelRegion::for_region_i32(src_min_x, src_min_y, src_width, src_height);
-    source_region.clamp(source_bitmap.width(), source_bitmap.height());
-    let source = if source_bitmap.ptr_eq(target) {
-        None
-    } else {
-        Some(source_bitmap.read_area(source_region))
-    };
+/// Feedback masks for maximal-length Galois LFSRs, indexed by register width.
+///
+/// These are the masks of the "digital dissolve" from Graphics Gems, which Flash uses to pick
+/// the order of `pixelDissolve`.
+const DISSOLVE_LFSR_MASKS: [u32; 33] = [
+    0, 0, 0x3, 0x6, 0xC, 0x14, 0x30, 0x60, 0xB8, 0x110, 0x240, 0x500, 0xCA0, 0x1B00, 0x3500,
+    0x6000, 0xB400, 0x12000, 0x20400, 0x72000, 0x90000, 0x140000, 0x300000, 0x420000, 0xD80000,
+    0x1200000, 0x3880000, 0x7200000, 0x9000000, 0x14000000, 0x32800000, 0x48000000, 0xA3000000,
+];
+
+/// Dissolves `num_pixels` pixels of `src_rect`, placed at `dest_point`, into `target`.
+///
+/// Dissolved pixels are copied from `source`, or set to `fill_color` when there is no source
+/// (or the source is the target itself). Pixels are visited in a pseudo-random order driven by
+/// `random_seed` that covers every pixel of the region exactly once before repeating, and which
+/// only depends on the seed and the region size. The returned value is the seed to pass to the
+/// next call to continue the dissolve.
+#[allow(clippy::too_many_arguments)]
+pub fn pixel_dissolve<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    source: Option<BitmapDataWrapper<'gc>>,
+    src_rect: (i32, i32, i32, i32),
+    dest_point: (i32, i32),
+    random_seed: i32,
+    num_pixels: i32,
+    fill_color: u32,
+) -> i32 {
+    let source = source.filter(|source| !source.ptr_eq(target));
+    let source_size = source.map_or((target.width(), target.height()), |source| {
+        (source.width(), source.height())
+    });
+    let Some((x_range, y_range, (dx, dy))) = clip_copy(
+        src_rect,
+        dest_point,
+        source_size,
+        (target.width(), target.height()),
+    ) else {
+        return random_seed;
+    };
+    if num_pixels <= 0 {
+        return random_seed;
+    }
+
+    let width = (x_range.end - x_range.start) as u32;
+    let height = (y_range.end - y_range.start) as u32;
+    let area = width * height;
+    // The register holds a column in its low bits and a row above them, each wide enough to
+    // represent the full width or height, so the all-ones value never lands in the region.
+    let column_bits = u32::BITS - width.leading_zeros();
+    let bits = (column_bits + u32::BITS - height.leading_zeros()).max(2);
+    let feedback = DISSOLVE_LFSR_MASKS[bits as usize];
+    let mut register = (random_seed as u32 & (u32::MAX >> (u32::BITS - bits))).max(1);
+
+    let source = source.map(|source| {
+        source.read_area(PixelRegion::for_region(
+            x_range.start as u32,
+            y_range.start as u32,
+            width,
+            height,
+        ))
+    });
+    let target = target.sync();
+    let mut write = target.write(context.gc_context);
+    let transparency = write.transparency();
+    let fill_color = Color::from(fill_color as i32).to_premultiplied_alpha(transparency);
+
+    for _ in 0..(num_pixels as u32).min(area) {
+        // The register cycles through every value in `1..2^bits`; the ones outside of the
+        // region are skipped.
+        let (column, row) = loop {
+            register = if register & 1 == 1 {
+                (register >> 1) ^ feedback
+            } else {
+                register >> 1
+            };
+            let index = register - 1;
+            let (column, row) = (index & ((1 << column_bits) - 1), index >> column_bits);
+            if column < width && row < height {
+                break (column, row);
+            }
+        };
+
+        let x = x_range.start + column as i64;
+        let y = y_range.start + row as i64;
+        let color = match &source {
+            Some(source) => {
+                let color = source.get_pixel32_raw(x as u32, y as u32);
+                if transparency {
+                    color
+                } else {
+                    color.with_alpha(0xFF)
+                }
+            }
+            None => fill_color,
+        };
+        write.set_pixel32_raw((x + dx) as u32, (y + dy) as u32, color);
+    }
+
+    write.set_cpu_dirty(PixelRegion::for_region(
+        (x_range.start + dx) as u32,
+        (y_range.start + dy) as u32,
+        width,
+        height,
+    ));
+
+    register as i32
+}
+
+pub fn scroll<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    x: i32,
+    y: i32,
+) {
+    let width = target.width() as i32;
+    let height = target.height() as i32;
+
+    if (x == 0 && y == 0) || x.abs() >= width || y.abs() >= height {
+        return; // no-op
+    }
+
+    // Pixels that nothing is scrolled onto are left as they are.
+    let row_len = (width - x.abs()) as usize;
+    let (src_x, dest_x) = if x > 0 { (0, x) } else { (-x, 0) };
+    let (dest_y_min, dest_y_max) = if y > 0 { (y, height) } else { (0, height + y) };
+
+    let target = target.sync();
+    let mut write = target.write(context.gc_context);
+    let pixels = write.pixels_mut();
+    let mut move_row = |dest_y: i32| {
+        let src = ((dest_y - y) * width + src_x) as usize;
+        let dest = (dest_y * width + dest_x) as usize;
+        pixels.copy_within(src..src + row_len, dest);
+    };
+
+    // This is an in-place move, so when scrolling downwards rows have to be moved
+    // starting from the bottom, so that they're read before being overwritten.
+    // `copy_within` takes care of any overlap within a row.
+    if y > 0 {
+        (dest_y_min..dest_y_max).rev().for_each(&mut move_row);
+    } else {
+        (dest_y_min..dest_y_max).for_each(&mut move_row);
+    }
+
+    let region =
+        PixelRegion::for_region_i32(dest_x, dest_y_min, row_len as i32, dest_y_max - dest_y_min);
+    write.set_cpu_dirty(region);
+}
+
+pub fn palette_map<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    source_bitmap: BitmapDataWrapper<'gc>,
+    src_rect: (i32, i32, i32, i32),
+    dest_point: (i32, i32),
+    channel_arrays: ([u32; 256], [u32; 256], [u32; 256], [u32; 256]),
+) {
+    let (src_min_x, src_min_y, src_width, src_height) = src_rect;
+    let (dest_min_x, dest_min_y) = dest_point;
+
+    let mut source_region =
+        PixelRegion::for_region_i32(src_min_x, src_min_y, src_width, src_height);
+    source_region.clamp(source_bitmap.width(), source_bitmap.height());
+    let source = if source_bitmap.ptr_eq(target) {
+        None
+    } else {
+        Some(source_bitmap.read_area(source_region))
+    };
+
+    let channels = [
+        PaletteChannel::new(&channel_arrays.0, 16),
+        PaletteChannel::new(&channel_arrays.1, 8),
+        PaletteChannel::new(&channel_arrays.2, 0),
+        PaletteChannel::new(&channel_arrays.3, 24),
+    ];
 
     let target = target.sync();
     let mut write = target.write(context.gc_context);
@@ -683,13 +1226,21 @@ pub fn palette_map<'gc>(
                     .to_un_multiplied_alpha()
             };
 
-            let r = channel_arrays.0[source_color.red() as usize];
-            let g = channel_arrays.1[source_color.green() as usize];
-            let b = channel_arrays.2[source_color.blue() as usize];
-            let a = channel_arrays.3[source_color.alpha() as usize];
-
-            let sum = u32::wrapping_add(u32::wrapping_add(r, g), u32::wrapping_add(b, a));
-            let mix_color = Color::from(sum as i32).to_premultiplied_alpha(true);
+            // Each entry is an ARGB color; their channels are summed separately, saturating
+            // at 255, so one channel overflowing doesn't carry into the next.
+            let bytes = [
+                source_color.red(),
+                source_color.green(),
+                source_color.blue(),
+                source_color.alpha(),
+            ];
+            let sum: u64 = channels
+                .iter()
+                .zip(bytes)
+                .map(|(channel, byte)| channel.lookup(byte))
+                .sum();
+            let mix_color =
+                Color::from(PaletteChannel::saturate(sum) as i32).to_premultiplied_alpha(true);
 
             write.set_pixel32_raw(dest_x as u32, dest_y as u32, mix_color);
         }
@@ -702,6 +1253,56 @@ pub fn palette_map<'gc>(
     write.set_cpu_dirty(dirty_region);
 }
 
+/// One of the channel arrays of a `paletteMap` call, prepared for summing.
+///
+/// The bytes of each ARGB entry are widened into separate 16-bit lanes of a `u64`, so that
+/// the entries for all four channels can be added together without one channel carrying
+/// into the next.
+enum PaletteChannel {
+    /// The array maps each value to itself in the channel at this shift, which is what's
+    /// used when no array was given for the channel.
+    Identity { shift: u32 },
+
+    /// Any other mapping.
+    Mapped(Box<[u64; 256]>),
+}
+
+impl PaletteChannel {
+    fn new(array: &[u32; 256], shift: u32) -> Self {
+        if array
+            .iter()
+            .enumerate()
+            .all(|(i, entry)| *entry == (i as u32) << shift)
+        {
+            Self::Identity { shift }
+        } else {
+            Self::Mapped(Box::new(array.map(Self::widen)))
+        }
+    }
+
+    /// Spreads the bytes of an ARGB color into 16-bit lanes.
+    fn widen(argb: u32) -> u64 {
+        (0..4).fold(0, |lanes, i| {
+            lanes | (((argb >> (i * 8)) & 0xFF) as u64) << (i * 16)
+        })
+    }
+
+    /// Packs summed lanes back into an ARGB color, saturating each channel at 255.
+    fn saturate(lanes: u64) -> u32 {
+        (0..4).fold(0, |argb, i| {
+            argb | (((lanes >> (i * 16)) & 0xFFFF).min(0xFF) as u32) << (i * 8)
+        })
+    }
+
+    fn lookup(&self, value: u8) -> u64 {
+        match self {
+            // Identity arrays skip the table: the value just goes into its own lane.
+            Self::Identity { shift } => (value as u64) << (shift * 2),
+            Self::Mapped(table) => table[value as usize],
+        }
+    }
+}
+
 /// Compare two BitmapData objects.
 /// Returns `None` if the bitmaps are equivalent.
 pub fn compare<'gc>(
@@ -709,7 +1310,7 @@ pub fn compare<'gc>(
     right: BitmapDataWrapper<'gc>,
 ) -> Option<BitmapData<'gc>> {
     // This function expects that the two bitmaps have the same dimensions.
-    // TODO: Relax this assumption and return a special value instead?
+    // Use `compare_region` to compare bitmaps of different sizes.
     debug_assert_eq!(left.width(), right.width());
     debug_assert_eq!(left.height(), right.height());
 
@@ -718,44 +1319,99 @@ pub fn compare<'gc>(
     let right = right.sync();
     let right = right.read();
 
-    let mut different = false;
-    let pixels = left
+    // Identical buffers are by far the most common outcome, and can be spotted without
+    // looking at individual pixels.
+    if left.pixels() == right.pixels() {
+        return None;
+    }
+
+    // Only allocate the difference bitmap once a pixel that actually differs is found;
+    // distinct premultiplied values may still un-multiply to the same color.
+    let mut differences = left
         .pixels()
         .iter()
         .zip(right.pixels())
-        .map(|(bitmap_pixel, other_pixel)| {
-            let bitmap_pixel = bitmap_pixel.to_un_multiplied_alpha();
-            let other_pixel = other_pixel.to_un_multiplied_alpha();
-            if bitmap_pixel == other_pixel {
-                Color::argb(0, 0, 0, 0)
-            } else if bitmap_pixel.with_alpha(0) != other_pixel.with_alpha(0) {
-                different = true;
-                Color::argb(
-                    0xff,
-                    bitmap_pixel.red().wrapping_sub(other_pixel.red()),
-                    bitmap_pixel.green().wrapping_sub(other_pixel.green()),
-                    bitmap_pixel.blue().wrapping_sub(other_pixel.blue()),
-                )
-            } else {
-                different = true;
-                let alpha = bitmap_pixel.alpha().wrapping_sub(other_pixel.alpha());
-                Color::argb(alpha, alpha, alpha, alpha)
-            }
-        })
-        .collect();
+        .map(|(bitmap_pixel, other_pixel)| compare_pixel(*bitmap_pixel, *other_pixel))
+        .enumerate();
+    let (first_index, first_diff) =
+        differences.find_map(|(index, diff)| diff.map(|diff| (index, diff)))?;
+
+    let mut pixels = Vec::with_capacity(left.pixels().len());
+    pixels.resize(first_index, Color::argb(0, 0, 0, 0));
+    pixels.push(first_diff);
+    pixels.extend(differences.map(|(_, diff)| diff.unwrap_or_else(|| Color::argb(0, 0, 0, 0))));
+
+    Some(BitmapData::new_with_pixels(
+        left.width(),
+        left.height(),
+        true,
+        pixels,
+    ))
+}
+
+/// Compare the overlapping top-left region of two BitmapData objects, which
+/// may have different sizes.
+///
+/// This is not exposed to ActionScript (`BitmapData.compare` rejects bitmaps
+/// of different sizes), but is handy for diffing renders while debugging.
+/// Returns `None` if the overlapping regions are equivalent.
+pub fn compare_region<'gc>(
+    left: BitmapDataWrapper<'gc>,
+    right: BitmapDataWrapper<'gc>,
+) -> Option<BitmapData<'gc>> {
+    let width = left.width().min(right.width());
+    let height = left.height().min(right.height());
+
+    let left = left.sync();
+    let left = left.read();
+    let right = right.sync();
+    let right = right.read();
+
+    let mut different = false;
+    let mut pixels = Vec::with_capacity(width as usize * height as usize);
+    for y in 0..height {
+        for x in 0..width {
+            let diff = compare_pixel(left.get_pixel32_raw(x, y), right.get_pixel32_raw(x, y));
+            different |= diff.is_some();
+            pixels.push(diff.unwrap_or_else(|| Color::argb(0, 0, 0, 0)));
+        }
+    }
 
     if different {
-        Some(BitmapData::new_with_pixels(
-            left.width(),
-            left.height(),
-            true,
-            pixels,
-        ))
+        Some(BitmapData::new_with_pixels(width, height, true, pixels))
     } else {
         None
     }
 }
 
+/// Computes the difference between two premultiplied pixels, as used by
+/// `BitmapData.compare`. Returns `None` if the pixels are equal.
+///
+/// This is decided for each pixel on its own. If the colors differ, the result is opaque
+/// and holds the (wrapping) difference of each color channel, ignoring alpha. If only the
+/// alpha differs, the result is white (`0xZZFFFFFF`), with the difference of the alphas
+/// as its alpha.
+fn compare_pixel(bitmap_pixel: Color, other_pixel: Color) -> Option<Color> {
+    let bitmap_pixel = bitmap_pixel.to_un_multiplied_alpha();
+    let other_pixel = other_pixel.to_un_multiplied_alpha();
+    if bitmap_pixel == other_pixel {
+        None
+    } else if bitmap_pixel.with_alpha(0) != other_pixel.with_alpha(0) {
+        Some(Color::argb(
+            0xff,
+            bitmap_pixel.red().wrapping_sub(other_pixel.red()),
+            bitmap_pixel.green().wrapping_sub(other_pixel.green()),
+            bitmap_pixel.blue().wrapping_sub(other_pixel.blue()),
+        ))
+    } else {
+        let alpha = bitmap_pixel.alpha().wrapping_sub(other_pixel.alpha());
+        Some(Color::argb(alpha, alpha, alpha, alpha))
+    }
+}
+
+// Note that Flash treats the alpha threshold as the smallest opaque alpha value,
+// so a pixel with exactly the threshold alpha counts as a hit.
+
 pub fn hit_test_point(
     target: BitmapDataWrapper,
     alpha_threshold: u32,
@@ -782,16 +1438,21 @@ pub fn hit_test_rectangle(
 ) -> bool {
     let mut region = PixelRegion::for_region_i32(top_left.0, top_left.1, size.0, size.1);
     region.clamp(target.width(), target.height());
-    let read = target.read_area(region);
-
-    for x in region.x_min..region.x_max {
-        for y in region.y_min..region.y_max {
-            if read.get_pixel32_raw(x, y).alpha() as u32 >= alpha_threshold {
-                return true;
-            }
-        }
+    if region.width() == 0 || region.height() == 0 {
+        return false;
     }
-    false
+
+    let read = target.read_area(region);
+    let x_range = region.x_min as usize..region.x_max as usize;
+    read.pixels()
+        .chunks_exact(read.width() as usize)
+        .skip(region.y_min as usize)
+        .take(region.height() as usize)
+        .any(|row| {
+            row[x_range.clone()]
+                .iter()
+                .any(|pixel| pixel.alpha() as u32 >= alpha_threshold)
+        })
 }
 
 pub fn hit_test_bitmapdata<'gc>(
@@ -802,50 +1463,49 @@ pub fn hit_test_bitmapdata<'gc>(
     test_point: (i32, i32),
     test_threshold: u32,
 ) -> bool {
-    let xd = test_point.0 - self_point.0;
-    let yd = test_point.1 - self_point.1;
-    let self_width = target.width() as i32;
-    let self_height = target.height() as i32;
-    let test_width = test.width() as i32;
-    let test_height = test.height() as i32;
-    let (self_x0, test_x0, width) = if xd < 0 {
-        (
-            0,
-            (-xd) as u32,
-            self_width.min(test_width + xd).max(0) as u32,
-        )
-    } else {
-        (xd as u32, 0, test_width.min(self_width - xd).max(0) as u32)
-    };
-    let (self_y0, test_y0, height) = if yd < 0 {
-        (
-            0,
-            (-yd) as u32,
-            self_height.min(test_height + yd).max(0) as u32,
-        )
-    } else {
-        (
-            yd as u32,
-            0,
-            test_height.min(self_height - yd).max(0) as u32,
-        )
-    };
+    // Find where the two bitmaps overlap, in the coordinates of each of them.
+    let xd = test_point.0 as i64 - self_point.0 as i64;
+    let yd = test_point.1 as i64 - self_point.1 as i64;
+    let x_min = xd.max(0);
+    let y_min = yd.max(0);
+    let x_max = (target.width() as i64).min(test.width() as i64 + xd);
+    let y_max = (target.height() as i64).min(test.height() as i64 + yd);
+    if x_min >= x_max || y_min >= y_max {
+        return false;
+    }
 
-    let target = target.read_area(PixelRegion::for_region(self_x0, self_y0, width, height));
-    let test = test.read_area(PixelRegion::for_region(test_x0, test_y0, width, height));
+    let (width, height) = ((x_max - x_min) as usize, (y_max - y_min) as usize);
+    let (self_x0, self_y0) = (x_min as usize, y_min as usize);
+    let (test_x0, test_y0) = ((x_min - xd) as usize, (y_min - yd) as usize);
 
-    for x in 0..width {
-        for y in 0..height {
-            let self_is_opaque =
-                target.get_pixel32_raw(self_x0 + x, self_y0 + y).alpha() as u32 >= self_threshold;
-            let test_is_opaque =
-                test.get_pixel32_raw(test_x0 + x, test_y0 + y).alpha() as u32 >= test_threshold;
-            if self_is_opaque && test_is_opaque {
-                return true;
-            }
-        }
-    }
-    false
+    let target = target.read_area(PixelRegion::for_region(
+        self_x0 as u32,
+        self_y0 as u32,
+        width as u32,
+        height as u32,
+    ));
+    let test = test.read_area(PixelRegion::for_region(
+        test_x0 as u32,
+        test_y0 as u32,
+        width as u32,
+        height as u32,
+    ));
+    let self_stride = target.width() as usize;
+    let test_stride = test.width() as usize;
+
+    (0..height).any(|y| {
+        let self_start = (self_y0 + y) * self_stride + self_x0;
+        let test_start = (test_y0 + y) * test_stride + test_x0;
+        let self_row = &target.pixels()[self_start..self_start + width];
+        let test_row = &test.pixels()[test_start..test_start + width];
+        self_row
+            .iter()
+            .zip(test_row)
+            .any(|(self_pixel, test_pixel)| {
+                self_pixel.alpha() as u32 >= self_threshold
+                    && test_pixel.alpha() as u32 >= test_threshold
+            })
+    })
 }
 
 pub fn color_bounds_rect(
@@ -854,38 +1514,42 @@ pub fn color_bounds_rect(
     mask: i32,
     color: i32,
 ) -> (u32, u32, u32, u32) {
-    let mut min_x = target.width();
-    let mut max_x = 0;
-    let mut min_y = target.height();
-    let mut max_y = 0;
-
     let target = target.sync();
     let read = target.read();
+    let width = read.width() as usize;
+    if width == 0 {
+        return (0, 0, 0, 0);
+    }
 
-    for x in 0..read.width() {
-        for y in 0..read.height() {
-            let pixel_raw: i32 = read.get_pixel32_raw(x, y).into();
-            let color_matches = if find_color {
-                (pixel_raw & mask) == color
-            } else {
-                (pixel_raw & mask) != color
-            };
+    let matches = |pixel: &Color| ((i32::from(*pixel) & mask) == color) == find_color;
+    let rows = || read.pixels().chunks_exact(width);
 
-            if color_matches {
-                min_x = min_x.min(x);
-                max_x = max_x.max(x);
-                min_y = min_y.min(y);
-                max_y = max_y.max(y);
-            }
+    // Scan inwards from the top and bottom edges to find the first and last rows
+    // with a match, then only look at the rows in between for the left and right edges.
+    let Some(min_y) = rows().position(|row| row.iter().any(matches)) else {
+        return (0, 0, 0, 0);
+    };
+    let max_y = rows()
+        .rposition(|row| row.iter().any(matches))
+        .unwrap_or(min_y);
+
+    let mut min_x = width;
+    let mut max_x = 0;
+    for row in rows().skip(min_y).take(max_y - min_y + 1) {
+        if let Some(x) = row[..min_x].iter().position(matches) {
+            min_x = x;
+        }
+        if let Some(x) = row[max_x..].iter().rposition(matches) {
+            max_x += x;
         }
     }
 
     // Flash treats a match of (0, 0) alone as none.
     if max_x > 0 || max_y > 0 {
-        let x = min_x;
-        let y = min_y;
-        let w = max_x - min_x + 1;
-        let h = max_y - min_y + 1;
+        let x = min_x as u32;
+        let y = min_y as u32;
+        let w = (max_x - min_x + 1) as u32;
+        let h = (max_y - min_y + 1) as u32;
         (x, y, w, h)
     } else {
         (0, 0, 0, 0)
@@ -901,7 +1565,6 @@ pub fn merge<'gc>(
     rgba_mult: (i32, i32, i32, i32),
 ) {
     let (src_min_x, src_min_y, src_width, src_height) = src_rect;
-    let (dest_min_x, dest_min_y) = dest_point;
     let transparency = target.transparency();
 
     let mut source_region =
@@ -913,72 +1576,74 @@ pub fn merge<'gc>(
         Some(source_bitmap.read_area(source_region))
     };
 
+    // The multipliers are in the same (little-endian BGRA) order as the bytes of a `Color`.
+    let (red_mult, green_mult, blue_mult, alpha_mult) = rgba_mult;
+    let mults = [blue_mult, green_mult, red_mult, alpha_mult].map(|m| m.clamp(0, 256) as u16);
+    let mix = |source: Color, dest: Color| {
+        let source = i32::from(source).to_le_bytes();
+        let dest = i32::from(dest).to_le_bytes();
+        let mut mixed = [0; 4];
+        for (((mixed, source), dest), mult) in mixed.iter_mut().zip(source).zip(dest).zip(mults) {
+            *mixed = ((source as u16 * mult + dest as u16 * (256 - mult)) >> 8) as u8;
+        }
+        Color::from(i32::from_le_bytes(mixed))
+    };
+
+    let Some((columns, rows, (dx, dy))) = clip_copy(
+        src_rect,
+        dest_point,
+        (source_bitmap.width(), source_bitmap.height()),
+        (target.width(), target.height()),
+    ) else {
+        return;
+    };
+
     let target = target.sync();
     let mut write = target.write(context.gc_context);
 
-    for src_y in src_min_y..(src_min_y + src_height) {
-        for src_x in src_min_x..(src_min_x + src_width) {
-            let dest_x = src_x - src_min_x + dest_min_x;
-            let dest_y = src_y - src_min_y + dest_min_y;
-
-            if !write.is_point_in_bounds(dest_x, dest_y) {
-                continue;
-            }
+    // Merging a bitmap into itself would otherwise read back pixels that this merge has
+    // already written, so take a snapshot of the source pixels first.
+    let snapshot: Vec<Color> = if source.is_none() {
+        let read = &*write;
+        rows.clone()
+            .flat_map(|y| {
+                columns
+                    .clone()
+                    .map(move |x| read.get_pixel32_raw(x as u32, y as u32))
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
 
-            let source_color = if let Some(source) = &source {
-                if !source.is_point_in_bounds(src_x, src_y) {
-                    continue;
-                }
-                source
-                    .get_pixel32_raw(src_x as u32, src_y as u32)
-                    .to_un_multiplied_alpha()
-            } else {
-                if !write.is_point_in_bounds(src_x, src_y) {
-                    continue;
-                }
-                write
-                    .get_pixel32_raw(src_x as u32, src_y as u32)
-                    .to_un_multiplied_alpha()
+    let width = (columns.end - columns.start) as usize;
+    for (row, src_y) in rows.clone().enumerate() {
+        for (column, src_x) in columns.clone().enumerate() {
+            let source_color = match &source {
+                Some(source) => source.get_pixel32_raw(src_x as u32, src_y as u32),
+                None => snapshot[row * width + column],
             };
+            let (dest_x, dest_y) = ((src_x + dx) as u32, (src_y + dy) as u32);
+            let dest_color = write.get_pixel32_raw(dest_x, dest_y);
 
-            let dest_color = write
-                .get_pixel32_raw(dest_x as u32, dest_y as u32)
-                .to_un_multiplied_alpha();
-
-            let red_mult = rgba_mult.0.clamp(0, 256) as u16;
-            let green_mult = rgba_mult.1.clamp(0, 256) as u16;
-            let blue_mult = rgba_mult.2.clamp(0, 256) as u16;
-            let alpha_mult = rgba_mult.3.clamp(0, 256) as u16;
-
-            let red = (source_color.red() as u16 * red_mult
-                + dest_color.red() as u16 * (256 - red_mult))
-                / 256;
-            let green = (source_color.green() as u16 * green_mult
-                + dest_color.green() as u16 * (256 - green_mult))
-                / 256;
-            let blue = (source_color.blue() as u16 * blue_mult
-                + dest_color.blue() as u16 * (256 - blue_mult))
-                / 256;
-            let alpha = (source_color.alpha() as u16 * alpha_mult
-                + dest_color.alpha() as u16 * (256 - alpha_mult))
-                / 256;
-
-            let mix_color = Color::argb(alpha as u8, red as u8, green as u8, blue as u8);
-
+            let mix_color = mix(
+                source_color.to_un_multiplied_alpha(),
+                dest_color.to_un_multiplied_alpha(),
+            );
             write.set_pixel32_raw(
-                dest_x as u32,
-                dest_y as u32,
+                dest_x,
+                dest_y,
                 mix_color.to_premultiplied_alpha(transparency),
             );
         }
     }
 
-    let mut dirty_region = PixelRegion::encompassing_pixels_i32(
-        ((dest_min_x), (dest_min_y)),
-        ((dest_min_x + src_width), (dest_min_y + src_height)),
-    );
-    dirty_region.clamp(write.width(), write.height());
-    write.set_cpu_dirty(dirty_region);
+    write.set_cpu_dirty(PixelRegion::for_region(
+        (columns.start + dx) as u32,
+        (rows.start + dy) as u32,
+        width as u32,
+        (rows.end - rows.start) as u32,
+    ));
 }
 
 pub fn copy_pixels<'gc>(
@@ -1003,44 +1668,101 @@ pub fn copy_pixels<'gc>(
         Some(source_bitmap.read_area(source_region))
     };
 
-    let target = target.sync();
+    // Unless the source pixels need to be blended over the destination, or come from the
+    // destination itself, the destination pixels are simply replaced.
+    let blend = (source_transparency && !transparency) || merge_alpha;
+    let overwritten = clip_copy(
+        src_rect,
+        dest_point,
+        (source_bitmap.width(), source_bitmap.height()),
+        (target.width(), target.height()),
+    )
+    .filter(|_| source.is_some() && !blend);
+    let target = match overwritten {
+        Some((columns, rows, (dx, dy))) => target.sync_for_overwrite(PixelRegion::for_region(
+            (columns.start + dx) as u32,
+            (rows.start + dy) as u32,
+            (columns.end - columns.start) as u32,
+            (rows.end - rows.start) as u32,
+        )),
+        None => target.sync(),
+    };
     let mut write = target.write(context.gc_context);
 
-    for src_y in src_min_y..(src_min_y + src_height) {
-        for src_x in src_min_x..(src_min_x + src_width) {
-            let dest_x = src_x - src_min_x + dest_min_x;
-            let dest_y = src_y - src_min_y + dest_min_y;
-
-            if !write.is_point_in_bounds(dest_x, dest_y) {
-                continue;
+    // Copying within the same bitmap would otherwise read back pixels that this copy has
+    // already overwritten, so take a snapshot of the source region when the two overlap.
+    let source_snapshot = if source.is_none() {
+        let mut dest_region =
+            PixelRegion::for_region_i32(dest_min_x, dest_min_y, src_width, src_height);
+        dest_region.clamp(write.width(), write.height());
+        dest_region.intersects(source_region).then(|| {
+            let width = write.width() as usize;
+            let mut snapshot = Vec::with_capacity(
+                source_region.width() as usize * source_region.height() as usize,
+            );
+            for y in source_region.y_min..source_region.y_max {
+                let row = y as usize * width;
+                snapshot.extend_from_slice(
+                    &write.pixels()
+                        [row + source_region.x_min as usize..row + source_region.x_max as usize],
+                );
             }
+            snapshot
+        })
+    } else {
+        None
+    };
 
-            let source_color = if let Some(source) = &source {
-                if !source.is_point_in_bounds(src_x, src_y) {
-                    continue;
-                }
-                source.get_pixel32_raw(src_x as u32, src_y as u32)
-            } else {
-                if !write.is_point_in_bounds(src_x, src_y) {
+    // Unless the source pixels need to be blended over the destination, whole rows can be
+    // copied at once.
+    if blend {
+        for src_y in src_min_y..(src_min_y + src_height) {
+            for src_x in src_min_x..(src_min_x + src_width) {
+                let dest_x = src_x - src_min_x + dest_min_x;
+                let dest_y = src_y - src_min_y + dest_min_y;
+
+                if !write.is_point_in_bounds(dest_x, dest_y) {
                     continue;
                 }
-                write.get_pixel32_raw(src_x as u32, src_y as u32)
-            };
 
-            let mut dest_color = write.get_pixel32_raw(dest_x as u32, dest_y as u32);
+                let source_color = if let Some(source) = &source {
+                    if !source.is_point_in_bounds(src_x, src_y) {
+                        continue;
+                    }
+                    source.get_pixel32_raw(src_x as u32, src_y as u32)
+                } else {
+                    if !write.is_point_in_bounds(src_x, src_y) {
+                        continue;
+                    }
+                    if let Some(snapshot) = &source_snapshot {
+                        let x = src_x as usize - source_region.x_min as usize;
+                        let y = src_y as usize - source_region.y_min as usize;
+                        snapshot[y * source_region.width() as usize + x]
+                    } else {
+                        write.get_pixel32_raw(src_x as u32, src_y as u32)
+                    }
+                };
 
-            dest_color = if (source_transparency && !transparency) || merge_alpha {
-                dest_color.blend_over(&source_color)
-            } else {
-                source_color
-            };
+                let mut dest_color = write.get_pixel32_raw(dest_x as u32, dest_y as u32);
 
-            if !transparency {
-                dest_color = dest_color.with_alpha(0xFF)
-            }
+                dest_color = dest_color.blend_over(&source_color);
 
-            write.set_pixel32_raw(dest_x as u32, dest_y as u32, dest_color);
+                if !transparency {
+                    dest_color = dest_color.with_alpha(0xFF)
+                }
+
+                write.set_pixel32_raw(dest_x as u32, dest_y as u32, dest_color);
+            }
         }
+    } else {
+        copy_rows(
+            &mut write,
+            source.as_deref(),
+            source_snapshot.as_deref(),
+            source_region,
+            src_rect,
+            dest_point,
+        );
     }
     let mut dirty_region = PixelRegion::encompassing_pixels_i32(
         ((dest_min_x), (dest_min_y)),
@@ -1050,19 +1772,75 @@ pub fn copy_pixels<'gc>(
     write.set_cpu_dirty(dirty_region);
 }
 
-#[allow(clippy::too_many_arguments)]
-pub fn copy_pixels_with_alpha_source<'gc>(
-    context: &mut UpdateContext<'_, 'gc>,
-    target: BitmapDataWrapper<'gc>,
-    source_bitmap: BitmapDataWrapper<'gc>,
+/// Copies `src_rect` to `dest_point` a row at a time, for copies where the source pixels
+/// replace the destination pixels outright.
+///
+/// The pixels are read from `source` if it's a different bitmap than `target`. Otherwise they
+/// come from `snapshot`, a copy of `source_region`, or from `target` itself if the source and
+/// destination don't overlap.
+fn copy_rows(
+    target: &mut BitmapData,
+    source: Option<&BitmapData>,
+    snapshot: Option<&[Color]>,
+    source_region: PixelRegion,
     src_rect: (i32, i32, i32, i32),
     dest_point: (i32, i32),
-    alpha_bitmap: BitmapDataWrapper<'gc>,
-    alpha_point: (i32, i32),
-    merge_alpha: bool,
 ) {
-    let (src_min_x, src_min_y, src_width, src_height) = src_rect;
-    let (dest_min_x, dest_min_y) = dest_point;
+    let target_width = target.width() as i64;
+    let source_size = source.map_or((target.width(), target.height()), |source| {
+        (source.width(), source.height())
+    });
+    let source_width = source_size.0 as i64;
+    let Some((columns, rows, (dx, dy))) = clip_copy(
+        src_rect,
+        dest_point,
+        source_size,
+        (target.width(), target.height()),
+    ) else {
+        return;
+    };
+    let x_min = columns.start;
+    let len = (columns.end - x_min) as usize;
+    let transparency = target.transparency();
+    for y in rows {
+        let dest_start = ((y + dy) * target_width + x_min + dx) as usize;
+        let dest_row = dest_start..dest_start + len;
+        if let Some(source) = source {
+            let start = (y * source_width + x_min) as usize;
+            target.pixels_mut()[dest_row.clone()]
+                .copy_from_slice(&source.pixels()[start..start + len]);
+        } else if let Some(snapshot) = snapshot {
+            let row = (y as usize - source_region.y_min as usize) * source_region.width() as usize;
+            let start = row + x_min as usize - source_region.x_min as usize;
+            target.pixels_mut()[dest_row.clone()].copy_from_slice(&snapshot[start..start + len]);
+        } else {
+            let start = (y * target_width + x_min) as usize;
+            target
+                .pixels_mut()
+                .copy_within(start..start + len, dest_start);
+        }
+
+        if !transparency {
+            for pixel in &mut target.pixels_mut()[dest_row] {
+                *pixel = pixel.with_alpha(0xFF);
+            }
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn copy_pixels_with_alpha_source<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    source_bitmap: BitmapDataWrapper<'gc>,
+    src_rect: (i32, i32, i32, i32),
+    dest_point: (i32, i32),
+    alpha_bitmap: BitmapDataWrapper<'gc>,
+    alpha_point: (i32, i32),
+    merge_alpha: bool,
+) {
+    let (src_min_x, src_min_y, src_width, src_height) = src_rect;
+    let (dest_min_x, dest_min_y) = dest_point;
     let transparency = target.transparency();
     let source_transparency = source_bitmap.transparency();
     let alpha_transparency = alpha_bitmap.transparency();
@@ -1183,7 +1961,68 @@ pub fn apply_filter<'gc>(
     dest_point: (u32, u32),
     filter: Filter,
 ) {
-    let source_handle = source.bitmap_handle(context.gc_context, context.renderer);
+    // The render backends don't implement the gradient filters yet, so these run on the CPU.
+    match &filter {
+        Filter::GradientGlowFilter(filter) => {
+            return apply_gradient_filter_cpu(
+                context,
+                target,
+                source,
+                source_point,
+                source_size,
+                dest_point,
+                filter,
+                false,
+            );
+        }
+        Filter::GradientBevelFilter(filter) => {
+            return apply_gradient_filter_cpu(
+                context,
+                target,
+                source,
+                source_point,
+                source_size,
+                dest_point,
+                filter,
+                true,
+            );
+        }
+        // Neither do they run Pixel Bender shaders.
+        Filter::ShaderFilter(filter) => {
+            return apply_shader_filter_cpu(
+                context,
+                target,
+                source,
+                source_point,
+                source_size,
+                dest_point,
+                filter,
+            );
+        }
+        _ => {}
+    }
+
+    if context.force_cpu_bitmap_operations {
+        tracing::warn!(
+            "BitmapData.applyFilter: {} has no CPU implementation, leaving the target unchanged",
+            filter_name(&filter)
+        );
+        return;
+    }
+
+    // Filters such as blur sample neighbouring pixels, so filtering a bitmap in place must
+    // not read pixels that the filter has already written. Filter a copy of its texture
+    // instead, which stays on the GPU.
+    let snapshot = if source.ptr_eq(target) {
+        let quality = context.stage.quality();
+        snapshot_texture(context, source, quality)
+    } else {
+        None
+    };
+    let source_handle = match snapshot {
+        Some(snapshot) => snapshot,
+        None => source.bitmap_handle(context.gc_context, context.renderer),
+    };
     let (target, _) = target.overwrite_cpu_pixels_from_gpu(context);
     let mut write = target.write(context.gc_context);
     let dest = write.bitmap_handle(context.renderer).unwrap();
@@ -1205,6 +2044,325 @@ pub fn apply_filter<'gc>(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn apply_gradient_filter_cpu<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    source: BitmapDataWrapper<'gc>,
+    source_point: (u32, u32),
+    source_size: (u32, u32),
+    dest_point: (u32, u32),
+    filter: &GradientFilter,
+    bevel: bool,
+) {
+    let (width, height) = source_size;
+    let mut dest_region = PixelRegion::for_region(dest_point.0, dest_point.1, width, height);
+    dest_region.clamp(target.width(), target.height());
+    if dest_region.width() == 0 || dest_region.height() == 0 {
+        return;
+    }
+
+    // Copy the source area up front, as `source` and `target` may be the same object.
+    // Anything outside of the source bitmap is treated as transparent.
+    let mut source_region = PixelRegion::for_region(source_point.0, source_point.1, width, height);
+    source_region.clamp(source.width(), source.height());
+    let mut source_pixels = Vec::with_capacity(width as usize * height as usize);
+    {
+        let read = source.read_area(source_region);
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = (source_point.0 + x, source_point.1 + y);
+                source_pixels.push(if src_x < read.width() && src_y < read.height() {
+                    read.get_pixel32_raw(src_x, src_y)
+                } else {
+                    Color::argb(0, 0, 0, 0)
+                });
+            }
+        }
+    }
+
+    let result = gradient_filter_pixels(&source_pixels, width, height, filter, bevel);
+
+    let target = target.sync();
+    let mut write = target.write(context.gc_context);
+    let transparency = write.transparency();
+    for y in dest_region.y_min..dest_region.y_max {
+        for x in dest_region.x_min..dest_region.x_max {
+            let index = (x - dest_point.0) as usize + (y - dest_point.1) as usize * width as usize;
+            let mut color = result[index];
+            if !transparency {
+                color = color.with_alpha(0xFF);
+            }
+            write.set_pixel32_raw(x, y, color);
+        }
+    }
+    write.set_cpu_dirty(dest_region);
+}
+
+/// Runs a Pixel Bender shader over the source area with the CPU interpreter.
+///
+/// The source is the shader's first input. Shaders that can't be interpreted just copy the
+/// source area instead.
+fn apply_shader_filter_cpu<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    source: BitmapDataWrapper<'gc>,
+    source_point: (u32, u32),
+    source_size: (u32, u32),
+    dest_point: (u32, u32),
+    filter: &ShaderFilter,
+) {
+    let (width, height) = source_size;
+    let mut dest_region = PixelRegion::for_region(dest_point.0, dest_point.1, width, height);
+    dest_region.clamp(target.width(), target.height());
+    if dest_region.width() == 0 || dest_region.height() == 0 {
+        return;
+    }
+
+    // Copy the source area up front, as `source` and `target` may be the same object.
+    // Anything outside of the source bitmap is treated as transparent.
+    let mut source_region = PixelRegion::for_region(source_point.0, source_point.1, width, height);
+    source_region.clamp(source.width(), source.height());
+    let mut source_pixels = Vec::with_capacity(width as usize * height as usize);
+    {
+        let read = source.read_area(source_region);
+        for y in 0..height {
+            for x in 0..width {
+                let (src_x, src_y) = (source_point.0 + x, source_point.1 + y);
+                source_pixels.push(if src_x < read.width() && src_y < read.height() {
+                    read.get_pixel32_raw(src_x, src_y).to_un_multiplied_alpha()
+                } else {
+                    Color::argb(0, 0, 0, 0)
+                });
+            }
+        }
+    }
+
+    let result: Vec<Color> = match Shader::parse(&filter.bytecode) {
+        Ok(shader) => {
+            let sample = |_texture, x: f32, y: f32| {
+                let (x, y) = (x.floor(), y.floor());
+                if x < 0.0 || y < 0.0 || x >= width as f32 || y >= height as f32 {
+                    return [0.0; 4];
+                }
+                let color = source_pixels[x as usize + y as usize * width as usize];
+                [color.red(), color.green(), color.blue(), color.alpha()].map(|c| c as f32 / 255.0)
+            };
+            shader
+                .run(width, height, sample)
+                .into_iter()
+                .map(|rgba| {
+                    let [r, g, b, a] = rgba.map(|c| (c.clamp(0.0, 1.0) * 255.0).round() as u8);
+                    Color::argb(a, r, g, b)
+                })
+                .collect()
+        }
+        Err(e) => {
+            tracing::warn!("BitmapData.applyFilter: Unsupported shader ({e}), copying source");
+            source_pixels
+        }
+    };
+
+    let target = target.sync();
+    let mut write = target.write(context.gc_context);
+    let transparency = write.transparency();
+    for y in dest_region.y_min..dest_region.y_max {
+        for x in dest_region.x_min..dest_region.x_max {
+            let index = (x - dest_point.0) as usize + (y - dest_point.1) as usize * width as usize;
+            write.set_pixel32_raw(x, y, result[index].to_premultiplied_alpha(transparency));
+        }
+    }
+    write.set_cpu_dirty(dest_region);
+}
+
+/// Applies a gradient glow or bevel to a block of premultiplied pixels.
+///
+/// The (offset and blurred) alpha channel of the source selects a color from the
+/// filter's gradient, which is then masked according to the filter type.
+/// Glows map alpha directly onto the gradient, while bevels place highlights at the
+/// start of the gradient and shadows at the end, with a midpoint of 128.
+fn gradient_filter_pixels(
+    source: &[Color],
+    width: u32,
+    height: u32,
+    filter: &GradientFilter,
+    bevel: bool,
+) -> Vec<Color> {
+    let alpha: Vec<f32> = source.iter().map(|c| c.alpha() as f32 / 255.0).collect();
+    let angle = filter.angle.to_f32();
+    let distance = filter.distance.to_f32();
+    let offset = (
+        (angle.cos() * distance).round() as i32,
+        (angle.sin() * distance).round() as i32,
+    );
+    let blurred = |offset: (i32, i32)| {
+        let mut values = offset_values(&alpha, width, height, offset);
+        box_blur(
+            &mut values,
+            width,
+            height,
+            filter.blur_x.to_f32(),
+            filter.blur_y.to_f32(),
+            filter.num_passes(),
+        );
+        values
+    };
+
+    let strength = filter.strength.to_f32();
+    let gradient = gradient_lut(&filter.colors);
+    let shifted = blurred(offset);
+    let opposite = if bevel {
+        blurred((-offset.0, -offset.1))
+    } else {
+        Vec::new()
+    };
+
+    (0..source.len())
+        .map(|i| {
+            let ratio = if bevel {
+                let amount = ((opposite[i] - shifted[i]) * strength).clamp(-1.0, 1.0);
+                (1.0 - amount) * 127.5
+            } else if filter.is_inner() {
+                ((1.0 - shifted[i]) * strength).clamp(0.0, 1.0) * 255.0
+            } else {
+                (shifted[i] * strength).clamp(0.0, 1.0) * 255.0
+            };
+
+            let mask = if filter.is_on_top() {
+                1.0
+            } else if filter.is_inner() {
+                alpha[i]
+            } else {
+                1.0 - alpha[i]
+            };
+            let effect = gradient[ratio.round() as usize].map(|c| c * mask);
+            let src = source[i];
+            let src = [
+                src.red() as f32 / 255.0,
+                src.green() as f32 / 255.0,
+                src.blue() as f32 / 255.0,
+                src.alpha() as f32 / 255.0,
+            ];
+
+            let [r, g, b, a] = if filter.is_knockout() {
+                effect
+            } else if filter.is_inner() || filter.is_on_top() {
+                composite_over(effect, src)
+            } else {
+                composite_over(src, effect)
+            };
+            let to_u8 = |v: f32| (v * 255.0).round().clamp(0.0, 255.0) as u8;
+            Color::argb(to_u8(a), to_u8(r), to_u8(g), to_u8(b))
+        })
+        .collect()
+}
+
+/// Composites one premultiplied RGBA color over another.
+fn composite_over(top: [f32; 4], bottom: [f32; 4]) -> [f32; 4] {
+    let inverse = 1.0 - top[3];
+    [
+        top[0] + bottom[0] * inverse,
+        top[1] + bottom[1] * inverse,
+        top[2] + bottom[2] * inverse,
+        top[3] + bottom[3] * inverse,
+    ]
+}
+
+/// Builds the 256 premultiplied RGBA colors of a filter gradient.
+fn gradient_lut(records: &[GradientRecord]) -> Vec<[f32; 4]> {
+    let to_rgba = |color: &swf::Color| {
+        [
+            color.r as f32 / 255.0,
+            color.g as f32 / 255.0,
+            color.b as f32 / 255.0,
+            color.a as f32 / 255.0,
+        ]
+    };
+
+    (0..=255u8)
+        .map(|ratio| {
+            let next = records.iter().position(|record| record.ratio >= ratio);
+            let [r, g, b, a] = match next {
+                None => records
+                    .last()
+                    .map(|r| to_rgba(&r.color))
+                    .unwrap_or_default(),
+                Some(0) => to_rgba(&records[0].color),
+                Some(i) => {
+                    let (start, end) = (&records[i - 1], &records[i]);
+                    let t = f32::from(ratio - start.ratio) / f32::from(end.ratio - start.ratio);
+                    let (start, end) = (to_rgba(&start.color), to_rgba(&end.color));
+                    std::array::from_fn(|c| start[c] + (end[c] - start[c]) * t)
+                }
+            };
+            [r * a, g * a, b * a, a]
+        })
+        .collect()
+}
+
+/// Moves a block of values by `offset`, filling uncovered areas with zero.
+fn offset_values(values: &[f32], width: u32, height: u32, offset: (i32, i32)) -> Vec<f32> {
+    let (width, height) = (width as i32, height as i32);
+    let mut result = vec![0.0; values.len()];
+    for y in 0..height {
+        for x in 0..width {
+            let (src_x, src_y) = (x - offset.0, y - offset.1);
+            if (0..width).contains(&src_x) && (0..height).contains(&src_y) {
+                result[(x + y * width) as usize] = values[(src_x + src_y * width) as usize];
+            }
+        }
+    }
+    result
+}
+
+/// Applies a separable box blur `passes` times, in the same way as `BlurFilter`.
+/// Values outside of the block are treated as zero.
+fn box_blur(values: &mut [f32], width: u32, height: u32, blur_x: f32, blur_y: f32, passes: u8) {
+    let radius_x = ((blur_x - 1.0) / 2.0).max(0.0) as usize;
+    let radius_y = ((blur_y - 1.0) / 2.0).max(0.0) as usize;
+    let (width, height) = (width as usize, height as usize);
+    let mut line = Vec::new();
+
+    let mut blur_line = |values: &mut [f32], start: usize, stride: usize, len: usize, radius| {
+        if radius == 0 {
+            return;
+        }
+        line.clear();
+        line.extend((0..len).map(|i| values[start + i * stride]));
+        let window = (radius * 2 + 1) as f32;
+        let mut sum: f32 = line.iter().take(radius + 1).sum();
+        for i in 0..len {
+            values[start + i * stride] = sum / window;
+            if let Some(entering) = line.get(i + radius + 1) {
+                sum += entering;
+            }
+            if i >= radius {
+                sum -= line[i - radius];
+            }
+        }
+    };
+
+    for _ in 0..passes.max(1) {
+        for y in 0..height {
+            blur_line(values, y * width, 1, width, radius_x);
+        }
+        for x in 0..width {
+            blur_line(values, x, width, height, radius_y);
+        }
+    }
+}
+
+/// Draws `source` onto `target`.
+///
+/// If `repeat` is set and `source` is a `BitmapData`, it is tiled across the whole of `target`
+/// instead of being drawn once. Flash's own `draw` never does this, but it lets a `BitmapData`
+/// be used as a repeating fill.
+///
+/// At `StageQuality::Low`, `smoothing` is turned off, as well as the smoothing of any bitmaps
+/// inside a drawn display object. `quality` is also passed on to the render backend, which is
+/// left to pick its anti-aliasing from it; only wgpu does, with `StageQuality::sample_count`.
+/// Backends that can't render offscreen, like webgl and canvas, only draw `BitmapData` sources
+/// moved by whole pixels, which never need smoothing.
 #[allow(clippy::too_many_arguments)]
 pub fn draw<'gc>(
     context: &mut UpdateContext<'_, 'gc>,
@@ -1215,15 +2373,46 @@ pub fn draw<'gc>(
     blend_mode: BlendMode,
     clip_rect: Option<Rectangle<Twips>>,
     quality: StageQuality,
+    repeat: bool,
 ) -> Result<(), BitmapDataDrawError> {
+    let repeat = match &source {
+        IBitmapDrawable::BitmapData(data) => repeat && data.width() > 0 && data.height() > 0,
+        IBitmapDrawable::DisplayObject(_) => false,
+    };
+    let smoothing = smoothing && quality.allows_bitmap_smoothing();
+
     // Calculate the maximum potential area that this draw call will affect
-    let bounds = transform.matrix * source.bounds();
-    let mut dirty_region = PixelRegion::from(bounds);
+    let mut dirty_region = if repeat {
+        PixelRegion::for_whole_size(target.width(), target.height())
+    } else {
+        PixelRegion::from(transform.matrix * source.bounds())
+    };
     dirty_region.clamp(target.width(), target.height());
     if dirty_region.width() == 0 || dirty_region.height() == 0 {
         return Ok(());
     }
 
+    if context.force_cpu_bitmap_operations {
+        return draw_cpu(
+            context,
+            target,
+            source,
+            &transform,
+            blend_mode,
+            clip_rect.is_some(),
+            repeat,
+        );
+    }
+
+    // Drawing a bitmap onto itself must not sample pixels that this draw is writing to,
+    // so render from a copy of its texture instead. The copy stays on the GPU.
+    let snapshot = match &source {
+        IBitmapDrawable::BitmapData(data) if data.ptr_eq(target) => {
+            snapshot_texture(context, target, quality)
+        }
+        _ => None,
+    };
+
     let mut transform_stack = ruffle_render::transform::TransformStack::new();
     transform_stack.push(&transform);
 
@@ -1235,11 +2424,14 @@ pub fn draw<'gc>(
         transform_stack: &mut transform_stack,
         is_offscreen: true,
         stage: context.stage,
+        quality,
         allow_mask: true,
+        texture_versions: Vec::new(),
     };
 
     // Make the screen opacity match the opacity of this bitmap
 
+    let has_clip_rect = clip_rect.is_some();
     let clip_mat = clip_rect.map(|clip_rect| {
         // Note - we do *not* apply the matrix to the clip rect,
         // to match Flash's behavior.
@@ -1264,12 +2456,28 @@ pub fn draw<'gc>(
 
     match &mut source {
         IBitmapDrawable::BitmapData(data) => {
-            data.render(smoothing, &mut render_context);
+            if repeat {
+                render_tiled(
+                    *data,
+                    snapshot.as_ref(),
+                    smoothing,
+                    &transform.matrix,
+                    (target.width(), target.height()),
+                    &mut render_context,
+                );
+            } else {
+                render_source(*data, snapshot.as_ref(), smoothing, &mut render_context);
+            }
         }
         IBitmapDrawable::DisplayObject(object) => {
             // Note that we do *not* use `render_base`,
             // as we want to ignore the object's mask and normal transform
-            object.render_self(&mut render_context);
+            match object.scroll_rect() {
+                Some(scroll_rect) => {
+                    render_scrolled(*object, scroll_rect, &mut render_context);
+                }
+                None => object.render_self(&mut render_context),
+            }
         }
     }
 
@@ -1293,8 +2501,7 @@ pub fn draw<'gc>(
         commands
     };
 
-    let (target, include_dirty_area) = target.overwrite_cpu_pixels_from_gpu(context);
-    let mut write = target.write(context.gc_context);
+    let (target_cell, include_dirty_area) = target.overwrite_cpu_pixels_from_gpu(context);
     // If we have another dirty area to preserve, expand this to include it
     if let Some(old) = include_dirty_area {
         dirty_region.union(old);
@@ -1306,97 +2513,2571 @@ pub fn draw<'gc>(
 
     match image {
         Some(sync_handle) => {
-            write.set_gpu_dirty(sync_handle, dirty_region);
+            target_cell
+                .write(context.gc_context)
+                .set_gpu_dirty(sync_handle, dirty_region);
             Ok(())
         }
-        None => Err(BitmapDataDrawError::Unimplemented),
+        None => {
+            // The render backend can't draw offscreen, but we can still
+            // composite one BitmapData onto another ourselves.
+            draw_cpu(
+                context,
+                target,
+                source,
+                &transform,
+                blend_mode,
+                has_clip_rect,
+                repeat,
+            )
+        }
     }
 }
 
-pub fn get_vector(
-    target: BitmapDataWrapper,
-    x: i32,
-    y: i32,
-    width: i32,
-    height: i32,
-) -> Vec<Avm2Value> {
-    let mut region = PixelRegion::for_region_i32(x, y, width, height);
-    region.clamp(target.width(), target.height());
-
-    let mut result = Vec::with_capacity((region.width() * region.height()) as usize);
-
-    let read = target.read_area(region);
-
-    for y in region.y_min..region.y_max {
-        for x in region.x_min..region.x_max {
-            let color = read.get_pixel32_raw(x, y);
-            let color = u32::from(color.to_un_multiplied_alpha());
-            result.push(color.into());
-        }
+/// The ActionScript class name of `filter`, for logging.
+fn filter_name(filter: &Filter) -> &'static str {
+    match filter {
+        Filter::BevelFilter(_) => "BevelFilter",
+        Filter::BlurFilter(_) => "BlurFilter",
+        Filter::ColorMatrixFilter(_) => "ColorMatrixFilter",
+        Filter::ConvolutionFilter(_) => "ConvolutionFilter",
+        Filter::DisplacementMapFilter(_) => "DisplacementMapFilter",
+        Filter::DropShadowFilter(_) => "DropShadowFilter",
+        Filter::GlowFilter(_) => "GlowFilter",
+        Filter::GradientBevelFilter(_) => "GradientBevelFilter",
+        Filter::GradientGlowFilter(_) => "GradientGlowFilter",
+        Filter::ShaderFilter(_) => "ShaderFilter",
     }
-
-    result
 }
 
-pub fn get_pixels_as_byte_array<'gc>(
-    target: BitmapDataWrapper,
-    x: i32,
-    y: i32,
-    width: i32,
-    height: i32,
-) -> Result<ByteArrayStorage, Error<'gc>> {
-    let mut result = ByteArrayStorage::new();
-    let mut region = PixelRegion::for_region_i32(x, y, width, height);
-    region.clamp(target.width(), target.height());
-
-    let read = target.read_area(region);
-    for y in region.y_min..region.y_max {
-        for x in region.x_min..region.x_max {
-            let color = read.get_pixel32_raw(x, y);
-            result.write_int(color.to_un_multiplied_alpha().into())?;
+/// Draws `source` onto `target` without the render backend.
+///
+/// Only a `BitmapData` drawn with a whole-pixel translation and no clip rect is supported;
+/// anything else logs why and fails with `BitmapDataDrawError::Unimplemented`.
+fn draw_cpu<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    source: IBitmapDrawable<'gc>,
+    transform: &Transform,
+    blend_mode: BlendMode,
+    has_clip_rect: bool,
+    repeat: bool,
+) -> Result<(), BitmapDataDrawError> {
+    let source = match source {
+        IBitmapDrawable::BitmapData(source) => source,
+        IBitmapDrawable::DisplayObject(_) => {
+            tracing::warn!("BitmapData.draw: Display objects can't be drawn on the CPU");
+            return Err(BitmapDataDrawError::Unimplemented);
         }
+    };
+    if has_clip_rect {
+        tracing::warn!("BitmapData.draw: Draws with a clipRect can't run on the CPU");
+        return Err(BitmapDataDrawError::Unimplemented);
     }
+    let Some(offset) = pixel_translation(&transform.matrix) else {
+        tracing::warn!("BitmapData.draw: Only whole-pixel translations can be drawn on the CPU");
+        return Err(BitmapDataDrawError::Unimplemented);
+    };
 
-    Ok(result)
+    draw_bitmap_data_cpu(
+        context,
+        target,
+        source,
+        offset,
+        &transform.color_transform,
+        blend_mode,
+        repeat,
+    );
+    Ok(())
 }
 
-pub fn set_pixels_from_byte_array<'gc>(
+/// Draws `source` as it looks on `frame` (1-based), leaving `source` itself on
+/// its current frame.
+///
+/// A fresh copy of the clip is instantiated from its library and sent to
+/// `frame`, and that copy is drawn instead. No scripts run for the copy: its
+/// frame scripts are skipped, and AVM2 objects are never constructed for it or
+/// its children. Clips without a library definition (such as ones made by
+/// `createEmptyMovieClip`) can't be copied, and are drawn as they look now.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_at_frame<'gc>(
     context: &mut UpdateContext<'_, 'gc>,
     target: BitmapDataWrapper<'gc>,
-    x: i32,
-    y: i32,
-    width: i32,
-    height: i32,
-    bytearray: &mut ByteArrayStorage,
-) -> Result<(), EofError> {
-    let mut region = PixelRegion::for_region_i32(x, y, width, height);
-    region.clamp(target.width(), target.height());
-    let transparency = target.transparency();
-
-    let target = if region.width() == target.width() && region.height() == target.height() {
-        // If we're filling the whole region, we can discard the gpu data
-        target.overwrite_cpu_pixels_from_gpu(context).0
-    } else {
-        // If we're filling a partial region, finish any gpu->cpu sync
-        target.sync()
-    };
-    let mut write = target.write(context.gc_context);
+    source: MovieClip<'gc>,
+    frame: u16,
+    transform: Transform,
+    smoothing: bool,
+    blend_mode: BlendMode,
+    clip_rect: Option<Rectangle<Twips>>,
+    quality: StageQuality,
+) -> Result<(), BitmapDataDrawError> {
+    let source = copy_at_frame(context, source, frame).unwrap_or(source);
+    draw(
+        context,
+        target,
+        IBitmapDrawable::DisplayObject(source.into()),
+        transform,
+        smoothing,
+        blend_mode,
+        clip_rect,
+        quality,
+        false,
+    )
+}
 
-    if region.width() > 0 && region.height() > 0 {
-        for y in region.y_min..region.y_max {
-            for x in region.x_min..region.x_max {
-                // Copy data from bytearray until EOFError or finished
-                let color = bytearray.read_int()?;
-                write.set_pixel32_raw(
-                    x,
-                    y,
-                    Color::from(color).to_premultiplied_alpha(transparency),
-                );
+/// Instantiates a detached copy of `source` from its library, sent to `frame`.
+fn copy_at_frame<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    source: MovieClip<'gc>,
+    frame: u16,
+) -> Option<MovieClip<'gc>> {
+    let copy = context
+        .library
+        .library_for_movie_mut(source.movie())
+        .instantiate_by_id(source.id(), context.gc_context)
+        .ok()?
+        .as_movie_clip()?;
+    copy.seek_detached(context, frame);
+
+    // AVM1 actions the seek queued would otherwise run later against the copy.
+    fn mark_removed<'gc>(object: DisplayObject<'gc>, mc: MutationContext<'gc, '_>) {
+        object.set_avm1_removed(mc, true);
+        if let Some(container) = object.as_container() {
+            for child in container.iter_render_list() {
+                mark_removed(child, mc);
             }
         }
-
-        write.set_cpu_dirty(region);
     }
+    mark_removed(copy.into(), context.gc_context);
 
-    Ok(())
+    Some(copy)
+}
+
+/// Copies the current texture of `source` into a new texture, without reading it back
+/// to the CPU.
+///
+/// Returns `None` if the render backend can't draw offscreen.
+fn snapshot_texture<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    source: BitmapDataWrapper<'gc>,
+    quality: StageQuality,
+) -> Option<BitmapHandle> {
+    let (width, height) = (source.width(), source.height());
+    let handle = source.bitmap_handle(context.gc_context, context.renderer);
+    let copy = context.renderer.create_empty_texture(width, height).ok()?;
+
+    let mut commands = CommandList::new();
+    commands.render_bitmap(handle, Transform::default(), false);
+    // The copy is only ever sampled on the GPU, so its sync handle isn't needed.
+    context.renderer.render_offscreen(
+        copy.clone(),
+        commands,
+        quality,
+        PixelRegion::for_whole_size(width, height),
+    )?;
+    Some(copy)
+}
+
+/// Renders `object` cropped and scrolled by its scroll rect, as it would be on the stage.
+///
+/// The crop is applied in the object's own coordinate space, so the visible part of
+/// the object ends up with its top left corner at the origin of the draw.
+fn render_scrolled<'gc>(
+    object: DisplayObject<'gc>,
+    scroll_rect: Rectangle<Twips>,
+    context: &mut RenderContext<'_, 'gc>,
+) {
+    let crop_mat = context.transform_stack.transform().matrix
+        * Matrix::scale(
+            scroll_rect.width().to_pixels() as f32,
+            scroll_rect.height().to_pixels() as f32,
+        );
+    context.commands.push_mask();
+    // The color doesn't matter, as this is a mask.
+    context.commands.draw_rect(swf::Color::BLACK, crop_mat);
+    context.commands.activate_mask();
+
+    context.transform_stack.push(&Transform {
+        matrix: Matrix::translate(-scroll_rect.x_min, -scroll_rect.y_min),
+        color_transform: Default::default(),
+    });
+    object.render_self(context);
+    context.transform_stack.pop();
+
+    // Draw the rectangle again after deactivating the mask,
+    // to reset the stencil buffer.
+    context.commands.deactivate_mask();
+    context.commands.draw_rect(swf::Color::BLACK, crop_mat);
+    context.commands.pop_mask();
+}
+
+/// Renders `source` with the current transform, or `snapshot` in its place if there is one.
+fn render_source<'gc>(
+    source: BitmapDataWrapper<'gc>,
+    snapshot: Option<&BitmapHandle>,
+    smoothing: bool,
+    context: &mut RenderContext<'_, 'gc>,
+) {
+    match snapshot {
+        Some(handle) => context.commands.render_bitmap(
+            handle.clone(),
+            context.transform_stack.transform(),
+            smoothing,
+        ),
+        None => source.render(smoothing, context),
+    }
+}
+
+/// The most tiles `render_tiled` will draw, so that a tiny or heavily scaled down source
+/// can't flood the render backend with commands.
+const MAX_TILES: usize = 1 << 16;
+
+/// Renders `source` once for every tile needed to cover a bitmap of `target_size`,
+/// after `matrix` is applied.
+///
+/// Nothing is rendered if that would take more than `MAX_TILES` tiles.
+fn render_tiled<'gc>(
+    source: BitmapDataWrapper<'gc>,
+    snapshot: Option<&BitmapHandle>,
+    smoothing: bool,
+    matrix: &Matrix,
+    target_size: (u32, u32),
+    context: &mut RenderContext<'_, 'gc>,
+) {
+    let Some(inverse) = matrix.inverse() else {
+        return;
+    };
+    // The part of the source's (infinitely tiled) space that ends up on the target.
+    let covered = inverse
+        * Rectangle {
+            x_min: Twips::ZERO,
+            y_min: Twips::ZERO,
+            x_max: Twips::from_pixels(target_size.0.into()),
+            y_max: Twips::from_pixels(target_size.1.into()),
+        };
+    let tile_width = source.width() as f64;
+    let tile_height = source.height() as f64;
+    let tiles = |min: Twips, max: Twips, size: f64| {
+        (min.to_pixels() / size).floor() as i32..(max.to_pixels() / size).ceil() as i32
+    };
+
+    let rows = tiles(covered.y_min, covered.y_max, tile_height);
+    let columns = tiles(covered.x_min, covered.x_max, tile_width);
+    if rows.len().saturating_mul(columns.len()) > MAX_TILES {
+        tracing::warn!(
+            "BitmapData.draw: not repeating a {}x{} source, as it would take {}x{} tiles",
+            source.width(),
+            source.height(),
+            columns.len(),
+            rows.len()
+        );
+        return;
+    }
+
+    for row in rows {
+        for column in columns.clone() {
+            context.transform_stack.push(&Transform {
+                matrix: Matrix::translate(
+                    Twips::from_pixels(column as f64 * tile_width),
+                    Twips::from_pixels(row as f64 * tile_height),
+                ),
+                color_transform: Default::default(),
+            });
+            render_source(source, snapshot, smoothing, context);
+            context.transform_stack.pop();
+        }
+    }
+}
+
+/// Returns the translation of `matrix` in whole pixels, if that is all it does.
+fn pixel_translation(matrix: &Matrix) -> Option<(i32, i32)> {
+    let is_whole_pixel = |twips: Twips| twips.get() % Twips::TWIPS_PER_PIXEL == 0;
+    if matrix.a == 1.0
+        && matrix.b == 0.0
+        && matrix.c == 0.0
+        && matrix.d == 1.0
+        && is_whole_pixel(matrix.tx)
+        && is_whole_pixel(matrix.ty)
+    {
+        Some((matrix.tx.to_pixels() as i32, matrix.ty.to_pixels() as i32))
+    } else {
+        None
+    }
+}
+
+/// Composites `source` onto `target` on the CPU, with the same blending
+/// the render backends use for `draw`.
+///
+/// If `repeat` is set, `source` is tiled across the whole of `target`.
+#[allow(clippy::too_many_arguments)]
+fn draw_bitmap_data_cpu<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    source: BitmapDataWrapper<'gc>,
+    offset: (i32, i32),
+    color_transform: &ColorTransform,
+    blend_mode: BlendMode,
+    repeat: bool,
+) {
+    let (source_width, source_height) = (source.width() as i32, source.height() as i32);
+    let mut dest_region = if repeat {
+        PixelRegion::for_whole_size(target.width(), target.height())
+    } else {
+        PixelRegion::for_region_i32(offset.0, offset.1, source_width, source_height)
+    };
+    dest_region.clamp(target.width(), target.height());
+    if dest_region.width() == 0 || dest_region.height() == 0 {
+        return;
+    }
+
+    let source_region = if repeat {
+        PixelRegion::for_whole_size(source.width(), source.height())
+    } else {
+        PixelRegion::for_region(
+            (dest_region.x_min as i32 - offset.0) as u32,
+            (dest_region.y_min as i32 - offset.1) as u32,
+            dest_region.width(),
+            dest_region.height(),
+        )
+    };
+    let source_copy;
+    let source_read;
+    let source_pixels: &BitmapData = if source.ptr_eq(target) {
+        // Copy the source first, so that we don't read pixels we've already drawn over.
+        source_copy = clone(source);
+        &source_copy
+    } else {
+        source_read = source.read_area(source_region);
+        &source_read
+    };
+
+    let target = target.sync();
+    let mut write = target.write(context.gc_context);
+    let transparency = write.transparency();
+
+    for y in dest_region.y_min..dest_region.y_max {
+        for x in dest_region.x_min..dest_region.x_max {
+            // Without `repeat`, the destination region only covers the source once,
+            // so wrapping around has no effect.
+            let src_x = (x as i32 - offset.0).rem_euclid(source_width) as u32;
+            let src_y = (y as i32 - offset.1).rem_euclid(source_height) as u32;
+            let mut source_color = source_pixels.get_pixel32_raw(src_x, src_y);
+            if *color_transform != ColorTransform::IDENTITY {
+                let color =
+                    color_transform * swf::Color::from(source_color.to_un_multiplied_alpha());
+                source_color = Color::from(color).to_premultiplied_alpha(true);
+            }
+
+            let mut dest_color = write.get_pixel32_raw(x, y).blend(&source_color, blend_mode);
+            if !transparency {
+                dest_color = dest_color.with_alpha(0xFF);
+            }
+            write.set_pixel32_raw(x, y, dest_color);
+        }
+    }
+    write.set_cpu_dirty(dest_region);
+}
+
+pub fn get_vector(
+    target: BitmapDataWrapper,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> Vec<Avm2Value> {
+    let mut region = PixelRegion::for_region_i32(x, y, width, height);
+    region.clamp(target.width(), target.height());
+
+    let mut result = Vec::with_capacity((region.width() * region.height()) as usize);
+
+    let read = target.read_area(region);
+
+    for y in region.y_min..region.y_max {
+        for x in region.x_min..region.x_max {
+            let color = read.get_pixel32_raw(x, y);
+            let color = u32::from(color.to_un_multiplied_alpha());
+            result.push(color.into());
+        }
+    }
+
+    result
+}
+
+pub fn get_pixels_as_byte_array<'gc>(
+    target: BitmapDataWrapper,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+) -> Result<ByteArrayStorage, Error<'gc>> {
+    let mut result = ByteArrayStorage::new();
+    let mut region = PixelRegion::for_region_i32(x, y, width, height);
+    region.clamp(target.width(), target.height());
+
+    let read = target.read_area(region);
+    for y in region.y_min..region.y_max {
+        for x in region.x_min..region.x_max {
+            let color = read.get_pixel32_raw(x, y);
+            result.write_int(color.to_un_multiplied_alpha().into())?;
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn set_pixels_from_byte_array<'gc>(
+    context: &mut UpdateContext<'_, 'gc>,
+    target: BitmapDataWrapper<'gc>,
+    x: i32,
+    y: i32,
+    width: i32,
+    height: i32,
+    bytearray: &mut ByteArrayStorage,
+) -> Result<(), EofError> {
+    let mut region = PixelRegion::for_region_i32(x, y, width, height);
+    region.clamp(target.width(), target.height());
+    let transparency = target.transparency();
+
+    let target = if region.width() == target.width() && region.height() == target.height() {
+        // If we're filling the whole region, we can discard the gpu data
+        target.overwrite_cpu_pixels_from_gpu(context).0
+    } else {
+        // If we're filling a partial region, finish any gpu->cpu sync
+        target.sync()
+    };
+    let mut write = target.write(context.gc_context);
+
+    if region.width() > 0 && region.height() > 0 {
+        for y in region.y_min..region.y_max {
+            for x in region.x_min..region.x_max {
+                // Copy data from bytearray until EOFError or finished
+                let color = bytearray.read_int()?;
+                write.set_pixel32_raw(
+                    x,
+                    y,
+                    Color::from(color).to_premultiplied_alpha(transparency),
+                );
+            }
+        }
+
+        write.set_cpu_dirty(region);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::{with_player, with_update_context};
+    use gc_arena::{rootless_arena, GcCell, MutationContext};
+
+    fn new_bitmap_data<'gc>(
+        mc: MutationContext<'gc, '_>,
+        width: u32,
+        height: u32,
+        pixels: &[i32],
+    ) -> BitmapDataWrapper<'gc> {
+        let pixels = pixels
+            .iter()
+            .map(|p| Color::from(*p).to_premultiplied_alpha(true))
+            .collect();
+        let bitmap_data = BitmapData::new_with_pixels(width, height, true, pixels);
+        BitmapDataWrapper::new(GcCell::allocate(mc, bitmap_data))
+    }
+
+    fn with_bitmap_data<F>(width: u32, height: u32, pixels: &[i32], callback: F)
+    where
+        F: for<'gc> FnOnce(MutationContext<'gc, '_>, BitmapDataWrapper<'gc>),
+    {
+        rootless_arena(|mc| callback(mc, new_bitmap_data(mc, width, height, pixels)))
+    }
+
+    #[test]
+    fn get_pixels_as_byte_array_region() {
+        #[rustfmt::skip]
+        let pixels = [
+            0xFF112233u32 as i32, 0xFF445566u32 as i32, 0xFF000000u32 as i32,
+            0xFF778899u32 as i32, 0xFFAABBCCu32 as i32, 0xFF000000u32 as i32,
+            0xFF000000u32 as i32, 0xFF000000u32 as i32, 0xFF000000u32 as i32,
+        ];
+        with_bitmap_data(3, 3, &pixels, |_mc, bitmap_data| {
+            let bytearray = get_pixels_as_byte_array(bitmap_data, 0, 0, 2, 2)
+                .unwrap_or_else(|_| panic!("Failed to get pixels"));
+            assert_eq!(bytearray.len(), 16);
+            // Like Flash, the ByteArray is left positioned after the written pixels.
+            assert_eq!(bytearray.position(), 16);
+            assert_eq!(
+                bytearray.bytes(),
+                &[
+                    0xFF, 0x11, 0x22, 0x33, 0xFF, 0x44, 0x55, 0x66, 0xFF, 0x77, 0x88, 0x99, 0xFF,
+                    0xAA, 0xBB, 0xCC
+                ]
+            );
+        });
+    }
+
+    #[test]
+    fn get_pixels_as_byte_array_clamps_to_bounds() {
+        let pixels = [0xFF010203u32 as i32; 4];
+        with_bitmap_data(2, 2, &pixels, |_mc, bitmap_data| {
+            let bytearray = get_pixels_as_byte_array(bitmap_data, 1, 1, 4, 4)
+                .unwrap_or_else(|_| panic!("Failed to get pixels"));
+            assert_eq!(bytearray.bytes(), &[0xFF, 0x01, 0x02, 0x03]);
+        });
+    }
+
+    #[test]
+    fn compare_region_overlap() {
+        rootless_arena(|mc| {
+            let left = new_bitmap_data(mc, 4, 4, &[0xFF102030u32 as i32; 16]);
+            let mut right_pixels = [0xFF102030u32 as i32; 9];
+            right_pixels[4] = 0xFF000010u32 as i32;
+            let right = new_bitmap_data(mc, 3, 3, &right_pixels);
+
+            let diff = compare_region(left, right).expect("Overlap should differ");
+            assert_eq!((diff.width(), diff.height()), (3, 3));
+            for y in 0..3 {
+                for x in 0..3 {
+                    let expected = if (x, y) == (1, 1) {
+                        Color::argb(0xFF, 0x10, 0x20, 0x20)
+                    } else {
+                        Color::argb(0, 0, 0, 0)
+                    };
+                    assert_eq!(diff.get_pixel32_raw(x, y), expected);
+                }
+            }
+
+            let same = new_bitmap_data(mc, 3, 3, &[0xFF102030u32 as i32; 9]);
+            assert!(compare_region(left, same).is_none());
+        });
+    }
+
+    #[test]
+    fn compare_color_and_alpha_differences() {
+        rootless_arena(|mc| {
+            #[rustfmt::skip]
+            let (left, right, expected) = (
+                [0xFF102030u32, 0x80FFFFFF, 0xFF000000, 0x40FFFFFF, 0xFF336699],
+                [0xFF0A1040u32, 0x40FFFFFF, 0x80FF0000, 0xC0FFFFFF, 0xFF336699],
+                [0xFF0610F0u32, 0x40FFFFFF, 0xFF010000, 0x80FFFFFF, 0x00000000],
+            );
+            let left = new_bitmap_data(mc, 5, 1, &left.map(|p| p as i32));
+            let right = new_bitmap_data(mc, 5, 1, &right.map(|p| p as i32));
+
+            // Color differences are opaque and ignore alpha, even when the alpha differs too;
+            // alpha-only differences are white, with the alpha difference as their alpha.
+            let diff = compare(left, right).expect("Bitmaps should differ");
+            for (x, expected) in expected.into_iter().enumerate() {
+                assert_eq!(
+                    diff.get_pixel32_raw(x as u32, 0).to_un_multiplied_alpha(),
+                    Color::from(expected as i32),
+                    "pixel {x}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn compare_large_bitmaps() {
+        rootless_arena(|mc| {
+            let pixels = vec![0xFF336699u32 as i32; 2048 * 2048];
+            let left = new_bitmap_data(mc, 2048, 2048, &pixels);
+            let right = new_bitmap_data(mc, 2048, 2048, &pixels);
+            assert!(compare(left, right).is_none());
+
+            // Pixels before and after the first difference are still encoded.
+            let mut other_pixels = pixels;
+            other_pixels[2048 * 1000 + 5] = 0xFF336690u32 as i32;
+            other_pixels[2048 * 2048 - 1] = 0;
+            let other = new_bitmap_data(mc, 2048, 2048, &other_pixels);
+            let diff = compare(left, other).expect("Bitmaps should differ");
+            assert_eq!(diff.get_pixel32_raw(0, 0), Color::argb(0, 0, 0, 0));
+            assert_eq!(diff.get_pixel32_raw(5, 1000), Color::argb(0xFF, 0, 0, 0x09));
+            assert_eq!(diff.get_pixel32_raw(6, 1000), Color::argb(0, 0, 0, 0));
+            assert_eq!(
+                diff.get_pixel32_raw(2047, 2047),
+                Color::argb(0xFF, 0x33, 0x66, 0x99)
+            );
+        });
+    }
+
+    #[test]
+    fn draw_bitmap_data_multiply() {
+        with_update_context(|context| {
+            let target = new_bitmap_data(context.gc_context, 2, 2, &[0xFF808080u32 as i32; 4]);
+            let source = new_bitmap_data(context.gc_context, 2, 2, &[0xFF4080FFu32 as i32; 4]);
+            let result = draw(
+                context,
+                target,
+                IBitmapDrawable::BitmapData(source),
+                Transform::default(),
+                false,
+                BlendMode::Multiply,
+                None,
+                StageQuality::High,
+                false,
+            );
+            assert!(result.is_ok());
+            for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                assert_eq!(get_pixel32(target, x, y), 0xFF204080u32 as i32);
+            }
+        });
+    }
+
+    #[test]
+    fn draw_bitmap_data_onto_itself_matches_separate_source() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(0x7A11);
+        let pixels: Vec<i32> = (0..12 * 10).map(|_| rng.gen()).collect();
+        with_update_context(|context| {
+            for blend_mode in [BlendMode::Normal, BlendMode::Multiply, BlendMode::Add] {
+                for (tx, ty) in [(0, 0), (3, 2), (-4, 1), (5, -7)] {
+                    let transform = Transform {
+                        matrix: Matrix::translate(
+                            Twips::from_pixels(tx.into()),
+                            Twips::from_pixels(ty.into()),
+                        ),
+                        color_transform: ColorTransform {
+                            a_multiply: Fixed8::from_f64(0.9),
+                            ..ColorTransform::IDENTITY
+                        },
+                    };
+                    let target = new_bitmap_data(context.gc_context, 12, 10, &pixels);
+                    let expected = new_bitmap_data(context.gc_context, 12, 10, &pixels);
+                    let source = BitmapDataWrapper::new(GcCell::allocate(
+                        context.gc_context,
+                        clone(expected),
+                    ));
+
+                    for (target, source) in [(target, target), (expected, source)] {
+                        let result = draw(
+                            context,
+                            target,
+                            IBitmapDrawable::BitmapData(source),
+                            transform.clone(),
+                            false,
+                            blend_mode,
+                            None,
+                            StageQuality::High,
+                            false,
+                        );
+                        assert!(result.is_ok());
+                    }
+                    assert_eq!(
+                        target.sync().read().pixels(),
+                        expected.sync().read().pixels(),
+                        "{blend_mode:?} at ({tx}, {ty})"
+                    );
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn forced_cpu_draw_supports_bitmap_data_sources() {
+        with_update_context(|context| {
+            context.force_cpu_bitmap_operations = true;
+            let source = new_bitmap_data(context.gc_context, 2, 2, &[0xFF4080FFu32 as i32; 4]);
+            let transform = Transform {
+                matrix: Matrix::translate(Twips::from_pixels(1.0), Twips::from_pixels(-1.0)),
+                color_transform: Default::default(),
+            };
+            for blend_mode in [
+                BlendMode::Normal,
+                BlendMode::Layer,
+                BlendMode::Multiply,
+                BlendMode::Screen,
+                BlendMode::Lighten,
+                BlendMode::Darken,
+                BlendMode::Difference,
+                BlendMode::Add,
+                BlendMode::Subtract,
+                BlendMode::Invert,
+                BlendMode::Alpha,
+                BlendMode::Erase,
+                BlendMode::Overlay,
+                BlendMode::HardLight,
+            ] {
+                for repeat in [false, true] {
+                    let target =
+                        new_bitmap_data(context.gc_context, 3, 3, &[0xFF808080u32 as i32; 9]);
+                    let result = draw(
+                        context,
+                        target,
+                        IBitmapDrawable::BitmapData(source),
+                        transform.clone(),
+                        false,
+                        blend_mode,
+                        None,
+                        StageQuality::High,
+                        repeat,
+                    );
+                    assert!(result.is_ok(), "{blend_mode:?}, repeat {repeat}");
+                }
+            }
+
+            // A clip rect needs the render backend, so this is refused rather than rendered.
+            let target = new_bitmap_data(context.gc_context, 3, 3, &[0; 9]);
+            let result = draw(
+                context,
+                target,
+                IBitmapDrawable::BitmapData(source),
+                transform,
+                false,
+                BlendMode::Normal,
+                Some(Rectangle {
+                    x_min: Twips::ZERO,
+                    y_min: Twips::ZERO,
+                    x_max: Twips::from_pixels(2.0),
+                    y_max: Twips::from_pixels(2.0),
+                }),
+                StageQuality::High,
+                false,
+            );
+            assert!(matches!(result, Err(BitmapDataDrawError::Unimplemented)));
+        });
+    }
+
+    #[test]
+    fn draw_bitmap_data_repeat_tiles_source() {
+        let tile_pixel = |x: i32, y: i32| 0xFF000000u32 as i32 | (x << 8) | y;
+        with_update_context(|context| {
+            let tile: Vec<i32> = (0..16 * 16).map(|i| tile_pixel(i % 16, i / 16)).collect();
+            let source = new_bitmap_data(context.gc_context, 16, 16, &tile);
+            let target = new_bitmap_data(context.gc_context, 64, 64, &[0; 64 * 64]);
+            let transform = Transform {
+                matrix: Matrix::translate(Twips::from_pixels(5.0), Twips::from_pixels(-3.0)),
+                color_transform: Default::default(),
+            };
+            let result = draw(
+                context,
+                target,
+                IBitmapDrawable::BitmapData(source),
+                transform,
+                false,
+                BlendMode::Normal,
+                None,
+                StageQuality::High,
+                true,
+            );
+            assert!(result.is_ok());
+            for y in 0..64 {
+                for x in 0..64 {
+                    let expected = tile_pixel((x - 5).rem_euclid(16), (y + 3).rem_euclid(16));
+                    assert_eq!(
+                        get_pixel32(target, x as u32, y as u32),
+                        expected,
+                        "({x}, {y})"
+                    );
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn apply_outer_gradient_glow() {
+        with_update_context(|context| {
+            let mut pixels = [0; 100];
+            for y in 3..7 {
+                for x in 3..7 {
+                    pixels[x + y * 10] = 0xFF0000FFu32 as i32;
+                }
+            }
+            let bitmap_data = new_bitmap_data(context.gc_context, 10, 10, &pixels);
+            let filter = GradientFilter {
+                colors: vec![
+                    GradientRecord {
+                        ratio: 0,
+                        color: swf::Color::from_rgb(0xFF0000, 0),
+                    },
+                    GradientRecord {
+                        ratio: 255,
+                        color: swf::Color::from_rgb(0xFF0000, 255),
+                    },
+                ],
+                blur_x: swf::Fixed16::from_f64(4.0),
+                blur_y: swf::Fixed16::from_f64(4.0),
+                angle: swf::Fixed16::ZERO,
+                distance: swf::Fixed16::ZERO,
+                strength: Fixed8::ONE,
+                flags: swf::GradientFilterFlags::from_passes(1),
+            };
+            apply_filter(
+                context,
+                bitmap_data,
+                bitmap_data,
+                (0, 0),
+                (10, 10),
+                (0, 0),
+                Filter::GradientGlowFilter(filter),
+            );
+
+            // Just outside the left edge, a third of the blurred area is covered by the shape.
+            assert_eq!(get_pixel32(bitmap_data, 2, 4), 0x55FF0000);
+            // The shape itself is left untouched by an outer glow.
+            assert_eq!(get_pixel32(bitmap_data, 4, 4), 0xFF0000FFu32 as i32);
+            // Out of reach of the blur.
+            assert_eq!(get_pixel32(bitmap_data, 0, 0), 0);
+        });
+    }
+
+    #[test]
+    fn apply_filter_in_place_matches_separate_source() {
+        let mut pixels = [0; 100];
+        for y in 3..7 {
+            for x in 3..7 {
+                pixels[x + y * 10] = 0xFF0000FFu32 as i32;
+            }
+        }
+        let blur = Filter::BlurFilter(swf::BlurFilter {
+            blur_x: swf::Fixed16::from_f64(4.0),
+            blur_y: swf::Fixed16::from_f64(4.0),
+            flags: swf::BlurFilterFlags::from_passes(1),
+        });
+        let glow = Filter::GradientGlowFilter(GradientFilter {
+            colors: vec![
+                GradientRecord {
+                    ratio: 0,
+                    color: swf::Color::from_rgb(0xFF0000, 0),
+                },
+                GradientRecord {
+                    ratio: 255,
+                    color: swf::Color::from_rgb(0xFF0000, 255),
+                },
+            ],
+            blur_x: swf::Fixed16::from_f64(4.0),
+            blur_y: swf::Fixed16::from_f64(4.0),
+            angle: swf::Fixed16::ZERO,
+            distance: swf::Fixed16::ZERO,
+            strength: Fixed8::ONE,
+            flags: swf::GradientFilterFlags::from_passes(1),
+        });
+
+        with_update_context(|context| {
+            for filter in [blur, glow] {
+                // Filter a region that overlaps where it's written to, so that an in-place
+                // filter that reads its own output would show.
+                let target = new_bitmap_data(context.gc_context, 10, 10, &pixels);
+                let expected = new_bitmap_data(context.gc_context, 10, 10, &pixels);
+                let source = new_bitmap_data(context.gc_context, 10, 10, &pixels);
+                for (target, source) in [(target, target), (expected, source)] {
+                    apply_filter(
+                        context,
+                        target,
+                        source,
+                        (1, 1),
+                        (8, 8),
+                        (2, 2),
+                        filter.clone(),
+                    );
+                }
+                assert_eq!(
+                    target.sync().read().pixels(),
+                    expected.sync().read().pixels(),
+                    "{filter:?}"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn in_place_operations_copy_the_texture_on_the_gpu() {
+        use crate::test_utils::RecordingRenderer;
+        use ruffle_render::commands::Command;
+
+        let blur = Filter::BlurFilter(swf::BlurFilter {
+            blur_x: swf::Fixed16::from_f64(4.0),
+            blur_y: swf::Fixed16::from_f64(4.0),
+            flags: swf::BlurFilterFlags::from_passes(1),
+        });
+        with_update_context(|context| {
+            let mut renderer = RecordingRenderer::default();
+            let mut context = context.reborrow();
+            context.renderer = &mut renderer;
+
+            let target = new_bitmap_data(context.gc_context, 10, 8, &[0xFF0000FFu32 as i32; 80]);
+            let handle = target.bitmap_handle(context.gc_context, context.renderer);
+            apply_filter(&mut context, target, target, (0, 0), (10, 8), (0, 0), blur);
+            let result = draw(
+                &mut context,
+                target,
+                IBitmapDrawable::BitmapData(target),
+                Transform::default(),
+                false,
+                BlendMode::Normal,
+                None,
+                StageQuality::High,
+                false,
+            );
+            assert!(result.is_ok());
+            drop(context);
+
+            // Each operation copies the texture into a new one without uploading any pixels.
+            assert_eq!(renderer.empty_textures, [(10, 8), (10, 8)]);
+            assert_eq!(renderer.offscreen_renders.len(), 3);
+            for copy in &renderer.offscreen_renders[..2] {
+                assert!(matches!(
+                    copy.commands.as_slice(),
+                    [Command::RenderBitmap { bitmap, .. }] if *bitmap == handle
+                ));
+            }
+            // The draw then samples the copy rather than the texture it's writing to.
+            assert!(renderer.offscreen_renders[2]
+                .commands
+                .iter()
+                .all(|command| !matches!(command, Command::RenderBitmap { bitmap, .. } if *bitmap == handle)));
+        });
+    }
+
+    #[test]
+    fn draw_quality_sets_anti_aliasing_and_bitmap_smoothing() {
+        use crate::display_object::Bitmap;
+        use crate::test_utils::RecordingRenderer;
+        use ruffle_render::commands::Command;
+
+        /// Draws a bitmap at `quality`, returning the sample counts the backend was asked to
+        /// anti-alias with and the smoothing of the bitmaps it drew.
+        fn draw_at(quality: StageQuality, display_object: bool) -> (Vec<u32>, Vec<bool>) {
+            let mut sample_counts = Vec::new();
+            let mut smoothing = Vec::new();
+            with_update_context(|context| {
+                let mut renderer = RecordingRenderer::default();
+                let mut context = context.reborrow();
+                context.renderer = &mut renderer;
+
+                let source = new_bitmap_data(context.gc_context, 2, 2, &[-1; 4]);
+                let target = new_bitmap_data(context.gc_context, 4, 4, &[0; 16]);
+                let drawable = if display_object {
+                    let bitmap = Bitmap::new_with_bitmap_data(&mut context, 0, source, true);
+                    IBitmapDrawable::DisplayObject(bitmap.into())
+                } else {
+                    IBitmapDrawable::BitmapData(source)
+                };
+                let result = draw(
+                    &mut context,
+                    target,
+                    drawable,
+                    Transform::default(),
+                    true,
+                    BlendMode::Normal,
+                    None,
+                    quality,
+                    false,
+                );
+                assert!(result.is_ok());
+                drop(context);
+
+                sample_counts = renderer
+                    .offscreen_qualities
+                    .iter()
+                    .map(|quality| quality.sample_count())
+                    .collect();
+                smoothing = renderer
+                    .offscreen_renders
+                    .iter()
+                    .flat_map(|render| &render.commands)
+                    .filter_map(|command| match command {
+                        Command::RenderBitmap { smoothing, .. } => Some(*smoothing),
+                        _ => None,
+                    })
+                    .collect();
+            });
+            (sample_counts, smoothing)
+        }
+
+        // BEST only differs from HIGH in how Flash downsamples bitmaps, which Ruffle doesn't do.
+        for display_object in [false, true] {
+            let draw_with = |quality| draw_at(quality, display_object);
+            assert_eq!(draw_with(StageQuality::Low), (vec![1], vec![false]));
+            assert_eq!(draw_with(StageQuality::Medium), (vec![2], vec![true]));
+            assert_eq!(draw_with(StageQuality::High), (vec![4], vec![true]));
+            assert_eq!(draw_with(StageQuality::Best), (vec![4], vec![true]));
+        }
+    }
+
+    #[test]
+    fn draw_repeat_caps_tiles_on_the_gpu() {
+        use crate::test_utils::RecordingRenderer;
+        use ruffle_render::commands::Command;
+
+        fn tiles_rendered(scale: f32) -> Option<usize> {
+            let mut tiles = None;
+            with_update_context(|context| {
+                let mut renderer = RecordingRenderer::default();
+                let mut context = context.reborrow();
+                context.renderer = &mut renderer;
+
+                let source = new_bitmap_data(context.gc_context, 2, 2, &[-1; 4]);
+                let target = new_bitmap_data(context.gc_context, 10, 8, &[0; 80]);
+                let transform = Transform {
+                    matrix: Matrix::scale(scale, scale),
+                    color_transform: Default::default(),
+                };
+                let result = draw(
+                    &mut context,
+                    target,
+                    IBitmapDrawable::BitmapData(source),
+                    transform,
+                    false,
+                    BlendMode::Normal,
+                    None,
+                    StageQuality::High,
+                    true,
+                );
+                assert!(result.is_ok());
+                drop(context);
+
+                tiles = renderer.offscreen_renders.last().map(|render| {
+                    render
+                        .commands
+                        .iter()
+                        .filter(|command| matches!(command, Command::RenderBitmap { .. }))
+                        .count()
+                });
+            });
+            tiles
+        }
+
+        // A 2x2 source covers a 10x8 target in 5x4 tiles.
+        assert_eq!(tiles_rendered(1.0), Some(20));
+        // Scaled down to 1/1000th, it would take millions of tiles, so none are drawn.
+        assert_eq!(tiles_rendered(0.001).unwrap_or(0), 0);
+    }
+
+    /// Assembles a Pixel Bender kernel that samples its input at the current coordinate,
+    /// then runs `body` on the sampled color in `f1`, which is also the output.
+    fn shader_bytecode(body: &[u8]) -> Vec<u8> {
+        let mut bytecode = vec![0xA5, 1, 0, 0, 0];
+        bytecode.extend(b"\xA4\x04\x00Test");
+        // Parameters: `in float2 _OutCoord` in f0.rg, and `out pixel4 dst` in f1.
+        bytecode.extend(b"\xA1\x01\x02\x00\x00\x0C_OutCoord\x00");
+        bytecode.extend(b"\xA3\x00\x04src\x00");
+        bytecode.extend(b"\xA1\x02\x04\x01\x00\x0Fdst\x00");
+        // f1 = sampleNearest(src, f0.rg)
+        bytecode.extend([0x30, 0x01, 0x00, 0xF0, 0x00, 0x00, 0x10, 0x00]);
+        bytecode.extend(body);
+        bytecode
+    }
+
+    fn apply_shader(
+        context: &mut UpdateContext<'_, '_>,
+        pixels: &[i32],
+        bytecode: Vec<u8>,
+    ) -> Vec<i32> {
+        let source = new_bitmap_data(context.gc_context, 2, 1, pixels);
+        let target = new_bitmap_data(context.gc_context, 2, 1, &[0; 2]);
+        let filter = ShaderFilter {
+            bytecode: bytecode.into(),
+        };
+        apply_filter(
+            context,
+            target,
+            source,
+            (0, 0),
+            (2, 1),
+            (0, 0),
+            Filter::ShaderFilter(filter),
+        );
+        vec![get_pixel32(target, 0, 0), get_pixel32(target, 1, 0)]
+    }
+
+    #[test]
+    fn shader_filter_brightness() {
+        with_update_context(|context| {
+            let mut body = Vec::new();
+            // f2.r = 1.5
+            body.extend([0x32, 0x02, 0x00, 0x80]);
+            body.extend(1.5f32.to_be_bytes());
+            // f1.rgb *= f2.rrr
+            body.extend([0x03, 0x01, 0x00, 0xE2, 0x02, 0x00, 0x00, 0x00]);
+
+            let pixels = [0xFF204080u32 as i32, 0x80FFFFFFu32 as i32];
+            assert_eq!(
+                apply_shader(context, &pixels, shader_bytecode(&body)),
+                [0xFF3060C0u32 as i32, 0x80FFFFFFu32 as i32]
+            );
+        });
+    }
+
+    #[test]
+    fn shader_filter_unsupported_copies_source() {
+        with_update_context(|context| {
+            // f1 = sin(f1), which isn't interpreted.
+            let body = [0x0C, 0x01, 0x00, 0xF3, 0x01, 0x00, 0x1B, 0x00];
+            let pixels = [0xFF204080u32 as i32, 0x80FFFFFFu32 as i32];
+            assert_eq!(
+                apply_shader(context, &pixels, shader_bytecode(&body)),
+                pixels
+            );
+        });
+    }
+
+    #[test]
+    fn scroll_in_every_direction() {
+        // Pixels are numbered by their original position, to show where each one came from.
+        let pixels: Vec<i32> = (0..16).map(|i| 0xFF000000u32 as i32 | i).collect();
+        for (x, y) in [(1, 0), (-1, 0), (0, 2), (0, -2), (2, 1), (-1, -3), (3, -1)] {
+            with_update_context(|context| {
+                let bitmap_data = new_bitmap_data(context.gc_context, 4, 4, &pixels);
+                scroll(context, bitmap_data, x, y);
+                for dest_y in 0..4 {
+                    for dest_x in 0..4 {
+                        let (src_x, src_y) = (dest_x - x, dest_y - y);
+                        let source = if (0..4).contains(&src_x) && (0..4).contains(&src_y) {
+                            (src_x, src_y)
+                        } else {
+                            (dest_x, dest_y)
+                        };
+                        assert_eq!(
+                            get_pixel32(bitmap_data, dest_x as u32, dest_y as u32),
+                            pixels[(source.0 + source.1 * 4) as usize],
+                            "scroll({x}, {y}) at ({dest_x}, {dest_y})"
+                        );
+                    }
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn scroll_beyond_bounds_is_a_no_op() {
+        let pixels: Vec<i32> = (0..6).map(|i| 0xFF000000u32 as i32 | i).collect();
+        for (x, y) in [(3, 0), (-3, 0), (0, 2), (0, -2), (100, -100)] {
+            with_update_context(|context| {
+                let bitmap_data = new_bitmap_data(context.gc_context, 3, 2, &pixels);
+                scroll(context, bitmap_data, x, y);
+                for (i, expected) in pixels.iter().enumerate() {
+                    let (px, py) = (i as u32 % 3, i as u32 / 3);
+                    assert_eq!(
+                        get_pixel32(bitmap_data, px, py),
+                        *expected,
+                        "scroll({x}, {y})"
+                    );
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn fill_rect_sub_region() {
+        with_update_context(|context| {
+            let target = new_bitmap_data(context.gc_context, 4, 4, &[0xFF000000u32 as i32; 16]);
+            fill_rect(context, target, 1, 2, 2, 5, 0xFF00FF00u32 as i32);
+            for y in 0..4 {
+                for x in 0..4 {
+                    let expected = if (1..3).contains(&x) && (2..4).contains(&y) {
+                        0xFF00FF00u32
+                    } else {
+                        0xFF000000u32
+                    };
+                    assert_eq!(get_pixel32(target, x, y), expected as i32, "({x}, {y})");
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn flood_fill_stops_at_pixel_limit() {
+        with_update_context(|context| {
+            let filled = |target| {
+                (0..16)
+                    .filter(|i| get_pixel32(target, i % 4, i / 4) == 0xFF00FF00u32 as i32)
+                    .count()
+            };
+
+            let target = new_bitmap_data(context.gc_context, 4, 4, &[0xFF000000u32 as i32; 16]);
+            flood_fill(context, target, 1, 1, 0xFF00FF00u32 as i32, Some(5));
+            assert_eq!(filled(target), 5);
+
+            let target = new_bitmap_data(context.gc_context, 4, 4, &[0xFF000000u32 as i32; 16]);
+            flood_fill(context, target, 1, 1, 0xFF00FF00u32 as i32, None);
+            assert_eq!(filled(target), 16);
+        });
+    }
+
+    #[test]
+    fn region_is_uniform_and_redundant_fill() {
+        with_update_context(|context| {
+            let mut pixels = [0xFF000000u32 as i32; 16];
+            pixels[15] = 0xFFFF0000u32 as i32;
+            let target = new_bitmap_data(context.gc_context, 4, 4, &pixels);
+
+            let whole = PixelRegion::for_whole_size(4, 4);
+            assert_eq!(region_is_uniform(target, whole), None);
+            assert_eq!(
+                region_is_uniform(target, PixelRegion::for_region(0, 0, 3, 4)),
+                Some(0xFF000000)
+            );
+            assert_eq!(
+                region_is_uniform(target, PixelRegion::for_region(3, 3, 10, 10)),
+                Some(0xFFFF0000)
+            );
+            assert_eq!(
+                region_is_uniform(target, PixelRegion::for_region(4, 4, 1, 1)),
+                None
+            );
+
+            // Filling with the color that's already there doesn't need a new upload.
+            let texture = target
+                .draw_to_texture(context.gc_context, context.renderer)
+                .unwrap();
+            fill_rect(context, target, 0, 0, 3, 3, 0xFF000000u32 as i32);
+            target.draw_to_texture(context.gc_context, context.renderer);
+            assert!(target.is_texture_valid(&texture));
+
+            fill_rect(context, target, 0, 0, 4, 4, 0xFF000000u32 as i32);
+            target.draw_to_texture(context.gc_context, context.renderer);
+            assert!(!target.is_texture_valid(&texture));
+            assert_eq!(region_is_uniform(target, whole), Some(0xFF000000));
+        });
+    }
+
+    #[test]
+    fn redundant_fill_keeps_pixels_unallocated() {
+        with_update_context(|context| {
+            let cell = GcCell::allocate(context.gc_context, BitmapData::default());
+            cell.write(context.gc_context)
+                .init_pixels(4, 4, true, 0xFF112233u32 as i32);
+            let target = BitmapDataWrapper::new(cell);
+            let whole = PixelRegion::for_whole_size(4, 4);
+            let inner = PixelRegion::for_region(1, 1, 2, 2);
+
+            assert_eq!(region_is_uniform(target, inner), Some(0xFF112233));
+            fill_rect(context, target, 1, 1, 2, 2, 0xFF112233u32 as i32);
+            assert!(target.pending_fill(whole).is_some());
+
+            fill_rect(context, target, 1, 1, 2, 2, 0xFF000000u32 as i32);
+            assert!(target.pending_fill(whole).is_none());
+            assert_eq!(region_is_uniform(target, inner), Some(0xFF000000));
+            assert_eq!(region_is_uniform(target, whole), None);
+        });
+    }
+
+    #[test]
+    fn checksum_covers_transparency_and_region() {
+        rootless_arena(|mc| {
+            let pixels = vec![Color::from(0xFF112233u32 as i32); 4];
+            let opaque = BitmapData::new_with_pixels(2, 2, false, pixels.clone());
+            let opaque = BitmapDataWrapper::new(GcCell::allocate(mc, opaque));
+            let transparent = BitmapData::new_with_pixels(2, 2, true, pixels);
+            let transparent = BitmapDataWrapper::new(GcCell::allocate(mc, transparent));
+
+            let whole = PixelRegion::for_whole_size(2, 2);
+            assert_eq!(checksum(opaque, whole), checksum(opaque, whole));
+            assert_ne!(checksum(opaque, whole), checksum(transparent, whole));
+            // Same pixels, different shape.
+            assert_ne!(
+                checksum(opaque, PixelRegion::for_region(0, 0, 2, 1)),
+                checksum(opaque, PixelRegion::for_region(0, 0, 1, 2))
+            );
+            assert_eq!(
+                checksum(opaque, PixelRegion::for_region(0, 0, 10, 10)),
+                checksum(opaque, whole)
+            );
+        });
+    }
+
+    #[test]
+    fn fill_rect_opaque_wide_rows() {
+        with_update_context(|context| {
+            let pixels = vec![Color::from(0xFF000000u32 as i32); 15];
+            let bitmap_data = BitmapData::new_with_pixels(5, 3, false, pixels);
+            let bitmap_data =
+                BitmapDataWrapper::new(GcCell::allocate(context.gc_context, bitmap_data));
+            fill_rect(context, bitmap_data, -2, 1, 10, 1, 0x00336699);
+            assert_eq!(
+                checksum(bitmap_data, PixelRegion::for_whole_size(5, 3)),
+                0xc9b56529c3ab1af8
+            );
+            for y in 0..3 {
+                for x in 0..5 {
+                    let expected = if y == 1 { 0xFF336699u32 } else { 0xFF000000u32 };
+                    assert_eq!(
+                        get_pixel32(bitmap_data, x, y),
+                        expected as i32,
+                        "({x}, {y})"
+                    );
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn color_bounds_rect_single_differing_pixel() {
+        const WHITE: i32 = 0xFFFFFFFFu32 as i32;
+        const BLACK: i32 = 0xFF000000u32 as i32;
+        let mut pixels = [WHITE; 12];
+        pixels[4 + 2] = BLACK;
+        with_bitmap_data(4, 3, &pixels, |_mc, bitmap_data| {
+            let bounds =
+                |find_color, mask, color| color_bounds_rect(bitmap_data, find_color, mask, color);
+            assert_eq!(bounds(true, -1, BLACK), (2, 1, 1, 1));
+            assert_eq!(bounds(false, -1, WHITE), (2, 1, 1, 1));
+            assert_eq!(bounds(true, -1, WHITE), (0, 0, 4, 3));
+            assert_eq!(bounds(false, -1, BLACK), (0, 0, 4, 3));
+            // Only the masked channels take part in the comparison.
+            assert_eq!(bounds(false, 0xFF000000u32 as i32, BLACK), (0, 0, 0, 0));
+            assert_eq!(bounds(false, 0x00FF0000, 0x00FF0000), (2, 1, 1, 1));
+        });
+    }
+
+    #[test]
+    fn color_bounds_rect_uniform_bitmap() {
+        with_bitmap_data(4, 3, &[0xFF336699u32 as i32; 12], |_mc, bitmap_data| {
+            let bounds = |find_color, color| color_bounds_rect(bitmap_data, find_color, -1, color);
+            assert_eq!(bounds(true, 0xFF336699u32 as i32), (0, 0, 4, 3));
+            assert_eq!(bounds(false, 0xFF336699u32 as i32), (0, 0, 0, 0));
+            assert_eq!(bounds(true, 0xFF000000u32 as i32), (0, 0, 0, 0));
+            assert_eq!(bounds(false, 0xFF000000u32 as i32), (0, 0, 4, 3));
+        });
+    }
+
+    #[test]
+    fn color_bounds_rect_matches_brute_force() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        const COLORS: [i32; 4] = [0, 0xFF000000u32 as i32, 0x80FF0000u32 as i32, -1];
+        let mut rng = SmallRng::seed_from_u64(0xB0B5);
+        for _ in 0..500 {
+            let (width, height) = (rng.gen_range(1..10), rng.gen_range(1..10));
+            let density = rng.gen_range(0.0..1.0);
+            let pixels: Vec<i32> = (0..width * height)
+                .map(|_| {
+                    if rng.gen_bool(density) {
+                        COLORS[rng.gen_range(1..COLORS.len())]
+                    } else {
+                        COLORS[0]
+                    }
+                })
+                .collect();
+            let find_color = rng.gen();
+            let mask = [-1, 0xFF000000u32 as i32, 0x00FF0000][rng.gen_range(0..3)];
+            let color = COLORS[rng.gen_range(0..COLORS.len())] & mask;
+
+            with_bitmap_data(width, height, &pixels, |_mc, bitmap_data| {
+                let (mut min_x, mut max_x, mut min_y, mut max_y) = (width, 0, height, 0);
+                for y in 0..height {
+                    for x in 0..width {
+                        let pixel = i32::from(bitmap_data.read_pixel_raw(x, y));
+                        if ((pixel & mask) == color) == find_color {
+                            min_x = min_x.min(x);
+                            max_x = max_x.max(x);
+                            min_y = min_y.min(y);
+                            max_y = max_y.max(y);
+                        }
+                    }
+                }
+                let expected = if max_x > 0 || max_y > 0 {
+                    (min_x, min_y, max_x - min_x + 1, max_y - min_y + 1)
+                } else {
+                    (0, 0, 0, 0)
+                };
+                assert_eq!(
+                    color_bounds_rect(bitmap_data, find_color, mask, color),
+                    expected,
+                    "{width}x{height} {pixels:X?} find_color={find_color} mask={mask:X} color={color:X}"
+                );
+            });
+        }
+    }
+
+    #[test]
+    fn for_each_pixel_in_rect_average() {
+        #[rustfmt::skip]
+        let pixels = [
+            0xFF000000u32 as i32, 0xFF204060u32 as i32, 0xFFFFFFFFu32 as i32,
+            0xFF406080u32 as i32, 0xFF6080A0u32 as i32, 0xFFFFFFFFu32 as i32,
+        ];
+        with_bitmap_data(3, 2, &pixels, |_mc, bitmap_data| {
+            let mut count = 0;
+            let mut sum = [0u32; 3];
+            for_each_pixel_in_rect(
+                bitmap_data,
+                PixelRegion::for_region_i32(-1, -1, 3, 5),
+                |_, _, color| {
+                    count += 1;
+                    sum[0] += color.red() as u32;
+                    sum[1] += color.green() as u32;
+                    sum[2] += color.blue() as u32;
+                },
+            );
+            assert_eq!(count, 4);
+            assert_eq!(sum.map(|c| c / count), [0x30, 0x48, 0x60]);
+        });
+    }
+
+    #[test]
+    fn pixels_in_rect_row_major_order() {
+        with_bitmap_data(2, 2, &[0; 4], |_mc, bitmap_data| {
+            let coords: Vec<_> = bitmap_data
+                .pixels_in_rect(PixelRegion::for_whole_size(5, 5))
+                .map(|(x, y, _)| (x, y))
+                .collect();
+            assert_eq!(coords, [(0, 0), (1, 0), (0, 1), (1, 1)]);
+            assert_eq!(
+                bitmap_data
+                    .pixels_in_rect(PixelRegion::for_region(3, 0, 2, 2))
+                    .count(),
+                0
+            );
+        });
+    }
+
+    #[test]
+    fn clear_fills_whole_bitmap() {
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 3, 2, &[0x12345678; 6]);
+            clear(context, bitmap_data, 0xFF00FF80u32 as i32);
+            for y in 0..2 {
+                for x in 0..3 {
+                    assert_eq!(get_pixel32(bitmap_data, x, y), 0xFF00FF80u32 as i32);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn clear_opaque_forces_alpha() {
+        with_update_context(|context| {
+            let bitmap_data = BitmapData::new_with_pixels(2, 2, false, vec![Color::from(0); 4]);
+            let bitmap_data =
+                BitmapDataWrapper::new(GcCell::allocate(context.gc_context, bitmap_data));
+            clear(context, bitmap_data, 0x00336699);
+            for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+                assert_eq!(get_pixel32(bitmap_data, x, y), 0xFF336699u32 as i32);
+            }
+        });
+    }
+
+    #[test]
+    fn palette_map_clamps_channel_sums() {
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 1, 1, &[0xFF102030u32 as i32]);
+            let identity = |shift: u32| {
+                let mut array = [0; 256];
+                for (i, entry) in array.iter_mut().enumerate() {
+                    *entry = (i as u32) << shift;
+                }
+                array
+            };
+            let mut red = [0; 256];
+            red[0x10] = 0x00C00000;
+            let mut green = [0; 256];
+            green[0x20] = 0x00800000;
+
+            palette_map(
+                context,
+                bitmap_data,
+                bitmap_data,
+                (0, 0, 1, 1),
+                (0, 0),
+                (red, green, identity(0), identity(24)),
+            );
+            assert_eq!(get_pixel32(bitmap_data, 0, 0), 0xFFFF0030u32 as i32);
+        });
+    }
+
+    #[test]
+    fn palette_map_matches_per_channel_sums() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(0x9A1E77E);
+        let pixels: Vec<i32> = (0..64)
+            .map(|_| rng.gen::<i32>() | 0xFF000000u32 as i32)
+            .collect();
+        let mut array = |shift: u32, identity: bool| {
+            let mut array = [0; 256];
+            for (i, entry) in array.iter_mut().enumerate() {
+                *entry = if identity {
+                    (i as u32) << shift
+                } else {
+                    rng.gen::<u32>() & 0xFF7F7F7F
+                };
+            }
+            array
+        };
+        let arrays = (
+            array(16, false),
+            array(8, true),
+            array(0, false),
+            array(24, true),
+        );
+
+        let expected: Vec<i32> = pixels
+            .iter()
+            .map(|pixel| {
+                let color = Color::from(*pixel);
+                let entries = [
+                    arrays.0[color.red() as usize],
+                    arrays.1[color.green() as usize],
+                    arrays.2[color.blue() as usize],
+                    arrays.3[color.alpha() as usize],
+                ];
+                let channel = |shift: u32| {
+                    entries
+                        .iter()
+                        .map(|entry| (entry >> shift) & 0xFF)
+                        .sum::<u32>()
+                        .min(0xFF)
+                };
+                (channel(24) << 24 | channel(16) << 16 | channel(8) << 8 | channel(0)) as i32
+            })
+            .collect();
+
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 8, 8, &pixels);
+            palette_map(
+                context,
+                bitmap_data,
+                bitmap_data,
+                (0, 0, 8, 8),
+                (0, 0),
+                arrays,
+            );
+            for (i, expected) in expected.iter().enumerate() {
+                let (x, y) = (i as u32 % 8, i as u32 / 8);
+                assert_eq!(get_pixel32(bitmap_data, x, y), *expected, "({x}, {y})");
+            }
+        });
+    }
+
+    #[test]
+    fn set_pixel_hsv_keeps_alpha() {
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 2, 1, &[0x80FF0000u32 as i32; 2]);
+            assert_eq!(get_pixel_hsv(bitmap_data, 0, 0), (0.0, 1.0, 1.0));
+
+            set_pixel_hsv(context, bitmap_data, 1, 0, (120.0, 1.0, 1.0));
+            assert_eq!(get_pixel32(bitmap_data, 1, 0), 0x8000FF00u32 as i32);
+            assert_eq!(get_pixel_hsv(bitmap_data, 1, 0), (120.0, 1.0, 1.0));
+            assert_eq!(get_pixel_hsv(bitmap_data, 2, 0), (0.0, 0.0, 0.0));
+        });
+    }
+
+    #[test]
+    fn merge_from_source_sub_rect() {
+        let pixels: Vec<i32> = (0..16).map(|i| 0xFF000000u32 as i32 | i).collect();
+        with_update_context(|context| {
+            let source = new_bitmap_data(context.gc_context, 4, 4, &pixels);
+            let target = new_bitmap_data(context.gc_context, 4, 4, &[0xFF000000u32 as i32; 16]);
+            merge(
+                context,
+                target,
+                source,
+                (2, 2, 2, 2),
+                (1, 0),
+                (256, 256, 256, 256),
+            );
+            for y in 0..4 {
+                for x in 0..4 {
+                    let expected = if (1..3).contains(&x) && (0..2).contains(&y) {
+                        pixels[((y + 2) * 4 + x + 1) as usize]
+                    } else {
+                        0xFF000000u32 as i32
+                    };
+                    assert_eq!(get_pixel32(target, x, y), expected, "({x}, {y})");
+                }
+            }
+
+            // Merging a bitmap into itself reads the pixels from before the merge.
+            let target = new_bitmap_data(context.gc_context, 4, 1, &pixels[..4]);
+            merge(
+                context,
+                target,
+                target,
+                (0, 0, 3, 1),
+                (1, 0),
+                (256, 256, 256, 256),
+            );
+            let merged: Vec<i32> = (0..4).map(|x| get_pixel32(target, x, 0)).collect();
+            assert_eq!(merged, [pixels[0], pixels[0], pixels[1], pixels[2]]);
+        });
+    }
+
+    #[test]
+    fn merge_multipliers() {
+        const SOURCE: i32 = 0xFF804020u32 as i32;
+        const DEST: i32 = 0xFF20C0FFu32 as i32;
+        let channel = |color: i32, shift: u32| (color >> shift) as u32 & 0xFF;
+        with_update_context(|context| {
+            for mult in [-5, 0, 1, 64, 128, 255, 256, 300] {
+                let source = new_bitmap_data(context.gc_context, 1, 1, &[SOURCE]);
+                let target = new_bitmap_data(context.gc_context, 1, 1, &[DEST]);
+                merge(
+                    context,
+                    target,
+                    source,
+                    (0, 0, 1, 1),
+                    (0, 0),
+                    (mult, mult, mult, mult),
+                );
+
+                let m = mult.clamp(0, 256) as u32;
+                let expected = [24, 16, 8, 0].iter().fold(0, |color, shift| {
+                    let mixed =
+                        (channel(SOURCE, *shift) * m + channel(DEST, *shift) * (256 - m)) >> 8;
+                    color | (mixed << shift)
+                });
+                assert_eq!(
+                    get_pixel32(target, 0, 0),
+                    expected as i32,
+                    "multiplier {mult}"
+                );
+            }
+
+            let source = new_bitmap_data(context.gc_context, 1, 1, &[SOURCE]);
+            let target = new_bitmap_data(context.gc_context, 1, 1, &[DEST]);
+            merge(
+                context,
+                target,
+                source,
+                (0, 0, 1, 1),
+                (0, 0),
+                (128, 128, 128, 0),
+            );
+            assert_eq!(get_pixel32(target, 0, 0), 0xFF50808Fu32 as i32);
+        });
+    }
+
+    #[test]
+    fn flood_fill_follows_snake() {
+        const FILL: i32 = 0xFF00FF00u32 as i32;
+        const WALL: i32 = 0xFF000000u32 as i32;
+        const OPEN: i32 = 0xFFFFFFFFu32 as i32;
+        #[rustfmt::skip]
+        let rows = [
+            ".#...",
+            ".#.#.",
+            ".#.#.",
+            ".#.#.",
+            "...#.",
+        ];
+        let pixels: Vec<i32> = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| if c == '#' { WALL } else { OPEN })
+            .collect();
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 5, 5, &pixels);
+            flood_fill(context, bitmap_data, 0, 0, FILL);
+            assert_eq!(
+                checksum(bitmap_data, PixelRegion::for_whole_size(5, 5)),
+                0x8101b96b0c8b06f4
+            );
+            assert_eq!(
+                checksum(bitmap_data, PixelRegion::for_region(1, 1, 3, 2)),
+                0x7ef0cc296755f51d
+            );
+            for (i, expected) in pixels.iter().enumerate() {
+                let (x, y) = (i as u32 % 5, i as u32 / 5);
+                let expected = if *expected == OPEN { FILL } else { WALL };
+                assert_eq!(get_pixel32(bitmap_data, x, y), expected, "at ({x}, {y})");
+            }
+        });
+    }
+
+    #[test]
+    fn flood_fill_does_not_cross_diagonals() {
+        with_update_context(|context| {
+            let pixels = [0, -1, -1, 0];
+            let bitmap_data = new_bitmap_data(context.gc_context, 2, 2, &pixels);
+            flood_fill(context, bitmap_data, 0, 0, 0xFF123456u32 as i32);
+            assert_eq!(get_pixel32(bitmap_data, 0, 0), 0xFF123456u32 as i32);
+            assert_eq!(get_pixel32(bitmap_data, 1, 1), 0);
+        });
+    }
+
+    #[test]
+    fn flood_fill_does_not_leak_through_diagonal_wall() {
+        const FILL: i32 = 0xFF00FF00u32 as i32;
+        const WALL: i32 = 0xFF000000u32 as i32;
+        const OPEN: i32 = 0xFFFFFFFFu32 as i32;
+        // The wall is only one pixel thick along the diagonal, so the two sides
+        // touch at their corners but are not 4-connected.
+        #[rustfmt::skip]
+        let rows = [
+            "....#",
+            "...#.",
+            "..#..",
+            ".#...",
+            "#....",
+        ];
+        let pixels: Vec<i32> = rows
+            .iter()
+            .flat_map(|row| row.chars())
+            .map(|c| if c == '#' { WALL } else { OPEN })
+            .collect();
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 5, 5, &pixels);
+            flood_fill(context, bitmap_data, 0, 0, FILL);
+            for (i, expected) in pixels.iter().enumerate() {
+                let (x, y) = (i as u32 % 5, i as u32 / 5);
+                let expected = match *expected {
+                    OPEN if x + y < 4 => FILL,
+                    other => other,
+                };
+                assert_eq!(get_pixel32(bitmap_data, x, y), expected, "at ({x}, {y})");
+            }
+        });
+    }
+
+    #[test]
+    fn flood_fill_with_seed_color_is_a_no_op() {
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 3, 3, &[-1; 9]);
+            flood_fill(context, bitmap_data, 1, 1, -1);
+            assert_eq!(get_pixel32(bitmap_data, 0, 0), -1);
+        });
+    }
+
+    #[test]
+    fn flood_fill_large_bitmap() {
+        with_update_context(|context| {
+            let (width, height) = (2880, 2880);
+            let pixels = vec![Color::from(0); (width * height) as usize];
+            let bitmap_data = BitmapData::new_with_pixels(width, height, true, pixels);
+            let bitmap_data =
+                BitmapDataWrapper::new(GcCell::allocate(context.gc_context, bitmap_data));
+            flood_fill(context, bitmap_data, 1000, 2000, -1);
+            for (x, y) in [
+                (0, 0),
+                (width - 1, 0),
+                (0, height - 1),
+                (width - 1, height - 1),
+            ] {
+                assert_eq!(get_pixel32(bitmap_data, x, y), -1);
+            }
+        });
+    }
+
+    #[test]
+    fn hit_test_threshold_is_inclusive() {
+        with_bitmap_data(
+            2,
+            1,
+            &[0x80FFFFFFu32 as i32, 0x7FFFFFFF],
+            |_mc, bitmap_data| {
+                assert!(hit_test_point(bitmap_data, 0x80, (0, 0)));
+                assert!(!hit_test_point(bitmap_data, 0x81, (0, 0)));
+                assert!(!hit_test_point(bitmap_data, 0x80, (1, 0)));
+
+                assert!(hit_test_rectangle(bitmap_data, 0x80, (0, 0), (2, 1)));
+                assert!(!hit_test_rectangle(bitmap_data, 0x80, (1, 0), (1, 1)));
+            },
+        );
+    }
+
+    #[test]
+    fn hit_test_rectangle_clamps_to_bounds() {
+        with_bitmap_data(2, 2, &[0, 0, 0, -1], |_mc, bitmap_data| {
+            assert!(hit_test_rectangle(bitmap_data, 0xFF, (-5, -5), (100, 100)));
+            assert!(!hit_test_rectangle(bitmap_data, 0x01, (-5, -5), (6, 100)));
+            assert!(!hit_test_rectangle(bitmap_data, 0x01, (2, 2), (10, 10)));
+        });
+    }
+
+    #[test]
+    fn hit_test_bitmapdata_overlap_edges() {
+        rootless_arena(|mc| {
+            let solid = new_bitmap_data(mc, 4, 4, &[-1; 16]);
+            let mut corner_pixels = [-1; 16];
+            corner_pixels[0] = 0x40FFFFFF;
+            let corner = new_bitmap_data(mc, 4, 4, &corner_pixels);
+
+            // Touching edges and corners don't overlap.
+            for test_point in [(14, 10), (10, 14), (6, 10), (14, 14), (6, 6)] {
+                assert!(!hit_test_bitmapdata(
+                    solid,
+                    (10, 10),
+                    0xFF,
+                    solid,
+                    test_point,
+                    0xFF
+                ));
+            }
+
+            // A single overlapping pixel is enough, and its alpha still has to pass the threshold.
+            assert!(hit_test_bitmapdata(
+                solid,
+                (10, 10),
+                0xFF,
+                corner,
+                (13, 13),
+                0x40
+            ));
+            assert!(!hit_test_bitmapdata(
+                solid,
+                (10, 10),
+                0xFF,
+                corner,
+                (13, 13),
+                0x41
+            ));
+            assert!(hit_test_bitmapdata(
+                corner,
+                (13, 13),
+                0x40,
+                solid,
+                (10, 10),
+                0xFF
+            ));
+        });
+    }
+
+    fn perlin_noise_pixels(channel_options: ChannelOptions, grayscale: bool) -> Vec<i32> {
+        let mut pixels = Vec::new();
+        with_update_context(|context| {
+            let bitmap_data = new_bitmap_data(context.gc_context, 16, 16, &[0; 256]);
+            perlin_noise(
+                context,
+                bitmap_data,
+                (4.0, 4.0),
+                2,
+                1,
+                false,
+                true,
+                channel_options,
+                grayscale,
+                vec![(0.0, 0.0); 2],
+            );
+            for y in 0..16 {
+                for x in 0..16 {
+                    pixels.push(get_pixel32(bitmap_data, x, y));
+                }
+            }
+        });
+        pixels
+    }
+
+    #[test]
+    fn copy_pixels_onto_overlapping_self() {
+        let (width, height) = (8, 6);
+        let pixels: Vec<i32> = (0..width * height)
+            .map(|i| 0xFF000000u32 as i32 | (i * 0x040302))
+            .collect();
+        for (dx, dy) in [(2, 1), (-2, -1), (3, 0), (0, -2)] {
+            let (src_x, src_y, src_width, src_height) = (2, 1, 5, 4);
+            let mut expected = pixels.clone();
+            for y in src_y..src_y + src_height {
+                for x in src_x..src_x + src_width {
+                    let (dest_x, dest_y) = (x + dx, y + dy);
+                    if (0..width).contains(&dest_x) && (0..height).contains(&dest_y) {
+                        expected[(dest_y * width + dest_x) as usize] =
+                            pixels[(y * width + x) as usize];
+                    }
+                }
+            }
+
+            with_update_context(|context| {
+                let bitmap_data =
+                    new_bitmap_data(context.gc_context, width as u32, height as u32, &pixels);
+                copy_pixels(
+                    context,
+                    bitmap_data,
+                    bitmap_data,
+                    (src_x, src_y, src_width, src_height),
+                    (src_x + dx, src_y + dy),
+                    false,
+                );
+                for (i, expected) in expected.iter().enumerate() {
+                    let (x, y) = (i as u32 % width as u32, i as u32 / width as u32);
+                    assert_eq!(
+                        get_pixel32(bitmap_data, x, y),
+                        *expected,
+                        "at ({x}, {y}) shifted by ({dx}, {dy})"
+                    );
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn copy_pixels_matches_per_pixel_reference() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(0x5EED);
+        with_update_context(|context| {
+            let mc = context.gc_context;
+            let random_bitmap = |rng: &mut SmallRng, transparency: bool| {
+                let (width, height) = (rng.gen_range(1..12), rng.gen_range(1..12));
+                let pixels = (0..width * height)
+                    .map(|_| {
+                        let color = Color::from(rng.gen::<i32>());
+                        let color = if transparency {
+                            color
+                        } else {
+                            color.with_alpha(0xFF)
+                        };
+                        color.to_premultiplied_alpha(transparency)
+                    })
+                    .collect();
+                let bitmap_data = BitmapData::new_with_pixels(width, height, transparency, pixels);
+                BitmapDataWrapper::new(GcCell::allocate(mc, bitmap_data))
+            };
+            let pixels =
+                |bitmap_data: BitmapDataWrapper| bitmap_data.sync().read().pixels().to_vec();
+
+            for _ in 0..500 {
+                let transparency = rng.gen();
+                let target = random_bitmap(&mut rng, transparency);
+                let source = if rng.gen_bool(0.3) {
+                    target
+                } else {
+                    let transparency = rng.gen();
+                    random_bitmap(&mut rng, transparency)
+                };
+                let src_rect = (
+                    rng.gen_range(-4..12),
+                    rng.gen_range(-4..12),
+                    rng.gen_range(-2..14),
+                    rng.gen_range(-2..14),
+                );
+                let dest_point = (rng.gen_range(-6..14), rng.gen_range(-6..14));
+                let merge_alpha = rng.gen_bool(0.2);
+
+                // Model the copy one pixel at a time, always reading the original pixels.
+                let (width, height) = (target.width() as i32, target.height() as i32);
+                let (source_width, source_height) = (source.width() as i32, source.height() as i32);
+                let source_pixels = pixels(source);
+                let mut expected = pixels(target);
+                for y in src_rect.1..src_rect.1 + src_rect.3 {
+                    for x in src_rect.0..src_rect.0 + src_rect.2 {
+                        let dest_x = x - src_rect.0 + dest_point.0;
+                        let dest_y = y - src_rect.1 + dest_point.1;
+                        if !(0..source_width).contains(&x)
+                            || !(0..source_height).contains(&y)
+                            || !(0..width).contains(&dest_x)
+                            || !(0..height).contains(&dest_y)
+                        {
+                            continue;
+                        }
+                        let source_color = source_pixels[(y * source_width + x) as usize];
+                        let dest = &mut expected[(dest_y * width + dest_x) as usize];
+                        if (source.transparency() && !transparency) || merge_alpha {
+                            *dest = dest.blend_over(&source_color);
+                        } else {
+                            *dest = source_color;
+                        }
+                        if !transparency {
+                            *dest = dest.with_alpha(0xFF);
+                        }
+                    }
+                }
+
+                copy_pixels(context, target, source, src_rect, dest_point, merge_alpha);
+                assert_eq!(
+                    pixels(target),
+                    expected,
+                    "copying {src_rect:?} to {dest_point:?} (merge_alpha: {merge_alpha})"
+                );
+            }
+        });
+    }
+
+    #[test]
+    fn threshold_masks_threshold_value() {
+        let pixels = [
+            0xFF102030u32 as i32,
+            0xFF112030u32 as i32,
+            0xFF10FF30u32 as i32,
+            0xFF000000u32 as i32,
+        ];
+        with_update_context(|context| {
+            let source = new_bitmap_data(context.gc_context, 4, 1, &pixels);
+            // Only the red channel is compared; the other bits of the threshold must be ignored.
+            for (operation, threshold, expected) in [
+                (
+                    ThresholdOperation::Equals,
+                    0xAA10BBCC,
+                    [true, false, true, false],
+                ),
+                (
+                    ThresholdOperation::LessThan,
+                    0xFF11FFFF,
+                    [true, false, true, true],
+                ),
+                (
+                    ThresholdOperation::GreaterThan,
+                    0x0010FFFF,
+                    [false, true, false, false],
+                ),
+            ] {
+                let target = new_bitmap_data(context.gc_context, 4, 1, &[0; 4]);
+                let modified = threshold(
+                    context,
+                    target,
+                    source,
+                    (0, 0, 4, 1),
+                    (0, 0),
+                    operation,
+                    threshold,
+                    -1,
+                    0x00FF0000,
+                    false,
+                );
+                assert_eq!(
+                    modified as usize,
+                    expected.iter().filter(|passed| **passed).count()
+                );
+                for (x, passed) in expected.into_iter().enumerate() {
+                    assert_eq!(
+                        get_pixel32(target, x as u32, 0),
+                        if passed { -1 } else { 0 }
+                    );
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn threshold_chunked_matches_pixelwise() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        const OPERATIONS: [ThresholdOperation; 6] = [
+            ThresholdOperation::Equals,
+            ThresholdOperation::NotEquals,
+            ThresholdOperation::LessThan,
+            ThresholdOperation::LessThanOrEquals,
+            ThresholdOperation::GreaterThan,
+            ThresholdOperation::GreaterThanOrEquals,
+        ];
+
+        let mut rng = SmallRng::seed_from_u64(0x7E57);
+        let random_bitmap = |rng: &mut SmallRng, width: u32, height: u32| {
+            // A small palette, so that `Equals` passes often enough to matter.
+            let palette: Vec<i32> = (0..4).map(|_| rng.gen()).collect();
+            let pixels = (0..width * height)
+                .map(|_| {
+                    let color = palette[rng.gen_range(0..palette.len())];
+                    Color::from(color).to_premultiplied_alpha(true)
+                })
+                .collect();
+            BitmapData::new_with_pixels(width, height, true, pixels)
+        };
+
+        for _ in 0..500 {
+            let (width, height) = (rng.gen_range(1..24), rng.gen_range(1..12));
+            let target = random_bitmap(&mut rng, width, height);
+            // `copy_source` reads the source at destination coordinates, so keep it at least as
+            // large as the target.
+            let source_size = (width + rng.gen_range(0..4), height + rng.gen_range(0..4));
+            let source = random_bitmap(&mut rng, source_size.0, source_size.1);
+            let src_rect = (
+                rng.gen_range(-4..24),
+                rng.gen_range(-4..12),
+                rng.gen_range(-2..28),
+                rng.gen_range(-2..14),
+            );
+            let dest_point = (rng.gen_range(-6..24), rng.gen_range(-6..12));
+            let test = ThresholdTest {
+                operation: OPERATIONS[rng.gen_range(0..OPERATIONS.len())],
+                masked_threshold: source.pixels()[0].to_un_multiplied_alpha().into(),
+                mask: [u32::MAX, 0xFF00FF00, 0x000000FF][rng.gen_range(0..3)],
+                colour: Color::from(rng.gen::<i32>()),
+                copy_source: rng.gen(),
+            };
+            let test = ThresholdTest {
+                masked_threshold: test.masked_threshold & test.mask,
+                ..test
+            };
+
+            let mut expected = target.clone();
+            let expected_result =
+                threshold_pixelwise(&mut expected, Some(&source), src_rect, dest_point, test);
+            let mut actual = target.clone();
+            let actual_result =
+                threshold_chunked(&mut actual, Some(&source), src_rect, dest_point, test);
+            assert_eq!(
+                actual_result, expected_result,
+                "{src_rect:?} to {dest_point:?}"
+            );
+            assert_eq!(actual.pixels(), expected.pixels());
+
+            // Thresholding a bitmap onto a separate part of itself.
+            let self_rect = (0, 0, width as i32 / 2, height as i32);
+            let self_dest = (width as i32 - width as i32 / 2, rng.gen_range(-2..3));
+            let mut expected = target.clone();
+            let expected_result =
+                threshold_pixelwise(&mut expected, None, self_rect, self_dest, test);
+            let mut actual = target.clone();
+            let actual_result = threshold_chunked(&mut actual, None, self_rect, self_dest, test);
+            assert_eq!(actual_result, expected_result);
+            assert_eq!(actual.pixels(), expected.pixels());
+        }
+    }
+
+    #[test]
+    fn color_transform_round_trips_match_per_pixel_reference() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(0xFADE);
+        let pixels: Vec<i32> = (0..32 * 32).map(|_| rng.gen()).collect();
+        let forward = ColorTransform {
+            r_multiply: Fixed8::from_f64(0.75),
+            a_multiply: Fixed8::from_f64(0.5),
+            r_add: 10,
+            g_add: -1,
+            ..ColorTransform::IDENTITY
+        };
+        let inverse = ColorTransform {
+            r_multiply: Fixed8::from_f64(1.0 / 0.75),
+            a_multiply: Fixed8::from_f64(2.0),
+            r_add: -10,
+            g_add: 1,
+            ..ColorTransform::IDENTITY
+        };
+
+        with_update_context(|context| {
+            let target = new_bitmap_data(context.gc_context, 32, 32, &pixels);
+            let mut expected = target.sync().read().pixels().to_vec();
+            for _ in 0..100 {
+                for transform in [&forward, &inverse] {
+                    // Leave the outer ring of pixels untouched.
+                    color_transform(context, target, 1, 1, 31, 31, transform);
+                    for y in 1..31 {
+                        for x in 1..31 {
+                            let pixel = &mut expected[y * 32 + x];
+                            let color =
+                                transform * swf::Color::from(pixel.to_un_multiplied_alpha());
+                            *pixel = Color::from(color).to_premultiplied_alpha(true);
+                        }
+                    }
+                }
+            }
+            assert_eq!(target.sync().read().pixels(), &expected[..]);
+
+            // Identity transforms and empty regions leave every pixel alone.
+            color_transform(context, target, 0, 0, 32, 32, &ColorTransform::IDENTITY);
+            color_transform(context, target, 20, 4, 10, 40, &forward);
+            assert_eq!(target.sync().read().pixels(), &expected[..]);
+        });
+    }
+
+    #[test]
+    fn pixel_dissolve_from_source_bitmap() {
+        let source_pixels: Vec<i32> = (0..64)
+            .map(|i| 0xFF000000u32 as i32 | i * 0x010203)
+            .collect();
+        with_update_context(|context| {
+            let mc = context.gc_context;
+            let source = new_bitmap_data(mc, 8, 8, &source_pixels);
+            let filled = new_bitmap_data(mc, 10, 10, &[0xFF000000u32 as i32; 100]);
+            let dissolved = new_bitmap_data(mc, 10, 10, &[0xFF000000u32 as i32; 100]);
+
+            let (src_rect, dest_point) = ((1, 1, 6, 5), (3, 2));
+            let fill_seed = pixel_dissolve(
+                context, filled, None, src_rect, dest_point, 42, 12, 0xFFFFFFFF,
+            );
+            let source_seed = pixel_dissolve(
+                context,
+                dissolved,
+                Some(source),
+                src_rect,
+                dest_point,
+                42,
+                12,
+                0,
+            );
+            assert_eq!(fill_seed, source_seed);
+
+            // Both modes visit the same pixels; the source mode copies source content there.
+            let mut visited = 0;
+            for y in 0..10 {
+                for x in 0..10 {
+                    let color = get_pixel32(dissolved, x, y);
+                    if get_pixel32(filled, x, y) == 0xFFFFFFFFu32 as i32 {
+                        visited += 1;
+                        let (source_x, source_y) = (x as i32 - 2, y as i32 - 1);
+                        assert_eq!(color, source_pixels[(source_y * 8 + source_x) as usize]);
+                    } else {
+                        assert_eq!(color, 0xFF000000u32 as i32);
+                    }
+                }
+            }
+            assert_eq!(visited, 12);
+
+            // Continuing from the returned seed finishes the region, visiting every pixel once.
+            pixel_dissolve(
+                context,
+                dissolved,
+                Some(source),
+                src_rect,
+                dest_point,
+                source_seed,
+                18,
+                0,
+            );
+            for y in 0..10 {
+                for x in 0..10 {
+                    let (source_x, source_y) = (x as i32 - 2, y as i32 - 1);
+                    let expected = if (1..7).contains(&source_x) && (1..6).contains(&source_y) {
+                        source_pixels[(source_y * 8 + source_x) as usize]
+                    } else {
+                        0xFF000000u32 as i32
+                    };
+                    assert_eq!(get_pixel32(dissolved, x, y), expected);
+                }
+            }
+        });
+    }
+
+    #[test]
+    fn pixel_dissolve_follows_digital_dissolve_order() {
+        with_update_context(|context| {
+            let target = new_bitmap_data(context.gc_context, 3, 2, &[0xFF000000u32 as i32; 6]);
+            let white = 0xFFFFFFFFu32;
+
+            // The register holds the column in its low two bits and the row above them, so
+            // the order for a 3x2 region starting from seed 1 is fixed, ending at the origin.
+            let order = [(1, 1), (2, 0), (0, 1), (2, 1), (1, 0), (0, 0)];
+            let mut seed = 1;
+            for (step, &(x, y)) in order.iter().enumerate() {
+                assert_eq!(get_pixel32(target, x, y), 0xFF000000u32 as i32);
+                seed = pixel_dissolve(context, target, None, (0, 0, 3, 2), (0, 0), seed, 1, white);
+                assert_eq!(get_pixel32(target, x, y), white as i32, "step {step}");
+                if step == 2 {
+                    assert_eq!(seed, 5);
+                }
+            }
+            // A full cycle brings the register back to where it started.
+            assert_eq!(seed, 1);
+        });
+    }
+
+    #[test]
+    fn noise_parallel_matches_serial() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let hash = |pixels: &mut dyn Iterator<Item = i32>| {
+            let mut hasher = DefaultHasher::new();
+            pixels.for_each(|pixel| pixel.hash(&mut hasher));
+            hasher.finish()
+        };
+        let (width, height) = (53, 29);
+        for seed in [-7, 0, 1, 1234, i32::MAX - 1] {
+            for (options, gray_scale) in [(15, false), (7, false), (5, false), (8, true), (0, true)]
+            {
+                let channel_options = ChannelOptions::from_bits_truncate(options);
+                let render = |parallel| {
+                    let mut bitmap_data = BitmapData::new_with_pixels(
+                        width,
+                        height,
+                        true,
+                        vec![Color::from(0); (width * height) as usize],
+                    );
+                    write_noise(
+                        &mut bitmap_data,
+                        parallel,
+                        seed,
+                        16,
+                        240,
+                        channel_options,
+                        gray_scale,
+                    );
+                    hash(&mut bitmap_data.pixels().iter().map(|p| i32::from(*p)))
+                };
+
+                // The whole bitmap is drawn from a single sequence, in row-major order.
+                let true_seed = if seed <= 0 { -seed + 1 } else { seed };
+                let mut rng = LehmerRng::with_seed(true_seed as u32);
+                let mut channel = |enabled: bool, default| {
+                    if enabled {
+                        rng.gen_range(16..240)
+                    } else {
+                        default
+                    }
+                };
+                let has = |channel| channel_options.contains(channel);
+                let expected = hash(&mut (0..width * height).map(|_| {
+                    let color = if gray_scale {
+                        let gray = channel(true, 0);
+                        Color::argb(channel(has(ChannelOptions::ALPHA), 255), gray, gray, gray)
+                    } else {
+                        let r = channel(has(ChannelOptions::RED), 0);
+                        let g = channel(has(ChannelOptions::GREEN), 0);
+                        let b = channel(has(ChannelOptions::BLUE), 0);
+                        Color::argb(channel(has(ChannelOptions::ALPHA), 255), r, g, b)
+                    };
+                    i32::from(color)
+                }));
+
+                assert_eq!(render(false), expected, "seed {seed}, options {options}");
+                assert_eq!(render(true), expected, "seed {seed}, options {options}");
+            }
+        }
+    }
+
+    #[test]
+    fn noise_gray_scale() {
+        for (transparency, options, random_alpha) in [
+            (true, 0, false),
+            (true, 8, true),
+            (false, 8, false),
+            (false, 15, false),
+        ] {
+            let mut bitmap_data =
+                BitmapData::new_with_pixels(16, 16, transparency, vec![Color::from(0); 16 * 16]);
+            let channel_options = ChannelOptions::from_bits_truncate(options);
+            write_noise(&mut bitmap_data, false, 42, 0, 200, channel_options, true);
+
+            let pixels = bitmap_data.pixels();
+            for pixel in pixels {
+                assert_eq!(pixel.red(), pixel.green(), "{transparency} {options}");
+                assert_eq!(pixel.green(), pixel.blue(), "{transparency} {options}");
+            }
+            // Flash leaves the alpha channel to `channelOptions`, but only where it can show.
+            assert_eq!(
+                pixels.iter().any(|pixel| pixel.alpha() != 255),
+                random_alpha,
+                "{transparency} {options}"
+            );
+        }
+    }
+
+    #[test]
+    fn perlin_noise_parallel_matches_serial() {
+        use std::collections::hash_map::DefaultHasher;
+        use std::hash::{Hash, Hasher};
+
+        let render = |parallel| {
+            let mut bitmap_data =
+                BitmapData::new_with_pixels(97, 61, true, vec![Color::from(0); 97 * 61]);
+            write_perlin_noise(
+                &mut bitmap_data,
+                parallel,
+                (24.0, 16.0),
+                6,
+                1234,
+                true,
+                true,
+                ChannelOptions::from_bits_truncate(15),
+                false,
+                &[(3.0, 7.0); 6],
+            );
+            let mut hasher = DefaultHasher::new();
+            for pixel in bitmap_data.pixels() {
+                i32::from(*pixel).hash(&mut hasher);
+            }
+            hasher.finish()
+        };
+        assert_eq!(render(false), render(true));
+    }
+
+    #[test]
+    fn perlin_noise_all_channels_varies_alpha() {
+        let pixels = perlin_noise_pixels(ChannelOptions::from_bits_truncate(15), false);
+        let alpha = |p: &i32| (*p as u32) >> 24;
+        assert!(pixels.iter().any(|p| alpha(p) != alpha(&pixels[0])));
+    }
+
+    #[test]
+    fn perlin_noise_without_alpha_is_opaque() {
+        let pixels = perlin_noise_pixels(ChannelOptions::from_bits_truncate(7), false);
+        assert!(pixels.iter().all(|p| (*p as u32) >> 24 == 0xFF));
+    }
+
+    #[test]
+    fn perlin_noise_grayscale_writes_identical_rgb() {
+        let pixels = perlin_noise_pixels(ChannelOptions::from_bits_truncate(7), true);
+        for p in pixels {
+            let [b, g, r, a] = p.to_le_bytes();
+            assert_eq!(a, 0xFF);
+            assert!(r == g && g == b, "{p:#010X} is not gray");
+        }
+    }
+
+    #[test]
+    fn clone_shares_pixels_until_written() {
+        let pixels = [0xFF102030u32 as i32, 0xFF405060u32 as i32];
+        with_update_context(|context| {
+            let original = new_bitmap_data(context.gc_context, 2, 1, &pixels);
+            let copy =
+                BitmapDataWrapper::new(GcCell::allocate(context.gc_context, clone(original)));
+            assert!(copy
+                .sync()
+                .read()
+                .shares_pixels_with(&original.sync().read()));
+
+            set_pixel32(context, copy, 1, 0, 0xFFFFFFFFu32 as i32);
+            assert!(!copy
+                .sync()
+                .read()
+                .shares_pixels_with(&original.sync().read()));
+            assert_eq!(i32::from(original.read_pixel_raw(1, 0)), pixels[1]);
+            assert_eq!(i32::from(copy.read_pixel_raw(1, 0)), 0xFFFFFFFFu32 as i32);
+        });
+    }
+
+    #[test]
+    fn set_pixels_shares_storage() {
+        rootless_arena(|mc| {
+            let source = new_bitmap_data(mc, 2, 2, &[0xFF000000u32 as i32; 4]);
+            let mut copy = BitmapData::default();
+            copy.set_pixels(2, 2, true, source.sync().read().shared_pixels());
+            assert!(copy.shares_pixels_with(&source.sync().read()));
+
+            copy.pixels_mut()[0] = Color::from(0xFFFFFFFFu32 as i32);
+            assert!(!copy.shares_pixels_with(&source.sync().read()));
+            assert_eq!(
+                i32::from(source.sync().read().pixels()[0]),
+                0xFF000000u32 as i32
+            );
+        });
+    }
+
+    #[test]
+    fn draw_at_frame_leaves_source_alone() {
+        use crate::character::Character;
+        use crate::tag_utils::{SwfMovie, SwfSlice};
+        use std::sync::Arc;
+
+        let header = swf::Header {
+            compression: swf::Compression::None,
+            version: 10,
+            stage_size: Rectangle::default(),
+            frame_rate: Fixed8::ONE,
+            num_frames: 4,
+        };
+        let tags = [
+            swf::Tag::ShowFrame,
+            swf::Tag::ShowFrame,
+            swf::Tag::ShowFrame,
+            swf::Tag::ShowFrame,
+        ];
+        let mut data = vec![];
+        swf::write_swf(&header, &tags, &mut data).unwrap();
+        let movie = Arc::new(SwfMovie::from_data(&data, "file:///clip.swf".into(), None).unwrap());
+
+        with_update_context(|context| {
+            let clip =
+                MovieClip::new_with_data(context.gc_context, 1, SwfSlice::from(movie.clone()), 4);
+            let library = context.library.library_for_movie_mut(movie.clone());
+            library.register_character(1, Character::MovieClip(clip));
+            let live = library
+                .instantiate_by_id(1, context.gc_context)
+                .unwrap()
+                .as_movie_clip()
+                .unwrap();
+            live.goto_frame(context, 1, true);
+
+            let copy = copy_at_frame(context, live, 3).unwrap();
+            assert_eq!(copy.current_frame(), 3);
+            assert!(copy.avm1_removed());
+
+            // The null renderer can't draw clips, but the live clip must stay put either way.
+            let target = new_bitmap_data(context.gc_context, 2, 2, &[0; 4]);
+            let _ = draw_at_frame(
+                context,
+                target,
+                live,
+                3,
+                Transform::default(),
+                false,
+                BlendMode::Normal,
+                None,
+                StageQuality::Low,
+            );
+            assert_eq!(live.current_frame(), 1);
+            assert!(!live.avm1_removed());
+        });
+    }
+
+    #[test]
+    fn detached_seek_runs_no_frame_scripts() {
+        use crate::avm2::{
+            Activation as Avm2Activation, ArrayObject as Avm2ArrayObject, ArrayStorage, Multiname,
+            Object as Avm2Object, TObject as _,
+        };
+        use crate::character::Character;
+        use crate::tag_utils::{SwfMovie, SwfSlice};
+        use std::sync::Arc;
+
+        let header = swf::Header {
+            compression: swf::Compression::None,
+            version: 10,
+            stage_size: Rectangle::default(),
+            frame_rate: Fixed8::ONE,
+            num_frames: 4,
+        };
+        let tags = [
+            swf::Tag::FileAttributes(swf::FileAttributes::IS_ACTION_SCRIPT_3),
+            swf::Tag::ShowFrame,
+            swf::Tag::ShowFrame,
+            swf::Tag::ShowFrame,
+            swf::Tag::ShowFrame,
+        ];
+        let mut data = vec![];
+        swf::write_swf(&header, &tags, &mut data).unwrap();
+        let movie = Arc::new(SwfMovie::from_data(&data, "file:///clip.swf".into(), None).unwrap());
+
+        /// Instantiates the clip with `frame_script` on frame 3.
+        fn instantiate<'gc>(
+            context: &mut UpdateContext<'_, 'gc>,
+            movie: Arc<SwfMovie>,
+            frame_script: Avm2Object<'gc>,
+        ) -> MovieClip<'gc> {
+            let instance = context
+                .library
+                .library_for_movie_mut(movie)
+                .instantiate_by_id(1, context.gc_context)
+                .unwrap()
+                .as_movie_clip()
+                .unwrap();
+            instance.register_frame_script(3, Some(frame_script), context);
+            instance
+        }
+
+        with_player(
+            crate::player::PlayerBuilder::new().with_movie((*movie).clone()),
+            |context| {
+                let clip = MovieClip::new_with_data(
+                    context.gc_context,
+                    1,
+                    SwfSlice::from(movie.clone()),
+                    4,
+                );
+                context
+                    .library
+                    .library_for_movie_mut(movie.clone())
+                    .register_character(1, Character::MovieClip(clip));
+
+                // The frame script pops the only element of an array, so we can tell if it ran.
+                let (array, frame_script) = {
+                    let mut activation = Avm2Activation::from_nothing(context.reborrow());
+                    let storage = ArrayStorage::from_args(&[1.into()]);
+                    let array = Avm2ArrayObject::from_storage(&mut activation, storage).unwrap();
+                    let pop = Multiname::new(activation.avm2().as3_namespace, "pop");
+                    let pop = array.get_property(&pop, &mut activation).unwrap();
+                    (array, pop.as_object().unwrap())
+                };
+                let script_ran = || array.as_array_storage().unwrap().length() == 0;
+
+                let detached = instantiate(context, movie.clone(), frame_script);
+                detached.seek_detached(context, 3);
+                assert_eq!(detached.current_frame(), 3);
+                assert!(!script_ran());
+                assert!(matches!(detached.object2(), Avm2Value::Null));
+
+                // A normal goto runs the same frame script.
+                let live = instantiate(context, movie.clone(), frame_script);
+                live.goto_frame(context, 3, true);
+                assert!(script_ran());
+            },
+        );
+    }
+
+    #[test]
+    fn get_pixel32_of_opaque_bitmap_has_full_alpha() {
+        rootless_arena(|mc| {
+            // Storage of an opaque bitmap can end up with a zero alpha channel.
+            let pixels = vec![Color::from(0x00123456); 4];
+            let bitmap_data = BitmapData::new_with_pixels(2, 2, false, pixels);
+            let target = BitmapDataWrapper::new(GcCell::allocate(mc, bitmap_data));
+            assert_eq!(get_pixel32(target, 1, 1), 0xFF123456u32 as i32);
+            assert_eq!(get_pixel32(target, 2, 2), 0);
+        });
+    }
 }
@@ -0,0 +1,380 @@
+//! A CPU interpreter for a small subset of Pixel Bender bytecode, as used by `ShaderFilter`.
+//!
+//! Only straight-line arithmetic on float registers and texture sampling are supported, which
+//! covers simple color effects (brightness, tinting, `mix` between samples, ...). Shaders using
+//! anything else fail to parse with `ShaderError::UnsupportedOpcode`, so that callers can fall
+//! back to something sensible.
+
+use std::fmt;
+
+/// The parameter that Pixel Bender fills with the coordinate of the pixel being produced.
+const OUT_COORD: &[u8] = b"_OutCoord";
+
+/// Register indices with this bit set refer to integer registers.
+const INT_REGISTER: u16 = 0x8000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShaderError {
+    UnexpectedEof,
+    UnsupportedOpcode(u8),
+    UnsupportedRegister(u16),
+    MissingOutput,
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ShaderError::UnexpectedEof => write!(f, "unexpected end of bytecode"),
+            ShaderError::UnsupportedOpcode(opcode) => write!(f, "unsupported opcode {opcode:#04x}"),
+            ShaderError::UnsupportedRegister(register) => {
+                write!(f, "unsupported register {register:#06x}")
+            }
+            ShaderError::MissingOutput => write!(f, "shader has no output parameter"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Min,
+    Max,
+    Mov,
+}
+
+impl Arithmetic {
+    fn from_opcode(opcode: u8) -> Option<Self> {
+        Some(match opcode {
+            0x01 => Arithmetic::Add,
+            0x02 => Arithmetic::Sub,
+            0x03 => Arithmetic::Mul,
+            0x05 => Arithmetic::Div,
+            0x09 => Arithmetic::Min,
+            0x0A => Arithmetic::Max,
+            0x1D => Arithmetic::Mov,
+            _ => return None,
+        })
+    }
+
+    fn apply(self, dst: f32, src: f32) -> f32 {
+        match self {
+            Arithmetic::Add => dst + src,
+            Arithmetic::Sub => dst - src,
+            Arithmetic::Mul => dst * src,
+            Arithmetic::Div => dst / src,
+            Arithmetic::Min => dst.min(src),
+            Arithmetic::Max => dst.max(src),
+            Arithmetic::Mov => src,
+        }
+    }
+}
+
+/// A source operand: a register, read through a swizzle of `size` components.
+#[derive(Debug, Clone, Copy)]
+struct Source {
+    register: usize,
+    swizzle: [usize; 4],
+    size: usize,
+}
+
+/// A destination operand: a register, and the channels of it that are written.
+#[derive(Debug, Clone, Copy)]
+struct Destination {
+    register: usize,
+    mask: u8,
+}
+
+impl Destination {
+    /// The written channels, in order.
+    fn channels(self) -> impl Iterator<Item = usize> {
+        (0..4).filter(move |channel| self.mask & (0x8 >> channel) != 0)
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Instruction {
+    Arithmetic(Arithmetic, Destination, Source),
+    LoadFloat(Destination, f32),
+    Sample(Destination, Source, u8),
+}
+
+/// A parsed Pixel Bender kernel.
+#[derive(Debug, Clone)]
+pub struct Shader {
+    instructions: Vec<Instruction>,
+    out_coord: Option<Destination>,
+    output: Destination,
+    num_registers: usize,
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8], ShaderError> {
+        if self.data.len() < len {
+            return Err(ShaderError::UnexpectedEof);
+        }
+        let (bytes, rest) = self.data.split_at(len);
+        self.data = rest;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8, ShaderError> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, ShaderError> {
+        let bytes = self.bytes(2)?;
+        Ok(u16::from_le_bytes([bytes[0], bytes[1]]))
+    }
+
+    fn string(&mut self) -> Result<&'a [u8], ShaderError> {
+        let len = self
+            .data
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or(ShaderError::UnexpectedEof)?;
+        let string = self.bytes(len)?;
+        self.bytes(1)?;
+        Ok(string)
+    }
+
+    fn float_register(&mut self) -> Result<usize, ShaderError> {
+        let register = self.u16()?;
+        if register & INT_REGISTER != 0 {
+            return Err(ShaderError::UnsupportedRegister(register));
+        }
+        Ok(register as usize)
+    }
+
+    /// Reads a metadata value of the given type, which is only needed to skip over it.
+    fn skip_metadata_value(&mut self, kind: u8) -> Result<(), ShaderError> {
+        let len = match kind {
+            // float, float2, float3, float4
+            1..=4 => kind as usize * 4,
+            // float2x2, float3x3, float4x4
+            5 => 16,
+            6 => 36,
+            7 => 64,
+            // int, int2, int3, int4
+            8..=11 => (kind - 7) as usize * 2,
+            // string
+            12 => {
+                self.string()?;
+                return Ok(());
+            }
+            _ => return Err(ShaderError::UnsupportedOpcode(kind)),
+        };
+        self.bytes(len)?;
+        Ok(())
+    }
+}
+
+impl Shader {
+    /// Parses Pixel Bender bytecode (the contents of a `.pbj` file).
+    pub fn parse(bytecode: &[u8]) -> Result<Self, ShaderError> {
+        let mut reader = Reader { data: bytecode };
+        let mut instructions = Vec::new();
+        let mut out_coord = None;
+        let mut output = None;
+
+        while !reader.data.is_empty() {
+            let opcode = reader.u8()?;
+            match opcode {
+                // Kernel metadata
+                0xA0 => {
+                    let kind = reader.u8()?;
+                    reader.string()?;
+                    reader.skip_metadata_value(kind)?;
+                }
+                // Parameter
+                0xA1 => {
+                    let qualifier = reader.u8()?;
+                    let _kind = reader.u8()?;
+                    let register = reader.float_register()?;
+                    let mask = reader.u8()?;
+                    let name = reader.string()?;
+                    let parameter = Destination { register, mask };
+                    if qualifier == 2 {
+                        output = Some(parameter);
+                    } else if name == OUT_COORD {
+                        out_coord = Some(parameter);
+                    }
+                }
+                // Parameter metadata
+                0xA2 => {
+                    let kind = reader.u8()?;
+                    reader.string()?;
+                    reader.skip_metadata_value(kind)?;
+                }
+                // Texture
+                0xA3 => {
+                    reader.u8()?;
+                    reader.u8()?;
+                    reader.string()?;
+                }
+                // Kernel name
+                0xA4 => {
+                    let len = reader.u16()?;
+                    reader.bytes(len as usize)?;
+                }
+                // Version
+                0xA5 => {
+                    reader.bytes(4)?;
+                }
+                // Load constant
+                0x32 => {
+                    let register = reader.float_register()?;
+                    let mask = reader.u8()? >> 4;
+                    // Unlike everything else, float constants are stored big-endian.
+                    let bytes = reader.bytes(4)?;
+                    let value = f32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+                    instructions.push(Instruction::LoadFloat(
+                        Destination { register, mask },
+                        value,
+                    ));
+                }
+                // Sample (nearest)
+                0x30 => {
+                    let register = reader.float_register()?;
+                    let mask = reader.u8()? >> 4;
+                    let source = reader.float_register()?;
+                    let swizzle = reader.u8()?;
+                    let texture = reader.u8()?;
+                    instructions.push(Instruction::Sample(
+                        Destination { register, mask },
+                        Source {
+                            register: source,
+                            swizzle: decode_swizzle(swizzle),
+                            size: 2,
+                        },
+                        texture,
+                    ));
+                }
+                _ => {
+                    let op = Arithmetic::from_opcode(opcode)
+                        .ok_or(ShaderError::UnsupportedOpcode(opcode))?;
+                    let register = reader.float_register()?;
+                    let mask = reader.u8()?;
+                    let source = reader.float_register()?;
+                    let swizzle = reader.u8()?;
+                    reader.u8()?;
+                    // Matrix operands are not supported.
+                    if mask & 0x0C != 0 {
+                        return Err(ShaderError::UnsupportedOpcode(opcode));
+                    }
+                    instructions.push(Instruction::Arithmetic(
+                        op,
+                        Destination {
+                            register,
+                            mask: mask >> 4,
+                        },
+                        Source {
+                            register: source,
+                            swizzle: decode_swizzle(swizzle),
+                            size: (mask & 0x3) as usize + 1,
+                        },
+                    ));
+                }
+            }
+        }
+
+        let output = output.ok_or(ShaderError::MissingOutput)?;
+        let num_registers = instructions
+            .iter()
+            .map(|instruction| match instruction {
+                Instruction::Arithmetic(_, dst, src) | Instruction::Sample(dst, src, _) => {
+                    dst.register.max(src.register)
+                }
+                Instruction::LoadFloat(dst, _) => dst.register,
+            })
+            .chain(out_coord.map(|out_coord| out_coord.register))
+            .chain(std::iter::once(output.register))
+            .max()
+            .unwrap_or(0)
+            + 1;
+
+        Ok(Self {
+            instructions,
+            out_coord,
+            output,
+            num_registers,
+        })
+    }
+
+    /// Runs the kernel for every pixel of a `width` by `height` output, returning the
+    /// (un-multiplied) RGBA output of each pixel in row-major order.
+    ///
+    /// `sample` is called with a texture index and a coordinate, and returns the
+    /// (un-multiplied) RGBA color at that point of the input.
+    pub fn run(
+        &self,
+        width: u32,
+        height: u32,
+        sample: impl Fn(u8, f32, f32) -> [f32; 4],
+    ) -> Vec<[f32; 4]> {
+        let mut output = Vec::with_capacity(width as usize * height as usize);
+        let mut registers = vec![[0.0; 4]; self.num_registers];
+        for y in 0..height {
+            for x in 0..width {
+                registers.fill([0.0; 4]);
+                if let Some(out_coord) = self.out_coord {
+                    let coord = [x as f32 + 0.5, y as f32 + 0.5];
+                    for (channel, value) in out_coord.channels().zip(coord) {
+                        registers[out_coord.register][channel] = value;
+                    }
+                }
+
+                for instruction in &self.instructions {
+                    match *instruction {
+                        Instruction::Arithmetic(op, dst, src) => {
+                            let src_values = read_source(&registers, src);
+                            let dst_values = &mut registers[dst.register];
+                            for (channel, value) in dst.channels().zip(src_values) {
+                                dst_values[channel] = op.apply(dst_values[channel], value);
+                            }
+                        }
+                        Instruction::LoadFloat(dst, value) => {
+                            for channel in dst.channels() {
+                                registers[dst.register][channel] = value;
+                            }
+                        }
+                        Instruction::Sample(dst, src, texture) => {
+                            let mut coord = read_source(&registers, src);
+                            let (x, y) = (coord.next().unwrap(), coord.next().unwrap());
+                            let color = sample(texture, x, y);
+                            for (channel, value) in dst.channels().zip(color) {
+                                registers[dst.register][channel] = value;
+                            }
+                        }
+                    }
+                }
+
+                let mut color = [0.0; 4];
+                for channel in self.output.channels() {
+                    color[channel] = registers[self.output.register][channel];
+                }
+                output.push(color);
+            }
+        }
+        output
+    }
+}
+
+/// Decodes a swizzle byte, which stores the source channel of each component in two bits,
+/// starting from the most significant bits.
+fn decode_swizzle(swizzle: u8) -> [usize; 4] {
+    [6, 4, 2, 0].map(|shift| ((swizzle >> shift) & 0x3) as usize)
+}
+
+fn read_source(registers: &[[f32; 4]], source: Source) -> impl Iterator<Item = f32> {
+    let values = source
+        .swizzle
+        .map(|channel| registers[source.register][channel]);
+    values.into_iter().take(source.size)
+}
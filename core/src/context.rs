@@ -11,6 +11,7 @@ use crate::backend::{
     storage::StorageBackend,
     ui::{InputManager, UiBackend},
 };
+use crate::bitmap::BitmapMemoryBudget;
 use crate::context_menu::ContextMenuState;
 use crate::display_object::{EditText, InteractiveObject, MovieClip, SoundTransform, Stage};
 use crate::external::ExternalInterface;
@@ -30,6 +31,7 @@ use instant::Instant;
 use rand::rngs::SmallRng;
 use ruffle_render::backend::RenderBackend;
 use ruffle_render::commands::CommandList;
+use ruffle_render::quality::StageQuality;
 use ruffle_render::transform::TransformStack;
 use ruffle_video::backend::VideoBackend;
 use std::collections::{HashMap, VecDeque};
@@ -187,6 +189,20 @@ pub struct UpdateContext<'a, 'gc> {
 
     /// Manager of in-progress media streams.
     pub stream_manager: &'a mut StreamManager<'gc>,
+
+    /// The pixel memory used by `BitmapData` objects, and how much they may use.
+    pub bitmap_memory: &'a BitmapMemoryBudget,
+
+    /// The maximum number of pixels a single flood fill may change, if any.
+    pub flood_fill_limit: Option<usize>,
+
+    /// Whether `BitmapData` operations must run on the CPU instead of the render backend.
+    ///
+    /// See `PlayerBuilder::with_force_cpu_bitmap_operations` for what is left undone.
+    pub force_cpu_bitmap_operations: bool,
+
+    /// The ARGB color that AVM1 fills a new `BitmapData` with when no fill color is given.
+    pub default_bitmap_fill_color: u32,
 }
 
 /// Convenience methods for controlling audio.
@@ -341,6 +357,10 @@ impl<'a, 'gc> UpdateContext<'a, 'gc> {
             actions_since_timeout_check: self.actions_since_timeout_check,
             frame_phase: self.frame_phase,
             stream_manager: self.stream_manager,
+            bitmap_memory: self.bitmap_memory,
+            flood_fill_limit: self.flood_fill_limit,
+            force_cpu_bitmap_operations: self.force_cpu_bitmap_operations,
+            default_bitmap_fill_color: self.default_bitmap_fill_color,
         }
     }
 
@@ -445,9 +465,19 @@ pub struct RenderContext<'a, 'gc> {
     /// The current player's stage (including all loaded levels)
     pub stage: Stage<'gc>,
 
+    /// The quality to render at. This is the stage quality, unless rendering for
+    /// `BitmapData.drawWithQuality`. Bitmaps are never smoothed at `StageQuality::Low`.
+    pub quality: StageQuality,
+
     /// Whether to allow pushing a new mask. A masker-inside-a-masker does not work in Flash, instead
     /// causing the inner mask to be included as part of the outer mask. Maskee-inside-a-maskee works as one expects.
     pub allow_mask: bool,
+
+    /// A version number for each `BitmapData` or video frame drawn into `commands`, in order.
+    ///
+    /// These keep the same texture when their pixels change, so the commands alone can't
+    /// tell whether a bitmap cached by `render_cached` has gone out of date.
+    pub texture_versions: Vec<u64>,
 }
 
 /// The type of action being run.
@@ -14,7 +14,10 @@ use crate::vminterface::Instantiator;
 use bitflags::bitflags;
 use gc_arena::{Collect, MutationContext};
 use ruffle_macros::enum_trait_object;
-use ruffle_render::transform::Transform;
+use ruffle_render::bitmap::{BitmapHandle, PixelRegion};
+use ruffle_render::commands::CommandList;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::transform::{Transform, TransformStack};
 use std::cell::{Ref, RefMut};
 use std::fmt::Debug;
 use std::sync::Arc;
@@ -41,7 +44,7 @@ pub use crate::display_object::container::{
 };
 pub use avm1_button::{Avm1Button, ButtonState, ButtonTracking};
 pub use avm2_button::Avm2Button;
-pub use bitmap::Bitmap;
+pub use bitmap::{Bitmap, PixelSnapping};
 pub use edit_text::{AutoSizeMode, EditText, TextSelection};
 pub use graphic::Graphic;
 pub use interactive::{Avm2MousePick, InteractiveObject, TInteractiveObject};
@@ -120,6 +123,26 @@ pub struct DisplayObjectBase<'gc> {
     /// changes immediately (without needing wait for a render)
     #[collect(require_static)]
     next_scroll_rect: Rectangle<Twips>,
+
+    /// The bitmap this object was last cached into, see `render_cached`.
+    #[collect(require_static)]
+    cache: Option<BitmapCache>,
+}
+
+/// The cached bitmap of a display object, kept between frames by `render_cached`.
+#[derive(Clone, Debug)]
+struct BitmapCache {
+    /// The texture holding the object, with its filters applied.
+    handle: BitmapHandle,
+    width: u32,
+    height: u32,
+    quality: StageQuality,
+    filters: Vec<Filter>,
+
+    /// What was drawn into the texture before it was filtered, along with the versions of
+    /// the textures that were drawn. The object has changed if it now draws anything else.
+    commands: CommandList,
+    texture_versions: Vec<u64>,
 }
 
 impl<'gc> Default for DisplayObjectBase<'gc> {
@@ -145,6 +168,7 @@ impl<'gc> Default for DisplayObjectBase<'gc> {
             flags: DisplayObjectFlags::VISIBLE,
             scroll_rect: None,
             next_scroll_rect: Default::default(),
+            cache: None,
         }
     }
 }
@@ -351,6 +375,9 @@ impl<'gc> DisplayObjectBase<'gc> {
 
     fn set_filters(&mut self, filters: Vec<Filter>) {
         self.filters = filters;
+        if !self.is_bitmap_cached() {
+            self.cache = None;
+        }
     }
 
     fn alpha(&self) -> f64 {
@@ -495,11 +522,14 @@ impl<'gc> DisplayObjectBase<'gc> {
     }
 
     fn is_bitmap_cached(&self) -> bool {
-        self.flags.contains(DisplayObjectFlags::CACHE_AS_BITMAP)
+        self.flags.contains(DisplayObjectFlags::CACHE_AS_BITMAP) || !self.filters.is_empty()
     }
 
     fn set_is_bitmap_cached(&mut self, value: bool) {
         self.flags.set(DisplayObjectFlags::CACHE_AS_BITMAP, value);
+        if !self.is_bitmap_cached() {
+            self.cache = None;
+        }
     }
 
     fn instantiated_by_timeline(&self) -> bool {
@@ -550,6 +580,7 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         return;
     }
     context.transform_stack.push(this.base().transform());
+    let object_matrix = context.transform_stack.transform().matrix;
     let blend_mode = this.blend_mode();
     let original_commands = if blend_mode != BlendMode::Normal {
         Some(std::mem::take(&mut context.commands))
@@ -612,7 +643,9 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
         context.commands.activate_mask();
     }
 
-    this.render_self(context);
+    if !this.is_bitmap_cached() || !render_cached(this, context, &object_matrix) {
+        render_self_snapped(this, context, &object_matrix);
+    }
 
     if let Some(rect_mat) = scroll_rect_matrix {
         // Draw the rectangle again after deactivating the mask,
@@ -645,6 +678,173 @@ pub fn render_base<'gc>(this: DisplayObject<'gc>, context: &mut RenderContext<'_
     context.transform_stack.pop();
 }
 
+/// Returns the translation, in stage coordinates, that moves the origin of `matrix`
+/// to the nearest whole pixel, as Flash does for objects cached as bitmaps.
+fn pixel_snap(matrix: &Matrix) -> (Twips, Twips) {
+    let snap = |position: Twips| Twips::from_pixels(position.to_pixels().round()) - position;
+    (snap(matrix.tx), snap(matrix.ty))
+}
+
+/// Calls `render_self`, first moving `this` to the nearest whole pixel if it has
+/// `cacheAsBitmap` set, as its cached bitmap would be in Flash.
+///
+/// This is used when `render_cached` can't cache the object, so that it still lands where
+/// its cached bitmap would have.
+fn render_self_snapped<'gc>(
+    this: DisplayObject<'gc>,
+    context: &mut RenderContext<'_, 'gc>,
+    object_matrix: &Matrix,
+) {
+    let cache_as_bitmap = this
+        .base()
+        .flags
+        .contains(DisplayObjectFlags::CACHE_AS_BITMAP);
+    let snap_transform = if cache_as_bitmap {
+        let matrix = context.transform_stack.transform().matrix;
+        matrix.inverse().map(|inverse| {
+            let (snap_x, snap_y) = pixel_snap(object_matrix);
+            Transform {
+                matrix: inverse * Matrix::translate(snap_x, snap_y) * matrix,
+                color_transform: Default::default(),
+            }
+        })
+    } else {
+        None
+    };
+
+    if let Some(snap_transform) = &snap_transform {
+        context.transform_stack.push(snap_transform);
+    }
+    this.render_self(context);
+    if snap_transform.is_some() {
+        context.transform_stack.pop();
+    }
+}
+
+/// Renders `this` as a cached bitmap, with its filters applied, in place of `render_self`.
+///
+/// Like Flash, the object is first moved to the nearest whole pixel, so that its cached
+/// bitmap lines up with the pixel grid. It is then drawn into an offscreen texture covering
+/// its bounds (grown to fit whatever its filters draw around it), each filter is applied in
+/// turn, and the result is drawn in the object's place with its color transform. Filters
+/// the render backend can't apply are skipped.
+///
+/// The texture is kept, and only drawn again once the object draws something different
+/// into it. Moving the object by whole pixels or changing its color transform doesn't do so.
+///
+/// Returns `false`, having drawn nothing, if the object should be rendered directly, without
+/// its filters, instead: when the backend can't render offscreen, or the bitmap would be
+/// larger than Flash allows for a `BitmapData`.
+fn render_cached<'gc>(
+    this: DisplayObject<'gc>,
+    context: &mut RenderContext<'_, 'gc>,
+    object_matrix: &Matrix,
+) -> bool {
+    let (snap_x, snap_y) = pixel_snap(object_matrix);
+    let snapped_matrix = Matrix::translate(snap_x, snap_y) * *object_matrix;
+
+    let filters = this.filters();
+    let bounds = filters.iter().fold(
+        this.bounds_with_transform(&snapped_matrix),
+        |bounds, filter| filter.calculate_dest_rect(bounds),
+    );
+    if !bounds.is_valid() {
+        return true;
+    }
+    let x = bounds.x_min.to_pixels().floor();
+    let y = bounds.y_min.to_pixels().floor();
+    let width = (bounds.x_max.to_pixels().ceil() - x) as u32;
+    let height = (bounds.y_max.to_pixels().ceil() - y) as u32;
+    if width == 0 || height == 0 {
+        return true;
+    }
+    if width > 8191 || height > 8191 || width * height > 16_777_215 {
+        return false;
+    }
+
+    let transform = context.transform_stack.transform();
+    let mut transform_stack = TransformStack::new();
+    transform_stack.push(&Transform {
+        matrix: Matrix::translate(
+            snap_x - Twips::from_pixels(x),
+            snap_y - Twips::from_pixels(y),
+        ) * transform.matrix,
+        color_transform: Default::default(),
+    });
+    let mut offscreen_context = RenderContext {
+        renderer: context.renderer,
+        commands: CommandList::new(),
+        gc_context: context.gc_context,
+        library: context.library,
+        transform_stack: &mut transform_stack,
+        is_offscreen: true,
+        stage: context.stage,
+        quality: context.quality,
+        allow_mask: true,
+        texture_versions: Vec::new(),
+    };
+    this.render_self(&mut offscreen_context);
+    let commands = offscreen_context.commands;
+    let texture_versions = offscreen_context.texture_versions;
+
+    let quality = context.quality;
+    let cached = this.base().cache.as_ref().and_then(|cache| {
+        let unchanged = (cache.width, cache.height) == (width, height)
+            && cache.quality == quality
+            && cache.filters == filters
+            && cache.commands == commands
+            && cache.texture_versions == texture_versions;
+        unchanged.then(|| cache.handle.clone())
+    });
+    let handle = match cached {
+        Some(handle) => handle,
+        None => {
+            let Ok(handle) = context.renderer.create_empty_texture(width, height) else {
+                return false;
+            };
+            let region = PixelRegion::for_whole_size(width, height);
+            if context
+                .renderer
+                .render_offscreen(handle.clone(), commands.clone(), quality, region)
+                .is_none()
+            {
+                return false;
+            }
+            for filter in &filters {
+                // The backend copies the source first when filtering a texture in place.
+                context.renderer.apply_filter(
+                    handle.clone(),
+                    (0, 0),
+                    (width, height),
+                    handle.clone(),
+                    (0, 0),
+                    filter.clone(),
+                );
+            }
+            this.base_mut(context.gc_context).cache = Some(BitmapCache {
+                handle: handle.clone(),
+                width,
+                height,
+                quality,
+                filters,
+                commands,
+                texture_versions,
+            });
+            handle
+        }
+    };
+
+    context.commands.render_bitmap(
+        handle,
+        Transform {
+            matrix: Matrix::translate(Twips::from_pixels(x), Twips::from_pixels(y)),
+            color_transform: transform.color_transform,
+        },
+        false,
+    );
+    true
+}
+
 #[enum_trait_object(
     #[derive(Clone, Collect, Debug, Copy)]
     #[collect(no_drop)]
@@ -1245,14 +1445,14 @@ pub trait TDisplayObject<'gc>:
     }
 
     /// Whether this display object is cached into a bitmap rendering.
-    /// This is set implicitly when a filter or blend mode is applied, or explicitly by the user
+    /// This is set implicitly when a filter is applied, or explicitly by the user
     /// via the `cacheAsBitmap` property.
     fn is_bitmap_cached(&self) -> bool {
         self.base().is_bitmap_cached()
     }
 
     /// Explicilty sets this display object to be cached into a bitmap rendering.
-    /// Note that the object will still be bitmap cached if a filter or blend mode is active.
+    /// Note that the object will still be bitmap cached if a filter is active.
     fn set_is_bitmap_cached(&self, gc_context: MutationContext<'gc, '_>, value: bool) {
         self.base_mut(gc_context).set_is_bitmap_cached(value)
     }
@@ -1628,6 +1828,17 @@ pub trait TDisplayObject<'gc>:
         self.world_bounds().contains(pos)
     }
 
+    /// Tests if a given stage position point lies within this object's scroll rect.
+    /// An object without a scroll rect isn't cropped, so this is always `true` for it.
+    fn hit_test_scroll_rect(&self, pos: (Twips, Twips)) -> bool {
+        let Some(rect) = self.scroll_rect() else {
+            return true;
+        };
+        // The local matrix includes the scroll, so local points inside the rect are visible.
+        self.global_to_local_matrix()
+            .map_or(false, |matrix| rect.contains(matrix * pos))
+    }
+
     /// Tests if a given object's world bounds intersects with the world bounds
     /// of this object.
     fn hit_test_object(&self, other: DisplayObject<'gc>) -> bool {
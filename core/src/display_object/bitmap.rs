@@ -14,6 +14,8 @@ use crate::vminterface::Instantiator;
 use core::fmt;
 use gc_arena::{Collect, GcCell, MutationContext};
 use ruffle_render::bitmap::BitmapFormat;
+use ruffle_render::matrix::Matrix;
+use ruffle_wstr::WStr;
 use std::cell::{Ref, RefMut};
 use std::sync::Arc;
 
@@ -43,6 +45,65 @@ pub enum BitmapClass<'gc> {
     BitmapData(Avm2ClassObject<'gc>),
 }
 
+/// How a `Bitmap` aligns itself to the pixel grid when rendered.
+#[derive(Clone, Collect, Copy, Debug, Default, PartialEq, Eq)]
+#[collect(require_static)]
+pub enum PixelSnapping {
+    /// Render wherever the transform places the bitmap.
+    Never,
+
+    /// Snap to whole pixels, but only while the bitmap is drawn unrotated and
+    /// at (very nearly) its natural size.
+    #[default]
+    Auto,
+
+    /// Always snap to whole pixels.
+    Always,
+}
+
+impl PixelSnapping {
+    /// Parse one of the `flash.display.PixelSnapping` names.
+    pub fn from_wstr(name: &WStr) -> Option<Self> {
+        if name == WStr::from_units(b"never") {
+            Some(Self::Never)
+        } else if name == WStr::from_units(b"auto") {
+            Some(Self::Auto)
+        } else if name == WStr::from_units(b"always") {
+            Some(Self::Always)
+        } else {
+            None
+        }
+    }
+
+    /// The `flash.display.PixelSnapping` name of this mode.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Never => "never",
+            Self::Auto => "auto",
+            Self::Always => "always",
+        }
+    }
+
+    /// Round the translation of `matrix` to whole pixels, if this mode asks
+    /// for it.
+    pub fn apply(self, matrix: &mut Matrix) {
+        let snap = match self {
+            Self::Never => false,
+            Self::Auto => {
+                matrix.b == 0.0
+                    && matrix.c == 0.0
+                    && (matrix.a - 1.0).abs() <= 0.001
+                    && (matrix.d - 1.0).abs() <= 0.001
+            }
+            Self::Always => true,
+        };
+        if snap {
+            matrix.tx = Twips::from_pixels(matrix.tx.to_pixels().round());
+            matrix.ty = Twips::from_pixels(matrix.ty.to_pixels().round());
+        }
+    }
+}
+
 /// A Bitmap display object is a raw bitamp on the stage.
 /// This can only be instanitated on the display list in SWFv9 AVM2 files.
 /// In AVM1, this is only a library symbol that is referenced by `Graphic`.
@@ -81,6 +142,9 @@ pub struct BitmapData<'gc> {
     /// Whether or not bitmap smoothing is enabled.
     smoothing: bool,
 
+    /// How this bitmap is aligned to the pixel grid.
+    pixel_snapping: PixelSnapping,
+
     /// The AVM2 side of this object.
     ///
     /// AVM1 code cannot directly reference `Bitmap`s, so this does not support
@@ -120,6 +184,7 @@ impl<'gc> Bitmap<'gc> {
                 width,
                 height,
                 smoothing,
+                pixel_snapping: PixelSnapping::default(),
                 avm2_object: None,
                 avm2_bitmap_class: BitmapClass::NoSubclass,
                 movie: context.swf.clone(),
@@ -191,15 +256,15 @@ impl<'gc> Bitmap<'gc> {
     pub fn set_bitmap_data(
         self,
         context: &mut UpdateContext<'_, 'gc>,
-        bitmap_data: GcCell<'gc, crate::bitmap::bitmap_data::BitmapData<'gc>>,
+        bitmap_data: BitmapDataWrapper<'gc>,
     ) {
         let mut write = self.0.write(context.gc_context);
         // Refresh our cached values, even if we're writing the same BitmapData
         // that we currently have stored. This will update them to '0' if the
         // BitmapData has been disposed since it was originally set.
-        write.width = bitmap_data.read().width();
-        write.height = bitmap_data.read().height();
-        write.bitmap_data = BitmapDataWrapper::new(bitmap_data);
+        write.width = bitmap_data.width();
+        write.height = bitmap_data.height();
+        write.bitmap_data = bitmap_data;
     }
 
     pub fn avm2_bitmapdata_class(self) -> Option<Avm2ClassObject<'gc>> {
@@ -239,6 +304,14 @@ impl<'gc> Bitmap<'gc> {
     pub fn set_smoothing(self, mc: MutationContext<'gc, '_>, smoothing: bool) {
         self.0.write(mc).smoothing = smoothing;
     }
+
+    pub fn pixel_snapping(self) -> PixelSnapping {
+        self.0.read().pixel_snapping
+    }
+
+    pub fn set_pixel_snapping(self, mc: MutationContext<'gc, '_>, pixel_snapping: PixelSnapping) {
+        self.0.write(mc).pixel_snapping = pixel_snapping;
+    }
 }
 
 impl<'gc> TDisplayObject<'gc> for Bitmap<'gc> {
@@ -313,9 +386,11 @@ impl<'gc> TDisplayObject<'gc> for Bitmap<'gc> {
         }
 
         let bitmap_data = self.0.read();
+        let mut transform = context.transform_stack.transform();
+        bitmap_data.pixel_snapping.apply(&mut transform.matrix);
         bitmap_data
             .bitmap_data
-            .render(bitmap_data.smoothing, context);
+            .render_with_transform(bitmap_data.smoothing, transform, context);
     }
 
     fn object2(&self) -> Avm2Value<'gc> {
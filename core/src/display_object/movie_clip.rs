@@ -14,6 +14,7 @@ use bitflags::bitflags;
 use crate::avm1::Avm1;
 use crate::avm1::{Activation as Avm1Activation, ActivationIdentifier};
 use crate::binary_data::BinaryData;
+use crate::bitmap::bitmap_data::BitmapDataWrapper;
 use crate::character::Character;
 use crate::context::{ActionType, RenderContext, UpdateContext};
 use crate::display_object::container::{
@@ -36,6 +37,8 @@ use crate::tag_utils::{self, ControlFlow, DecodeResult, Error, SwfMovie, SwfSlic
 use crate::vminterface::{AvmObject, Instantiator};
 use core::fmt;
 use gc_arena::{Collect, Gc, GcCell, GcWeakCell, MutationContext};
+use ruffle_render::backend::RenderBackend;
+use ruffle_render::bitmap::BitmapInfo;
 use ruffle_render::filters::Filter;
 use smallvec::SmallVec;
 use std::cell::{Ref, RefMut};
@@ -110,6 +113,10 @@ pub struct MovieClipData<'gc> {
     flags: MovieClipFlags,
     avm2_class: Option<Avm2ClassObject<'gc>>,
     drawing: Drawing,
+
+    /// The `BitmapData`s used by bitmap fills in `drawing`. Like a `Bitmap`, these fills
+    /// show any later changes to the `BitmapData`, which are uploaded when this clip renders.
+    drawing_bitmaps: Vec<BitmapDataWrapper<'gc>>,
     is_focusable: bool,
     has_focus: bool,
     enabled: bool,
@@ -154,6 +161,7 @@ impl<'gc> MovieClip<'gc> {
                 flags: MovieClipFlags::empty(),
                 avm2_class: None,
                 drawing: Drawing::new(),
+                drawing_bitmaps: Vec::new(),
                 is_focusable: false,
                 has_focus: false,
                 enabled: true,
@@ -194,6 +202,7 @@ impl<'gc> MovieClip<'gc> {
                 flags: MovieClipFlags::empty(),
                 avm2_class: Some(class),
                 drawing: Drawing::new(),
+                drawing_bitmaps: Vec::new(),
                 is_focusable: false,
                 has_focus: false,
                 enabled: true,
@@ -238,6 +247,7 @@ impl<'gc> MovieClip<'gc> {
                 flags: MovieClipFlags::PLAYING,
                 avm2_class: None,
                 drawing: Drawing::new(),
+                drawing_bitmaps: Vec::new(),
                 is_focusable: false,
                 has_focus: false,
                 enabled: true,
@@ -304,6 +314,7 @@ impl<'gc> MovieClip<'gc> {
                 flags: MovieClipFlags::PLAYING,
                 avm2_class: None,
                 drawing: Drawing::new(),
+                drawing_bitmaps: Vec::new(),
                 is_focusable: false,
                 has_focus: false,
                 enabled: true,
@@ -1676,10 +1687,32 @@ impl<'gc> MovieClip<'gc> {
     }
 
     pub fn run_goto(
+        self,
+        context: &mut UpdateContext<'_, 'gc>,
+        frame: FrameNumber,
+        is_implicit: bool,
+    ) {
+        self.run_goto_internal(context, frame, is_implicit, true)
+    }
+
+    /// Sends a clip that isn't on the display list to `frame` (1-based), without running
+    /// any scripts.
+    ///
+    /// The timeline is applied as in an explicit goto, but no frame scripts run and no AVM2
+    /// objects are constructed for the clip or its new children. Any AVM1 actions the goto
+    /// queues are left in the action queue, so callers must keep them from running. This is
+    /// only suitable for clips that are rendered and then thrown away.
+    pub fn seek_detached(self, context: &mut UpdateContext<'_, 'gc>, frame: FrameNumber) {
+        self.stop(context);
+        self.run_goto_internal(context, frame.max(1), false, false);
+    }
+
+    fn run_goto_internal(
         mut self,
         context: &mut UpdateContext<'_, 'gc>,
         frame: FrameNumber,
         is_implicit: bool,
+        run_scripts: bool,
     ) {
         if cfg!(feature = "timeline_debug") {
             tracing::debug!(
@@ -1938,7 +1971,7 @@ impl<'gc> MovieClip<'gc> {
         // frame".
         //
         // Our queued place tags will now run at this time, too.
-        if !is_implicit {
+        if !is_implicit && run_scripts {
             self.construct_frame(context);
             self.frame_constructed(context);
             self.avm2_root(context)
@@ -2257,6 +2290,43 @@ impl<'gc> MovieClip<'gc> {
         RefMut::map(self.0.write(gc_context), |s| &mut s.drawing)
     }
 
+    /// Adds `bitmap_data` to the bitmaps of this clip's drawing, returning the id to use
+    /// in a bitmap fill. Later changes to `bitmap_data` are shown by the fill.
+    pub fn add_drawing_bitmap(
+        self,
+        context: &mut UpdateContext<'_, 'gc>,
+        bitmap_data: BitmapDataWrapper<'gc>,
+    ) -> u16 {
+        let bitmap = BitmapInfo {
+            handle: bitmap_data.bitmap_handle(context.gc_context, context.renderer),
+            width: bitmap_data.width() as u16,
+            height: bitmap_data.height() as u16,
+        };
+        let mut write = self.0.write(context.gc_context);
+        write.drawing_bitmaps.push(bitmap_data);
+        write.drawing.add_bitmap(bitmap)
+    }
+
+    /// Clears this clip's drawing, along with the bitmaps used by its fills.
+    pub fn clear_drawing(self, gc_context: MutationContext<'gc, '_>) {
+        let mut write = self.0.write(gc_context);
+        write.drawing.clear();
+        write.drawing_bitmaps.clear();
+    }
+
+    /// Uploads any changes to the `BitmapData`s used by bitmap fills in this clip's drawing.
+    pub fn update_drawing_bitmaps(
+        self,
+        gc_context: MutationContext<'gc, '_>,
+        renderer: &mut dyn RenderBackend,
+    ) {
+        for bitmap_data in &self.0.read().drawing_bitmaps {
+            if !bitmap_data.disposed() {
+                bitmap_data.bitmap_handle(gc_context, renderer);
+            }
+        }
+    }
+
     pub fn is_button_mode(&self, context: &mut UpdateContext<'_, 'gc>) -> bool {
         if self.forced_button_mode()
             || self
@@ -2556,6 +2626,7 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
     }
 
     fn render_self(&self, context: &mut RenderContext<'_, 'gc>) {
+        self.update_drawing_bitmaps(context.gc_context, context.renderer);
         self.0.read().drawing.render(context);
         self.render_children(context);
     }
@@ -2581,7 +2652,7 @@ impl<'gc> TDisplayObject<'gc> for MovieClip<'gc> {
             return false;
         }
 
-        if self.world_bounds().contains(point) {
+        if self.world_bounds().contains(point) && self.hit_test_scroll_rect(point) {
             let Some(local_matrix) = self.global_to_local_matrix() else { return false; };
             if let Some(masker) = self.masker() {
                 if !masker.hit_test_shape(context, point, HitTestOptions::SKIP_INVISIBLE) {
@@ -2880,6 +2951,11 @@ impl<'gc> TInteractiveObject<'gc> for MovieClip<'gc> {
                 }
             }
 
+            // Nothing outside the scroll rect is visible, so none of it can be clicked.
+            if !self.hit_test_scroll_rect(point) {
+                return None;
+            }
+
             // In AVM2, mouse_enabled should only impact the ability to select the current clip
             // but it should still be possible to select any children where child.mouse_enabled() is
             // true.
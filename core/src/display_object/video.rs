@@ -486,7 +486,7 @@ impl<'gc> TDisplayObject<'gc> for Video<'gc> {
 
         let read = self.0.read();
 
-        if let Some((_frame_id, ref bitmap)) = read.decoded_frame {
+        if let Some((frame_id, ref bitmap)) = read.decoded_frame {
             let mut transform = context.transform_stack.transform();
             let bounds = self.self_bounds();
 
@@ -509,7 +509,7 @@ impl<'gc> TDisplayObject<'gc> for Video<'gc> {
                 }
             };
 
-            let smoothing = match (context.stage.quality(), version) {
+            let smoothing = match (context.quality, version) {
                 (StageQuality::Low, _) => false,
                 (_, 8..) => smoothed_flag,
                 (StageQuality::Medium, _) => false,
@@ -520,6 +520,7 @@ impl<'gc> TDisplayObject<'gc> for Video<'gc> {
             context
                 .commands
                 .render_bitmap(bitmap.handle.clone(), transform, smoothing);
+            context.texture_versions.push(frame_id.into());
         } else {
             tracing::warn!("Video has no decoded frame to render.");
         }
@@ -17,6 +17,9 @@ extern crate num_derive;
 #[macro_use]
 mod avm1;
 mod avm2;
+#[cfg(feature = "benchmarks")]
+#[doc(hidden)]
+pub mod benchmarks;
 mod binary_data;
 pub mod bitmap;
 mod character;
@@ -39,6 +42,9 @@ mod prelude;
 mod streams;
 pub mod string;
 pub mod tag_utils;
+#[cfg(any(test, feature = "benchmarks"))]
+#[cfg_attr(not(test), allow(dead_code))]
+mod test_utils;
 pub mod timer;
 mod types;
 mod vminterface;
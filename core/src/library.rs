@@ -162,8 +162,9 @@ impl<'gc> MovieLibrary<'gc> {
         export_name: AvmString<'gc>,
     ) -> Option<&Character<'gc>> {
         if let Some(character) = self.characters.get(&id) {
+            // Exports are kept apart by case, so that SWF7+ content can tell them apart.
             self.export_characters
-                .insert(export_name, character.clone(), false);
+                .insert(export_name, character.clone(), true);
             Some(character)
         } else {
             tracing::warn!(
@@ -183,8 +184,16 @@ impl<'gc> MovieLibrary<'gc> {
         self.characters.get(&id)
     }
 
-    pub fn character_by_export_name(&self, name: AvmString<'gc>) -> Option<&Character<'gc>> {
-        self.export_characters.get(name, false)
+    /// Looks up a character by its export name.
+    ///
+    /// Like other AVM1 identifiers, export names are only case sensitive from SWF7 on, so
+    /// callers should pass `activation.is_case_sensitive()` as `case_sensitive`.
+    pub fn character_by_export_name(
+        &self,
+        name: AvmString<'gc>,
+        case_sensitive: bool,
+    ) -> Option<&Character<'gc>> {
+        self.export_characters.get(name, case_sensitive)
     }
 
     /// Instantiates the library item with the given character ID into a display object.
@@ -204,12 +213,15 @@ impl<'gc> MovieLibrary<'gc> {
 
     /// Instantiates the library item with the given export name into a display object.
     /// The object must then be post-instantiated before being used.
+    ///
+    /// The name is matched as in `character_by_export_name`.
     pub fn instantiate_by_export_name(
         &self,
         export_name: AvmString<'gc>,
+        case_sensitive: bool,
         gc_context: MutationContext<'gc, '_>,
     ) -> Result<DisplayObject<'gc>, &'static str> {
-        if let Some(character) = self.export_characters.get(export_name, false) {
+        if let Some(character) = self.character_by_export_name(export_name, case_sensitive) {
             self.instantiate_display_object(character, gc_context)
         } else {
             tracing::error!(
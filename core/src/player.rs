@@ -17,6 +17,7 @@ use crate::backend::{
     storage::StorageBackend,
     ui::{InputManager, MouseCursor, UiBackend},
 };
+use crate::bitmap::BitmapMemoryBudget;
 use crate::compatibility_rules::CompatibilityRules;
 use crate::config::Letterbox;
 use crate::context::{ActionQueue, ActionType, RenderContext, UpdateContext};
@@ -41,7 +42,7 @@ use crate::locale::get_current_date_time;
 use crate::prelude::*;
 use crate::streams::StreamManager;
 use crate::string::AvmString;
-use crate::stub::StubCollection;
+use crate::stub::{Stub, StubCallback, StubCollection};
 use crate::tag_utils::SwfMovie;
 use crate::timer::Timers;
 use crate::vminterface::Instantiator;
@@ -306,6 +307,18 @@ pub struct Player {
 
     /// Any compatibility rules to apply for this movie.
     compatibility_rules: CompatibilityRules,
+
+    /// The pixel memory used by `BitmapData` objects, and how much they may use.
+    bitmap_memory: BitmapMemoryBudget,
+
+    /// The maximum number of pixels a single `BitmapData.floodFill` call may fill.
+    flood_fill_limit: Option<usize>,
+
+    /// Whether `BitmapData` operations are kept off the render backend.
+    force_cpu_bitmap_operations: bool,
+
+    /// The ARGB color that AVM1 fills a new `BitmapData` with when no fill color is given.
+    default_bitmap_fill_color: u32,
 }
 
 impl Player {
@@ -1532,7 +1545,9 @@ impl Player {
                 transform_stack: &mut self.transform_stack,
                 is_offscreen: false,
                 stage,
+                quality: stage.quality(),
                 allow_mask: true,
+                texture_versions: Vec::new(),
             };
 
             stage.render(&mut render_context);
@@ -1771,6 +1786,10 @@ impl Player {
                 actions_since_timeout_check: &mut self.actions_since_timeout_check,
                 frame_phase: &mut self.frame_phase,
                 stub_tracker: &mut self.stub_tracker,
+                bitmap_memory: &self.bitmap_memory,
+                flood_fill_limit: self.flood_fill_limit,
+                force_cpu_bitmap_operations: self.force_cpu_bitmap_operations,
+                default_bitmap_fill_color: self.default_bitmap_fill_color,
                 stream_manager,
             };
 
@@ -1916,6 +1935,14 @@ impl Player {
         &self.compatibility_rules
     }
 
+    /// Returns every unimplemented ActionScript feature that the content has used so far,
+    /// sorted and without duplicates.
+    pub fn encountered_stubs(&self) -> Vec<&Stub> {
+        let mut stubs: Vec<_> = self.stub_tracker.iter().collect();
+        stubs.sort();
+        stubs
+    }
+
     pub fn log_backend(&self) -> &Log {
         &self.log
     }
@@ -1965,6 +1992,11 @@ pub struct PlayerBuilder {
     player_version: Option<u8>,
     quality: StageQuality,
     sandbox_type: SandboxType,
+    bitmap_memory_limit: Option<usize>,
+    flood_fill_limit: Option<usize>,
+    force_cpu_bitmap_operations: bool,
+    default_bitmap_fill_color: u32,
+    on_new_stub: Option<StubCallback>,
 }
 
 impl PlayerBuilder {
@@ -2006,6 +2038,11 @@ impl PlayerBuilder {
             player_version: None,
             quality: StageQuality::High,
             sandbox_type: SandboxType::LocalTrusted,
+            bitmap_memory_limit: None,
+            flood_fill_limit: None,
+            force_cpu_bitmap_operations: false,
+            default_bitmap_fill_color: 0xFFFFFFFF,
+            on_new_stub: None,
         }
     }
 
@@ -2157,6 +2194,47 @@ impl PlayerBuilder {
         self
     }
 
+    /// Limits the total pixel memory, in bytes, that `BitmapData` constructors may allocate.
+    /// Constructing a `BitmapData` that would exceed this fails as an invalid size would.
+    pub fn with_bitmap_memory_limit(mut self, limit: Option<usize>) -> Self {
+        self.bitmap_memory_limit = limit;
+        self
+    }
+
+    /// Limits the number of pixels a single `BitmapData.floodFill` call may fill.
+    /// A fill that reaches this limit stops early, leaving the area it has filled so far.
+    pub fn with_flood_fill_limit(mut self, limit: Option<usize>) -> Self {
+        self.flood_fill_limit = limit;
+        self
+    }
+
+    /// Runs every `BitmapData` operation on the CPU, never on the render backend.
+    /// This makes bitmap results the same on every backend, at the cost of speed.
+    ///
+    /// Not everything has a CPU implementation yet, and those calls log a warning each time:
+    /// - `draw` only handles a `BitmapData` source at a whole-pixel translation without a
+    ///   clip rect. Any other draw, including every display object, fails without drawing.
+    /// - `applyFilter` only handles the gradient filters and shader filters. Any other filter
+    ///   leaves the target unchanged.
+    pub fn with_force_cpu_bitmap_operations(mut self, force: bool) -> Self {
+        self.force_cpu_bitmap_operations = force;
+        self
+    }
+
+    /// Sets the ARGB color that AVM1 fills a new `BitmapData` with when the script doesn't
+    /// pass a fill color. Flash uses opaque white, which is the default.
+    pub fn with_default_bitmap_fill_color(mut self, color: u32) -> Self {
+        self.default_bitmap_fill_color = color;
+        self
+    }
+
+    /// Sets a callback that runs the first time each unimplemented ActionScript feature is hit,
+    /// so that frontends can tell the user that the content may not work correctly.
+    pub fn with_stub_callback(mut self, callback: impl 'static + FnMut(&Stub)) -> Self {
+        self.on_new_stub = Some(Box::new(callback));
+        self
+    }
+
     /// Builds the player, wiring up the backends and configuring the specified settings.
     pub fn build(self) -> Arc<Mutex<Player>> {
         use crate::backend::*;
@@ -2189,6 +2267,9 @@ impl PlayerBuilder {
 
         let player_version = self.player_version.unwrap_or(NEWEST_PLAYER_VERSION);
 
+        let mut stub_tracker = StubCollection::new();
+        stub_tracker.set_on_new_stub(self.on_new_stub);
+
         // Instantiate the player.
         let fake_movie = Arc::new(SwfMovie::empty(player_version));
         let frame_rate = 12.0;
@@ -2237,7 +2318,11 @@ impl PlayerBuilder {
                 load_behavior: self.load_behavior,
                 spoofed_url: self.spoofed_url.clone(),
                 compatibility_rules: self.compatibility_rules.clone(),
-                stub_tracker: StubCollection::new(),
+                stub_tracker,
+                bitmap_memory: BitmapMemoryBudget::new(self.bitmap_memory_limit),
+                flood_fill_limit: self.flood_fill_limit,
+                force_cpu_bitmap_operations: self.force_cpu_bitmap_operations,
+                default_bitmap_fill_color: self.default_bitmap_fill_color,
 
                 // GC data
                 gc_arena: Rc::new(RefCell::new(GcArena::new(
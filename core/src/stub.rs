@@ -111,9 +111,13 @@ impl Display for Stub {
     }
 }
 
-#[derive(Debug, Default)]
+/// A callback that is told about each stub the first time it is encountered.
+pub type StubCallback = Box<dyn FnMut(&Stub)>;
+
+#[derive(Default)]
 pub struct StubCollection {
     inner: FnvHashSet<Stub>,
+    on_new_stub: Option<StubCallback>,
 }
 
 impl StubCollection {
@@ -121,10 +125,18 @@ impl StubCollection {
         Self::default()
     }
 
+    /// Sets the callback that runs whenever a stub is encountered for the first time.
+    pub fn set_on_new_stub(&mut self, callback: Option<StubCallback>) {
+        self.on_new_stub = callback;
+    }
+
     pub fn encounter(&mut self, stub: &Stub) {
         if !self.inner.contains(stub) {
             tracing::warn!("Encountered stub: {stub}");
             self.inner.insert(stub.clone());
+            if let Some(callback) = &mut self.on_new_stub {
+                callback(stub);
+            }
         }
     }
 
@@ -132,3 +144,12 @@ impl StubCollection {
         self.inner.iter()
     }
 }
+
+impl Debug for StubCollection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StubCollection")
+            .field("inner", &self.inner)
+            .field("on_new_stub", &self.on_new_stub.is_some())
+            .finish()
+    }
+}
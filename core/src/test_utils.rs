@@ -0,0 +1,205 @@
+//! Helpers shared by the unit tests of the whole crate.
+
+use crate::avm2::Activation as Avm2Activation;
+use crate::context::UpdateContext;
+use crate::player::PlayerBuilder;
+use crate::tag_utils::SwfMovie;
+use gc_arena::MutationContext;
+use ruffle_render::backend::null::NullRenderer;
+use ruffle_render::backend::{
+    Context3D, Context3DCommand, RenderBackend, ShapeHandle, ViewportDimensions,
+};
+use ruffle_render::bitmap::{
+    Bitmap, BitmapHandle, BitmapSource, PixelRegion, RgbaBufRead, SyncHandle,
+};
+use ruffle_render::commands::CommandList;
+use ruffle_render::error::Error;
+use ruffle_render::filters::Filter;
+use ruffle_render::quality::StageQuality;
+use ruffle_render::shape_utils::DistilledShape;
+use std::borrow::Cow;
+use swf::Color;
+
+/// Runs `callback` with the update context of a player built from `builder`.
+pub fn with_player<F>(builder: PlayerBuilder, callback: F)
+where
+    F: for<'a, 'gc> FnOnce(&mut UpdateContext<'a, 'gc>),
+{
+    let player = builder.build();
+    let mut player = player.lock().unwrap();
+    player.mutate_with_update_context(callback);
+}
+
+/// Runs `callback` with the update context of a player holding an empty SWF 10 movie.
+pub fn with_update_context<F>(callback: F)
+where
+    F: for<'a, 'gc> FnOnce(&mut UpdateContext<'a, 'gc>),
+{
+    with_player(
+        PlayerBuilder::new().with_movie(SwfMovie::empty(10)),
+        callback,
+    );
+}
+
+/// Runs `callback` with an AVM2 activation for a player holding an empty SWF 10 movie.
+pub fn with_avm2_activation<F>(callback: F)
+where
+    F: for<'a, 'gc> FnOnce(&mut Avm2Activation<'a, 'gc>),
+{
+    with_update_context(|context| callback(&mut Avm2Activation::from_nothing(context.reborrow())));
+}
+
+/// A render backend that pretends to support offscreen rendering and filters, recording
+/// what it's asked to do.
+///
+/// The null renderer refuses these, so this lets tests reach the code paths that use them.
+/// Nothing is actually drawn: offscreen renders and filters read back as transparent black.
+pub struct RecordingRenderer {
+    inner: NullRenderer,
+
+    /// The commands of each `render_offscreen` call, in order.
+    pub offscreen_renders: Vec<CommandList>,
+
+    /// The quality of each `render_offscreen` call, in order.
+    pub offscreen_qualities: Vec<StageQuality>,
+
+    /// The filter of each `apply_filter` call, in order.
+    pub applied_filters: Vec<Filter>,
+
+    /// The size of each `create_empty_texture` call, in order.
+    pub empty_textures: Vec<(u32, u32)>,
+
+    /// The region of each `update_texture` call, in order.
+    ///
+    /// Unlike the null renderer, this claims to support partial texture updates.
+    pub texture_updates: Vec<PixelRegion>,
+}
+
+impl Default for RecordingRenderer {
+    fn default() -> Self {
+        Self {
+            inner: NullRenderer::new(ViewportDimensions {
+                width: 550,
+                height: 400,
+                scale_factor: 1.0,
+            }),
+            offscreen_renders: Vec::new(),
+            offscreen_qualities: Vec::new(),
+            applied_filters: Vec::new(),
+            empty_textures: Vec::new(),
+            texture_updates: Vec::new(),
+        }
+    }
+}
+
+/// The result of a `RecordingRenderer` offscreen render, which reads back as transparent black.
+#[derive(Debug)]
+pub struct RecordingSyncHandle {
+    pub width: u32,
+    pub height: u32,
+}
+
+impl SyncHandle for RecordingSyncHandle {
+    fn retrieve_offscreen_texture(self: Box<Self>, with_rgba: RgbaBufRead) -> Result<(), Error> {
+        let rgba = vec![0; self.width as usize * self.height as usize * 4];
+        with_rgba(&rgba, self.width * 4);
+        Ok(())
+    }
+}
+
+impl RenderBackend for RecordingRenderer {
+    fn viewport_dimensions(&self) -> ViewportDimensions {
+        self.inner.viewport_dimensions()
+    }
+
+    fn set_viewport_dimensions(&mut self, dimensions: ViewportDimensions) {
+        self.inner.set_viewport_dimensions(dimensions)
+    }
+
+    fn register_shape(
+        &mut self,
+        shape: DistilledShape,
+        bitmap_source: &dyn BitmapSource,
+    ) -> ShapeHandle {
+        self.inner.register_shape(shape, bitmap_source)
+    }
+
+    fn render_offscreen(
+        &mut self,
+        _handle: BitmapHandle,
+        commands: CommandList,
+        quality: StageQuality,
+        bounds: PixelRegion,
+    ) -> Option<Box<dyn SyncHandle>> {
+        self.offscreen_renders.push(commands);
+        self.offscreen_qualities.push(quality);
+        Some(Box::new(RecordingSyncHandle {
+            width: bounds.width(),
+            height: bounds.height(),
+        }))
+    }
+
+    fn apply_filter(
+        &mut self,
+        _source: BitmapHandle,
+        _source_point: (u32, u32),
+        source_size: (u32, u32),
+        _destination: BitmapHandle,
+        _dest_point: (u32, u32),
+        filter: Filter,
+    ) -> Option<Box<dyn SyncHandle>> {
+        self.applied_filters.push(filter);
+        Some(Box::new(RecordingSyncHandle {
+            width: source_size.0,
+            height: source_size.1,
+        }))
+    }
+
+    fn submit_frame(&mut self, clear: Color, commands: CommandList) {
+        self.inner.submit_frame(clear, commands)
+    }
+
+    fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, Error> {
+        self.inner.register_bitmap(bitmap)
+    }
+
+    fn create_empty_texture(&mut self, width: u32, height: u32) -> Result<BitmapHandle, Error> {
+        self.empty_textures.push((width, height));
+        self.inner.create_empty_texture(width, height)
+    }
+
+    fn update_texture(
+        &mut self,
+        bitmap: &BitmapHandle,
+        rgba: &[u8],
+        region: PixelRegion,
+    ) -> Result<(), Error> {
+        self.texture_updates.push(region);
+        self.inner.update_texture(bitmap, rgba, region)
+    }
+
+    fn supports_partial_texture_update(&self) -> bool {
+        true
+    }
+
+    fn create_context3d(&mut self) -> Result<Box<dyn Context3D>, Error> {
+        self.inner.create_context3d()
+    }
+
+    fn context3d_present<'gc>(
+        &mut self,
+        context: &mut dyn Context3D,
+        commands: Vec<Context3DCommand<'gc>>,
+        mc: MutationContext<'gc, '_>,
+    ) -> Result<(), Error> {
+        self.inner.context3d_present(context, commands, mc)
+    }
+
+    fn debug_info(&self) -> Cow<'static, str> {
+        Cow::Borrowed("Renderer: Recording")
+    }
+
+    fn set_quality(&mut self, quality: StageQuality) {
+        self.inner.set_quality(quality)
+    }
+}
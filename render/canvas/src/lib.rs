@@ -468,7 +468,7 @@ impl RenderBackend for WebCanvasRenderBackend {
     fn update_texture(
         &mut self,
         handle: &BitmapHandle,
-        rgba: Vec<u8>,
+        rgba: &[u8],
         _region: PixelRegion,
     ) -> Result<(), Error> {
         let data = as_bitmap_data(handle);
@@ -476,7 +476,7 @@ impl RenderBackend for WebCanvasRenderBackend {
             data.bitmap.width(),
             data.bitmap.height(),
             BitmapFormat::Rgba,
-            rgba,
+            rgba.to_vec(),
         ))
         .map_err(Error::JavascriptError)?;
         Ok(())
@@ -1,6 +1,6 @@
 pub mod null;
 
-use crate::bitmap::{Bitmap, BitmapHandle, BitmapSource, PixelRegion, SyncHandle};
+use crate::bitmap::{Bitmap, BitmapFormat, BitmapHandle, BitmapSource, PixelRegion, SyncHandle};
 use crate::commands::CommandList;
 use crate::error::Error;
 use crate::filters::Filter;
@@ -56,13 +56,33 @@ pub trait RenderBackend: Downcast {
     fn submit_frame(&mut self, clear: swf::Color, commands: CommandList);
 
     fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, Error>;
+
+    /// Creates a transparent texture of the given size, to be drawn into with
+    /// `render_offscreen` or `apply_filter`.
+    ///
+    /// Backends that can create a texture without uploading any pixels should do so here.
+    fn create_empty_texture(&mut self, width: u32, height: u32) -> Result<BitmapHandle, Error> {
+        let rgba = vec![0; width as usize * height as usize * 4];
+        self.register_bitmap(Bitmap::new(width, height, BitmapFormat::Rgba, rgba))
+    }
+    /// Replaces the pixels of `bitmap` inside of `region` with those of `rgba`.
+    ///
+    /// `rgba` always covers the whole bitmap, so that callers can reuse the same buffer.
     fn update_texture(
         &mut self,
         bitmap: &BitmapHandle,
-        rgba: Vec<u8>,
+        rgba: &[u8],
         region: PixelRegion,
     ) -> Result<(), Error>;
 
+    /// Whether `update_texture` only reads the pixels of `rgba` inside of `region`.
+    ///
+    /// If so, callers only need to fill in that part of the buffer. Otherwise,
+    /// the whole bitmap must be provided, and is uploaded in full.
+    fn supports_partial_texture_update(&self) -> bool {
+        false
+    }
+
     fn create_context3d(&mut self) -> Result<Box<dyn Context3D>, Error>;
     fn context3d_present<'gc>(
         &mut self,
@@ -373,6 +393,13 @@ pub struct ShapeHandle(pub Arc<dyn ShapeHandleImpl>);
 pub trait ShapeHandleImpl: Downcast + Debug {}
 impl_downcast!(ShapeHandleImpl);
 
+/// Handles are equal when they refer to the same registered shape.
+impl PartialEq for ShapeHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0).cast::<()>() == Arc::as_ptr(&other.0).cast::<()>()
+    }
+}
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 #[serde(deny_unknown_fields)]
 pub struct ViewportDimensions {
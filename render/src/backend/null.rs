@@ -73,10 +73,14 @@ impl RenderBackend for NullRenderer {
         Ok(BitmapHandle(Arc::new(NullBitmapHandle)))
     }
 
+    fn create_empty_texture(&mut self, _width: u32, _height: u32) -> Result<BitmapHandle, Error> {
+        Ok(BitmapHandle(Arc::new(NullBitmapHandle)))
+    }
+
     fn update_texture(
         &mut self,
         _bitmap: &BitmapHandle,
-        _rgba: Vec<u8>,
+        _rgba: &[u8],
         _region: PixelRegion,
     ) -> Result<(), Error> {
         Ok(())
@@ -14,6 +14,13 @@ pub struct BitmapHandle(pub Arc<dyn BitmapHandleImpl>);
 pub trait BitmapHandleImpl: Downcast + Debug {}
 impl_downcast!(BitmapHandleImpl);
 
+/// Handles are equal when they refer to the same texture.
+impl PartialEq for BitmapHandle {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::as_ptr(&self.0).cast::<()>() == Arc::as_ptr(&other.0).cast::<()>()
+    }
+}
+
 /// Info returned by the `register_bitmap` methods.
 #[derive(Clone, Debug, Collect)]
 #[collect(require_static)]
@@ -46,6 +53,22 @@ pub trait SyncHandle: Downcast + Debug {
         self: Box<Self>,
         with_rgba: RgbaBufRead,
     ) -> Result<(), crate::error::Error>;
+
+    /// Retrieves just `region` of the rendered pixels, without consuming this handle.
+    ///
+    /// `region` must lie within the area that was rendered. `with_rgba` receives the
+    /// pixels starting at the top-left corner of `region`, along with the row stride in bytes.
+    /// Backends that can't read back part of the texture return an error, in which
+    /// case the caller should fall back to `retrieve_offscreen_texture`.
+    fn retrieve_offscreen_region(
+        &self,
+        _region: PixelRegion,
+        _with_rgba: RgbaBufRead,
+    ) -> Result<(), crate::error::Error> {
+        Err(crate::error::Error::Unimplemented(
+            "retrieve_offscreen_region".into(),
+        ))
+    }
 }
 impl_downcast!(SyncHandle);
 
@@ -268,6 +291,15 @@ impl PixelRegion {
         self.y_max = self.y_max.max(other.y_max);
     }
 
+    /// Shrinks this region to the area it shares with `other`.
+    /// If the two regions don't overlap, the result is empty.
+    pub fn intersect(&mut self, other: PixelRegion) {
+        self.x_min = self.x_min.max(other.x_min);
+        self.y_min = self.y_min.max(other.y_min);
+        self.x_max = self.x_max.min(other.x_max).max(self.x_min);
+        self.y_max = self.y_max.min(other.y_max).max(self.y_min);
+    }
+
     pub fn encompass(&mut self, x: u32, y: u32) {
         self.x_min = self.x_min.min(x);
         self.y_min = self.y_min.min(y);
@@ -282,6 +314,49 @@ impl PixelRegion {
             && self.y_max >= other.y_min
     }
 
+    /// Returns `true` if every pixel of `other` is also inside this region.
+    pub fn contains(&self, other: PixelRegion) -> bool {
+        self.x_min <= other.x_min
+            && self.y_min <= other.y_min
+            && self.x_max >= other.x_max
+            && self.y_max >= other.y_max
+    }
+
+    /// Splits the part of this region that lies outside of `other` into up to four
+    /// non-overlapping, non-empty regions.
+    pub fn subtract(&self, other: PixelRegion) -> Vec<PixelRegion> {
+        let mut inner = other;
+        inner.intersect(*self);
+        if inner.width() == 0 || inner.height() == 0 {
+            return vec![*self];
+        }
+
+        // Full-width bands above and below `inner`, then the parts beside it.
+        [
+            PixelRegion {
+                y_max: inner.y_min,
+                ..*self
+            },
+            PixelRegion {
+                y_min: inner.y_max,
+                ..*self
+            },
+            PixelRegion {
+                x_min: self.x_min,
+                x_max: inner.x_min,
+                ..inner
+            },
+            PixelRegion {
+                x_min: inner.x_max,
+                x_max: self.x_max,
+                ..inner
+            },
+        ]
+        .into_iter()
+        .filter(|band| band.x_min < band.x_max && band.y_min < band.y_max)
+        .collect()
+    }
+
     pub fn width(&self) -> u32 {
         self.x_max - self.x_min
     }
@@ -17,7 +17,7 @@ pub trait CommandHandler {
     fn blend(&mut self, commands: CommandList, blend_mode: BlendMode);
 }
 
-#[derive(Debug, Default, Clone)]
+#[derive(Debug, Default, Clone, PartialEq)]
 pub struct CommandList {
     pub commands: Vec<Command>,
 }
@@ -94,7 +94,7 @@ impl CommandHandler for CommandList {
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Command {
     RenderBitmap {
         bitmap: BitmapHandle,
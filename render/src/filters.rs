@@ -1,7 +1,8 @@
 use crate::bitmap::BitmapHandle;
-use swf::Color;
+use std::sync::Arc;
+use swf::{Color, Fixed16, Rectangle, Twips};
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum Filter {
     BevelFilter(swf::BevelFilter),
     BlurFilter(swf::BlurFilter),
@@ -12,6 +13,7 @@ pub enum Filter {
     GlowFilter(swf::GlowFilter),
     GradientBevelFilter(swf::GradientFilter),
     GradientGlowFilter(swf::GradientFilter),
+    ShaderFilter(ShaderFilter),
 }
 
 impl From<&swf::Filter> for Filter {
@@ -39,6 +41,91 @@ impl From<&swf::Filter> for Filter {
     }
 }
 
+impl Filter {
+    /// Returns the area covered by the output of this filter when it is applied to `source_rect`.
+    ///
+    /// Blurs spread pixels outwards, and shadows and bevels offset them, so this is often larger
+    /// than `source_rect`. Filters that only change pixels in place return `source_rect` itself.
+    pub fn calculate_dest_rect(&self, source_rect: Rectangle<Twips>) -> Rectangle<Twips> {
+        // The blur, the number of blur passes, and the offset of any shadow or highlight.
+        // Bevels draw a shadow on one side and a highlight on the other, so are offset both ways.
+        let (blur_x, blur_y, passes, offset, both_ways) = match self {
+            Filter::BlurFilter(filter) => (
+                filter.blur_x,
+                filter.blur_y,
+                filter.num_passes(),
+                None,
+                false,
+            ),
+            Filter::GlowFilter(filter) => (
+                filter.blur_x,
+                filter.blur_y,
+                filter.num_passes(),
+                None,
+                false,
+            ),
+            Filter::DropShadowFilter(filter) => (
+                filter.blur_x,
+                filter.blur_y,
+                filter.num_passes(),
+                Some((filter.angle, filter.distance)),
+                false,
+            ),
+            Filter::BevelFilter(filter) => (
+                filter.blur_x,
+                filter.blur_y,
+                filter.num_passes(),
+                Some((filter.angle, filter.distance)),
+                true,
+            ),
+            Filter::GradientGlowFilter(filter) => (
+                filter.blur_x,
+                filter.blur_y,
+                filter.num_passes(),
+                Some((filter.angle, filter.distance)),
+                false,
+            ),
+            Filter::GradientBevelFilter(filter) => (
+                filter.blur_x,
+                filter.blur_y,
+                filter.num_passes(),
+                Some((filter.angle, filter.distance)),
+                true,
+            ),
+            _ => return source_rect,
+        };
+
+        // Each blur pass spreads every pixel by half the blur size in each direction.
+        let spread =
+            |blur: Fixed16| Twips::from_pixels((blur.to_f64() * f64::from(passes) / 2.0).ceil());
+        let (spread_x, spread_y) = (spread(blur_x), spread(blur_y));
+        let blurred = Rectangle {
+            x_min: source_rect.x_min - spread_x,
+            x_max: source_rect.x_max + spread_x,
+            y_min: source_rect.y_min - spread_y,
+            y_max: source_rect.y_max + spread_y,
+        };
+
+        let Some((angle, distance)) = offset else {
+            return blurred;
+        };
+        let (sin, cos) = angle.to_f64().sin_cos();
+        let dx = Twips::from_pixels(distance.to_f64() * cos);
+        let dy = Twips::from_pixels(distance.to_f64() * sin);
+        let shift = |dx: Twips, dy: Twips| Rectangle {
+            x_min: blurred.x_min + dx,
+            x_max: blurred.x_max + dx,
+            y_min: blurred.y_min + dy,
+            y_max: blurred.y_max + dy,
+        };
+        let mut dest = source_rect.union(&shift(dx, dy));
+        if both_ways {
+            dest = dest.union(&shift(-dx, -dy));
+        }
+        dest
+    }
+}
+
 impl Default for Filter {
     fn default() -> Self {
         // A default colormatrix is a filter that essentially does nothing,
@@ -47,7 +134,14 @@ impl Default for Filter {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+/// A filter running a Pixel Bender shader over its input.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShaderFilter {
+    /// The Pixel Bender bytecode of the shader.
+    pub bytecode: Arc<[u8]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DisplacementMapFilterComponent {
     Alpha,
     Blue,
@@ -55,7 +149,7 @@ pub enum DisplacementMapFilterComponent {
     Red,
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum DisplacementMapFilterMode {
     Clamp,
     Color,
@@ -63,7 +157,7 @@ pub enum DisplacementMapFilterMode {
     Wrap,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct DisplacementMapFilter {
     pub color: Color,
     pub component_x: u8,
@@ -89,3 +183,50 @@ impl Default for DisplacementMapFilter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use swf::{BlurFilter, BlurFilterFlags, DropShadowFilter, DropShadowFilterFlags, Fixed8};
+
+    fn rect(x_min: f64, y_min: f64, x_max: f64, y_max: f64) -> Rectangle<Twips> {
+        Rectangle {
+            x_min: Twips::from_pixels(x_min),
+            y_min: Twips::from_pixels(y_min),
+            x_max: Twips::from_pixels(x_max),
+            y_max: Twips::from_pixels(y_max),
+        }
+    }
+
+    #[test]
+    fn dest_rect_grows_with_blur_and_offset() {
+        let source = rect(0.0, 0.0, 10.0, 10.0);
+
+        let blur = Filter::BlurFilter(BlurFilter {
+            blur_x: Fixed16::from_f64(4.0),
+            blur_y: Fixed16::from_f64(2.0),
+            flags: BlurFilterFlags::from_passes(2),
+        });
+        assert_eq!(
+            blur.calculate_dest_rect(source.clone()),
+            rect(-4.0, -2.0, 14.0, 12.0)
+        );
+
+        let shadow = Filter::DropShadowFilter(DropShadowFilter {
+            color: Color::BLACK,
+            blur_x: Fixed16::ZERO,
+            blur_y: Fixed16::ZERO,
+            angle: Fixed16::ZERO,
+            distance: Fixed16::from_f64(5.0),
+            strength: Fixed8::ONE,
+            flags: DropShadowFilterFlags::from_passes(1),
+        });
+        assert_eq!(
+            shadow.calculate_dest_rect(source.clone()),
+            rect(0.0, 0.0, 15.0, 10.0)
+        );
+
+        let color_matrix = Filter::default();
+        assert_eq!(color_matrix.calculate_dest_rect(source.clone()), source);
+    }
+}
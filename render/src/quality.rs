@@ -7,7 +7,8 @@ use std::str::FromStr;
 /// The quality setting of the `Stage`.
 ///
 /// In the Flash Player, this settings affects anti-aliasing and bitmap smoothing.
-/// These settings currently have no effect in Ruffle, but the active setting is still stored.
+/// Ruffle never smooths bitmaps at `Low`, but only the wgpu backend changes its anti-aliasing,
+/// using `sample_count`.
 /// [StageQuality in the AS3 Reference](https://help.adobe.com/en_US/FlashPlatform/reference/actionscript/3/flash/display/StageQuality.html)
 #[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
 #[derive(Default, Clone, Collect, Copy, Debug, Eq, PartialEq)]
@@ -76,6 +77,12 @@ impl StageQuality {
             StageQuality::High16x16Linear => 16,
         }
     }
+
+    /// Returns whether bitmaps may be smoothed at this quality.
+    /// At `Low`, Flash samples bitmaps with nearest neighbor, whatever their smoothing flag.
+    pub fn allows_bitmap_smoothing(self) -> bool {
+        self != StageQuality::Low
+    }
 }
 
 impl Display for StageQuality {
@@ -1034,7 +1034,7 @@ impl RenderBackend for WebGlRenderBackend {
     fn update_texture(
         &mut self,
         handle: &BitmapHandle,
-        rgba: Vec<u8>,
+        rgba: &[u8],
         _region: PixelRegion,
     ) -> Result<(), BitmapError> {
         let data = as_registry_data(handle);
@@ -1052,7 +1052,7 @@ impl RenderBackend for WebGlRenderBackend {
                 0,
                 Gl::RGBA,
                 Gl::UNSIGNED_BYTE,
-                Some(&rgba),
+                Some(rgba),
             )
             .into_js_result()
             .map_err(|e| BitmapError::JavascriptError(e.into()))?;
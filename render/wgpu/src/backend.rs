@@ -490,26 +490,56 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
 
     #[instrument(level = "debug", skip_all)]
     fn register_bitmap(&mut self, bitmap: Bitmap) -> Result<BitmapHandle, BitmapError> {
-        if bitmap.width() > self.descriptors.limits.max_texture_dimension_2d
-            || bitmap.height() > self.descriptors.limits.max_texture_dimension_2d
+        let bitmap = bitmap.to_rgba();
+        let handle = self.create_empty_texture(bitmap.width(), bitmap.height())?;
+        let texture = as_texture(&handle);
+        self.descriptors.queue.write_texture(
+            wgpu::ImageCopyTexture {
+                texture: &texture.texture,
+                mip_level: 0,
+                origin: Default::default(),
+                aspect: wgpu::TextureAspect::All,
+            },
+            bitmap.data(),
+            wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(4 * bitmap.width()),
+                rows_per_image: None,
+            },
+            wgpu::Extent3d {
+                width: bitmap.width(),
+                height: bitmap.height(),
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Ok(handle)
+    }
+
+    #[instrument(level = "debug", skip_all)]
+    fn create_empty_texture(
+        &mut self,
+        width: u32,
+        height: u32,
+    ) -> Result<BitmapHandle, BitmapError> {
+        if width > self.descriptors.limits.max_texture_dimension_2d
+            || height > self.descriptors.limits.max_texture_dimension_2d
         {
             return Err(BitmapError::TooLarge);
         }
 
-        let bitmap = bitmap.to_rgba();
-        let extent = wgpu::Extent3d {
-            width: bitmap.width(),
-            height: bitmap.height(),
-            depth_or_array_layers: 1,
-        };
-
+        // wgpu clears new textures, so no pixels need to be uploaded.
         let texture_label = create_debug_label!("Bitmap");
         let texture = self
             .descriptors
             .device
             .create_texture(&wgpu::TextureDescriptor {
                 label: texture_label.as_deref(),
-                size: extent,
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
                 mip_level_count: 1,
                 sample_count: 1,
                 dimension: wgpu::TextureDimension::D2,
@@ -521,39 +551,21 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
                     | wgpu::TextureUsages::COPY_SRC,
             });
 
-        self.descriptors.queue.write_texture(
-            wgpu::ImageCopyTexture {
-                texture: &texture,
-                mip_level: 0,
-                origin: Default::default(),
-                aspect: wgpu::TextureAspect::All,
-            },
-            bitmap.data(),
-            wgpu::ImageDataLayout {
-                offset: 0,
-                bytes_per_row: NonZeroU32::new(4 * extent.width),
-                rows_per_image: None,
-            },
-            extent,
-        );
-
-        let handle = BitmapHandle(Arc::new(Texture {
+        Ok(BitmapHandle(Arc::new(Texture {
             texture: Arc::new(texture),
             bind_linear: Default::default(),
             bind_nearest: Default::default(),
-            width: bitmap.width(),
-            height: bitmap.height(),
+            width,
+            height,
             copy_count: Cell::new(0),
-        }));
-
-        Ok(handle)
+        })))
     }
 
     #[instrument(level = "debug", skip_all)]
     fn update_texture(
         &mut self,
         handle: &BitmapHandle,
-        rgba: Vec<u8>,
+        rgba: &[u8],
         region: PixelRegion,
     ) -> Result<(), BitmapError> {
         let texture = as_texture(handle);
@@ -588,6 +600,10 @@ impl<T: RenderTarget + 'static> RenderBackend for WgpuRenderBackend<T> {
         Ok(())
     }
 
+    fn supports_partial_texture_update(&self) -> bool {
+        true
+    }
+
     #[instrument(level = "debug", skip_all)]
     fn render_offscreen(
         &mut self,
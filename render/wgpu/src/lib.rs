@@ -188,6 +188,31 @@ impl SyncHandle for QueueSyncHandle {
         self.capture(with_rgba);
         Ok(())
     }
+
+    fn retrieve_offscreen_region(
+        &self,
+        region: PixelRegion,
+        with_rgba: RgbaBufRead,
+    ) -> Result<(), ruffle_render::error::Error> {
+        match self {
+            // The whole area has already been copied into a buffer, so a full
+            // readback costs the same as a partial one.
+            QueueSyncHandle::AlreadyCopied { .. } => {
+                Err(ruffle_render::error::Error::Unimplemented(
+                    "Partial readback of a copied buffer".into(),
+                ))
+            }
+            QueueSyncHandle::NotCopied {
+                handle,
+                descriptors,
+                pool,
+                ..
+            } => {
+                copy_and_capture(handle, region, descriptors, pool, with_rgba);
+                Ok(())
+            }
+        }
+    }
 }
 
 impl QueueSyncHandle {
@@ -211,55 +236,11 @@ impl QueueSyncHandle {
                 descriptors,
                 pool,
             } => {
-                let texture = as_texture(&handle);
-
-                let buffer_dimensions =
-                    BufferDimensions::new(copy_area.width() as usize, copy_area.height() as usize);
-                let buffer = pool.take(&descriptors, buffer_dimensions.clone());
-                let label = create_debug_label!("Render target transfer encoder");
-                let mut encoder =
-                    descriptors
-                        .device
-                        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
-                            label: label.as_deref(),
-                        });
-                encoder.copy_texture_to_buffer(
-                    wgpu::ImageCopyTexture {
-                        texture: &texture.texture,
-                        mip_level: 0,
-                        origin: wgpu::Origin3d {
-                            x: copy_area.x_min,
-                            y: copy_area.y_min,
-                            z: 0,
-                        },
-                        aspect: wgpu::TextureAspect::All,
-                    },
-                    wgpu::ImageCopyBuffer {
-                        buffer: &buffer,
-                        layout: wgpu::ImageDataLayout {
-                            offset: 0,
-                            bytes_per_row: Some(buffer_dimensions.padded_bytes_per_row),
-                            rows_per_image: None,
-                        },
-                    },
-                    wgpu::Extent3d {
-                        width: copy_area.width(),
-                        height: copy_area.height(),
-                        depth_or_array_layers: 1,
-                    },
-                );
-                let index = descriptors.queue.submit(Some(encoder.finish()));
-
-                let image = capture_image(
-                    &descriptors.device,
-                    &buffer,
-                    &buffer_dimensions,
-                    Some(index),
-                    with_rgba,
-                );
+                let image = copy_and_capture(&handle, copy_area, &descriptors, &pool, with_rgba);
 
                 // After we've read pixels from a texture enough times, we'll store this buffer so that
                 // future reads will be faster (it'll copy as part of the draw process instead)
+                let texture = as_texture(&handle);
                 texture
                     .copy_count
                     .set(texture.copy_count.get().saturating_add(1));
@@ -270,6 +251,61 @@ impl QueueSyncHandle {
     }
 }
 
+/// Copies `copy_area` of the texture behind `handle` into a buffer, and waits to read it back.
+fn copy_and_capture<R, F: FnOnce(&[u8], u32) -> R>(
+    handle: &BitmapHandle,
+    copy_area: PixelRegion,
+    descriptors: &Descriptors,
+    pool: &BufferPool<wgpu::Buffer, BufferDimensions>,
+    with_rgba: F,
+) -> R {
+    let texture = as_texture(handle);
+
+    let buffer_dimensions =
+        BufferDimensions::new(copy_area.width() as usize, copy_area.height() as usize);
+    let buffer = pool.take(descriptors, buffer_dimensions.clone());
+    let label = create_debug_label!("Render target transfer encoder");
+    let mut encoder = descriptors
+        .device
+        .create_command_encoder(&wgpu::CommandEncoderDescriptor {
+            label: label.as_deref(),
+        });
+    encoder.copy_texture_to_buffer(
+        wgpu::ImageCopyTexture {
+            texture: &texture.texture,
+            mip_level: 0,
+            origin: wgpu::Origin3d {
+                x: copy_area.x_min,
+                y: copy_area.y_min,
+                z: 0,
+            },
+            aspect: wgpu::TextureAspect::All,
+        },
+        wgpu::ImageCopyBuffer {
+            buffer: &buffer,
+            layout: wgpu::ImageDataLayout {
+                offset: 0,
+                bytes_per_row: Some(buffer_dimensions.padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        wgpu::Extent3d {
+            width: copy_area.width(),
+            height: copy_area.height(),
+            depth_or_array_layers: 1,
+        },
+    );
+    let index = descriptors.queue.submit(Some(encoder.finish()));
+
+    capture_image(
+        &descriptors.device,
+        &buffer,
+        &buffer_dimensions,
+        Some(index),
+        with_rgba,
+    )
+}
+
 #[derive(Debug)]
 pub struct Texture {
     pub(crate) texture: Arc<wgpu::Texture>,
@@ -385,6 +385,15 @@ impl BlendMode {
             n => n,
         })
     }
+
+    /// Parses a blend mode as ActionScript accepts it: either by its name in any case
+    /// (such as `"ADD"`), or by its SWF value written as a number (such as `"3"`).
+    pub fn from_name_or_number(s: &str) -> Option<Self> {
+        if let Ok(mode) = s.to_ascii_lowercase().parse() {
+            return Some(mode);
+        }
+        s.trim().parse().ok().and_then(Self::from_u8)
+    }
 }
 
 impl Display for BlendMode {